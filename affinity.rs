@@ -0,0 +1,134 @@
+/*
+Pinning worker threads to CPUs, for machines with several NUMA nodes (like dual-socket cluster nodes), where the parallel engines slow down badly
+ once their threads wander between sockets and work on memory allocated on the other one.
+The CPUs of each node are read from /sys/devices/system/node, keeping only those the process may run on (so taskset and cgroup limits are respected),
+ and a machine without it counts as a single node. A Placement picks a CPU for each worker:
+    compact  fills one node's CPUs before starting on the next, so a run with fewer threads than a node has CPUs stays on one socket
+    spread   deals the workers out a node at a time, for the memory bandwidth of every socket
+Linux puts memory on the node of the thread that first writes to it, so each worker pins itself before building its search and buffers
+ (the batches of mosaics it sends on), which then end up on its own node. Pinning is only supported on Linux.
+*/
+
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Placement {
+    #[default]
+    Unpinned,
+    Compact,
+    Spread,
+}
+
+impl FromStr for Placement {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Placement> {
+        match name {
+            "none" => Ok(Placement::Unpinned),
+            "compact" => Ok(Placement::Compact),
+            "spread" => Ok(Placement::Spread),
+            _ => Err(Error::new(ErrorKind::InvalidInput, format!("unknown placement {} (the placements are compact, spread and none)", name))),
+        }
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            Placement::Unpinned => "none",
+            Placement::Compact => "compact",
+            Placement::Spread => "spread",
+        })
+    }
+}
+
+//The CPUs in a list like 0-3,8-11, as in /sys/devices/system/node/node<n>/cpulist
+fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((first, last)) => cpus.extend(first.parse::<usize>().ok()?..=last.parse().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+//The CPUs of each NUMA node, in order of node, or none on machines that don't say
+fn nodes() -> Vec<Vec<usize>> {
+    let mut nodes: Vec<(usize, Vec<usize>)> = fs::read_dir("/sys/devices/system/node")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let node = entry.file_name().to_str()?.strip_prefix("node")?.parse().ok()?;
+            Some((node, parse_cpu_list(&fs::read_to_string(entry.path().join("cpulist")).ok()?)?))
+        })
+        .collect();
+    nodes.sort();
+    nodes.into_iter().map(|(_, cpus)| cpus).collect()
+}
+
+/* The CPU each of `threads` workers is pinned to with a placement (none without pinning), from the CPUs the process may run on,
+ going round them again for more workers than CPUs. */
+pub fn cpus(placement: Placement, threads: usize) -> Result<Vec<usize>> {
+    if placement == Placement::Unpinned {
+        return Ok(Vec::new());
+    }
+    let allowed = allowed_cpus()?;
+    let mut nodes: Vec<Vec<usize>> = nodes().into_iter().map(|cpus| cpus.into_iter().filter(|cpu| allowed.contains(cpu)).collect::<Vec<usize>>()).filter(|cpus| !cpus.is_empty()).collect();
+    if nodes.is_empty() {
+        nodes = vec![allowed];
+    }
+    let order: Vec<usize> = match placement {
+        Placement::Unpinned => unreachable!("unpinned workers have no CPUs"),
+        Placement::Compact => nodes.concat(),
+        Placement::Spread => (0..nodes.iter().map(Vec::len).max().unwrap_or(0)).flat_map(|index| nodes.iter().filter_map(move |cpus| cpus.get(index).copied())).collect(),
+    };
+    if order.is_empty() {
+        return Err(Error::other("none of the CPUs the process may run on were found"));
+    }
+    Ok((0..threads).map(|worker| order[worker % order.len()]).collect())
+}
+
+#[cfg(target_os = "linux")]
+fn allowed_cpus() -> Result<Vec<usize>> {
+    //Safety: the set is zeroed before use, and sched_getaffinity writes at most its size
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok((0..libc::CPU_SETSIZE as usize).filter(|&cpu| libc::CPU_ISSET(cpu, &set)).collect())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn allowed_cpus() -> Result<Vec<usize>> {
+    Err(Error::new(ErrorKind::Unsupported, "threads can only be pinned to CPUs on Linux"))
+}
+
+//Pins the calling thread to a CPU
+#[cfg(target_os = "linux")]
+pub fn pin(cpu: usize) -> Result<()> {
+    //Safety: the set is zeroed before use, and only holds a CPU below CPU_SETSIZE
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("CPU {} is past the CPUs threads can be pinned to", cpu)));
+        }
+        libc::CPU_SET(cpu, &mut set);
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin(_: usize) -> Result<()> {
+    Err(Error::new(ErrorKind::Unsupported, "threads can only be pinned to CPUs on Linux"))
+}
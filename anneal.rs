@@ -0,0 +1,119 @@
+/*
+Simulated annealing toward small representatives of a mosaic's Legendrian class. The walk of sample.rs is biased by a cost, accepting a proposal
+ with the Metropolis-Hastings ratio times e^(-(cost after - cost before) / T) for a temperature T falling geometrically from the start temperature to the end one,
+ so it wanders freely at first and settles into the cheapest mosaics it finds as it cools. The cheapest mosaic seen on the way is kept. The costs are
+    tiles       the number of nonblank tiles
+    size        the size of the board the mosaic trims to (see Mosaic::trim)
+    crossings   the number of crossings -- which only the Legendrian Reidemeister moves change, so they need a weight (see reidemeister.rs)
+with ties broken by the others in that order, which also nudge the walk: every cost counts a thousandth of the sum of the others.
+*/
+
+use crate::mosaic::Mosaic;
+use crate::sample::{MoveWeights, Sampler, SamplerOptions};
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+use std::str::FromStr;
+
+const CROSSING: u8 = 9;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Cost {
+    Tiles,
+    Size,
+    Crossings,
+}
+
+impl FromStr for Cost {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Cost> {
+        match name {
+            "tiles" => Ok(Cost::Tiles),
+            "size" => Ok(Cost::Size),
+            "crossings" => Ok(Cost::Crossings),
+            _ => Err(Error::new(ErrorKind::InvalidInput, format!("unknown cost {} (the costs are tiles, size and crossings)", name))),
+        }
+    }
+}
+
+impl fmt::Display for Cost {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            Cost::Tiles => "tiles",
+            Cost::Size => "size",
+            Cost::Crossings => "crossings",
+        })
+    }
+}
+
+impl Cost {
+    //The mosaic's costs, this one first and then the others in order -- compared as a whole to find the best mosaic
+    pub fn costs(&self, mosaic: &Mosaic) -> [usize; 3] {
+        let tiles = mosaic.tile_count();
+        let size = mosaic.bounds().map_or(0, |bounds| bounds.height.max(bounds.width));
+        let crossings = mosaic.tiles().iter().filter(|&&tile| tile == CROSSING).count();
+        match self {
+            Cost::Tiles => [tiles, size, crossings],
+            Cost::Size => [size, tiles, crossings],
+            Cost::Crossings => [crossings, tiles, size],
+        }
+    }
+
+    fn energy(&self, mosaic: &Mosaic) -> f64 {
+        let [cost, first, second] = self.costs(mosaic);
+        cost as f64 + (first + second) as f64 / 1000.0
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AnnealOptions {
+    pub cost: Cost,
+    pub steps: u64,
+    pub start_temperature: f64,
+    pub end_temperature: f64,
+    pub weights: MoveWeights,
+    pub seed: u64,
+}
+
+impl Default for AnnealOptions {
+    fn default() -> AnnealOptions {
+        AnnealOptions { cost: Cost::Tiles, steps: 100000, start_temperature: 2.0, end_temperature: 0.01, weights: MoveWeights::default(), seed: 0 }
+    }
+}
+
+pub struct Annealed {
+    pub best: Mosaic,
+    pub costs: [usize; 3],
+    //The step the best mosaic was first reached at (0 for the starting mosaic), and the number of steps that moved
+    pub found_at: u64,
+    pub accepted: u64,
+}
+
+//Anneals a suitably connected mosaic, returning the cheapest mosaic found
+pub fn anneal(mosaic: Mosaic, options: &AnnealOptions) -> Result<Annealed> {
+    if !(options.start_temperature > 0.0 && options.end_temperature > 0.0) {
+        return Err(Error::new(ErrorKind::InvalidInput, "annealing temperatures have to be positive"));
+    }
+    let cost = options.cost;
+    let mut sampler = Sampler::new(mosaic.clone(), SamplerOptions { weights: options.weights, burn_in: 0, thin: 1, seed: options.seed })?;
+    let (mut energy, mut costs) = (cost.energy(&mosaic), cost.costs(&mosaic));
+    let mut annealed = Annealed { best: mosaic, costs, found_at: 0, accepted: 0 };
+    let cooling = (options.end_temperature / options.start_temperature).ln() / options.steps.max(1) as f64;
+    for step in 1..=options.steps {
+        let temperature = options.start_temperature * (cooling * step as f64).exp();
+        let Some(proposal) = sampler.propose() else {
+            continue;
+        };
+        let proposal_energy = cost.energy(&proposal.mosaic);
+        if sampler.rng().unit() < proposal.ratio * (-(proposal_energy - energy) / temperature).exp() {
+            energy = proposal_energy;
+            sampler.accept(proposal);
+            costs = cost.costs(sampler.mosaic());
+            if costs < annealed.costs {
+                (annealed.best, annealed.costs, annealed.found_at) = (sampler.mosaic().clone(), costs, step);
+            }
+        }
+    }
+    annealed.accepted = sampler.accepted;
+    Ok(annealed)
+}
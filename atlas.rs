@@ -0,0 +1,328 @@
+/*
+Reference data on the classification of legendrian knots, for checking census representatives against what's already known.
+
+Entries are named as in knots.rs, one for each knot through 9 crossings and each mirror image (but 9_42's, which knots.rs never names), and only record what
+ follows from published results:
+ - the maximal tb of alternating knots, from Ng's theorem that the Khovanov bound is sharp for them: tb(K) = min deg V_K - σ(K)/2 - 1, from the
+   jones polynomials of knots.rs and the signatures of their reduced alternating diagrams (with positive knots of negative signature, as in the census),
+   which gives tb(K) + tb(m(K)) = -c(K) - 2 -- and from Etnyre-Honda for the torus knot 8_19 = T(3, 4) and its mirror
+ - the peaks of the mountain range, the (tb, rot) of the representatives that aren't stabilizations, where they're known: the unknot (Eliashberg-Fraser),
+   torus knots 3_1, 5_1, 7_1, 8_19, 9_1 and the figure eight knot (Etnyre-Honda) are legendrian simple, with rotation numbers at maximal tb of 0 for positive
+   torus knots and ±(q - p - 2np), 0 <= n < q/p - 1, for the negative torus knot T(p, -q), and their only peaks there
+ - 5_2, 7_2, 7_3, 7_4, 7_5, 8_15, 9_2 - 9_7, 9_9, 9_10, 9_13, 9_16, 9_18, 9_23, 9_35 and 9_38 (in the chiralities of knots.rs) are positive knots, so their
+   maximal tb is 2g - 1, which forces rotation number 0
+ - 5_2 is not legendrian simple (Chekanov's pair of tb = 1 representatives)
+Below its peaks, a mountain range is the stabilizations of the peaks: from (tb, rot), (tb - k, rot + j) for every |j| <= k with j = k mod 2.
+Every representative also satisfies the slice-Bennequin inequality tb + |rot| <= 2 g4 - 1 (Rudolph), with the 4-genus of knots.rs where it has it, so for
+ positive knots (where g4 = g) every (tb, rot) within it is a stabilization of the peak.
+The maximal tb of the other non-alternating knots (8_20, 8_21 and 9_42 - 9_49) is stored as unknown, as are their peaks.
+Which rotation numbers the other knots reach at maximal tb and the conjectured mountain ranges of Chongchitmate-Ng's atlas aren't recorded, so for
+ those only contradictions are found.
+Rotation numbers are stored up to sign (i.e. as |rot|), matching census records.
+*/
+
+use crate::knots::slice_genus;
+
+pub struct AtlasEntry {
+    pub name: &'static str,
+    //The maximal tb, if it's known
+    pub max_tb: Option<i32>,
+    //The peaks of the mountain range as (tb, |rot|), if they're all known
+    pub peaks: Option<&'static [(i32, i32)]>,
+    pub simple: Option<bool>,
+}
+
+const fn entry(name: &'static str, max_tb: Option<i32>, peaks: Option<&'static [(i32, i32)]>, simple: Option<bool>) -> AtlasEntry {
+    AtlasEntry { name, max_tb, peaks, simple }
+}
+
+//A knot whose maximal tb is all that's recorded
+const fn alternating(name: &'static str, max_tb: i32) -> AtlasEntry {
+    entry(name, Some(max_tb), None, None)
+}
+
+//A knot about which nothing is recorded
+const fn unknown(name: &'static str) -> AtlasEntry {
+    entry(name, None, None, None)
+}
+
+pub const ATLAS: &[AtlasEntry] = &[
+    entry("0_1", Some(-1), Some(&[(-1, 0)]), Some(true)),
+    entry("3_1", Some(1), Some(&[(1, 0)]), Some(true)),
+    entry("m(3_1)", Some(-6), Some(&[(-6, 1)]), Some(true)),
+    entry("4_1", Some(-3), Some(&[(-3, 0)]), Some(true)),
+    entry("5_1", Some(3), Some(&[(3, 0)]), Some(true)),
+    entry("m(5_1)", Some(-10), Some(&[(-10, 1), (-10, 3)]), Some(true)),
+    entry("5_2", Some(1), Some(&[(1, 0)]), Some(false)),
+    alternating("m(5_2)", -8),
+    alternating("6_1", -3),
+    alternating("m(6_1)", -5),
+    alternating("6_2", -1),
+    alternating("m(6_2)", -7),
+    alternating("6_3", -4),
+    entry("7_1", Some(5), Some(&[(5, 0)]), Some(true)),
+    entry("m(7_1)", Some(-14), Some(&[(-14, 1), (-14, 3), (-14, 5)]), Some(true)),
+    entry("7_2", Some(1), Some(&[(1, 0)]), None),
+    alternating("m(7_2)", -10),
+    entry("7_3", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(7_3)", -12),
+    entry("7_4", Some(1), Some(&[(1, 0)]), None),
+    alternating("m(7_4)", -10),
+    entry("7_5", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(7_5)", -12),
+    alternating("7_6", -1),
+    alternating("m(7_6)", -8),
+    alternating("7_7", -4),
+    alternating("m(7_7)", -5),
+    alternating("8_1", -3),
+    alternating("m(8_1)", -7),
+    alternating("8_2", 1),
+    alternating("m(8_2)", -11),
+    alternating("8_3", -5),
+    alternating("8_4", -3),
+    alternating("m(8_4)", -7),
+    alternating("8_5", 1),
+    alternating("m(8_5)", -11),
+    alternating("8_6", -1),
+    alternating("m(8_6)", -9),
+    alternating("8_7", -2),
+    alternating("m(8_7)", -8),
+    alternating("8_8", -4),
+    alternating("m(8_8)", -6),
+    alternating("8_9", -5),
+    alternating("8_10", -2),
+    alternating("m(8_10)", -8),
+    alternating("8_11", -1),
+    alternating("m(8_11)", -9),
+    alternating("8_12", -5),
+    alternating("8_13", -4),
+    alternating("m(8_13)", -6),
+    alternating("8_14", -1),
+    alternating("m(8_14)", -9),
+    entry("8_15", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(8_15)", -13),
+    alternating("8_16", -2),
+    alternating("m(8_16)", -8),
+    alternating("8_17", -5),
+    alternating("8_18", -5),
+    entry("8_19", Some(5), Some(&[(5, 0)]), Some(true)),
+    entry("m(8_19)", Some(-12), Some(&[(-12, 1)]), Some(true)),
+    unknown("8_20"),
+    unknown("m(8_20)"),
+    unknown("8_21"),
+    unknown("m(8_21)"),
+    entry("9_1", Some(7), Some(&[(7, 0)]), Some(true)),
+    entry("m(9_1)", Some(-18), Some(&[(-18, 1), (-18, 3), (-18, 5), (-18, 7)]), Some(true)),
+    entry("9_2", Some(1), Some(&[(1, 0)]), None),
+    alternating("m(9_2)", -12),
+    entry("9_3", Some(5), Some(&[(5, 0)]), None),
+    alternating("m(9_3)", -16),
+    entry("9_4", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(9_4)", -14),
+    entry("9_5", Some(1), Some(&[(1, 0)]), None),
+    alternating("m(9_5)", -12),
+    entry("9_6", Some(5), Some(&[(5, 0)]), None),
+    alternating("m(9_6)", -16),
+    entry("9_7", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(9_7)", -14),
+    alternating("9_8", -3),
+    alternating("m(9_8)", -8),
+    entry("9_9", Some(5), Some(&[(5, 0)]), None),
+    alternating("m(9_9)", -16),
+    entry("9_10", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(9_10)", -14),
+    alternating("9_11", 1),
+    alternating("m(9_11)", -12),
+    alternating("9_12", -1),
+    alternating("m(9_12)", -10),
+    entry("9_13", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(9_13)", -14),
+    alternating("9_14", -4),
+    alternating("m(9_14)", -7),
+    alternating("9_15", -1),
+    alternating("m(9_15)", -10),
+    entry("9_16", Some(5), Some(&[(5, 0)]), None),
+    alternating("m(9_16)", -16),
+    alternating("9_17", -3),
+    alternating("m(9_17)", -8),
+    entry("9_18", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(9_18)", -14),
+    alternating("9_19", -5),
+    alternating("m(9_19)", -6),
+    alternating("9_20", 1),
+    alternating("m(9_20)", -12),
+    alternating("9_21", -1),
+    alternating("m(9_21)", -10),
+    alternating("9_22", -3),
+    alternating("m(9_22)", -8),
+    entry("9_23", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(9_23)", -14),
+    alternating("9_24", -5),
+    alternating("m(9_24)", -6),
+    alternating("9_25", -1),
+    alternating("m(9_25)", -10),
+    alternating("9_26", -2),
+    alternating("m(9_26)", -9),
+    alternating("9_27", -5),
+    alternating("m(9_27)", -6),
+    alternating("9_28", -2),
+    alternating("m(9_28)", -9),
+    alternating("9_29", -3),
+    alternating("m(9_29)", -8),
+    alternating("9_30", -5),
+    alternating("m(9_30)", -6),
+    alternating("9_31", -2),
+    alternating("m(9_31)", -9),
+    alternating("9_32", -2),
+    alternating("m(9_32)", -9),
+    alternating("9_33", -5),
+    alternating("m(9_33)", -6),
+    alternating("9_34", -5),
+    alternating("m(9_34)", -6),
+    entry("9_35", Some(1), Some(&[(1, 0)]), None),
+    alternating("m(9_35)", -12),
+    alternating("9_36", 1),
+    alternating("m(9_36)", -12),
+    alternating("9_37", -5),
+    alternating("m(9_37)", -6),
+    entry("9_38", Some(3), Some(&[(3, 0)]), None),
+    alternating("m(9_38)", -14),
+    alternating("9_39", -1),
+    alternating("m(9_39)", -10),
+    alternating("9_40", -2),
+    alternating("m(9_40)", -9),
+    alternating("9_41", -4),
+    alternating("m(9_41)", -7),
+    unknown("9_42"),
+    unknown("9_43"),
+    unknown("m(9_43)"),
+    unknown("9_44"),
+    unknown("m(9_44)"),
+    unknown("9_45"),
+    unknown("m(9_45)"),
+    unknown("9_46"),
+    unknown("m(9_46)"),
+    unknown("9_47"),
+    unknown("m(9_47)"),
+    unknown("9_48"),
+    unknown("m(9_48)"),
+    unknown("9_49"),
+    unknown("m(9_49)"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AtlasMatch {
+    //(tb, rot) is realized by a known legendrian representative
+    Known,
+    //(tb, rot) isn't a stabilization of any known peak, so may be a new class (or a destabilizable diagram of a new peak)
+    PotentiallyNew,
+    //(tb, rot) contradicts the classification -- this indicates a misidentified knot or an incorrect invariant
+    Contradiction,
+    //Not enough is recorded about the knot to decide
+    Undetermined,
+}
+
+impl AtlasMatch {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AtlasMatch::Known => "known",
+            AtlasMatch::PotentiallyNew => "new?",
+            AtlasMatch::Contradiction => "contradiction",
+            AtlasMatch::Undetermined => "-",
+        }
+    }
+}
+
+pub fn lookup(name: &str) -> Option<&'static AtlasEntry> {
+    ATLAS.iter().find(|entry| entry.name == name)
+}
+
+//Checks whether a legendrian representative of the named knot with the given invariants belongs to a known class
+pub fn check(name: &str, tb: i32, rot: i32) -> AtlasMatch {
+    let entry = match lookup(name) {
+        Some(entry) => entry,
+        None => return AtlasMatch::Undetermined,
+    };
+    //tb + rot is always odd for knots
+    let slice_bennequin = slice_genus(name).is_some_and(|genus| tb + rot.abs() > 2 * genus as i32 - 1);
+    if entry.max_tb.is_some_and(|max_tb| tb > max_tb) || (tb + rot) % 2 == 0 || slice_bennequin {
+        return AtlasMatch::Contradiction;
+    }
+    match entry.peaks {
+        //Stabilizing a peak (tb0, r0) reaches every (tb, rot) with tb <= tb0 - |rot - r0|
+        Some(peaks) => {
+            if peaks.iter().any(|&(tb0, r0)| tb <= tb0 - (rot.abs() - r0).abs()) {
+                AtlasMatch::Known
+            } else if entry.simple == Some(true) {
+                AtlasMatch::Contradiction
+            } else {
+                AtlasMatch::PotentiallyNew
+            }
+        }
+        None => AtlasMatch::Undetermined,
+    }
+}
+
+/* The Legendrian unknot with the given tb and rot (which determine it, by Eliashberg-Fraser) as (positive, negative) stabilizations of the standard tb = -1 unknot,
+ for the orientation whose rotation number is at least 0, or None if no Legendrian unknot has that tb and rot. */
+pub fn unknot_stabilizations(tb: i32, rot: i32) -> Option<(usize, usize)> {
+    let (stabilizations, rot) = (-1 - tb, rot.abs());
+    if stabilizations < rot || (stabilizations + rot) % 2 != 0 {
+        return None;
+    }
+    Some((((stabilizations + rot) / 2) as usize, ((stabilizations - rot) / 2) as usize))
+}
+
+//Labels an unknot by its stabilizations, like S+^2 S- for two positive and one negative, or "standard" for the unknot with tb = -1
+pub fn unknot_label((positive, negative): (usize, usize)) -> String {
+    let power = |sign: &str, count: usize| match count {
+        0 => None,
+        1 => Some(format!("S{}", sign)),
+        count => Some(format!("S{}^{}", sign, count)),
+    };
+    let stabilizations: Vec<String> = [power("+", positive), power("-", negative)].into_iter().flatten().collect();
+    if stabilizations.is_empty() {
+        "standard".to_string()
+    } else {
+        stabilizations.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knots::table_order;
+
+    #[test]
+    fn alternating_knots_and_mirrors_have_tb_summing_to_minus_c_minus_2() {
+        for entry in ATLAS.iter().filter(|entry| !entry.name.starts_with("m(") && !["0_1", "8_19"].contains(&entry.name)) {
+            let Some(max_tb) = entry.max_tb else { continue };
+            let crossings = table_order(entry.name).0 as i32;
+            //Amphichiral knots are their own mirrors
+            let mirror = lookup(&format!("m({})", entry.name)).map_or(Some(max_tb), |mirror| mirror.max_tb);
+            assert_eq!(mirror.map(|mirror| max_tb + mirror), Some(-crossings - 2), "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn every_knot_through_9_crossings_has_an_entry() {
+        for (crossings, knots) in [(3, 1), (4, 1), (5, 2), (6, 3), (7, 7), (8, 21), (9, 49)] {
+            for index in 1..=knots {
+                assert!(lookup(&format!("{}_{}", crossings, index)).is_some(), "{}_{}", crossings, index);
+            }
+        }
+        assert!(lookup("m(9_49)").is_some_and(|entry| entry.max_tb.is_none() && entry.peaks.is_none() && entry.simple.is_none()));
+    }
+
+    #[test]
+    fn unknown_maximal_tb_only_checks_parity() {
+        assert_eq!(check("9_2", 1, 0), AtlasMatch::Known);
+        assert_eq!(check("9_2", 3, 0), AtlasMatch::Contradiction);
+        assert_eq!(check("m(9_1)", -18, 3), AtlasMatch::Known);
+        assert_eq!(check("m(9_1)", -18, 9), AtlasMatch::Contradiction);
+        assert_eq!(check("9_14", -4, 1), AtlasMatch::Undetermined);
+        assert_eq!(check("9_47", 5, 0), AtlasMatch::Undetermined);
+        assert_eq!(check("9_47", 4, 0), AtlasMatch::Contradiction);
+    }
+}
@@ -0,0 +1,153 @@
+/*
+An atlas of the knot types in a classified census (see census.rs), written as an HTML document with a page for each knot type, in order of crossing number.
+Each page has the knot type's mountain range: an SVG plot of the (tb, rot) its Legendrian representatives in the census realize, with rot taken both ways
+ since census records give it up to orientation, and lines joining classes a stabilization apart, (tb, r) to (tb - 1, r ± 1) -- the shape the classical
+ invariants give the classification, though a point can stand for several classes (as for 5_2). Where atlas.rs records the knot type's maximal tb, it's drawn
+ as a dashed line, with the rotation numbers known there ringed. Then come images of the smallest mosaic of each (tb, |rot|) (the fewest tiles of its trimmed
+ board, then the fewest nonblank tiles and crossings), highest tb first, and a table of every class realized with its number of mosaics and atlas comparison.
+Only knots with names are included, so links, virtual knots and unidentified knots are left out.
+*/
+
+use crate::atlas::{self, lookup};
+use crate::knots::table_order;
+use crate::mosaic::Mosaic;
+use crate::output;
+use crate::render::to_svg;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Result, Write};
+
+//Spacing of the mountain range plot's grid, and its margin, in pixels
+const STEP: i32 = 32;
+const MARGIN: i32 = 40;
+
+//The mosaics of a knot type in the census, by (tb, |rot|): how many there are and the smallest (trimmed)
+#[derive(Default)]
+struct KnotType {
+    classes: BTreeMap<(i32, i32), (u64, Mosaic)>,
+    mosaics: u64,
+}
+
+//An id for the page of a knot type, for links within the document
+fn anchor(name: &str) -> String {
+    name.replace("m(", "m-").replace(')', "")
+}
+
+//Reads the knots of a classified census by knot type
+fn read_knot_types(input_path: &str) -> Result<(HashMap<String, KnotType>, u64)> {
+    let mut knot_types: HashMap<String, KnotType> = HashMap::new();
+    let mut records = 0;
+    for line in BufReader::new(File::open(input_path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(" | ").map(str::trim).collect();
+        if fields.len() < 20 {
+            return Err(Error::new(ErrorKind::InvalidData, format!("{} isn't a census record", line)));
+        }
+        records += 1;
+        let name = fields[17];
+        if fields[1] != "1" || name == "-" {
+            continue;
+        }
+        let number = |field: &str| field.parse::<i32>().map_err(|_| Error::new(ErrorKind::InvalidData, format!("{} isn't a census record", line)));
+        let (tb, rot) = (number(fields[15])?, number(fields[16])?);
+        let mosaic = fields[0].parse::<Mosaic>()?.trim();
+        let knot_type = knot_types.entry(name.to_string()).or_default();
+        knot_type.mosaics += 1;
+        let (count, smallest) = knot_type.classes.entry((tb, rot)).or_insert((0, mosaic.clone()));
+        *count += 1;
+        if mosaic.smallness() < smallest.smallness() {
+            *smallest = mosaic;
+        }
+    }
+    Ok((knot_types, records))
+}
+
+//The mountain range of a knot type (see above), as an SVG image
+fn mountain_range(name: &str, knot_type: &KnotType) -> String {
+    let entry = lookup(name);
+    let realized: BTreeSet<(i32, i32)> = knot_type.classes.keys().flat_map(|&(tb, rot)| [(tb, rot), (tb, -rot)]).collect();
+    let peaks: Vec<(i32, i32)> =
+        entry.and_then(|entry| entry.peaks.map(|peaks| peaks.iter().flat_map(|&(tb, rot)| [(tb, rot), (tb, -rot)]).collect())).unwrap_or_default();
+    let points = || realized.iter().chain(&peaks);
+    let top = points().map(|&(tb, _)| tb).chain(entry.and_then(|entry| entry.max_tb)).max().unwrap_or(0);
+    let bottom = points().map(|&(tb, _)| tb).min().unwrap_or(0);
+    let reach = points().map(|&(_, rot)| rot.abs()).max().unwrap_or(0);
+    let position = |(tb, rot): (i32, i32)| (MARGIN + (rot + reach) * STEP, MARGIN + (top - tb) * STEP);
+    let (width, height) = (2 * MARGIN + 2 * reach * STEP, 2 * MARGIN + (top - bottom) * STEP);
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"mountain-range\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {0} {1}\">\n", width, height);
+    for tb in bottom..=top {
+        let (_, y) = position((tb, -reach));
+        svg += &format!("<text x=\"{}\" y=\"{}\" font-size=\"12\" text-anchor=\"end\" dominant-baseline=\"middle\">{}</text>\n", MARGIN - 12, y, tb);
+    }
+    for rot in -reach..=reach {
+        let (x, _) = position((bottom, rot));
+        svg += &format!("<text x=\"{}\" y=\"{}\" font-size=\"12\" text-anchor=\"middle\">{}</text>\n", x, height - MARGIN / 2, rot);
+    }
+    svg += &format!("<text x=\"4\" y=\"14\" font-size=\"12\">tb</text>\n<text x=\"{}\" y=\"{}\" font-size=\"12\" text-anchor=\"end\">rot</text>\n", width - 4, height - 4);
+    if let Some(max_tb) = entry.and_then(|entry| entry.max_tb) {
+        let ((left, y), (right, _)) = (position((max_tb, -reach)), position((max_tb, reach)));
+        svg += &format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{1}\" stroke=\"#c44\" stroke-dasharray=\"4 4\"><title>maximal tb {}</title></line>\n", left - STEP / 4, y, right + STEP / 4, max_tb);
+    }
+    for &(tb, rot) in &realized {
+        for below in [(tb - 1, rot - 1), (tb - 1, rot + 1)].into_iter().filter(|below| realized.contains(below)) {
+            let ((x1, y1), (x2, y2)) = (position((tb, rot)), position(below));
+            svg += &format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#888\"/>\n", x1, y1, x2, y2);
+        }
+    }
+    for &peak in &peaks {
+        let (x, y) = position(peak);
+        svg += &format!("<circle cx=\"{}\" cy=\"{}\" r=\"9\" fill=\"none\" stroke=\"#c44\" stroke-width=\"2\"><title>known peak</title></circle>\n", x, y);
+    }
+    for &(tb, rot) in &realized {
+        let (x, y) = position((tb, rot));
+        let count = knot_type.classes[&(tb, rot.abs())].0;
+        svg += &format!("<circle cx=\"{}\" cy=\"{}\" r=\"5\"><title>tb {}, rot {}: {} mosaics</title></circle>\n", x, y, tb, rot, count);
+    }
+    svg + "</svg>\n"
+}
+
+//Writes a knot type's page (see above), with images of the smallest mosaics of up to `representatives` classes
+fn write_page(output: &mut dyn Write, name: &str, knot_type: &KnotType, representatives: usize) -> Result<()> {
+    writeln!(output, "<section id=\"{}\">\n<h2>{}</h2>", anchor(name), name)?;
+    let max_tb = knot_type.classes.keys().map(|&(tb, _)| tb).max().unwrap_or_default();
+    let known = lookup(name).and_then(|entry| entry.max_tb).map_or(String::new(), |max_tb| format!(" (the atlas has {})", max_tb));
+    writeln!(output, "<p>{} (tb, |rot|) realized by {} mosaics, with maximal tb {}{}.</p>", knot_type.classes.len(), knot_type.mosaics, max_tb, known)?;
+    write!(output, "{}", mountain_range(name, knot_type))?;
+    //Highest tb first, then least |rot|
+    let mut classes: Vec<_> = knot_type.classes.iter().collect();
+    classes.sort_by_key(|&(&(tb, rot), _)| (-tb, rot));
+    writeln!(output, "<div class=\"representatives\">")?;
+    for (&(tb, rot), (_, mosaic)) in classes.iter().take(representatives) {
+        writeln!(output, "<figure>\n{}<figcaption>tb {}, |rot| {}<br><code>{}</code></figcaption>\n</figure>", to_svg(mosaic), tb, rot, mosaic)?;
+    }
+    writeln!(output, "</div>\n<table>\n<tr><th>tb</th><th>|rot|</th><th>mosaics</th><th>smallest</th><th>atlas</th></tr>")?;
+    for (&(tb, rot), (count, mosaic)) in &classes {
+        writeln!(output, "<tr><td>{}</td><td>{}</td><td>{}</td><td><code>{}</code></td><td>{}</td></tr>", tb, rot, count, mosaic, atlas::check(name, tb, rot).label())?;
+    }
+    writeln!(output, "</table>\n</section>")
+}
+
+/* Writes the atlas of a classified census (see above) to an output, showing the smallest mosaics of up to `representatives` classes of each knot type.
+Returns the number of knot types and of census records read. */
+pub fn write_atlas(input_path: &str, output_path: &str, representatives: usize) -> Result<(usize, u64)> {
+    let (knot_types, records) = read_knot_types(input_path)?;
+    let mut names: Vec<&String> = knot_types.keys().collect();
+    names.sort_by_key(|name| table_order(name));
+    let mut output = output::create(output_path)?;
+    writeln!(output, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Legendrian atlas of {}</title>", input_path)?;
+    writeln!(output, "<style>\nsection {{ break-before: page }}\n.representatives svg {{ width: 8em; height: 8em }}\nfigure {{ display: inline-block; text-align: center }}\ntd, th {{ padding: 0 1em }}\n</style>\n</head>\n<body>")?;
+    writeln!(output, "<h1>Legendrian atlas</h1>\n<p>{} knot types among the {} records of {}:</p>\n<ul>", names.len(), records, input_path)?;
+    for name in &names {
+        writeln!(output, "<li><a href=\"#{}\">{}</a> ({} mosaics)</li>", anchor(name), name, knot_types[*name].mosaics)?;
+    }
+    writeln!(output, "</ul>")?;
+    for name in &names {
+        write_page(&mut output, name, &knot_types[*name], representatives)?;
+    }
+    writeln!(output, "</body>\n</html>")?;
+    output.finish()?;
+    Ok((names.len(), records))
+}
@@ -0,0 +1,205 @@
+/*
+Benchmarks of the ways of enumerating the mosaics of a size against each other, so changes to one can be measured reproducibly. The engines are
+    dfs           the tile by tile depth first search of generator.rs, one thread stepping through every mosaic
+    dfs-parallel  the same search split between threads as generate --threads splits it (see spawn_shards), the threads taking the first rows of the mosaics in turn
+                   (pinned to CPUs with a placement, see affinity.rs) and sending them back in packed batches, read in order as generate writes them
+    visit         the same search calling a closure with each mosaic (see generate_with), to show what handing out mosaics costs over stepping through them
+    extend        extending every open block a size smaller with a column and a row (see extend.rs), each boundary profile's extensions found once
+    profiles      the boundary profile automaton of extend.rs, carrying the number of open blocks with each profile through the tiles a size at a time
+The searches step through each mosaic without writing it, and the automaton only counts them, so it isn't doing the same work -- it's there to show what
+ counting without enumerating costs, and the table marks it as counting only rather than giving it a speedup. Each engine runs some warm-up times unmeasured,
+ then is timed over some repetitions, and the table gives the fastest, median and mean time, the mosaics a second at the median and the speedup over
+ the median of the first engine that enumerates the mosaics. Every engine should count the same mosaics,
+ and a run where they don't is an error once the table's printed.
+*/
+
+use crate::affinity::{self, Placement};
+use crate::extend::{count_by_profiles, count_mosaics, MAX_BLOCK_SIZE};
+use crate::generator::{generate_with, spawn_shards, GeneratorConfig, MosaicGenerator};
+use std::fmt;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::num::NonZeroUsize;
+use std::ops::ControlFlow;
+use std::str::FromStr;
+use std::sync::atomic::AtomicU64;
+use std::thread;
+use std::time::Instant;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Engine {
+    Dfs,
+    ParallelDfs,
+    Visit,
+    Extend,
+    Profiles,
+}
+
+pub const ENGINES: [Engine; 5] = [Engine::Dfs, Engine::ParallelDfs, Engine::Visit, Engine::Extend, Engine::Profiles];
+
+impl FromStr for Engine {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Engine> {
+        ENGINES
+            .into_iter()
+            .find(|engine| engine.to_string() == name)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("unknown engine {} (the engines are dfs, dfs-parallel, visit, extend and profiles)", name)))
+    }
+}
+
+impl Engine {
+    //Whether the engine counts the mosaics without enumerating them, so has no speedup over the engines that do
+    pub fn counts_only(self) -> bool {
+        self == Engine::Profiles
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            Engine::Dfs => "dfs",
+            Engine::ParallelDfs => "dfs-parallel",
+            Engine::Visit => "visit",
+            Engine::Extend => "extend",
+            Engine::Profiles => "profiles",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BenchOptions {
+    pub engines: Vec<Engine>,
+    pub warm_up: usize,
+    pub repetitions: usize,
+    pub threads: usize,
+    pub placement: Placement,
+}
+
+impl Default for BenchOptions {
+    fn default() -> BenchOptions {
+        BenchOptions { engines: ENGINES.to_vec(), warm_up: 1, repetitions: 5, threads: thread::available_parallelism().map_or(1, NonZeroUsize::get), placement: Placement::Unpinned }
+    }
+}
+
+//The timings of an engine: the mosaics it counted and the seconds each repetition took
+pub struct Timing {
+    pub engine: Engine,
+    pub threads: usize,
+    pub mosaics: u128,
+    pub seconds: Vec<f64>,
+}
+
+impl Timing {
+    fn median(&self) -> f64 {
+        let mut seconds = self.seconds.clone();
+        seconds.sort_by(f64::total_cmp);
+        let middle = seconds.len() / 2;
+        if seconds.len().is_multiple_of(2) { (seconds[middle - 1] + seconds[middle]) / 2.0 } else { seconds[middle] }
+    }
+}
+
+//Enumerates the size x size mosaics with an engine, returning how many there are, with the threads of dfs-parallel placed by `placement`
+fn run(engine: Engine, size: usize, threads: usize, placement: Placement) -> Result<u128> {
+    Ok(match engine {
+        Engine::Dfs => {
+            let mut generator = MosaicGenerator::new(size);
+            let mut count = 0;
+            while generator.advance().is_some() {
+                count += 1;
+            }
+            count
+        }
+        Engine::ParallelDfs => {
+            let config = GeneratorConfig::new(size, "-").with_threads(threads).with_placement(placement);
+            let (constraints, backtracks, nodes) = (config.constraints(), AtomicU64::new(0), AtomicU64::new(0));
+            thread::scope(|scope| -> Result<u128> {
+                let mut shards = spawn_shards(scope, &config, &constraints, &backtracks, &nodes)?;
+                let mut count = 0;
+                while shards.advance().is_some() {
+                    count += 1;
+                }
+                Ok(count)
+            })?
+        }
+        Engine::Visit => match generate_with(size, |_| ControlFlow::<()>::Continue(())) {
+            ControlFlow::Continue(count) => count as u128,
+            ControlFlow::Break(()) => unreachable!("the benchmark's visitor never breaks"),
+        },
+        Engine::Extend => count_mosaics(size) as u128,
+        Engine::Profiles => count_by_profiles(size),
+    })
+}
+
+//Times each engine on the size x size mosaics
+pub fn bench(size: usize, options: &BenchOptions) -> Result<Vec<Timing>> {
+    if size == 0 || size > MAX_BLOCK_SIZE {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("benchmarks are of sizes 1 to {}", MAX_BLOCK_SIZE)));
+    }
+    //A placement that can't be made fails before anything's timed
+    affinity::cpus(options.placement, options.threads)?;
+    let mut timings = Vec::new();
+    for &engine in &options.engines {
+        let threads = if engine == Engine::ParallelDfs { options.threads } else { 1 };
+        for _ in 0..options.warm_up {
+            run(engine, size, threads, options.placement)?;
+        }
+        let mut timing = Timing { engine, threads, mosaics: 0, seconds: Vec::new() };
+        for _ in 0..options.repetitions.max(1) {
+            let started = Instant::now();
+            timing.mosaics = run(engine, size, threads, options.placement)?;
+            timing.seconds.push(started.elapsed().as_secs_f64());
+        }
+        timings.push(timing);
+    }
+    Ok(timings)
+}
+
+//Writes the comparison table of a benchmark (see above), as tab separated values
+pub fn write_table(output: &mut dyn Write, size: usize, timings: &[Timing]) -> Result<()> {
+    writeln!(output, "engine\tthreads\tmosaics\tfastest\tmedian\tmean\tmosaics/s\tspeedup")?;
+    let baseline = timings.iter().find(|timing| !timing.engine.counts_only()).map_or(1.0, Timing::median);
+    for timing in timings {
+        let (fastest, median) = (timing.seconds.iter().copied().fold(f64::INFINITY, f64::min), timing.median());
+        let mean = timing.seconds.iter().sum::<f64>() / timing.seconds.len() as f64;
+        writeln!(
+            output,
+            "{}\t{}\t{}\t{:.6}\t{:.6}\t{:.6}\t{:.0}\t{}",
+            timing.engine,
+            timing.threads,
+            timing.mosaics,
+            fastest,
+            median,
+            mean,
+            timing.mosaics as f64 / median,
+            if timing.engine.counts_only() { "counts only".to_string() } else { format!("{:.2}", baseline / median) }
+        )?;
+    }
+    if let Some(timing) = timings.iter().find(|timing| timing.mosaics != timings[0].mosaics) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("{} counted {} {}x{} mosaics, but {} counted {}", timing.engine, timing.mosaics, size, size, timings[0].engine, timings[0].mosaics),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn engines_agree_and_profiles_is_marked() {
+        let options = BenchOptions { warm_up: 0, repetitions: 1, threads: 3, ..BenchOptions::default() };
+        let timings = bench(4, &options).unwrap();
+        assert!(timings.iter().all(|timing| timing.mosaics == timings[0].mosaics));
+        //Virtual crossings add 4x4 mosaics
+        #[cfg(not(feature = "virtual"))]
+        assert_eq!(timings[0].mosaics, 1504);
+        let mut table = Vec::new();
+        write_table(&mut table, 4, &timings).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        let profiles = table.lines().find(|line| line.starts_with("profiles\t")).unwrap();
+        assert!(profiles.ends_with("\tcounts only"));
+        assert!(table.lines().find(|line| line.starts_with("dfs\t")).unwrap().ends_with("\t1.00"));
+    }
+}
@@ -0,0 +1,56 @@
+/*
+The Bennequin inequality as an end-to-end check of the invariant pipeline.
+Every Legendrian link L bounding an oriented surface Σ has tb(L) + |rot(L)| ≤ -χ(Σ) (Bennequin), and the slice-Bennequin inequality (Rudolph) sharpens this for knots to
+ tb(K) + |rot(K)| ≤ 2g_s(K) - 1, where g_s is the smooth 4-genus of the knot.
+The Seifert surface of a mosaic's diagram (in the traversed orientation, for links) has -χ = crossings - seifert circles, and named knots also get the bound from their slice genus (see knots.rs),
+ so a violation means one of tb, rot, the Seifert circles or the knot name was computed wrongly.
+*/
+
+use crate::diagram::Diagram;
+use crate::invariants::jones;
+use crate::knots::{knot_name, slice_genus};
+use crate::mosaic::{Mosaic, VIRTUAL_CROSSING};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Result};
+
+//The best upper bound on tb + |rot| for a classical mosaic, and where it came from
+fn bennequin_bound(diagram: &Diagram) -> Option<(i32, String)> {
+    let seifert = diagram.crossings.len() as i32 - diagram.seifert_circles() as i32;
+    let mut bound = (seifert, "seifert surface".to_string());
+    if diagram.component_count() == 1 {
+        if let Some((name, genus)) = knot_name(&jones(diagram)?).and_then(|name| slice_genus(&name).map(|genus| (name, genus))) {
+            if 2 * genus as i32 - 1 < bound.0 {
+                bound = (2 * genus as i32 - 1, format!("slice genus of {}", name));
+            }
+        }
+    }
+    Some(bound)
+}
+
+/* Checks the Bennequin inequality (see above) for every classical mosaic in the input file, which can be a file of mosaics or of census records, printing each violation.
+Returns the number of mosaics checked, or an InvalidData error if any of them violate it. */
+pub fn check_bennequin(input_path: &str) -> Result<usize> {
+    let (mut checked, mut violations) = (0, 0);
+    for line in BufReader::new(File::open(input_path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mosaic: Mosaic = line.split(" | ").next().unwrap_or_default().trim().parse()?;
+        if mosaic.tiles().contains(&VIRTUAL_CROSSING) {
+            continue;
+        }
+        if let Some((diagram, (bound, source))) = Diagram::trace(&mosaic).and_then(|diagram| bennequin_bound(&diagram).map(|bound| (diagram, bound))) {
+            let (tb, rot) = (diagram.tb(), diagram.rot());
+            if tb + rot.abs() > bound {
+                println!("{}: tb = {}, rot = {}, but tb + |rot| ≤ {} from the {}", mosaic, tb, rot, bound, source);
+                violations += 1;
+            }
+            checked += 1;
+        }
+    }
+    if violations > 0 {
+        return Err(Error::new(ErrorKind::InvalidData, format!("{} of {} mosaics violate the Bennequin inequality", violations, checked)));
+    }
+    Ok(checked)
+}
@@ -0,0 +1,9 @@
+//Compiles mosaics.proto into the gRPC service used by grpc.rs when the `grpc` feature is enabled (with protox, so protoc needn't be installed)
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=mosaics.proto");
+        let descriptors = protox::compile(["mosaics.proto"], ["."]).expect("mosaics.proto should compile");
+        tonic_build::configure().build_client(false).compile_fds(descriptors).expect("gRPC service generation failed");
+    }
+}
@@ -0,0 +1,145 @@
+/*
+An on-disk cache of the census's most expensive invariants -- the jones polynomial, the ruling polynomial and the augmentation number -- keyed by a hash of the mosaic's canonical form,
+ so they're computed once for all the mosaics equivalent to one another, and once across runs sharing a cache file.
+Translating a mosaic around the board keeps its diagram (and the orientation its components are traversed in), so every mosaic is keyed by its translation to the top left corner.
+The Legendrian symmetries (see symmetry.rs) keep the knot type and Legendrian type of a knot up to mirroring, which changes none of these invariants,
+ so knots are keyed by the least of the translations of their images; links aren't, since a symmetry can change the orientations their components are traversed in,
+ and neither are virtual knots.
+The cache file is tab separated, after a comment line: the first 16 hex digits of the SHA-256 hash of the canonical mosaic code, then each invariant,
+ with polynomials written as their lowest exponent and coefficients (e.g. 1:1,0,1,-1 for t + t^3 - t^4, in the t^(1/2) of invariants::jones) and - where undefined.
+New entries are appended to the file as they're computed, and the cache can be shared by the threads of a census (see pipeline.rs).
+*/
+
+use crate::dga::augmentation_number;
+use crate::diagram::Diagram;
+use crate::invariants::{jones, Polynomial};
+use crate::mosaic::VIRTUAL_CROSSING;
+use crate::ruling::ruling_polynomial;
+use crate::symmetry::{canonical, cornered};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Result, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const HEADER: &str = "#mosaic_gen invariant cache";
+
+#[derive(Clone)]
+pub struct Polynomials {
+    pub jones: Polynomial,
+    pub ruling: Option<Polynomial>,
+    pub augmentations: Option<String>,
+}
+
+impl Polynomials {
+    //Computes the invariants of a diagram, or None for the empty diagram
+    pub fn compute(diagram: &Diagram) -> Option<Polynomials> {
+        Some(Polynomials { jones: jones(diagram)?, ruling: ruling_polynomial(diagram), augmentations: augmentation_number(diagram) })
+    }
+}
+
+fn encode(polynomial: &Polynomial) -> String {
+    let coefficients: Vec<String> = polynomial.coefficients.iter().map(|coefficient| coefficient.to_string()).collect();
+    format!("{}:{}", polynomial.low, coefficients.join(","))
+}
+
+fn decode(field: &str) -> Option<Polynomial> {
+    let (low, coefficients) = field.split_once(':')?;
+    let coefficients = coefficients.split(',').filter(|coefficient| !coefficient.is_empty()).map(|coefficient| coefficient.parse().ok()).collect::<Option<Vec<i64>>>()?;
+    Some(Polynomial { low: low.parse().ok()?, coefficients })
+}
+
+//Reads an optional field, written - where undefined
+fn optional<T>(field: &str, read: impl Fn(&str) -> Option<T>) -> Option<Option<T>> {
+    if field == "-" { Some(None) } else { read(field).map(Some) }
+}
+
+pub struct InvariantCache {
+    entries: Mutex<HashMap<u64, Polynomials>>,
+    output: Mutex<BufWriter<File>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl InvariantCache {
+    //Opens a cache file, loading the entries it already has (or creating it)
+    pub fn open(path: &str) -> Result<InvariantCache> {
+        let mut entries = HashMap::new();
+        let exists = Path::new(path).exists();
+        if exists {
+            for (number, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+                let line = line?;
+                if line.starts_with('#') || line.trim().is_empty() {
+                    continue;
+                }
+                let invalid = || Error::new(ErrorKind::InvalidData, format!("{}:{}: not a cache entry", path, number + 1));
+                let fields: Vec<&str> = line.split('\t').collect();
+                let [key, jones, ruling, augmentations] = fields.as_slice() else {
+                    return Err(invalid());
+                };
+                let key = u64::from_str_radix(key, 16).map_err(|_| invalid())?;
+                let polynomials = Polynomials {
+                    jones: decode(jones).ok_or_else(invalid)?,
+                    ruling: optional(ruling, decode).ok_or_else(invalid)?,
+                    augmentations: optional(augmentations, |augmentations| Some(augmentations.to_string())).ok_or_else(invalid)?,
+                };
+                entries.insert(key, polynomials);
+            }
+        }
+        let mut output = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+        if !exists {
+            writeln!(output, "{}", HEADER)?;
+        }
+        Ok(InvariantCache { entries: Mutex::new(entries), output: Mutex::new(output), hits: AtomicU64::new(0), misses: AtomicU64::new(0) })
+    }
+
+    //The key of a diagram's mosaic (see above)
+    fn key(diagram: &Diagram) -> u64 {
+        let classical = !diagram.mosaic.tiles().contains(&VIRTUAL_CROSSING);
+        let canonical = if diagram.component_count() == 1 && classical { canonical(&diagram.mosaic) } else { cornered(&diagram.mosaic) };
+        let digest = Sha256::digest(canonical.to_string().as_bytes());
+        u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 digests have 32 bytes"))
+    }
+
+    /* The invariants of a diagram, from the cache if they're in it, or else computed and added to it.
+    They're computed without holding the cache, so two threads can compute the same entry at once, in which case it's only added once. */
+    pub fn get_or_compute(&self, diagram: &Diagram) -> Result<Option<Polynomials>> {
+        let key = InvariantCache::key(diagram);
+        if let Some(polynomials) = self.entries.lock().expect("the cache is never poisoned").get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(polynomials.clone()));
+        }
+        let Some(polynomials) = Polynomials::compute(diagram) else {
+            return Ok(None);
+        };
+        if self.entries.lock().expect("the cache is never poisoned").insert(key, polynomials.clone()).is_some() {
+            return Ok(Some(polynomials));
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        writeln!(
+            self.output.lock().expect("the cache is never poisoned"),
+            "{:016x}\t{}\t{}\t{}",
+            key,
+            encode(&polynomials.jones),
+            polynomials.ruling.as_ref().map_or("-".to_string(), encode),
+            polynomials.augmentations.as_deref().unwrap_or("-")
+        )?;
+        Ok(Some(polynomials))
+    }
+
+    //The number of lookups answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    //The number of entries added to the cache
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.output.lock().expect("the cache is never poisoned").flush()
+    }
+}
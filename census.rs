@@ -0,0 +1,316 @@
+/*
+Census records: the invariants of each mosaic in a file of generated mosaics, written one record per line as
+ mosaic code | components | crossings | seifert circles | genus bound | bridge number | alternating | sign | symmetries | composite | determinant | signature | grid | ruling polynomial | augmentation number | tb | rot | knot name | atlas | unknot class | jones polynomial | id
+The genus bound is the genus of the Seifert surface built by Seifert's algorithm (see Diagram::genus_bound), or "-" for virtual mosaics,
+ and the bridge number is the fewest local maxima of a height function along the rows, columns or diagonals of the mosaic (see Diagram::bridge_number).
+Alternating diagrams (see Diagram::is_alternating) are marked "alternating" in the alternating column, and others "-".
+Positive and negative diagrams, whose crossings all have the same sign (in the traversed orientations, for links), are marked "positive" or "negative" in the sign column, and others "-".
+The symmetries column lists the Legendrian symmetries fixing the mosaic up to translation (see symmetry.rs), comma separated (or "-" if there are none),
+ with a - before those taking a knot L to -L, i.e. reversing its orientation.
+Diagrams that are visibly connected sums of diagrams that could both be knotted (see Diagram::is_composite) are marked "composite", and others "-".
+The determinant and signature (of the traversed orientation, for links) are computed from Goeritz matrices (see goeritz.rs), or "-" for virtual mosaics.
+The grid column says whether the GRID invariants λ+ and λ- of a knot (see grid.rs) are nonzero, as two digits (1 where nonzero, 0 where it vanishes),
+ for the orientation whose rotation number is at least 0, or "-" for links, virtual knots and mosaics whose grid diagrams are too large.
+The ruling polynomial counts the normal rulings of the front (see ruling.rs), and is 0 if it has none, or "-" for virtual mosaics.
+The augmentation number counts the augmentations of the Chekanov-Eliashberg algebra of the front (see dga.rs), or is "-" for virtual mosaics and fronts with too many crossings and right cusps.
+The knot name is the KnotInfo name of the knot (see knots.rs), or "-" for links, virtual knots and knots that couldn't be identified.
+The atlas column compares (tb, rot) of named knots against the legendrian atlas reference data (see atlas.rs): known, new?, contradiction, or "-" if undetermined.
+Unknots are determined by (tb, rot) (Eliashberg-Fraser), so the unknot class gives the stabilizations of the standard tb = -1 unknot reaching them, like S+^2 S- (see atlas::unknot_label),
+ or is "-" for other mosaics (and for unknots whose (tb, rot) contradict the classification, which the atlas column flags). write_census also counts the unknots in each class.
+The id is the mosaic's stable ID (see Mosaic::id), for cross-referencing records with other censuses and databases.
+With --columns, the values of the named invariants (see invariant.rs) follow the id as extra columns, in the order given.
+With --cache <path>, the jones polynomial, ruling polynomial and augmentation number are kept in a cache file (see cache.rs), shared by equivalent mosaics and later runs.
+As in the python cataloguer, the rotation number of a knot is given up to orientation (i.e. as |rot|),
+ while for links it is the rotation number of the orientation each component was traversed in.
+*/
+
+use crate::atlas::{self, unknot_label, AtlasMatch};
+use crate::cache::{InvariantCache, Polynomials};
+use crate::diagram::Diagram;
+use crate::error::Result;
+use crate::generator::Constraints;
+use crate::goeritz::{determinant, signature};
+use crate::grid::grid_invariants;
+use crate::invariant::Invariant;
+use crate::invariants::Polynomial;
+use crate::knots::knot_name;
+use crate::mosaic::{read_mosaics, Mosaic, VIRTUAL_CROSSING};
+use crate::output::{self, Chunking};
+use crate::pipeline::{self, Stages};
+use crate::sink::generate_into;
+use crate::symmetry::{class_symmetries, ClassSymmetry};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::mpsc::sync_channel;
+use std::sync::Arc;
+use std::thread;
+
+//Generated mosaics a census's generating thread can get ahead of the pipeline by
+const GENERATED_BUFFER: usize = 4096;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CensusRecord {
+    pub mosaic: Mosaic,
+    pub components: usize,
+    pub crossings: usize,
+    pub seifert_circles: usize,
+    pub genus_bound: Option<usize>,
+    pub bridge_number: usize,
+    pub alternating: bool,
+    pub sign: i32,
+    pub symmetries: Vec<ClassSymmetry>,
+    pub composite: bool,
+    pub determinant: Option<u64>,
+    pub signature: Option<i32>,
+    pub grid: Option<(bool, bool)>,
+    pub ruling: Option<Polynomial>,
+    pub augmentations: Option<String>,
+    pub tb: i32,
+    pub rot: i32,
+    pub jones: Polynomial,
+    pub name: Option<String>,
+    pub atlas: AtlasMatch,
+    pub unknot: Option<(usize, usize)>,
+}
+
+//Counts of unknots by their (positive, negative) stabilizations
+pub type UnknotTally = BTreeMap<(usize, usize), u64>;
+
+impl CensusRecord {
+    //Computes the record for a mosaic, or None if the mosaic is empty or isn't suitably connected
+    pub fn new(mosaic: &Mosaic) -> Option<CensusRecord> {
+        let diagram = Diagram::trace(mosaic)?;
+        let polynomials = Polynomials::compute(&diagram)?;
+        Some(CensusRecord::with_polynomials(mosaic, &diagram, polynomials))
+    }
+
+    //Like CensusRecord::new, but taking the invariants kept in the cache from it where they're already known (see cache.rs)
+    pub fn cached(mosaic: &Mosaic, cache: &InvariantCache) -> Result<Option<CensusRecord>> {
+        let Some(diagram) = Diagram::trace(mosaic) else {
+            return Ok(None);
+        };
+        Ok(cache.get_or_compute(&diagram)?.map(|polynomials| CensusRecord::with_polynomials(mosaic, &diagram, polynomials)))
+    }
+
+    fn with_polynomials(mosaic: &Mosaic, diagram: &Diagram, polynomials: Polynomials) -> CensusRecord {
+        let Polynomials { jones, ruling, augmentations } = polynomials;
+        let components = diagram.component_count();
+        let (tb, rot) = (diagram.tb(), if components == 1 { diagram.rot().abs() } else { diagram.rot() });
+        //Virtual knots aren't in the knot table, even where their jones polynomials match a classical knot's
+        let classical = !mosaic.tiles().contains(&VIRTUAL_CROSSING);
+        let name = if components == 1 && classical { knot_name(&jones) } else { None };
+        CensusRecord {
+            mosaic: mosaic.clone(),
+            components,
+            crossings: diagram.crossings.len(),
+            seifert_circles: diagram.seifert_circles(),
+            genus_bound: if classical { Some(diagram.genus_bound()) } else { None },
+            bridge_number: diagram.bridge_number(),
+            alternating: diagram.is_alternating(),
+            sign: diagram.crossing_sign(),
+            symmetries: class_symmetries(diagram),
+            composite: diagram.is_composite(),
+            determinant: determinant(diagram),
+            signature: signature(diagram),
+            grid: grid_invariants(diagram),
+            ruling,
+            augmentations,
+            tb,
+            rot,
+            atlas: name.as_deref().map_or(AtlasMatch::Undetermined, |name| atlas::check(name, tb, rot)),
+            unknot: if name.as_deref() == Some("0_1") { atlas::unknot_stabilizations(tb, rot) } else { None },
+            name,
+            jones,
+        }
+    }
+}
+
+impl CensusRecord {
+    fn grid_label(&self) -> String {
+        self.grid.map_or("-".to_string(), |(plus, minus)| format!("{}{}", plus as u8, minus as u8))
+    }
+
+    pub fn symmetry_labels(&self) -> String {
+        if self.symmetries.is_empty() {
+            return "-".to_string();
+        }
+        self.symmetries
+            .iter()
+            .map(|fixed| format!("{}{}", if fixed.reversing == Some(true) { "-" } else { "" }, fixed.symmetry.label()))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+impl fmt::Display for CensusRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {:>3} | {:>3} | {} | {} | {} | {} | {}",
+            self.mosaic,
+            self.components,
+            self.crossings,
+            self.seifert_circles,
+            self.genus_bound.map_or("-".to_string(), |genus| genus.to_string()),
+            self.bridge_number,
+            if self.alternating { "alternating" } else { "-" },
+            match self.sign {
+                1 => "positive",
+                -1 => "negative",
+                _ => "-",
+            },
+            self.symmetry_labels(),
+            if self.composite { "composite" } else { "-" },
+            self.determinant.map_or("-".to_string(), |determinant| determinant.to_string()),
+            self.signature.map_or("-".to_string(), |signature| signature.to_string()),
+            self.grid_label(),
+            self.ruling.as_ref().map_or("-".to_string(), |ruling| ruling.format("z", 1)),
+            self.augmentations.as_deref().unwrap_or("-"),
+            self.tb,
+            self.rot,
+            self.name.as_deref().unwrap_or("-"),
+            self.atlas.label(),
+            self.unknot.map_or("-".to_string(), unknot_label),
+            self.jones.format("t", 2),
+            self.mosaic.id()
+        )
+    }
+}
+
+//Where the mosaics of a census come from
+pub enum Source<'a> {
+    //A file of mosaics
+    File(&'a str),
+    //Every mosaic of a size meeting the census's constraints, generated as the census is taken
+    Generated(usize),
+}
+
+/* Writes the census record of every nonempty mosaic from the source admitted by `constraints` (see Constraints::admits), followed by the value of each invariant in `columns`,
+ split into chunks according to `chunking`, taking what it can from `cache` (see cache.rs) if given.
+The mosaics are read or generated, filtered, and their records computed in the stages of a pipeline (see pipeline.rs), with the parallelism given by `stages`,
+ and the records are written in the order of the source.
+Returns the number of records written, and the tally of the unknots among them. */
+pub fn write_census(source: &Source, output_path: &str, chunking: &Chunking, constraints: &Constraints, columns: &[Arc<dyn Invariant>], cache: Option<&InvariantCache>, stages: &Stages) -> Result<(usize, UnknotTally)> {
+    let (mosaics, described): (Box<dyn Iterator<Item = io::Result<Mosaic>> + Send>, _) = match source {
+        Source::File(input_path) => (Box::new(read_mosaics(input_path)?.map(|mosaic| mosaic.map_err(io::Error::from))), ("input", input_path.to_string())),
+        Source::Generated(size) => {
+            //Generated on a thread of their own, into a bounded channel (see sink.rs), which stops generating once the census stops reading
+            let (mut sender, receiver) = sync_channel(GENERATED_BUFFER);
+            let generator = constraints.generator(*size)?;
+            thread::spawn(move || generate_into(generator, &mut sender));
+            (Box::new(receiver.into_iter().map(Ok)), ("size", size.to_string()))
+        }
+    };
+    let mut output_buffer = output::create_chunked(output_path, chunking, &[("command", "census".to_string()), described])?;
+    let mut count = 0;
+    let mut unknots = UnknotTally::new();
+    //Generated mosaics already meet the constraints
+    let filtered = matches!(source, Source::File(_));
+    pipeline::run(
+        mosaics,
+        stages,
+        |mosaic| !filtered || constraints.admits(mosaic),
+        |mosaic| {
+            let record = match cache {
+                Some(cache) => CensusRecord::cached(&mosaic, cache)?,
+                None => CensusRecord::new(&mosaic),
+            };
+            Ok(record.map(|record| {
+                let mut line = record.to_string();
+                for column in columns {
+                    line += &format!(" | {}", column.compute(&mosaic));
+                }
+                (line, record.unknot)
+            }))
+        },
+        |(line, unknot)| {
+            writeln!(output_buffer, "{}", line)?;
+            if let Some(class) = unknot {
+                *unknots.entry(class).or_default() += 1;
+            }
+            count += 1;
+            Ok(())
+        },
+    )?;
+    output_buffer.finish()?;
+    if let Some(cache) = cache {
+        cache.flush()?;
+    }
+    Ok((count, unknots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::generate_with;
+    use crate::invariant::{Registry, Value};
+    use std::ops::ControlFlow;
+    use std::{fs, process};
+
+    //The census column with each built-in invariant's value, for those with one -- true is written as the name of the column, and false as -
+    const COLUMNS: [(usize, &str); 15] = [
+        (1, "components"),
+        (2, "crossings"),
+        (3, "seifert_circles"),
+        (4, "genus_bound"),
+        (5, "bridge_number"),
+        (6, "alternating"),
+        (9, "composite"),
+        (10, "determinant"),
+        (11, "signature"),
+        (13, "ruling_polynomial"),
+        (14, "augmentation_number"),
+        (15, "tb"),
+        (16, "rot"),
+        (17, "name"),
+        (21, "id"),
+    ];
+
+    #[test]
+    fn columns_match_the_builtin_invariants() {
+        let registry = Registry::builtin();
+        let _ = generate_with::<()>(4, |mosaic| {
+            if let Some(record) = CensusRecord::new(mosaic) {
+                let line = record.to_string();
+                let fields: Vec<&str> = line.split(" | ").map(str::trim).collect();
+                assert_eq!(fields.len(), 22, "{}", line);
+                assert_eq!(fields[0], mosaic.to_string());
+                for (index, name) in COLUMNS {
+                    let value = match registry.get(name).unwrap().compute(mosaic) {
+                        Value::Boolean(value) => (if value { name } else { "-" }).to_string(),
+                        value => value.to_string(),
+                    };
+                    assert_eq!(fields[index], value, "{} of {}", name, mosaic);
+                }
+            }
+            ControlFlow::Continue(())
+        });
+    }
+
+    #[test]
+    fn extra_columns_follow_the_id_in_order() {
+        let path = std::env::temp_dir().join(format!("mosaic_gen-census-test-{}.txt", process::id()));
+        let columns = Registry::builtin().columns("tb, name,size,crossings").unwrap();
+        let stages = Stages { filter_threads: 1, compute_threads: 2, queued_batches: 2 };
+        let (count, unknots) = write_census(&Source::Generated(4), path.to_str().unwrap(), &Chunking::default(), &Constraints::default(), &columns, None, &stages).unwrap();
+        let census = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(census.lines().count(), count);
+        //Every generated mosaic but the empty one, in generation order
+        let mut expected = Vec::new();
+        let _ = generate_with::<()>(4, |mosaic| {
+            if mosaic.tiles().iter().any(|&tile| tile != 0) {
+                expected.push(mosaic.to_string());
+            }
+            ControlFlow::Continue(())
+        });
+        assert_eq!(census.lines().map(|line| line[..16].to_string()).collect::<Vec<String>>(), expected);
+        for line in census.lines() {
+            let fields: Vec<&str> = line.split(" | ").map(str::trim).collect();
+            assert_eq!(fields.len(), 26, "{}", line);
+            assert_eq!(fields[22..], [fields[15], fields[17], "4", fields[2]], "{}", line);
+        }
+        assert_eq!(unknots.values().sum::<u64>() as usize, census.lines().filter(|line| line.split(" | ").nth(17) == Some("0_1")).count());
+    }
+}
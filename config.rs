@@ -0,0 +1,35 @@
+/*
+TOML config files describing runs, given to a subcommand with --config <path>, so that complex runs can be reviewed and repeated.
+Each key of the file is the name of one of the subcommand's options (without the leading --), e.g. for generate
+
+    size = 6
+    output = "s3://mosaics/6x6.txt.gz"
+    metrics = "0.0.0.0:9464"
+    chunk-size = "4G"
+    manifest = true
+
+Strings and numbers are the option's value, and flags are given as booleans. Options given on the command line take precedence over the file.
+*/
+
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use toml::{Table, Value};
+
+//Reads a config file as (option, value) pairs, with a value of None for flags that are set (and flags that aren't set left out)
+pub fn read_config(path: &str) -> Result<Vec<(String, Option<String>)>> {
+    let invalid = |message: String| Error::new(ErrorKind::InvalidData, format!("{}: {}", path, message));
+    let table: Table = fs::read_to_string(path)?.parse().map_err(|error: toml::de::Error| invalid(error.message().to_string()))?;
+    let mut options = Vec::new();
+    for (key, value) in table {
+        let value = match value {
+            Value::String(value) => Some(value),
+            Value::Integer(value) => Some(value.to_string()),
+            Value::Float(value) => Some(value.to_string()),
+            Value::Boolean(true) => None,
+            Value::Boolean(false) => continue,
+            _ => return Err(invalid(format!("{} should be a string, number or boolean", key))),
+        };
+        options.push((format!("--{}", key), value));
+    }
+    Ok(options)
+}
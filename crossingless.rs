@@ -0,0 +1,43 @@
+/*
+Crossingless mosaics, i.e. fronts of Legendrian unknots and unlinks, for studying their representatives on small boards.
+These are generated directly, with the crossing tile left out of the generator's lists of valid tiles, so the (much larger) search through mosaics with crossings is never made.
+Each mosaic is tallied by its component count and (tb, rot), with rot given up to orientation for knots as in the census (see census.rs).
+*/
+
+use crate::diagram::Diagram;
+use crate::generator::{MosaicGenerator, TileSet, ALL_TILES};
+use crate::mosaic::VIRTUAL_CROSSING;
+use crate::output;
+use std::collections::BTreeMap;
+use std::io::{Result, Write};
+
+//Crossings, and virtual crossings with the `virtual` feature
+const CROSSING_TILES: TileSet = 1 << 9 | 1 << VIRTUAL_CROSSING;
+
+//Counts of crossingless mosaics by (components, tb, rot)
+pub type Tally = BTreeMap<(usize, i32, i32), u64>;
+
+/* Tallies the nonempty crossingless size x size mosaics, writing each one to `output_path` if given, in generation order.
+Returns the tally and the number of mosaics. */
+pub fn crossingless(size: usize, output_path: Option<&str>) -> Result<(Tally, u64)> {
+    let mut output_buffer = output_path.map(output::create).transpose()?;
+    let mut tally = Tally::new();
+    let mut count = 0;
+    for mosaic in MosaicGenerator::new(size).with_allowed_tiles(&vec![ALL_TILES & !CROSSING_TILES; size * size]) {
+        let diagram = Diagram::trace(&mosaic).expect("generated mosaics are suitably connected");
+        let components = diagram.component_count();
+        if components == 0 {
+            continue;
+        }
+        let rot = if components == 1 { diagram.rot().abs() } else { diagram.rot() };
+        *tally.entry((components, diagram.tb(), rot)).or_insert(0) += 1;
+        count += 1;
+        if let Some(output_buffer) = output_buffer.as_mut() {
+            writeln!(output_buffer, "{}", mosaic)?;
+        }
+    }
+    if let Some(mut output_buffer) = output_buffer {
+        output_buffer.finish()?;
+    }
+    Ok((tally, count))
+}
@@ -0,0 +1,158 @@
+/*
+Approximate deduplication of line-based outputs too large to deduplicate exactly in memory, by a Bloom filter in front of the output.
+A line is written unless the filter says it's probably been written already. The filter never forgets a line, so every repeated line is dropped,
+ but a line that hasn't been written can also be taken for one that has, at the false-positive rate the filter is sized for (given how many distinct lines to expect).
+The filter takes -n ln(p) / ln(2)^2 bits for n expected lines and false-positive rate p -- under 2 bytes a line at p = 0.001, however long the lines are.
+With the exact pass, the lines written and the lines dropped are also kept in temporary files (in the system temporary directory, or TMPDIR), and once the output is done,
+ both are sorted within the memory limit (see sort.rs) and compared, so that the lines dropped by mistake can be written after the rest:
+ the output is then exactly the distinct lines, though the few restored come last instead of where they first appeared.
+*/
+
+use crate::sort::sort_lines;
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::f64::consts::LN_2;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, BufWriter, Result, Write};
+use std::path::PathBuf;
+use std::process;
+
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DedupOptions {
+    //The number of distinct lines the filter is sized for
+    pub expected: u64,
+    pub false_positive_rate: f64,
+    pub exact: bool,
+    //The memory the exact pass can sort lines in before spilling them to disk (see sort::sort_lines)
+    pub memory_limit: Option<u64>,
+}
+
+struct BloomFilter {
+    bits: Vec<u64>,
+    bit_count: u64,
+    hashes: u64,
+}
+
+impl BloomFilter {
+    fn new(expected: u64, false_positive_rate: f64) -> BloomFilter {
+        let expected = expected.max(1) as f64;
+        let bit_count = ((-expected * false_positive_rate.ln() / (LN_2 * LN_2)).ceil() as u64).max(64);
+        let hashes = ((bit_count as f64 / expected * LN_2).round() as u64).max(1);
+        BloomFilter { bits: vec![0; bit_count.div_ceil(64) as usize], bit_count, hashes }
+    }
+
+    //Adds a line, returning whether it was (probably) there already
+    fn insert(&mut self, line: &str) -> bool {
+        //Bit positions by double hashing, from two hashes of the line
+        let mut hasher = DefaultHasher::new();
+        line.hash(&mut hasher);
+        let first = hasher.finish();
+        hasher.write_u8(0xff);
+        let second = hasher.finish() | 1;
+        let mut present = true;
+        for index in 0..self.hashes {
+            let bit = first.wrapping_add(index.wrapping_mul(second)) % self.bit_count;
+            let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+            present &= self.bits[word] & mask != 0;
+            self.bits[word] |= mask;
+        }
+        present
+    }
+}
+
+//The temporary files of the exact pass, removed once it's done (or fails)
+struct ExactPass {
+    paths: Vec<PathBuf>,
+    written: BufWriter<File>,
+    dropped: BufWriter<File>,
+    memory_limit: Option<u64>,
+}
+
+impl ExactPass {
+    fn path(paths: &mut Vec<PathBuf>, name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("mosaic_gen-dedup-{}-{}.txt", process::id(), name));
+        paths.push(path.clone());
+        path
+    }
+
+    fn new(memory_limit: Option<u64>) -> Result<ExactPass> {
+        let mut paths = Vec::new();
+        let written = BufWriter::new(File::create(ExactPass::path(&mut paths, "written"))?);
+        let dropped = BufWriter::new(File::create(ExactPass::path(&mut paths, "dropped"))?);
+        Ok(ExactPass { paths, written, dropped, memory_limit })
+    }
+
+    //Writes the lines dropped that were never written to the output, returning how many there were
+    fn restore(mut self, output: &mut dyn Write) -> Result<u64> {
+        self.written.flush()?;
+        self.dropped.flush()?;
+        //The unsorted files come first in paths
+        let unsorted = self.paths.clone();
+        let mut sorted = Vec::new();
+        for (path, name) in unsorted.iter().zip(["written", "dropped"]) {
+            let sorted_path = ExactPass::path(&mut self.paths, &format!("{}-sorted", name));
+            sort_lines(&path.to_string_lossy(), &sorted_path.to_string_lossy(), self.memory_limit, true)?;
+            sorted.push(BufReader::new(File::open(&sorted_path)?).lines());
+        }
+        let (mut written, dropped) = (sorted.remove(0), sorted.remove(0));
+        let mut next_written = written.next().transpose()?;
+        let mut restored = 0;
+        for line in dropped {
+            let line = line?;
+            while next_written.as_ref().is_some_and(|written| *written < line) {
+                next_written = written.next().transpose()?;
+            }
+            if next_written.as_ref() != Some(&line) {
+                writeln!(output, "{}", line)?;
+                restored += 1;
+            }
+        }
+        Ok(restored)
+    }
+}
+
+impl Drop for ExactPass {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+//A filter deciding which lines of an output to write
+pub struct Dedup {
+    filter: BloomFilter,
+    exact: Option<ExactPass>,
+    dropped: u64,
+}
+
+impl Dedup {
+    pub fn new(options: &DedupOptions) -> Result<Dedup> {
+        let exact = if options.exact { Some(ExactPass::new(options.memory_limit)?) } else { None };
+        Ok(Dedup { filter: BloomFilter::new(options.expected, options.false_positive_rate), exact, dropped: 0 })
+    }
+
+    //Whether to write a line, which isn't the same as any line written before (unless the filter is mistaken)
+    pub fn admit(&mut self, line: &str) -> Result<bool> {
+        let seen = self.filter.insert(line);
+        if let Some(exact) = &mut self.exact {
+            writeln!(if seen { &mut exact.dropped } else { &mut exact.written }, "{}", line)?;
+        }
+        if seen {
+            self.dropped += 1;
+        }
+        Ok(!seen)
+    }
+
+    //Finishes deduplicating, making the exact pass (if asked for) to write out the lines dropped by mistake, and returns the numbers of lines dropped and restored
+    pub fn finish(self, output: &mut dyn Write) -> Result<(u64, u64)> {
+        let restored = match self.exact {
+            Some(exact) => exact.restore(output)?,
+            None => 0,
+        };
+        Ok((self.dropped, restored))
+    }
+}
@@ -0,0 +1,318 @@
+/*
+The Chekanov-Eliashberg algebra of the front a mosaic encodes, over F2, and its augmentations.
+Resolving the front (Ng, "Computable Legendrian invariants") turns it into a Lagrangian projection with a crossing for each crossing of the front and one more at each right cusp,
+ which are the generators of the algebra, with the front read as in ruling.rs. The differential of a generator counts the immersed disks with a single positive corner there,
+ which all lie to its left: crossing a disk from right to left, it's made of sheets, each stretching between a lower and an upper boundary strand, starting from the one
+ filling the quadrant to the left of a crossing (or the inside of a right cusp), and ending as each sheet shrinks into a left cusp.
+ At a crossing, a sheet's upper boundary may turn a corner onto the other strand with the sheet filling the quadrant below the crossing (and its lower boundary likewise,
+ with the quadrant above), and a right cusp inside a sheet either stays inside it or splits it in two, around the outside of the cusp's loop (with two corners at the cusp)
+ or through the loop's crossing (with no corners and the two sheets overlapping inside the cusp). A disk's word is its corners, read counterclockwise from its positive corner,
+ and a right cusp's differential also has a 1, from the inside of its loop.
+
+An augmentation is an algebra map ε to F2 with ε∂ = 0, taken ungraded here, so with no condition on the degrees of the generators it's nonzero on.
+How many there are only depends on the diagram through a power of 2, since each stabilization of the algebra adds two generators and doubles them,
+ so the augmentation number #Aug·2^-(n + 1)/2, with n generators, is a Legendrian isotopy invariant: 1 for the unknot with tb = -1 (with 2 augmentations), and
+ 5/2 for the trefoil with tb = 1 (with 20 in its simplest front). By Ng and Sabloff it's R(2^-1/2), where R is the ruling polynomial, which the census shows alongside.
+Augmentations are counted by assigning the generators from left to right, as each differential only involves generators further left, so only for small diagrams.
+*/
+
+use crate::diagram::Diagram;
+use crate::ruling::{front_events, FrontEvent};
+use std::collections::HashMap;
+
+//The most generators augmentations are counted for
+pub const MAX_AUGMENTATION_GENERATORS: usize = 20;
+
+pub struct Dga {
+    //The crossings and right cusps of the front from left to right, with the positions of their tiles
+    pub generators: Vec<(FrontEvent, (usize, usize))>,
+    //The differential of each generator, as the words (of generator indices) it sums, the empty word being 1
+    pub differentials: Vec<Vec<Vec<usize>>>,
+}
+
+#[derive(Clone, Copy)]
+enum Token {
+    Generator(usize),
+    Sheet(usize),
+}
+
+//The corners met along a sheet's upper boundary (followed by the sheets it splits into) and its lower boundary, both in the order they're found, from right to left
+#[derive(Clone, Default)]
+struct Boundary {
+    upper: Vec<Token>,
+    lower: Vec<Token>,
+}
+
+//A disk as far as it's been swept: the positions of the lower and upper boundaries of its sheets over some x, with the boundary of each
+#[derive(Clone)]
+struct Disk {
+    sheets: Vec<(usize, usize, usize)>,
+    boundaries: Vec<Boundary>,
+}
+
+impl Disk {
+    //Reads the corners counterclockwise, from the upper boundary of a sheet through the sheets it splits into and back along its lower boundary
+    fn word(&self, sheet: usize, word: &mut Vec<usize>) {
+        let boundary = &self.boundaries[sheet];
+        for &token in boundary.upper.iter().chain(boundary.lower.iter().rev()) {
+            match token {
+                Token::Generator(generator) => word.push(generator),
+                Token::Sheet(sheet) => self.word(sheet, word),
+            }
+        }
+    }
+
+    //Splits a sheet at a right cusp inside it, into the part containing its upper boundary and the part containing its lower boundary, with any corners between them
+    fn split(&mut self, sheet: usize, upper: (usize, usize), lower: (usize, usize), corners: &[usize]) {
+        let boundary = self.sheets[sheet].2;
+        let (upper_boundary, lower_boundary) = (self.boundaries.len(), self.boundaries.len() + 1);
+        self.boundaries.extend([Boundary::default(), Boundary::default()]);
+        let tokens = &mut self.boundaries[boundary].upper;
+        tokens.push(Token::Sheet(upper_boundary));
+        tokens.extend(corners.iter().map(|&corner| Token::Generator(corner)));
+        tokens.push(Token::Sheet(lower_boundary));
+        self.sheets[sheet] = (upper.0, upper.1, upper_boundary);
+        self.sheets.insert(sheet + 1, (lower.0, lower.1, lower_boundary));
+    }
+}
+
+//Continues a disk leftwards past the events before `at`, choosing how each of the sheets from `sheet` on passes the next event, and collects the words of the disks that close
+fn extend_disk(events: &[(FrontEvent, Option<usize>)], at: usize, sheet: usize, disk: Disk, words: &mut Vec<Vec<usize>>) {
+    if disk.sheets.is_empty() {
+        let mut word = Vec::new();
+        disk.word(0, &mut word);
+        words.push(word);
+        return;
+    }
+    let Some(&(event, generator)) = at.checked_sub(1).map(|at| &events[at]) else {
+        return;
+    };
+    if sheet == disk.sheets.len() {
+        //Every sheet is past the event, so the positions move on to the other side of it
+        let mut disk = disk;
+        match event {
+            FrontEvent::LeftCusp(k) => {
+                disk.sheets.retain(|&(lower, upper, _)| (lower, upper) != (k, k + 1));
+                if disk.sheets.iter().any(|&(lower, upper, _)| [k, k + 1].contains(&lower) || [k, k + 1].contains(&upper)) {
+                    return;
+                }
+                for (lower, upper, _) in &mut disk.sheets {
+                    *lower -= if *lower > k + 1 { 2 } else { 0 };
+                    *upper -= if *upper > k + 1 { 2 } else { 0 };
+                }
+            }
+            FrontEvent::RightCusp(_) | FrontEvent::Crossing(_) => (),
+        }
+        extend_disk(events, at - 1, 0, disk, words);
+        return;
+    }
+    let (lower, upper, boundary) = disk.sheets[sheet];
+    match event {
+        FrontEvent::LeftCusp(_) => extend_disk(events, at, disk.sheets.len(), disk, words),
+        FrontEvent::RightCusp(k) => {
+            let shift = |position: usize| if position >= k { position + 2 } else { position };
+            let (lower, upper) = (shift(lower), shift(upper));
+            let mut inside = disk.clone();
+            inside.sheets[sheet] = (lower, upper, boundary);
+            if lower < k && upper > k + 1 {
+                let cusp = generator.expect("right cusps are generators");
+                let (mut outside, mut through) = (inside.clone(), inside.clone());
+                outside.split(sheet, (k + 1, upper), (lower, k), &[cusp, cusp]);
+                through.split(sheet, (k, upper), (lower, k + 1), &[]);
+                extend_disk(events, at, sheet + 2, outside, words);
+                extend_disk(events, at, sheet + 2, through, words);
+            }
+            extend_disk(events, at, sheet + 1, inside, words);
+        }
+        FrontEvent::Crossing(k) => {
+            let generator = generator.expect("crossings are generators");
+            let swap = |position: usize| if position == k { k + 1 } else if position == k + 1 { k } else { position };
+            //Following the strands through the crossing, or turning a corner (only into the quadrant below for an upper boundary, or above for a lower one)
+            let mut choices = vec![(swap(lower), swap(upper), false, false)];
+            if upper == k {
+                choices.push((swap(lower), upper, false, true));
+            }
+            if lower == k + 1 {
+                choices.push((lower, swap(upper), true, false));
+            }
+            for (lower, upper, lower_corner, upper_corner) in choices.into_iter().filter(|&(lower, upper, _, _)| lower < upper) {
+                let mut disk = disk.clone();
+                disk.sheets[sheet] = (lower, upper, boundary);
+                if lower_corner {
+                    disk.boundaries[boundary].lower.push(Token::Generator(generator));
+                }
+                if upper_corner {
+                    disk.boundaries[boundary].upper.push(Token::Generator(generator));
+                }
+                extend_disk(events, at, sheet + 1, disk, words);
+            }
+        }
+    }
+}
+
+impl Dga {
+    //The algebra of a diagram's front, or None for mosaics with virtual crossings
+    pub fn new(diagram: &Diagram) -> Option<Dga> {
+        let events = front_events(&diagram.mosaic)?;
+        let mut generator_count = 0;
+        let indexed: Vec<(FrontEvent, Option<usize>)> = events
+            .iter()
+            .map(|&(event, _)| match event {
+                FrontEvent::LeftCusp(_) => (event, None),
+                _ => {
+                    generator_count += 1;
+                    (event, Some(generator_count - 1))
+                }
+            })
+            .collect();
+        let mut differentials = Vec::new();
+        for (at, &(event, _)) in indexed.iter().enumerate() {
+            let k = match event {
+                FrontEvent::LeftCusp(_) => continue,
+                FrontEvent::RightCusp(k) | FrontEvent::Crossing(k) => k,
+            };
+            let mut words = if matches!(event, FrontEvent::RightCusp(_)) { vec![Vec::new()] } else { Vec::new() };
+            let disk = Disk { sheets: vec![(k, k + 1, 0)], boundaries: vec![Boundary::default()] };
+            extend_disk(&indexed, at, 0, disk, &mut words);
+            //Disks with the same word cancel in pairs over F2
+            let mut counts: HashMap<Vec<usize>, usize> = HashMap::new();
+            for word in words {
+                *counts.entry(word).or_default() += 1;
+            }
+            let mut words: Vec<Vec<usize>> = counts.into_iter().filter(|(_, count)| count % 2 == 1).map(|(word, _)| word).collect();
+            words.sort();
+            differentials.push(words);
+        }
+        let generators = events.into_iter().filter(|(event, _)| !matches!(event, FrontEvent::LeftCusp(_))).collect();
+        Some(Dga { generators, differentials })
+    }
+
+    //Crossings are labelled a1, a2, ... and right cusps c1, c2, ..., from left to right
+    pub fn label(&self, generator: usize) -> String {
+        let (event, _) = self.generators[generator];
+        let is_cusp = matches!(event, FrontEvent::RightCusp(_));
+        let number = self.generators[..=generator].iter().filter(|(other, _)| matches!(other, FrontEvent::RightCusp(_)) == is_cusp).count();
+        format!("{}{}", if is_cusp { 'c' } else { 'a' }, number)
+    }
+
+    pub fn format_differential(&self, generator: usize) -> String {
+        if self.differentials[generator].is_empty() {
+            return "0".to_string();
+        }
+        self.differentials[generator]
+            .iter()
+            .map(|word| if word.is_empty() { "1".to_string() } else { word.iter().map(|&factor| self.label(factor)).collect::<Vec<String>>().join("") })
+            .collect::<Vec<String>>()
+            .join(" + ")
+    }
+
+    //The number of augmentations extending the values already `assigned` to the first generators, where `free` generators aren't in any differential
+    fn count_from(&self, free: &[bool], assigned: &mut Vec<bool>) -> u64 {
+        let generator = assigned.len();
+        if generator == self.generators.len() {
+            return 1;
+        }
+        let value = self.differentials[generator].iter().filter(|word| word.iter().all(|&factor| assigned[factor])).count() % 2;
+        if value != 0 {
+            return 0;
+        }
+        //A free generator can take either value without affecting the rest
+        let mut count = 0;
+        for &augmented in if free[generator] { &[false][..] } else { &[false, true][..] } {
+            assigned.push(augmented);
+            count += self.count_from(free, assigned);
+            assigned.pop();
+        }
+        if free[generator] { 2 * count } else { count }
+    }
+
+    //The number of (ungraded) augmentations to F2, or None if there are more than MAX_AUGMENTATION_GENERATORS generators
+    pub fn augmentation_count(&self) -> Option<u64> {
+        let free: Vec<bool> = (0..self.generators.len()).map(|generator| !self.differentials.iter().flatten().any(|word| word.contains(&generator))).collect();
+        (self.generators.len() <= MAX_AUGMENTATION_GENERATORS).then(|| self.count_from(&free, &mut Vec::new()))
+    }
+}
+
+//Writes the augmentation number count·2^-(generators + 1)/2 exactly, e.g. 5/2 or 3√2/4
+pub fn format_augmentation_number(count: u64, generators: usize) -> String {
+    if count == 0 {
+        return "0".to_string();
+    }
+    let twos = count.trailing_zeros() as i64;
+    let odd = count >> twos;
+    let twice_exponent = 2 * twos - (generators + 1) as i64;
+    //An odd exponent leaves a factor of √2
+    let (root, exponent) = (twice_exponent % 2 != 0, twice_exponent.div_euclid(2));
+    let scaled = if exponent >= 0 { odd << exponent } else { odd };
+    let numerator = match (scaled, root) {
+        (1, true) => "√2".to_string(),
+        (scaled, root) => format!("{}{}", scaled, if root { "√2" } else { "" }),
+    };
+    if exponent >= 0 { numerator } else { format!("{}/{}", numerator, 1u64 << -exponent) }
+}
+
+//The augmentation number of a diagram (see above), or None for mosaics with virtual crossings or too many generators
+pub fn augmentation_number(diagram: &Diagram) -> Option<String> {
+    let dga = Dga::new(diagram)?;
+    let count = dga.augmentation_count()?;
+    Some(format_augmentation_number(count, dga.generators.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::generate_with;
+    use crate::mosaic::VIRTUAL_CROSSING;
+    use crate::ruling::ruling_polynomial;
+    use std::ops::ControlFlow;
+
+    #[test]
+    fn augmentation_numbers_are_written_exactly() {
+        assert_eq!(format_augmentation_number(0, 5), "0");
+        assert_eq!(format_augmentation_number(2, 1), "1");
+        assert_eq!(format_augmentation_number(20, 5), "5/2");
+        assert_eq!(format_augmentation_number(3, 2), "3√2/4");
+        assert_eq!(format_augmentation_number(1, 0), "√2/2");
+        assert_eq!(format_augmentation_number(16, 1), "8");
+        assert_eq!(format_augmentation_number(8, 2), "2√2");
+    }
+
+    #[test]
+    fn augmentations_of_the_unknot_and_trefoil() {
+        //The standard unknot, the max-tb right-handed trefoil (in a front with seven generators) and a stabilization of it
+        for (code, count, number) in [("0000000000000000002100034", 2, "1"), ("0021025971629943943103554", 40, "5/2"), ("0021002891299463791603434", 0, "0")] {
+            let diagram = Diagram::trace(&code.parse().unwrap()).unwrap();
+            assert_eq!(Dga::new(&diagram).unwrap().augmentation_count(), Some(count), "{}", code);
+            assert_eq!(augmentation_number(&diagram).as_deref(), Some(number), "{}", code);
+        }
+    }
+
+    #[test]
+    fn augmentation_numbers_are_the_ruling_polynomial_at_one_over_root_two() {
+        let (mut index, mut augmented, mut unaugmented) = (0, 0, 0);
+        let _ = generate_with::<()>(5, |mosaic| {
+            index += 1;
+            if index % 97 != 0 || mosaic.tiles().contains(&VIRTUAL_CROSSING) {
+                return ControlFlow::Continue(());
+            }
+            let Some(diagram) = Diagram::trace(mosaic).filter(|diagram| diagram.component_count() == 1) else {
+                return ControlFlow::Continue(());
+            };
+            let dga = Dga::new(&diagram).unwrap();
+            if let Some(count) = dga.augmentation_count() {
+                //Ng and Sabloff: #Aug·2^-(n + 1)/2 = R(2^-1/2)
+                let ruling = ruling_polynomial(&diagram).unwrap();
+                let at_root = ruling.coefficients.iter().enumerate().map(|(power, &coefficient)| coefficient as f64 * 0.5f64.powf((ruling.low + power as i32) as f64 / 2.0)).sum::<f64>();
+                let number = count as f64 * 0.5f64.powf((dga.generators.len() + 1) as f64 / 2.0);
+                assert!((number - at_root).abs() < 1e-9, "{}: {} augmentations, ruling polynomial {}", mosaic, count, ruling.format("z", 1));
+                if count == 0 {
+                    unaugmented += 1;
+                } else {
+                    augmented += 1;
+                }
+            }
+            ControlFlow::Continue(())
+        });
+        assert!(augmented > 10 && unaugmented > 10);
+    }
+}
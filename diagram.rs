@@ -0,0 +1,427 @@
+/*
+Traversal of the strands of a legendrian mosaic, following the same procedure as the python cataloguer:
+Starting from the first tile (left to right, top to bottom) with an untraversed strand, we follow the strand from tile to tile until we return to where we started,
+ recording the faces we enter and leave each tile through. This is repeated until every strand has been traversed, giving one traversal per link component.
+The orientation of each component is the direction in which it was traversed.
+
+Crossings are numbered in the order they are first reached, and their signs/over strands are determined by the directions of the two strands passing through them.
+In the front projection (the mosaic rotated 45°) the strand running between faces 1 and 3 is always the over strand.
+*/
+
+use crate::mosaic::{Mosaic, NONE, PARTNER};
+
+//A single pass of a strand through a tile
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Visit {
+    pub tile: usize,
+    pub entry: u8,
+    pub exit: u8,
+}
+
+impl Visit {
+    //Cusps are traversed downward along (0,3) and (1,2), upward along (3,0) and (2,1)
+    pub fn is_down_cusp(&self) -> bool {
+        matches!((self.entry, self.exit), (0, 3) | (1, 2))
+    }
+
+    pub fn is_up_cusp(&self) -> bool {
+        matches!((self.entry, self.exit), (3, 0) | (2, 1))
+    }
+
+    //Direction of travel through the tile, as an (x, y) vector in the unrotated mosaic with y pointing up
+    pub fn direction(&self) -> (i32, i32) {
+        match self.entry {
+            0 => (-1, 0),
+            1 => (0, -1),
+            2 => (1, 0),
+            _ => (0, 1),
+        }
+    }
+}
+
+//Position of a visit within a diagram, as (component, index of the visit in that component)
+pub type Position = (usize, usize);
+
+#[derive(Clone, Debug)]
+pub struct Crossing {
+    pub tile: usize,
+    pub over: Position,
+    pub under: Position,
+    pub sign: i32,
+}
+
+//Invariants of one orientation of a diagram (see Diagram::orientations)
+#[derive(Clone, Debug)]
+pub struct Orientation {
+    pub reversed: Vec<bool>,
+    pub writhe: i32,
+    pub rot: i32,
+    pub sl: i32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagram {
+    pub mosaic: Mosaic,
+    pub components: Vec<Vec<Visit>>,
+    pub crossings: Vec<Crossing>,
+}
+
+impl Diagram {
+    //Traces every component of a mosaic, returning None if the mosaic is not suitably connected
+    pub fn trace(mosaic: &Mosaic) -> Option<Diagram> {
+        if !mosaic.is_suitably_connected() {
+            return None;
+        }
+        let tiles = mosaic.tiles();
+        let mut used_faces = vec![0u8; tiles.len()]; //Bitmask of faces already traversed for each tile
+        let mut components = Vec::new();
+
+        for start_tile in 0..tiles.len() {
+            loop {
+                let tile = tiles[start_tile] as usize;
+                let start_face = match (0..4).find(|&face| PARTNER[tile][face as usize] != NONE && used_faces[start_tile] & (1 << face) == 0) {
+                    Some(face) => face,
+                    None => break,
+                };
+
+                let mut component = Vec::new();
+                let (mut curr_tile, mut face) = (start_tile, start_face);
+                loop {
+                    let exit = PARTNER[tiles[curr_tile] as usize][face as usize];
+                    used_faces[curr_tile] |= (1 << face) | (1 << exit);
+                    component.push(Visit { tile: curr_tile, entry: face, exit });
+                    curr_tile = mosaic.neighbor(curr_tile, exit)?;
+                    face = (exit + 2) % 4; //incoming face for next tile
+                    if curr_tile == start_tile && face == start_face {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+
+        let mut diagram = Diagram { mosaic: mosaic.clone(), components, crossings: Vec::new() };
+        diagram.find_crossings();
+        Some(diagram)
+    }
+
+    //Numbers crossings in the order they're first reached and determines their signs
+    fn find_crossings(&mut self) {
+        let tiles = self.mosaic.tiles();
+        let mut first_visit: Vec<Option<Position>> = vec![None; tiles.len()];
+        self.crossings.clear();
+        for (c, component) in self.components.iter().enumerate() {
+            for (i, visit) in component.iter().enumerate() {
+                if tiles[visit.tile] != 9 {
+                    continue;
+                }
+                match first_visit[visit.tile] {
+                    None => first_visit[visit.tile] = Some((c, i)),
+                    Some(first) => {
+                        let (over, under) = if visit.entry % 2 == 1 { ((c, i), first) } else { (first, (c, i)) };
+                        self.crossings.push(Crossing { tile: visit.tile, over, under, sign: 0 });
+                    }
+                }
+            }
+        }
+        //Orders crossings by their first visit rather than their second
+        self.crossings.sort_by_key(|crossing| first_visit[crossing.tile]);
+        for i in 0..self.crossings.len() {
+            let over = self.visit(self.crossings[i].over).direction();
+            let under = self.visit(self.crossings[i].under).direction();
+            self.crossings[i].sign = (over.0 * under.1 - over.1 * under.0).signum();
+        }
+    }
+
+    pub fn visit(&self, position: Position) -> Visit {
+        self.components[position.0][position.1]
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn writhe(&self) -> i32 {
+        self.crossings.iter().map(|crossing| crossing.sign).sum()
+    }
+
+    pub fn up_cusps(&self) -> usize {
+        self.components.iter().flatten().filter(|visit| visit.is_up_cusp()).count()
+    }
+
+    pub fn down_cusps(&self) -> usize {
+        self.components.iter().flatten().filter(|visit| visit.is_down_cusp()).count()
+    }
+
+    //Thurston-Bennequin number: writhe - (number of cusps) / 2
+    pub fn tb(&self) -> i32 {
+        self.writhe() - ((self.up_cusps() + self.down_cusps()) / 2) as i32
+    }
+
+    //Rotation number of the traversed orientation: (down cusps - up cusps) / 2
+    pub fn rot(&self) -> i32 {
+        (self.down_cusps() as i32 - self.up_cusps() as i32) / 2
+    }
+
+    /* The writhe, rotation number and self-linking number (of the positive transverse push-off, tb - rot) of every orientation of the diagram,
+    with reversed[component] for the components reversed from how they were traversed -- 2^components orientations in all.
+    tb doesn't depend on the orientation, but reversing a component negates its rotation number and the signs of its crossings with other components. */
+    pub fn orientations(&self) -> Vec<Orientation> {
+        let component_rots: Vec<i32> = self
+            .components
+            .iter()
+            .map(|component| {
+                let (down, up) = (component.iter().filter(|visit| visit.is_down_cusp()).count(), component.iter().filter(|visit| visit.is_up_cusp()).count());
+                (down as i32 - up as i32) / 2
+            })
+            .collect();
+        let tb = self.tb();
+        (0..1u64 << self.components.len())
+            .map(|reversals| {
+                let reversed: Vec<bool> = (0..self.components.len()).map(|component| reversals & (1 << component) != 0).collect();
+                let writhe = self
+                    .crossings
+                    .iter()
+                    .map(|crossing| if reversed[crossing.over.0] != reversed[crossing.under.0] { -crossing.sign } else { crossing.sign })
+                    .sum();
+                let rot = component_rots.iter().zip(&reversed).map(|(&rot, &reversed)| if reversed { -rot } else { rot }).sum();
+                Orientation { reversed, writhe, rot, sl: tb - rot }
+            })
+            .collect()
+    }
+
+    /* Extended gauss code of each component -- crossings are numbered from 1,
+    and are positive when passed over and negative when passed under */
+    pub fn gauss_codes(&self) -> Vec<Vec<i32>> {
+        let mut codes: Vec<Vec<(usize, i32)>> = vec![Vec::new(); self.components.len()];
+        for (n, crossing) in self.crossings.iter().enumerate() {
+            codes[crossing.over.0].push((crossing.over.1, n as i32 + 1));
+            codes[crossing.under.0].push((crossing.under.1, -(n as i32 + 1)));
+        }
+        codes
+            .into_iter()
+            .map(|mut code| {
+                code.sort();
+                code.into_iter().map(|(_, label)| label).collect()
+            })
+            .collect()
+    }
+
+    /* Crossings (as indices into self.crossings) that are Reidemeister I kinks: a strand leaves the crossing and comes back to it without passing through any other crossing,
+    so the loop it makes could be untwisted. These are the crossings appearing twice in a row (cyclically) in a gauss code. */
+    pub fn kinks(&self) -> Vec<usize> {
+        let mut kinks: Vec<usize> = self
+            .gauss_codes()
+            .iter()
+            .flat_map(|code| (0..code.len()).filter(move |&i| code.len() > 1 && code[i].abs() == code[(i + 1) % code.len()].abs()).map(move |i| code[i].unsigned_abs() as usize - 1))
+            .collect();
+        kinks.sort();
+        kinks.dedup();
+        kinks
+    }
+
+    /* Whether the diagram is alternating: along every component, the crossings passed are alternately over and under.
+    Only crossing tiles count, so cusps and the other turns of a front (and virtual crossings) are passed over without breaking the alternation. */
+    pub fn is_alternating(&self) -> bool {
+        self.gauss_codes().iter().all(|code| (0..code.len()).all(|i| (code[i] > 0) != (code[(i + 1) % code.len()] > 0)))
+    }
+
+    //Total linking number of the traversed orientations: half the sum of the signs of the crossings between different components
+    pub fn linking_number(&self) -> i32 {
+        self.crossings.iter().filter(|crossing| crossing.over.0 != crossing.under.0).map(|crossing| crossing.sign).sum::<i32>() / 2
+    }
+
+    /* Whether the diagram is connected as a 4-valent graph, i.e. every pair of components is joined by a chain of crossings between them,
+    so it isn't the split union of simpler diagrams. Diagrams of knots (and the empty diagram) are connected. */
+    pub fn is_connected(&self) -> bool {
+        self.pieces() <= 1
+    }
+
+    /* Whether the diagram is visibly composite: some circle meets it in just two points with at least three crossings on either side,
+    so it's drawn as the connected sum of two diagrams that could both be knotted (a diagram with fewer than three crossings is of an unknot or unlink -- this way kinks don't count).
+    Such a circle cuts one component into two arcs, so it's found as a stretch of that component's passes through crossings whose crossings,
+    along with every other component they reach, meet the rest of the diagram only at the ends of the stretch. Split diagrams aren't composite (see is_connected). */
+    pub fn is_composite(&self) -> bool {
+        if self.crossings.len() < 6 || !self.is_connected() {
+            return false;
+        }
+        //The crossing at each pass of each component, and the (component, pass) of each crossing's two passes
+        let mut passes: Vec<Vec<(usize, usize)>> = vec![Vec::new(); self.components.len()];
+        for (k, crossing) in self.crossings.iter().enumerate() {
+            for position in [crossing.over, crossing.under] {
+                passes[position.0].push((position.1, k));
+            }
+        }
+        let mut ends = vec![[(0, 0); 2]; self.crossings.len()];
+        let mut seen = vec![0; self.crossings.len()];
+        for (c, component_passes) in passes.iter_mut().enumerate() {
+            component_passes.sort();
+            for (pass, &(_, k)) in component_passes.iter().enumerate() {
+                ends[k][seen[k]] = (c, pass);
+                seen[k] += 1;
+            }
+        }
+        (0..self.components.len()).any(|c| {
+            let length = passes[c].len();
+            (0..length).any(|start| {
+                (1..length).any(|stretch| {
+                    let mut inside: Vec<Vec<bool>> = passes.iter().map(|component_passes| vec![false; component_passes.len()]).collect();
+                    for offset in 0..stretch {
+                        inside[c][(start + offset) % length] = true;
+                    }
+                    //Pulls in every component reached from inside, failing if a crossing inside is passed again on the rest of component c
+                    loop {
+                        let reached = ends.iter().find_map(|&[a, b]| match (inside[a.0][a.1], inside[b.0][b.1]) {
+                            (true, false) => Some(b),
+                            (false, true) => Some(a),
+                            _ => None,
+                        });
+                        match reached {
+                            None => break,
+                            Some((component, _)) if component == c => return false,
+                            Some((component, _)) => inside[component].iter_mut().for_each(|pass| *pass = true),
+                        }
+                    }
+                    let crossings_inside = ends.iter().filter(|&&[a, _]| inside[a.0][a.1]).count();
+                    crossings_inside >= 3 && self.crossings.len() - crossings_inside >= 3
+                })
+            })
+        })
+    }
+
+    //Number of connected pieces of the diagram as a 4-valent graph, each a set of components joined through crossings
+    fn pieces(&self) -> usize {
+        self.piece_of_components().iter().max().map_or(0, |&piece| piece + 1)
+    }
+
+    //The piece of the diagram (see pieces) each component is in, numbered in order of their first components
+    fn piece_of_components(&self) -> Vec<usize> {
+        let mut piece_of: Vec<Option<usize>> = vec![None; self.components.len()];
+        let mut pieces = 0;
+        for start in 0..self.components.len() {
+            if piece_of[start].is_some() {
+                continue;
+            }
+            let mut stack = vec![start];
+            while let Some(component) = stack.pop() {
+                if piece_of[component].is_some() {
+                    continue;
+                }
+                piece_of[component] = Some(pieces);
+                for crossing in &self.crossings {
+                    if crossing.over.0 == component || crossing.under.0 == component {
+                        stack.extend([crossing.over.0, crossing.under.0]);
+                    }
+                }
+            }
+            pieces += 1;
+        }
+        piece_of.into_iter().flatten().collect()
+    }
+
+    /* Each piece of the diagram (see pieces) drawn on its own, on a board of the same size with its tiles where they were:
+    a tile holding strands of two pieces (a 7, an 8 or a virtual crossing) keeps just the strand of each piece. */
+    pub fn piece_mosaics(&self) -> Vec<Mosaic> {
+        let size = self.mosaic.size();
+        let piece_of = self.piece_of_components();
+        (0..self.pieces())
+            .map(|piece| {
+                let mut partners = vec![[NONE; 4]; size * size];
+                for (component, visits) in self.components.iter().enumerate() {
+                    if piece_of[component] == piece {
+                        for visit in visits {
+                            partners[visit.tile][visit.entry as usize] = visit.exit;
+                            partners[visit.tile][visit.exit as usize] = visit.entry;
+                        }
+                    }
+                }
+                let tiles: Vec<u8> = partners
+                    .iter()
+                    .enumerate()
+                    .map(|(index, partners)| {
+                        let tile = self.mosaic.tiles()[index];
+                        if PARTNER[tile as usize] == *partners {
+                            tile
+                        } else {
+                            PARTNER.iter().position(|tile_partners| tile_partners == partners).expect("a strand of a tile is a tile") as u8
+                        }
+                    })
+                    .collect();
+                Mosaic::from_tiles(size, tiles).expect("pieces of a mosaic are made of tiles")
+            })
+            .collect()
+    }
+
+    /* Splits the diagram into its pieces, each drawn as its own mosaic (see piece_mosaics) trimmed to its rows and columns and padded with blank tiles to a square (see Mosaic::trim).
+    Each mosaic is a diagram of its part of the link, so a split diagram gives several smaller mosaics whose union it is, and a connected one gives itself, trimmed. */
+    pub fn split(&self) -> Vec<Mosaic> {
+        self.piece_mosaics().iter().map(Mosaic::trim).collect()
+    }
+
+    /* Number of Seifert circles: the loops left by smoothing every crossing the way that respects the traversed orientations,
+    so a strand arriving at a crossing leaves along the other strand's exit. Each visit is arrived at exactly once, so the circles are the cycles of following arrivals. */
+    pub fn seifert_circles(&self) -> usize {
+        let mut crossing_at: Vec<Option<(Position, Position)>> = vec![None; self.mosaic.tiles().len()];
+        for crossing in &self.crossings {
+            crossing_at[crossing.tile] = Some((crossing.over, crossing.under));
+        }
+        let mut arrived: Vec<Vec<bool>> = self.components.iter().map(|component| vec![false; component.len()]).collect();
+        let mut circles = 0;
+        for (c, component) in self.components.iter().enumerate() {
+            for i in 0..component.len() {
+                if arrived[c][i] {
+                    continue;
+                }
+                circles += 1;
+                let mut position = (c, i);
+                while !arrived[position.0][position.1] {
+                    arrived[position.0][position.1] = true;
+                    let leaving = match crossing_at[self.visit(position).tile] {
+                        Some((over, under)) => if position == over { under } else { over },
+                        None => position,
+                    };
+                    position = (leaving.0, (leaving.1 + 1) % self.components[leaving.0].len());
+                }
+            }
+        }
+        circles
+    }
+
+    /* Genus of the Seifert surface built from the Seifert circles, an upper bound on the genus of the link: each piece of the diagram (see pieces) gives a connected surface
+    with a disk per circle and a band per crossing, so the genera sum to (crossings - circles - components) / 2 + pieces.
+    Virtual crossings aren't smoothed, so this needn't mean anything for virtual diagrams. */
+    pub fn genus_bound(&self) -> usize {
+        (self.crossings.len() + 2 * self.pieces()).saturating_sub(self.seifert_circles() + self.component_count()) / 2
+    }
+
+    /* Number of local maxima of a height function on the diagram, given as the direction it increases in (an (x, y) vector as in Visit::direction):
+    the number of times a component stops climbing and starts descending, with steps level in the height not counting either way. */
+    pub fn maxima(&self, height: (i32, i32)) -> usize {
+        self.components
+            .iter()
+            .map(|component| {
+                let steps: Vec<i32> = component.iter().map(|visit| visit.direction()).map(|(x, y)| (x * height.0 + y * height.1).signum()).filter(|&step| step != 0).collect();
+                (0..steps.len()).filter(|&i| steps[i] > 0 && steps[(i + 1) % steps.len()] < 0).count()
+            })
+            .sum()
+    }
+
+    /* Bridge number of the diagram: the fewest maxima of the heights along the mosaic's columns and rows and along its diagonals (the x and z axes of the front),
+    an upper bound on the bridge index of the link. */
+    pub fn bridge_number(&self) -> usize {
+        [(0, 1), (1, 0), (1, 1), (1, -1)].into_iter().map(|height| self.maxima(height)).min().unwrap_or(0)
+    }
+
+    /* 1 if the diagram is positive (it has crossings, all positive in the traversed orientations), -1 if it's negative, and 0 otherwise.
+    For links, reversing components can change which this is. */
+    pub fn crossing_sign(&self) -> i32 {
+        match self.crossings.first() {
+            Some(first) if self.crossings.iter().all(|crossing| crossing.sign == first.sign) => first.sign,
+            _ => 0,
+        }
+    }
+
+    pub fn crossing_signs(&self) -> Vec<i32> {
+        self.crossings.iter().map(|crossing| crossing.sign).collect()
+    }
+}
@@ -0,0 +1,40 @@
+/*
+The errors of the generator's API -- parsing and building mosaics, parsing orders and resume tokens, and runs of mosaic_gen --
+ of cutting sub-mosaics and reading files of mosaics (Mosaic::submosaic, Tangle::cut and read_mosaics),
+ and of the entry points of the census, extend, sort and pipe commands (write_census, CensusRecord::cached, extend, sort_lines, Step::parse_all and pipe::run),
+ so code generating and processing mosaics can tell what went wrong by matching on the cause rather than reading messages.
+Each converts to an io::Error of the matching kind, keeping the Error inside (get it back with io::Error::get_ref and downcast_ref).
+*/
+
+use std::io;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    //Reading or writing failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    //Text that isn't what it was given as: what it should have been (like "mosaic code"), the text, and what's wrong with it
+    #[error("invalid {what} \"{text}\": {reason}")]
+    Parse { what: &'static str, text: String, reason: String },
+    //A value that doesn't make sense where it's used, like a tile outside the board or a start mosaic of the wrong size
+    #[error("{0}")]
+    Invalid(String),
+    //A size of mosaic an operation doesn't support
+    #[error("{size}x{size} mosaics aren't supported: {reason}")]
+    UnsupportedSize { size: usize, reason: String },
+    //A resume token that can't carry on the run it was given to
+    #[error("resume token {token} can't be resumed: {reason}")]
+    Checkpoint { token: String, reason: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> io::Error {
+        match error {
+            Error::Io(error) => error,
+            Error::Parse { .. } => io::Error::new(io::ErrorKind::InvalidData, error),
+            Error::Invalid(_) | Error::UnsupportedSize { .. } | Error::Checkpoint { .. } => io::Error::new(io::ErrorKind::InvalidInput, error),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+/*
+Conversion of traced mosaics into formats understood by other knot theory software.
+
+Regina (https://regina-normal.github.io) builds links from the same data the python cataloguer uses for sagemath:
+ a list of crossing signs, and the extended gauss code of each component (crossings numbered from 1, positive over / negative under).
+Components without crossings are given as empty lists, so unknotted and split components survive the conversion.
+
+GAP and Magma are given the Wirtinger presentation of the link group: a generator for each arc of the diagram (the stretches of strand between undercrossings,
+ numbered along each component in turn from the first under pass), and a relator at each crossing conjugating the arc leaving it by the over arc,
+ x_k x_i x_k^-1 x_j^-1 at a positive crossing with over arc k, incoming arc i and outgoing arc j, and x_k^-1 x_i x_k x_j^-1 at a negative one.
+A component passing under nothing is a single arc.
+
+PD codes (as in KnotTheory) label the edges between crossings from 1 along each component in turn, and give each crossing as X[i, j, k, l]:
+ the labels of its four edges anticlockwise, starting from the incoming under edge.
+*/
+
+use crate::diagram::Diagram;
+use crate::mosaic::read_mosaics;
+use crate::output;
+use std::io::{Result, Write};
+
+//Computer algebra systems a Wirtinger presentation can be written for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GroupSyntax {
+    Gap,
+    Magma,
+}
+
+impl GroupSyntax {
+    //The usual extension of files in the syntax (see output::expand_path)
+    pub fn extension(&self) -> &'static str {
+        match self {
+            GroupSyntax::Gap => "g",
+            GroupSyntax::Magma => "m",
+        }
+    }
+}
+
+//A word in the generators of a free group, as (generator, exponent) pairs with generators numbered from 1
+type Word = Vec<(usize, i32)>;
+
+//Python expression constructing the link represented by a diagram with Regina's Link.fromData
+pub fn regina_link(diagram: &Diagram) -> String {
+    let components = diagram
+        .gauss_codes()
+        .iter()
+        .map(|code| format!("{:?}", code))
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("Link.fromData({:?}, [{}])", diagram.crossing_signs(), components)
+}
+
+/* Writes a python script (to be run with Regina's python module) constructing the link represented by every mosaic in the input file.
+Each entry also records the tb and rotation numbers computed here, and running the script prints them alongside Regina's own invariants for comparison.
+Empty mosaics are skipped, as they don't represent a link. */
+pub fn regina_export(input_path: &str, output_path: &str) -> Result<usize> {
+    let mut output_buffer = output::create(output_path)?;
+    let mut count = 0;
+
+    writeln!(output_buffer, "from regina import *\n")?;
+    writeln!(output_buffer, "#(mosaic code, link, tb, rotation number)")?;
+    writeln!(output_buffer, "mosaics = [")?;
+    for mosaic in read_mosaics(input_path)? {
+        let mosaic = mosaic?;
+        let diagram = match Diagram::trace(&mosaic) {
+            Some(diagram) if diagram.component_count() > 0 => diagram,
+            _ => continue,
+        };
+        writeln!(output_buffer, "    (\"{}\", {}, {}, {}),", mosaic, regina_link(&diagram), diagram.tb(), diagram.rot())?;
+        count += 1;
+    }
+    writeln!(output_buffer, "]\n")?;
+    writeln!(output_buffer, "if __name__ == \"__main__\":")?;
+    writeln!(output_buffer, "    for code, link, tb, rot in mosaics:")?;
+    writeln!(output_buffer, "        print(code, link.countComponents(), tb, rot, link.homfly(), sep=\" | \")")?;
+    output_buffer.finish()?;
+    Ok(count)
+}
+
+/* The Wirtinger presentation of the group of the link represented by a diagram (see above), as its number of generators and its relators.
+The empty diagram has no generators. */
+pub fn wirtinger_presentation(diagram: &Diagram) -> (usize, Vec<Word>) {
+    //Indices of the visits of each component passing under a crossing, in order
+    let mut unders: Vec<Vec<usize>> = vec![Vec::new(); diagram.component_count()];
+    for crossing in &diagram.crossings {
+        unders[crossing.under.0].push(crossing.under.1);
+    }
+    let mut first_arc = Vec::with_capacity(unders.len());
+    let mut arcs = 0;
+    for component_unders in unders.iter_mut() {
+        component_unders.sort();
+        first_arc.push(arcs);
+        arcs += component_unders.len().max(1);
+    }
+    //Each arc runs from just after an under pass up to and including the next, so a visit is on the arc begun by the last under pass before it
+    let arc = |(component, visit): (usize, usize)| {
+        let component_unders = &unders[component];
+        let begun = component_unders.iter().filter(|&&under| under < visit).count();
+        first_arc[component] + 1 + (begun + component_unders.len().max(1) - 1) % component_unders.len().max(1)
+    };
+    let relators = diagram
+        .crossings
+        .iter()
+        .map(|crossing| {
+            let (over, incoming) = (arc(crossing.over), arc(crossing.under));
+            let outgoing = arc((crossing.under.0, crossing.under.1 + 1));
+            vec![(over, crossing.sign), (incoming, 1), (over, -crossing.sign), (outgoing, -1)]
+        })
+        .collect();
+    (arcs, relators)
+}
+
+//A word written as a product of powers of F.1, F.2, ..., which both GAP and Magma understand
+fn format_word(word: &Word) -> String {
+    word.iter().map(|&(generator, exponent)| if exponent == 1 { format!("F.{}", generator) } else { format!("F.{}^{}", generator, exponent) }).collect::<Vec<String>>().join("*")
+}
+
+/* The PD code of a diagram (see above), as the edge labels of each crossing, along with the number of components without crossings, which PD codes can't record.
+With `simplified`, kinks are removed first (Reidemeister I moves, repeated while they leave new kinks), and components left without crossings are counted with the others. */
+pub fn pd_code(diagram: &Diagram, simplified: bool) -> (Vec<[usize; 4]>, usize) {
+    //Visits of each component at crossings, whose incoming edge is the one before it and outgoing edge the one after it
+    let mut passes: Vec<Vec<usize>> = vec![Vec::new(); diagram.component_count()];
+    for crossing in &diagram.crossings {
+        passes[crossing.over.0].push(crossing.over.1);
+        passes[crossing.under.0].push(crossing.under.1);
+    }
+    let mut first_edge = Vec::with_capacity(passes.len());
+    let mut edges = 1;
+    for component_passes in passes.iter_mut() {
+        component_passes.sort();
+        first_edge.push(edges);
+        edges += component_passes.len();
+    }
+    let loops = passes.iter().filter(|component_passes| component_passes.is_empty()).count();
+    let edge = |(component, visit): (usize, usize), outgoing: bool| {
+        let count = passes[component].len();
+        let pass = passes[component].iter().position(|&pass| pass == visit).expect("crossings are passed");
+        first_edge[component] + if outgoing { pass } else { (pass + count - 1) % count }
+    };
+    let mut code: Vec<[usize; 4]> = diagram
+        .crossings
+        .iter()
+        .map(|crossing| {
+            let (over, under) = (diagram.visit(crossing.over), diagram.visit(crossing.under));
+            //Faces are numbered anticlockwise, so a face's position in the crossing is how far round it is from the incoming under edge
+            let mut labels = [0; 4];
+            for (face, position, outgoing) in [(under.entry, crossing.under, false), (under.exit, crossing.under, true), (over.entry, crossing.over, false), (over.exit, crossing.over, true)] {
+                labels[((face + 4 - under.entry) % 4) as usize] = edge(position, outgoing);
+            }
+            labels
+        })
+        .collect();
+    if !simplified {
+        return (code, loops);
+    }
+
+    //A kink is a crossing with an edge joining it to itself: removing it joins up its other two edges, and the loop disappears if they're the same edge
+    let mut loops = loops;
+    while let Some(kink) = code.iter().position(|labels| (0..4).any(|i| labels[(i + 1) % 4] == labels[i])) {
+        let labels = code.remove(kink);
+        let looped = (0..4).find(|&i| labels[(i + 1) % 4] == labels[i]).unwrap();
+        let (kept, merged) = (labels[(looped + 2) % 4], labels[(looped + 3) % 4]);
+        if kept == merged {
+            loops += 1;
+        }
+        for label in code.iter_mut().flatten() {
+            if *label == merged {
+                *label = kept;
+            }
+        }
+    }
+    //Relabels the edges left from 1
+    let mut remaining: Vec<usize> = code.iter().flatten().copied().collect();
+    remaining.sort();
+    remaining.dedup();
+    for label in code.iter_mut().flatten() {
+        *label = remaining.binary_search(label).unwrap() + 1;
+    }
+    (code, loops)
+}
+
+//A PD code written as in KnotTheory, e.g. PD[X[1, 5, 2, 4], X[3, 1, 4, 6], X[5, 3, 6, 2]]
+pub fn format_pd(code: &[[usize; 4]]) -> String {
+    let crossings: Vec<String> = code.iter().map(|labels| format!("X[{}, {}, {}, {}]", labels[0], labels[1], labels[2], labels[3])).collect();
+    format!("PD[{}]", crossings.join(", "))
+}
+
+/* Writes a GAP or Magma script building the group of the link represented by every mosaic in the input file from its Wirtinger presentation,
+ as a list `groups` of (mosaic code, group) pairs. Empty mosaics are skipped. Returns the number of groups written. */
+pub fn wirtinger_export(input_path: &str, output_path: &str, syntax: GroupSyntax) -> Result<usize> {
+    let mut output_buffer = output::create(output_path)?;
+    let mut count = 0;
+    writeln!(output_buffer, "{}", if syntax == GroupSyntax::Gap { "groups := [];;" } else { "groups := [* *];" })?;
+    for mosaic in read_mosaics(input_path)? {
+        let mosaic = mosaic?;
+        let diagram = match Diagram::trace(&mosaic) {
+            Some(diagram) if diagram.component_count() > 0 => diagram,
+            _ => continue,
+        };
+        let (generators, relators) = wirtinger_presentation(&diagram);
+        let relator_list = relators.iter().map(format_word).collect::<Vec<String>>().join(", ");
+        match syntax {
+            GroupSyntax::Gap => writeln!(output_buffer, "F := FreeGroup({});; Add(groups, [\"{}\", F / [{}]]);;", generators, mosaic, relator_list)?,
+            //Crossingless links have free groups, which Magma is given as they are
+            GroupSyntax::Magma if relators.is_empty() => writeln!(output_buffer, "F := FreeGroup({}); Append(~groups, <\"{}\", F>);", generators, mosaic)?,
+            GroupSyntax::Magma => writeln!(output_buffer, "F := FreeGroup({}); Append(~groups, <\"{}\", quo<F | {}>>);", generators, mosaic, relator_list)?,
+        }
+        count += 1;
+    }
+    output_buffer.finish()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    //The standard 2x2 unknot and the max-tb right-handed trefoil
+    const UNKNOT: &str = "2134";
+    const TREFOIL: &str = "0021025971629943943103554";
+
+    #[test]
+    fn regina_links_from_signs_and_gauss_codes() {
+        let trace = |code: &str| Diagram::trace(&code.parse().unwrap()).unwrap();
+        assert_eq!(regina_link(&trace(UNKNOT)), "Link.fromData([], [[]])");
+        assert_eq!(regina_link(&trace(TREFOIL)), "Link.fromData([1, 1, 1, 1], [[1, 2, -3, -1, 4, 3, -2, -4]])");
+    }
+
+    #[test]
+    fn regina_export_skips_empty_mosaics() {
+        let path = env::temp_dir().join(format!("mosaic_gen-regina-{}", process::id())).to_str().unwrap().to_string();
+        let output_path = format!("{}.py", path);
+        fs::write(&path, format!("0000\n{}\n\n{}\n", UNKNOT, TREFOIL)).unwrap();
+        assert_eq!(regina_export(&path, &output_path).unwrap(), 2);
+        let script = fs::read_to_string(&output_path).unwrap();
+        assert!(script.starts_with("from regina import *\n"));
+        assert!(script.contains(&format!("    (\"{}\", {}, 1, 0),\n", TREFOIL, regina_link(&Diagram::trace(&TREFOIL.parse().unwrap()).unwrap()))));
+        assert!(!script.contains("\"0000\""));
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&output_path);
+    }
+}
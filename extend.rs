@@ -0,0 +1,327 @@
+/*
+Incremental generation of mosaics from the mosaics a size smaller, by extending their blocks with a new column on the right and a new row along the bottom.
+The top left n x n block of a suitably connected (n+1) x (n+1) mosaic is an open block: suitably connected, except that strands may leave by its right and bottom edges.
+What an open block can be extended with depends only on its boundary profile -- which faces along its right and bottom edges strands leave by --
+ so the extensions of each profile are found once and shared by every block with that profile, rather than searched again for each of them.
+Extending every open n x n block with the extensions closing its edges gives every (n+1) x (n+1) mosaic (once each), and with the extensions leaving them open,
+ every open (n+1) x (n+1) block, to be extended in turn.
+Only the number of blocks with each profile is needed to count mosaics, so a boundary-profile summary of the open n x n blocks -- a count for each profile --
+ can be extended the same way to the summary of the open (n+1) x (n+1) blocks, whose blocks with no strands leaving them are the (n+1) x (n+1) mosaics.
+Block files hold the mosaic codes of open blocks, one per line, and summary files each profile as the faces along the right edge (top to bottom) and bottom edge (left to right),
+ with a 1 for each face a strand leaves by, and its count, like 0110/1001 52, each after a comment line giving the size of the blocks.
+Mosaics and blocks are written grouped by the blocks they extend, rather than in generation order (sorting them puts them in generation order, see sort.rs).
+*/
+
+use crate::error::{Error, Result};
+use crate::mosaic::{Mosaic, NONE, PARTNER, TILE_COUNT};
+use crate::output;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+const BLOCKS_HEADER: &str = "#mosaic_gen open blocks";
+const PROFILES_HEADER: &str = "#mosaic_gen boundary profiles";
+
+/* The faces strands leave an open size x size block by, as bit `row` for the right face of the tile at the end of each row,
+ and bit size + `col` for the bottom face of the tile at the bottom of each column. */
+type Profile = u64;
+
+//The largest blocks that can be extended, leaving room in a Profile for the faces of the extended blocks
+pub const MAX_BLOCK_SIZE: usize = 30;
+
+fn connects(tile: u8, face: usize) -> bool {
+    PARTNER[tile as usize][face] != NONE
+}
+
+//Where the open blocks (or their summary) to extend are
+pub enum Source<'a> {
+    Blocks(usize),   //Every open block of the size, found by extending the empty block a column and row at a time
+    Profiles(usize), //The summary of the open blocks of the size, found the same way
+    File(&'a str),   //A block or summary file
+}
+
+//What was written: the mosaics or open blocks of a size, or the summary of the open blocks, with the number of mosaics it counts
+pub enum Extended {
+    Mosaics { size: usize, count: u64 },
+    Blocks { size: usize, count: u64 },
+    Profiles { size: usize, profiles: usize, mosaics: u128 },
+}
+
+/* The extensions of open size x size blocks with each profile, closing their edges or leaving them open, each as the tiles of
+ the new column from the top, then the new row from the left, then the new corner tile -- along with the profile of the extended block. */
+struct Extensions {
+    size: usize,
+    open: bool,
+    by_profile: HashMap<Profile, Vec<(Vec<u8>, Profile)>>,
+}
+
+impl Extensions {
+    fn new(size: usize, open: bool) -> Extensions {
+        Extensions { size, open, by_profile: HashMap::new() }
+    }
+
+    fn of(&mut self, profile: Profile) -> &[(Vec<u8>, Profile)] {
+        let (size, open) = (self.size, self.open);
+        self.by_profile.entry(profile).or_insert_with(|| {
+            let mut extensions = Vec::new();
+            search(size, open, profile, &mut Vec::with_capacity(2 * size + 1), &mut extensions);
+            extensions
+        })
+    }
+}
+
+//Finds every extension of the tiles chosen so far, tile by tile in the order of Extensions
+fn search(size: usize, open: bool, profile: Profile, chosen: &mut Vec<u8>, extensions: &mut Vec<(Vec<u8>, Profile)>) {
+    let index = chosen.len();
+    if index == 2 * size + 1 {
+        let mut extended = 0;
+        for (row, &tile) in chosen[..size].iter().chain([&chosen[2 * size]]).enumerate() {
+            extended |= (connects(tile, 0) as Profile) << row;
+        }
+        for (col, &tile) in chosen[size..].iter().enumerate() {
+            extended |= (connects(tile, 3) as Profile) << (size + 1 + col);
+        }
+        extensions.push((chosen.clone(), extended));
+        return;
+    }
+    let bit = |bit: usize| profile & (1 << bit) != 0;
+    //Whether strands enter the tile from above and from the left, and whether it's on the right and bottom edges of the extended block
+    let (above, left, right_edge, bottom_edge) = if index < size {
+        (index > 0 && connects(chosen[index - 1], 3), bit(index), true, false)
+    } else if index < 2 * size {
+        (bit(index), index > size && connects(chosen[index - 1], 0), false, true)
+    } else {
+        (size > 0 && connects(chosen[size - 1], 3), size > 0 && connects(chosen[2 * size - 1], 0), true, true)
+    };
+    for tile in 0..TILE_COUNT {
+        if connects(tile, 1) == above && connects(tile, 2) == left && (open || !((right_edge && connects(tile, 0)) || (bottom_edge && connects(tile, 3)))) {
+            chosen.push(tile);
+            search(size, open, profile, chosen, extensions);
+            chosen.pop();
+        }
+    }
+}
+
+//The tiles of a size x size block extended by the tiles of an extension
+fn extend_block(block: &[u8], size: usize, extension: &[u8]) -> Vec<u8> {
+    let mut tiles = Vec::with_capacity((size + 1) * (size + 1));
+    for row in 0..size {
+        tiles.extend_from_slice(&block[row * size..(row + 1) * size]);
+        tiles.push(extension[row]);
+    }
+    tiles.extend_from_slice(&extension[size..]);
+    tiles
+}
+
+//The profile of a size x size block, or None if it isn't an open block
+fn profile_of(block: &[u8], size: usize) -> Option<Profile> {
+    let mut profile = 0;
+    for (index, &tile) in block.iter().enumerate() {
+        let (row, col) = (index / size, index % size);
+        if (row == 0 && connects(tile, 1)) || (col == 0 && connects(tile, 2)) {
+            return None;
+        }
+        if col + 1 < size {
+            if connects(tile, 0) != connects(block[index + 1], 2) {
+                return None;
+            }
+        } else {
+            profile |= (connects(tile, 0) as Profile) << row;
+        }
+        if row + 1 < size {
+            if connects(tile, 3) != connects(block[index + size], 1) {
+                return None;
+            }
+        } else {
+            profile |= (connects(tile, 3) as Profile) << (size + col);
+        }
+    }
+    Some(profile)
+}
+
+//Calls `found` with every open size x size block extending `block` (of size `from`), and its profile
+fn each_block(block: &[u8], from: usize, profile: Profile, size: usize, extensions: &mut [Extensions], found: &mut dyn FnMut(&[u8], Profile) -> io::Result<()>) -> io::Result<()> {
+    if from == size {
+        return found(block, profile);
+    }
+    //`extensions` holds the extensions of blocks of size `from` and up
+    let (this, rest) = extensions.split_first_mut().expect("there are extensions of every size up to the blocks'");
+    for (extension, extended) in this.of(profile) {
+        each_block(&extend_block(block, from, extension), from + 1, *extended, size, rest, found)?;
+    }
+    Ok(())
+}
+
+//The number of open size x size blocks with each profile
+fn profiles(size: usize) -> BTreeMap<Profile, u128> {
+    let mut counts = BTreeMap::from([(0, 1)]);
+    for from in 0..size {
+        counts = extend_profiles(&counts, from);
+    }
+    counts
+}
+
+//The number of size x size mosaics, found by extending every open block of the size below with the extensions closing its edges (without writing them)
+pub fn count_mosaics(size: usize) -> u64 {
+    let mut closing = Extensions::new(size - 1, false);
+    let mut smaller: Vec<Extensions> = (0..size - 1).map(|from| Extensions::new(from, true)).collect();
+    let mut count = 0;
+    each_block(&[], 0, 0, size - 1, &mut smaller, &mut |_, profile| {
+        count += closing.of(profile).len() as u64;
+        Ok(())
+    })
+    .expect("counting never fails");
+    count
+}
+
+//The number of size x size mosaics, from the summary of the open blocks of the size: those no strands leave
+pub fn count_by_profiles(size: usize) -> u128 {
+    profiles(size).get(&0).copied().unwrap_or(0)
+}
+
+/* The summary of the open blocks a size larger than those counted in `counts`.
+Rather than listing each profile's extensions, the counts are carried through the new tiles one at a time (in the order of Extensions),
+ by the faces of the old profile still to be met, the faces of the new profile so far, and the faces of the last tile (and the column's bottom tile) that the next tiles meet. */
+fn extend_profiles(counts: &BTreeMap<Profile, u128>, size: usize) -> BTreeMap<Profile, u128> {
+    let mut states: HashMap<(Profile, Profile, bool, bool), u128> = counts.iter().map(|(&profile, &count)| ((profile, 0, false, false), count)).collect();
+    for index in 0..=2 * size {
+        let mut next = HashMap::with_capacity(states.len());
+        for ((old, new, last, column_bottom), count) in states {
+            //The row starts at the left edge, below the column's bottom tile
+            let (last, column_bottom) = if index == size { (false, last) } else { (last, column_bottom) };
+            let (above, left) = if index < size {
+                (last, old & 1 != 0)
+            } else if index < 2 * size {
+                (old & 1 != 0, last)
+            } else {
+                (column_bottom, last)
+            };
+            for tile in (0..TILE_COUNT).filter(|&tile| connects(tile, 1) == above && connects(tile, 2) == left) {
+                let (right, down) = (connects(tile, 0) as Profile, connects(tile, 3) as Profile);
+                let (new, last) = if index < size {
+                    (new | right << index, down != 0)
+                } else if index < 2 * size {
+                    (new | down << (index + 1), right != 0)
+                } else {
+                    (new | right << size | down << (2 * size + 1), false)
+                };
+                *next.entry((old >> 1, new, last, column_bottom)).or_insert(0) += count;
+            }
+        }
+        states = next;
+    }
+    let mut extended = BTreeMap::new();
+    for ((_, new, _, _), count) in states {
+        *extended.entry(new).or_insert(0) += count;
+    }
+    extended
+}
+
+//A profile written as its faces, like 0110/1001
+fn profile_faces(profile: Profile, size: usize) -> String {
+    let faces = |bits: std::ops::Range<usize>| bits.map(|bit| if profile & (1 << bit) != 0 { '1' } else { '0' }).collect::<String>();
+    format!("{}/{}", faces(0..size), faces(size..2 * size))
+}
+
+fn parse_profile_faces(faces: &str, size: usize) -> Option<Profile> {
+    let (right, bottom) = faces.split_once('/')?;
+    if right.len() != size || bottom.len() != size {
+        return None;
+    }
+    right.chars().chain(bottom.chars()).enumerate().try_fold(0, |profile, (bit, face)| match face {
+        '0' => Some(profile),
+        '1' => Some(profile | 1 << bit),
+        _ => None,
+    })
+}
+
+//The size of the blocks in a file's header line, like "#mosaic_gen open blocks 4x4"
+fn header_size(header: &str, prefix: &str) -> Option<usize> {
+    let (rows, cols) = header.strip_prefix(prefix)?.trim().split_once('x')?;
+    Some(rows.parse().ok()?).filter(|&size| cols.parse() == Ok(size) && size <= MAX_BLOCK_SIZE)
+}
+
+//Reads the counts of a summary file, after its header
+fn read_profiles(lines: impl Iterator<Item = io::Result<String>>, size: usize, input_path: &str) -> io::Result<BTreeMap<Profile, u128>> {
+    let invalid = |what: &str, value: &str| io::Error::new(io::ErrorKind::InvalidData, format!("invalid {} \"{}\" in {}", what, value, input_path));
+    let mut counts = BTreeMap::new();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (faces, count) = line.trim().split_once(' ').ok_or_else(|| invalid("boundary profile", &line))?;
+        let profile = parse_profile_faces(faces, size).ok_or_else(|| invalid("boundary profile", faces))?;
+        counts.insert(profile, count.trim().parse().map_err(|_| invalid("count", count))?);
+    }
+    Ok(counts)
+}
+
+/* Extends the open blocks (or summary) of a size to the next size up, writing the mosaics of that size to output_path, or with `open`, its open blocks --
+ or for a summary, the summary of its open blocks. */
+pub fn extend(source: &Source, output_path: &str, open: bool) -> Result<Extended> {
+    let (size, blocks, summary) = match *source {
+        Source::Blocks(size) => (size, None, None),
+        Source::Profiles(size) => (size, None, Some(profiles(size))),
+        Source::File(input_path) => {
+            let mut lines = BufReader::new(File::open(input_path)?).lines();
+            let header = lines.next().transpose()?.unwrap_or_default();
+            match (header_size(&header, BLOCKS_HEADER), header_size(&header, PROFILES_HEADER)) {
+                (Some(size), _) => (size, Some((lines, input_path)), None),
+                (None, Some(size)) => (size, None, Some(read_profiles(lines, size, input_path)?)),
+                (None, None) => {
+                    return Err(Error::Invalid(format!(
+                        "{} is neither a block file nor a summary file (a census only has the blocks no strands leave, so write the open blocks with extend blocks:<size> <output> --open)",
+                        input_path
+                    )))
+                }
+            }
+        }
+    };
+    if summary.is_some() && open {
+        return Err(Error::Invalid("--open extends blocks, and summaries always count open blocks".to_string()));
+    }
+    let mut output_buffer = output::create(output_path)?;
+    if let Some(counts) = summary {
+        let extended = extend_profiles(&counts, size);
+        writeln!(output_buffer, "{} {}x{}", PROFILES_HEADER, size + 1, size + 1)?;
+        for (&profile, count) in &extended {
+            writeln!(output_buffer, "{} {}", profile_faces(profile, size + 1), count)?;
+        }
+        output_buffer.finish()?;
+        return Ok(Extended::Profiles { size: size + 1, profiles: extended.len(), mosaics: extended.get(&0).copied().unwrap_or(0) });
+    }
+
+    if open {
+        writeln!(output_buffer, "{} {}x{}", BLOCKS_HEADER, size + 1, size + 1)?;
+    }
+    let mut extensions = Extensions::new(size, open);
+    let mut count = 0;
+    let mut write = |block: &[u8], profile: Profile| -> io::Result<()> {
+        for (extension, _) in extensions.of(profile) {
+            writeln!(output_buffer, "{}", Mosaic::from_tiles(size + 1, extend_block(block, size, extension))?)?;
+            count += 1;
+        }
+        Ok(())
+    };
+    match blocks {
+        Some((lines, input_path)) => {
+            for line in lines {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let block: Mosaic = line.parse()?;
+                let profile = Some(block.tiles()).filter(|_| block.size() == size).and_then(|tiles| profile_of(tiles, size));
+                let profile = profile.ok_or_else(|| Error::Invalid(format!("{} in {} isn't an open {}x{} block", block, input_path, size, size)))?;
+                write(block.tiles(), profile)?;
+            }
+        }
+        None => {
+            let mut smaller: Vec<Extensions> = (0..size).map(|from| Extensions::new(from, true)).collect();
+            each_block(&[], 0, 0, size, &mut smaller, &mut write)?;
+        }
+    }
+    output_buffer.finish()?;
+    Ok(if open { Extended::Blocks { size: size + 1, count } } else { Extended::Mosaics { size: size + 1, count } })
+}
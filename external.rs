@@ -0,0 +1,87 @@
+/*
+Invariants computed by external programs, such as Khovanov homology engines (KnotJob, khoca, JavaKh), joined back into census records.
+The records of a census are sent to the program in batches, running it once for each batch with the batch on its standard input, one line per record:
+    rank<TAB>PD code<TAB>loops
+ where the rank is the record's line number in the census (counted from 0), the PD code is simplified by Reidemeister I moves (see export::pd_code),
+ and loops is the number of components without crossings, which PD codes can't record.
+The program answers with lines rank<TAB>result on its standard output, in any order, and each record is written out with its result as a last column
+ (or "-" if it gave none). The command is run with sh -c, so a short script can adapt an engine's own formats to these.
+Mosaics with virtual crossings have no PD code, so they're not sent.
+*/
+
+use crate::diagram::Diagram;
+use crate::export::{format_pd, pd_code};
+use crate::mosaic::{Mosaic, VIRTUAL_CROSSING};
+use crate::output;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Result, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+//Runs the command on a batch of input lines, returning its results by rank
+fn run_batch(command: &str, batch: String) -> Result<HashMap<usize, String>> {
+    let mut child = Command::new("sh").arg("-c").arg(command).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+    //Writes the batch from another thread, so a program answering as it reads can't fill its output pipe while we're still writing
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let writer = thread::spawn(move || stdin.write_all(batch.as_bytes()));
+    let mut answers = String::new();
+    child.stdout.take().expect("stdout is piped").read_to_string(&mut answers)?;
+    let status = child.wait()?;
+    writer.join().map_err(|_| Error::other("writing to the external command panicked"))??;
+    if !status.success() {
+        return Err(Error::other(format!("external command \"{}\" failed ({})", command, status)));
+    }
+    let mut results = HashMap::new();
+    for line in answers.lines().filter(|line| !line.trim().is_empty()) {
+        let (rank, result) = line
+            .split_once('\t')
+            .and_then(|(rank, result)| Some((rank.trim().parse::<usize>().ok()?, result.trim().to_string())))
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("external command answered \"{}\", not rank<TAB>result", line)))?;
+        results.insert(rank, result);
+    }
+    Ok(results)
+}
+
+//Writes the records of a batch with their results, returning how many had one
+fn write_batch(output_buffer: &mut dyn Write, first_rank: usize, records: &[String], results: &HashMap<usize, String>) -> Result<usize> {
+    let mut answered = 0;
+    for (offset, record) in records.iter().enumerate() {
+        let result = results.get(&(first_rank + offset));
+        answered += result.is_some() as usize;
+        writeln!(output_buffer, "{} | {}", record, result.map_or("-", String::as_str))?;
+    }
+    Ok(answered)
+}
+
+/* Writes every census record of the input file with a last column computed by an external command (see above), sending it `batch_size` records at a time.
+Returns the number of records and how many of them the command gave results for. */
+pub fn compute_external(input_path: &str, output_path: &str, command: &str, batch_size: usize) -> Result<(usize, usize)> {
+    let mut output_buffer = output::create(output_path)?;
+    let (mut records, mut batch) = (Vec::new(), String::new());
+    let (mut count, mut answered) = (0, 0);
+    for line in BufReader::new(File::open(input_path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let rank = count + records.len();
+        let mosaic: Mosaic = line.split(" | ").next().unwrap_or_default().parse()?;
+        if let Some(diagram) = Diagram::trace(&mosaic).filter(|_| !mosaic.tiles().contains(&VIRTUAL_CROSSING)) {
+            let (code, loops) = pd_code(&diagram, true);
+            batch.push_str(&format!("{}\t{}\t{}\n", rank, format_pd(&code), loops));
+        }
+        records.push(line);
+        if records.len() == batch_size {
+            answered += write_batch(&mut output_buffer, count, &records, &run_batch(command, std::mem::take(&mut batch))?)?;
+            count += records.len();
+            records.clear();
+        }
+    }
+    if !records.is_empty() {
+        answered += write_batch(&mut output_buffer, count, &records, &run_batch(command, batch)?)?;
+        count += records.len();
+    }
+    output_buffer.finish()?;
+    Ok((count, answered))
+}
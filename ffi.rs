@@ -0,0 +1,210 @@
+/*
+C API (enabled by the `ffi` feature, in the cdylib and staticlib the library target builds), so C and C++ programs can generate mosaics and compute their invariants.
+The matching header is legendrian_mosaics.h, generated from this file with cbindgen.
+
+Mosaics are passed as opaque LmMosaic pointers created by lm_mosaic_parse and released with lm_mosaic_free.
+Functions that can fail return an LmStatus, writing their result through an out pointer only on success.
+*/
+
+use crate::diagram::Diagram;
+use crate::generator::generate_with;
+use crate::mosaic::Mosaic;
+use std::ffi::{c_char, c_void, CStr};
+use std::ops::ControlFlow;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LmStatus {
+    Ok = 0,
+    NullPointer = -1,
+    NotSuitablyConnected = -2,
+    InvalidSize = -3,
+}
+
+pub struct LmMosaic {
+    mosaic: Mosaic,
+    diagram: Option<Diagram>,
+}
+
+/* Called with the tiles of each generated mosaic (size * size values from 0 to 9, or 10 for a virtual crossing with the `virtual` feature, left to right, top to bottom) and the user data passed to lm_generate.
+The tile buffer is only valid for the duration of the call. Returning nonzero stops generation. */
+pub type LmMosaicCallback = Option<extern "C" fn(tiles: *const u8, tile_count: usize, user_data: *mut c_void) -> i32>;
+
+/// Parses a null terminated mosaic code, returning NULL if it isn't a valid mosaic code.
+///
+/// # Safety
+/// `code` must be NULL or point to a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn lm_mosaic_parse(code: *const c_char) -> *mut LmMosaic {
+    if code.is_null() {
+        return ptr::null_mut();
+    }
+    let mosaic = match CStr::from_ptr(code).to_str().ok().and_then(|code| code.parse::<Mosaic>().ok()) {
+        Some(mosaic) => mosaic,
+        None => return ptr::null_mut(),
+    };
+    let diagram = Diagram::trace(&mosaic);
+    Box::into_raw(Box::new(LmMosaic { mosaic, diagram }))
+}
+
+/// Releases a mosaic returned by lm_mosaic_parse.
+///
+/// # Safety
+/// `mosaic` must be NULL or a pointer returned by lm_mosaic_parse that hasn't already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn lm_mosaic_free(mosaic: *mut LmMosaic) {
+    if !mosaic.is_null() {
+        drop(Box::from_raw(mosaic));
+    }
+}
+
+/// Size n of an n x n mosaic, or 0 for NULL.
+///
+/// # Safety
+/// `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse.
+#[no_mangle]
+pub unsafe extern "C" fn lm_mosaic_size(mosaic: *const LmMosaic) -> usize {
+    mosaic.as_ref().map_or(0, |mosaic| mosaic.mosaic.size())
+}
+
+/// Whether the mosaic is suitably connected (false for NULL).
+///
+/// # Safety
+/// `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse.
+#[no_mangle]
+pub unsafe extern "C" fn lm_mosaic_is_suitably_connected(mosaic: *const LmMosaic) -> bool {
+    mosaic.as_ref().is_some_and(|mosaic| mosaic.diagram.is_some())
+}
+
+//Applies f to the traced diagram of a mosaic, writing the result to out
+unsafe fn with_diagram<T>(mosaic: *const LmMosaic, out: *mut T, f: impl Fn(&Diagram) -> T) -> LmStatus {
+    let mosaic = match mosaic.as_ref() {
+        Some(mosaic) if !out.is_null() => mosaic,
+        _ => return LmStatus::NullPointer,
+    };
+    match &mosaic.diagram {
+        Some(diagram) => {
+            *out = f(diagram);
+            LmStatus::Ok
+        }
+        None => LmStatus::NotSuitablyConnected,
+    }
+}
+
+/// Writes the Thurston-Bennequin number of the mosaic to `tb`.
+///
+/// # Safety
+/// `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse, and `tb` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn lm_mosaic_tb(mosaic: *const LmMosaic, tb: *mut i32) -> LmStatus {
+    with_diagram(mosaic, tb, Diagram::tb)
+}
+
+/// Writes the rotation number of the mosaic (with each component oriented in the direction it's traversed) to `rot`.
+///
+/// # Safety
+/// `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse, and `rot` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn lm_mosaic_rot(mosaic: *const LmMosaic, rot: *mut i32) -> LmStatus {
+    with_diagram(mosaic, rot, Diagram::rot)
+}
+
+/// Writes the number of link components of the mosaic to `components`.
+///
+/// # Safety
+/// `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse, and `components` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn lm_mosaic_components(mosaic: *const LmMosaic, components: *mut usize) -> LmStatus {
+    with_diagram(mosaic, components, Diagram::component_count)
+}
+
+/// Writes the stable ID of the mosaic (see Mosaic::id) to `id`, as 16 bytes, most significant first.
+///
+/// # Safety
+/// `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse, and `id` must be NULL or valid for writes of 16 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn lm_mosaic_id(mosaic: *const LmMosaic, id: *mut u8) -> LmStatus {
+    match mosaic.as_ref() {
+        Some(mosaic) if !id.is_null() => {
+            ptr::copy_nonoverlapping(mosaic.mosaic.id().0.to_be_bytes().as_ptr(), id, 16);
+            LmStatus::Ok
+        }
+        _ => LmStatus::NullPointer,
+    }
+}
+
+/// Calls `callback` with every suitably connected size x size mosaic, in the same order as mosaic_gen,
+/// writing the number of mosaics passed to the callback to `count` (if it isn't NULL).
+///
+/// # Safety
+/// `count` must be NULL or valid for writes, and `callback` must be safe to call with `user_data`.
+#[no_mangle]
+pub unsafe extern "C" fn lm_generate(size: usize, callback: LmMosaicCallback, user_data: *mut c_void, count: *mut u64) -> LmStatus {
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return LmStatus::NullPointer,
+    };
+    if size == 0 {
+        return LmStatus::InvalidSize;
+    }
+    let mut generated = 0;
+    let _ = generate_with(size, |mosaic| {
+        generated += 1;
+        if callback(mosaic.tiles().as_ptr(), mosaic.tiles().len(), user_data) != 0 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    });
+    if let Some(count) = count.as_mut() {
+        *count = generated;
+    }
+    LmStatus::Ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::MosaicGenerator;
+    use std::ffi::CString;
+
+    //Counts the mosaics it's called with in the u64 user data, stopping at 5
+    extern "C" fn count_to_five(tiles: *const u8, tile_count: usize, user_data: *mut c_void) -> i32 {
+        assert!(!tiles.is_null() && tile_count == 16);
+        let count = unsafe { &mut *(user_data as *mut u64) };
+        *count += 1;
+        (*count == 5) as i32
+    }
+
+    #[test]
+    fn mosaics_give_their_invariants() {
+        unsafe {
+            let trefoil = CString::new("0021025971629943943103554").unwrap();
+            let mosaic = lm_mosaic_parse(trefoil.as_ptr());
+            let (mut tb, mut components) = (0, 0);
+            assert_eq!(lm_mosaic_size(mosaic), 5);
+            assert!(lm_mosaic_is_suitably_connected(mosaic));
+            assert_eq!(lm_mosaic_tb(mosaic, &mut tb), LmStatus::Ok);
+            assert_eq!(lm_mosaic_components(mosaic, &mut components), LmStatus::Ok);
+            assert_eq!((tb, components), (1, 1));
+            assert_eq!(lm_mosaic_tb(mosaic, ptr::null_mut()), LmStatus::NullPointer);
+            lm_mosaic_free(mosaic);
+
+            let broken = CString::new("2100").unwrap();
+            let mosaic = lm_mosaic_parse(broken.as_ptr());
+            assert!(!mosaic.is_null() && !lm_mosaic_is_suitably_connected(mosaic));
+            assert_eq!(lm_mosaic_rot(mosaic, &mut tb), LmStatus::NotSuitablyConnected);
+            lm_mosaic_free(mosaic);
+            let invalid = CString::new("213").unwrap();
+            assert!(lm_mosaic_parse(invalid.as_ptr()).is_null());
+            assert_eq!(lm_mosaic_size(ptr::null()), 0);
+        }
+    }
+
+    #[test]
+    fn generation_stops_when_the_callback_asks() {
+        let (mut calls, mut count) = (0u64, 0);
+        let status = unsafe { lm_generate(4, Some(count_to_five), &mut calls as *mut u64 as *mut c_void, &mut count) };
+        assert_eq!((status, calls, count), (LmStatus::Ok, 5, 5));
+        assert!(MosaicGenerator::new(4).count() > 5);
+        assert_eq!(unsafe { lm_generate(0, Some(count_to_five), ptr::null_mut(), ptr::null_mut()) }, LmStatus::InvalidSize);
+        assert_eq!(unsafe { lm_generate(4, None, ptr::null_mut(), ptr::null_mut()) }, LmStatus::NullPointer);
+    }
+}
@@ -0,0 +1,1839 @@
+/*
+Iterative generation of all suitably connected n x n legendrian mosaics.
+MosaicGenerator steps through the mosaics one at a time, so they can be written to a file (see mosaic_gen) or processed as they're produced.
+*/
+
+use crate::affinity::{self, Placement};
+use crate::diagram::Diagram;
+use crate::error::{Error, Result};
+use crate::invariant::Condition;
+use crate::metrics::Metrics;
+use crate::mosaic::{self, Mosaic, TILE_COUNT};
+use crate::output::{self, Chunking};
+use crate::pattern::Pattern;
+use crate::polite::{self, ThrottledSink};
+use crate::pruning::{Components, Crossings, Cusps, Pruner};
+use crate::rank;
+use crate::self_check::SelfCheck;
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::mpsc::{channel, sync_channel, Receiver};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, Scope};
+use std::time::Instant;
+
+/* 
+Connection table for mosaic generation
+Essentially a hash map, states of surrounding tiles are converted to a base-3 number,
+ and the list of tiles at that index in CONNECTION_TABLE represents all tiles that would be suitably connected given these surroundings
+ 0: no connection to tile
+ 1: must connect to tile
+ 2: undecided, may or may not connect
+
+ Digits in the number are assigned as below:
+    1
+ 2 ▇▇ 0
+    3
+ e.g. 0201 would indicate that the tile:
+ must connect to the tile to its right,
+ mustn't connect to the tiles above/below it,
+ may or may not connect to the tile to its left
+*/
+const CONNECTION_TABLE: &[&[usize]]= &[ 
+    //000x
+    &[0],        
+    &[],
+    &[0],
+    //001x
+    &[],       
+    &[3],
+    &[3],
+    //002x
+    &[0],        
+    &[3],
+    &[0,3],
+    //010x
+    &[],        
+    &[5],
+    &[5],
+    //011x
+    &[4],       
+    &[],
+    &[4],
+    //012x
+    &[4],        
+    &[5],
+    &[4,5],
+    //020x
+    &[0],        
+    &[5],
+    &[0,5],
+    //021x
+    &[4],       
+    &[3],
+    &[3,4],
+    //022x
+    &[0,4],        
+    &[3,5],
+    &[0,3,4,5],
+    //100x
+    &[],        
+    &[2],
+    &[2],
+    //101x
+    &[6],       
+    &[],
+    &[6],
+    //102x
+    &[6],        
+    &[2],
+    &[2,6],
+    //110x
+    &[1],        
+    &[],
+    &[1],
+    //111x
+    &[],       
+    &[7,8,9],
+    &[7,8,9],
+    //112x
+    &[1],        
+    &[7,8,9],
+    &[1,7,8,9],
+    //120x
+    &[1],        
+    &[2],
+    &[1,2],
+    //121x
+    &[6],       
+    &[7,8,9],
+    &[6,7,8,9],
+    //122x
+    &[6],        
+    &[2,7,8,9],
+    &[2,6,7,8,9],
+    //200x
+    &[0],        
+    &[2],
+    &[0,2],
+    //201x
+    &[6],       
+    &[3],
+    &[3,6],
+    //202x
+    &[0,6],        
+    &[2,3],
+    &[0,2,3,6],
+    //210x
+    &[1],        
+    &[5],
+    &[1,5],
+    //211x
+    &[4],       
+    &[7,8,9],
+    &[4,7,8,9],
+    //212x
+    &[1,4],        
+    &[5,7,8,9],
+    &[1,5,7,8,9],
+    //220x
+    &[0,1],        
+    &[2,5],
+    &[0,1,2,5],
+    //221x
+    &[4,6],       
+    &[3,7,8,9],
+    &[3,4,5,7,8,9],
+    //222x
+    &[0,1,4,6],        
+    &[2,3,5,7,8,9],
+    &[0,1,2,3,4,5,6,7,8,9]
+];
+
+/* Generates all size x size mosaics, in increasing order
+This essentially just counts upwards -- each mosaic is represented by a base-10 number with size^2 digits, produced by reading the mosaic left to right, top to bottom.
+e.g. 555020001 = 555
+                 020
+                 001
+During generation, we keep a list of valid tiles for each digit based on the tiles leftward and above that digit in the underlying mosaic.
+Whenever we have to "carry" a digit, we create new lists of valid tiles for every digit to the right of the carried digit
+This guarantees that we produce every suitably connected size x size mosaic.
+The generator stops at each complete mosaic, and picks up where it left off the next time it's advanced.
+It can also count down, or read the digits off the mosaic by columns (see Order).
+*/
+pub struct MosaicGenerator {
+    size: usize,
+    vector_length: usize,
+    /* The tiles, packed four bits to a tile sixteen to a word as in the batches of sharded runs (see packed_words), so two words up to 5x5, with a blank tile after
+     them standing in for those past the top and left edges. Each tile's connection index is read off the tiles above it and to its left here, and the
+     shards copy their batches straight from it. At 5x5 this searched about a fifth slower alone than a usize per tile (dfs, and visit about an eighth),
+     as each tile placed reads back the word the last was written to, and level with it sharded; a u128 board was slower still, at its variable shifts. */
+    board: Vec<u64>,
+    //The tiles unpacked, for the pruners and the mosaics returned, brought up to date from the board only when they're read
+    tiles: Vec<usize>,
+    stale: usize, //The first step whose tile may have changed on the board since the tiles were last unpacked
+    curr_tile: usize,
+    rightward: bool,
+    emitted: bool, //Whether the current (complete) mosaic has already been returned
+    finished: bool,
+    digit_index: Vec<usize>,
+    valid_tiles_for: Vec<&'static [usize]>,
+    backtracks: u64,
+    nodes: u64,
+    tables: Vec<Table>, //Connection table for each tile of the mosaic, leaving out any tiles forbidden there
+    pruners: Vec<Box<dyn Pruner>>, //Rules abandoning partial mosaics that can't be completed to ones meeting the constraints (see pruning.rs)
+    diagram_filters: DiagramFilters, //Only mosaics whose diagrams pass these are generated
+    space_efficient: bool, //Only space-efficient mosaics are generated
+    conditions: Vec<Condition>, //Only mosaics meeting these conditions on their invariants are generated
+    order: Order,
+    positions: Vec<usize>, //The index into the mosaic of each step of the search, in the order's reading of the board
+    steps: Vec<Step>, //What each step of the search looks at, from the positions and tables
+}
+
+/* What the connection index of a step's tile depends on, worked out once for each generator rather than at every step of the search:
+ the tile's position, its connection table, the digits of the index for its right and bottom (0 on the edge of the board, 2 inside it),
+ and the positions of the tiles above it and to its left, which give it the other digits -- on the top and left edges, the blank tile after the board. */
+#[derive(Clone, Copy)]
+struct Step {
+    position: usize,
+    table: Table,
+    edges: usize,
+    above: usize,
+    left: usize,
+}
+
+pub const CROSSING: usize = 9;
+
+/* The order mosaics are generated in: lexicographic in their tiles read left to right then top to bottom (the order of mosaic codes, lex),
+ or read top to bottom then left to right (column-major, colex), either from the smallest tiles up or from the largest down (revlex and revcolex).
+Every order visits the same mosaics. A search reading the board by columns still meets each tile's neighbours above and to its left before it,
+ so it uses the same connection table, but the pruners only follow a board being read by rows (see pruning.rs), so in column-major orders
+ they're checked once each mosaic is complete, as filters, and prune nothing. */
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Order {
+    #[default]
+    Lex,
+    Revlex,
+    Colex,
+    Revcolex,
+}
+
+impl FromStr for Order {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Order> {
+        [Order::Lex, Order::Revlex, Order::Colex, Order::Revcolex]
+            .into_iter()
+            .find(|order| order.to_string() == name)
+            .ok_or_else(|| Error::Parse { what: "order", text: name.to_string(), reason: "the orders are lex, revlex, colex and revcolex".to_string() })
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            Order::Lex => "lex",
+            Order::Revlex => "revlex",
+            Order::Colex => "colex",
+            Order::Revcolex => "revcolex",
+        })
+    }
+}
+
+impl Order {
+    pub fn column_major(self) -> bool {
+        matches!(self, Order::Colex | Order::Revcolex)
+    }
+
+    pub fn reverse(self) -> bool {
+        matches!(self, Order::Revlex | Order::Revcolex)
+    }
+
+    //The indices into a size x size mosaic of its tiles in the order they're read
+    fn positions(self, size: usize) -> impl Iterator<Item = usize> {
+        let column_major = self.column_major();
+        (0..size * size).map(move |step| if column_major { step % size * size + step / size } else { step })
+    }
+
+    //The tiles of a size x size mosaic in the order they're read
+    pub fn read(self, tiles: &[usize], size: usize) -> Vec<usize> {
+        self.positions(size).map(|position| tiles[position]).collect()
+    }
+
+    //The mosaic with tiles read in this order
+    fn place(self, read: &[usize], size: usize) -> Vec<usize> {
+        let mut tiles = vec![0; size * size];
+        for (step, position) in self.positions(size).enumerate() {
+            tiles[position] = read[step];
+        }
+        tiles
+    }
+
+    //Compares two size x size mosaics' tiles by which comes first in this order
+    pub fn compare(self, tiles: &[usize], other: &[usize], size: usize) -> Ordering {
+        let ordering = self.positions(size).map(|position| tiles[position].cmp(&other[position])).find(|ordering| ordering.is_ne()).unwrap_or(Ordering::Equal);
+        if self.reverse() { ordering.reverse() } else { ordering }
+    }
+}
+
+//Tiles allowed at a position of the mosaic, as a bit per tile
+pub type TileSet = u16;
+pub const ALL_TILES: TileSet = (1 << TILE_COUNT) - 1;
+
+//The tiles with a strand leaving by their bottom, and by their right
+const OPEN_BOTTOM: TileSet = ALL_TILES & !(1 | 1 << 3 | 1 << 4 | 1 << 5);
+const OPEN_RIGHT: TileSet = ALL_TILES & !(1 | 1 << 1 | 1 << 4 | 1 << 6);
+
+//Lists of valid tiles for each index into the connection table, as in CONNECTION_TABLE
+type Table = &'static [&'static [usize]];
+
+//CONNECTION_TABLE, with the virtual crossing allowed wherever a crossing is when the `virtual` feature is on
+#[cfg(not(feature = "virtual"))]
+fn connection_table() -> Table {
+    CONNECTION_TABLE
+}
+
+#[cfg(feature = "virtual")]
+fn connection_table() -> Table {
+    static TABLE: OnceLock<Table> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let table: Vec<&'static [usize]> = CONNECTION_TABLE
+            .iter()
+            .map(|&valid| if valid.contains(&CROSSING) { &*Vec::leak([valid, &[mosaic::VIRTUAL_CROSSING as usize]].concat()) } else { valid })
+            .collect();
+        Vec::leak(table)
+    })
+}
+
+/* The connection table restricted to a set of tiles. Each restricted table is built once and kept for the rest of the run,
+ so generators can keep borrowing lists of valid tiles from it however many are created. */
+fn restricted_table(allowed: TileSet) -> Table {
+    static TABLES: OnceLock<Mutex<HashMap<TileSet, Table>>> = OnceLock::new();
+    if allowed == ALL_TILES {
+        return connection_table();
+    }
+    let mut tables = TABLES.get_or_init(|| Mutex::new(HashMap::new())).lock().expect("restricted table cache is never poisoned");
+    tables.entry(allowed).or_insert_with(|| {
+        let table: Vec<&'static [usize]> = connection_table()
+            .iter()
+            .map(|valid| &*Vec::leak(valid.iter().copied().filter(|&tile| allowed & (1 << tile) != 0).collect()))
+            .collect();
+        Vec::leak(table)
+    })
+}
+
+impl MosaicGenerator {
+    pub fn new(size: usize) -> MosaicGenerator {
+        let vector_length = size*size - 1;
+        MosaicGenerator {
+            size,
+            vector_length,
+            board: vec![0; (vector_length + 2).div_ceil(16)],
+            tiles: vec![0; vector_length + 1],
+            stale: 0,
+            curr_tile: 0,
+            rightward: true,
+            emitted: false,
+            finished: false,
+            digit_index: vec![0; vector_length + 1],
+            valid_tiles_for: vec![&[]; vector_length + 1],
+            backtracks: 0,
+            nodes: 0,
+            tables: vec![connection_table(); vector_length + 1],
+            pruners: Vec::new(),
+            diagram_filters: DiagramFilters::default(),
+            space_efficient: false,
+            conditions: Vec::new(),
+            order: Order::Lex,
+            positions: (0..vector_length + 1).collect(),
+            steps: Vec::new(),
+        }
+        .laid_out()
+    }
+
+    //Works out the steps of the search from the positions and tables
+    fn laid_out(mut self) -> MosaicGenerator {
+        let (size, outside) = (self.size, self.vector_length + 1);
+        self.steps = self
+            .positions
+            .iter()
+            .map(|&position| {
+                let (row, col) = (position / size, position % size);
+                Step {
+                    position,
+                    table: self.tables[position],
+                    edges: if col == size - 1 { 0 } else { 2 } + 27 * if row == size - 1 { 0 } else { 2 },
+                    above: if row == 0 { outside } else { position - size },
+                    left: if col == 0 { outside } else { position - 1 },
+                }
+            })
+            .collect();
+        self
+    }
+
+    //Generates the mosaics in another order (see Order). Must be called before the generator's first used
+    pub fn with_order(mut self, order: Order) -> MosaicGenerator {
+        self.order = order;
+        self.positions = order.positions(self.size).collect();
+        self.laid_out()
+    }
+
+    //Restricts generation to mosaics with only the tiles allowed[index] at each index (read left to right, top to bottom). Must be called before the generator's first used
+    pub fn with_allowed_tiles(mut self, allowed: &[TileSet]) -> MosaicGenerator {
+        self.tables = allowed.iter().map(|&allowed| restricted_table(allowed)).collect();
+        self.laid_out()
+    }
+
+    /* Prunes the search with a rule abandoning partial mosaics that can't be completed to ones it admits (see pruning.rs), alongside any others.
+    Must be called before the generator's first used. */
+    pub fn with_pruner(mut self, pruner: Box<dyn Pruner>) -> MosaicGenerator {
+        self.pruners.push(pruner);
+        self
+    }
+
+    /* Restricts generation to reduced mosaics, leaving out those with kinks that a Reidemeister I move could untwist (see Diagram::kinks).
+    Kinks aren't always confined to a block of tiles, so they're looked for in each complete mosaic rather than pruned. */
+    pub fn with_reduced(mut self) -> MosaicGenerator {
+        self.diagram_filters.reduced = true;
+        self
+    }
+
+    //Restricts generation to space-efficient mosaics (see Mosaic::is_space_efficient), checked in each complete mosaic
+    pub fn with_space_efficient(mut self) -> MosaicGenerator {
+        self.space_efficient = true;
+        self
+    }
+
+    //Restricts generation to mosaics whose diagrams are connected (see Diagram::is_connected), leaving out split diagrams, checked in each complete mosaic
+    pub fn with_connected(mut self) -> MosaicGenerator {
+        self.diagram_filters.connected = true;
+        self
+    }
+
+    //Restricts generation to mosaics with alternating diagrams (see Diagram::is_alternating), checked in each complete mosaic
+    pub fn with_alternating(mut self) -> MosaicGenerator {
+        self.diagram_filters.alternating = true;
+        self
+    }
+
+    //Restricts generation to mosaics whose diagrams aren't visibly composite (see Diagram::is_composite), checked in each complete mosaic
+    pub fn with_prime(mut self) -> MosaicGenerator {
+        self.diagram_filters.prime = true;
+        self
+    }
+
+    //Restricts generation to mosaics of links whose total linking number is ±`linking_number` (see Diagram::linking_number), checked in each complete mosaic
+    pub fn with_linking_number(mut self, linking_number: i32) -> MosaicGenerator {
+        self.diagram_filters.linking_number = Some(linking_number);
+        self
+    }
+
+    //Restricts generation to mosaics meeting conditions on their invariants (see invariant.rs), checked in each complete mosaic
+    pub fn with_conditions(mut self, conditions: Vec<Condition>) -> MosaicGenerator {
+        self.conditions = conditions;
+        self
+    }
+
+    //Restricts generation to mosaics containing `pattern`, pruning partial mosaics that can no longer contain it
+    pub fn with_pattern(self, pattern: Pattern) -> MosaicGenerator {
+        self.with_pruner(Box::new(pattern))
+    }
+
+    /* Whether the mosaic so far (up to and including the tile of `step`, which has just been set) has been ruled out by a pruner.
+    Reading the board by columns, the pruners only see complete mosaics, tile by tile in the order they follow. */
+    fn pruned(&mut self, step: usize) -> bool {
+        if self.pruners.is_empty() {
+            return false;
+        }
+        self.unpack_to(if self.order.column_major() { self.vector_length } else { step });
+        let (tiles, size) = (&self.tiles, self.size);
+        if self.order.column_major() {
+            return step == self.vector_length && self.pruners.iter_mut().any(|pruner| (0..tiles.len()).any(|tile| pruner.prunes(tiles, size, tile)));
+        }
+        self.pruners.iter_mut().any(|pruner| pruner.prunes(tiles, size, step))
+    }
+
+    //The valid tile at `index` of a step's list of valid tiles, taken from the end of the list in reverse orders
+    fn choice(&self, step: usize, index: usize) -> usize {
+        let valid = self.valid_tiles_for[step];
+        if self.order.reverse() { valid[valid.len() - 1 - index] } else { valid[index] }
+    }
+
+    //Whether the complete mosaic is left out by the filters that can only be checked once it's complete
+    fn rejected(&self) -> bool {
+        if !self.space_efficient && !self.diagram_filters.any() && self.conditions.is_empty() {
+            return false;
+        }
+        let mosaic = Mosaic::from_tiles(self.size, self.tiles.iter().map(|&tile| tile as u8).collect()).expect("generated tiles are valid");
+        (self.space_efficient && !mosaic.is_space_efficient()) || !self.diagram_filters.admit(&mosaic) || !self.conditions.iter().all(|condition| condition.admits(&mosaic))
+    }
+
+    //Tells the pruners about every tile after the generator's been moved to a new position
+    fn recount(&mut self) {
+        self.unpack_to(self.vector_length);
+        let (tiles, size) = (&self.tiles, self.size);
+        for pruner in &mut self.pruners {
+            for tile in 0..tiles.len() {
+                pruner.prunes(tiles, size, tile);
+            }
+        }
+    }
+
+    //Number of times generation has stepped back to an earlier tile, either at a dead end or after trying every valid tile
+    pub fn backtracks(&self) -> u64 {
+        self.backtracks
+    }
+
+    //Number of tiles placed so far, counting each partial mosaic the search visits once
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /* Estimated fraction of the search completed so far, from 0 to 1.
+    Each tile's valid tiles are treated as splitting the remaining search evenly, so this is exact only when every branch holds as many mosaics as its siblings. */
+    pub fn progress(&self) -> f64 {
+        if self.finished {
+            return 1.0;
+        }
+        let (mut progress, mut scale) = (0.0, 1.0);
+        for tile in 0..self.curr_tile {
+            let choices = self.valid_tiles_for[tile].len() as f64;
+            progress += scale * self.digit_index[tile].saturating_sub(1) as f64 / choices;
+            scale /= choices;
+        }
+        progress
+    }
+
+    /* Moves a new generator to carry on from just after the mosaic with the given tiles, as if it had just returned that mosaic,
+    or returns None if the tiles aren't a suitably connected size x size mosaic (with only allowed tiles). */
+    pub fn resume_after(self, tiles: &[usize]) -> Option<MosaicGenerator> {
+        let mut generator = self;
+        if tiles.len() != generator.tiles.len() {
+            return None;
+        }
+        for index in 0..tiles.len() {
+            let tile = tiles[generator.positions[index]];
+            generator.valid_tiles_for[index] = generator.valid_tiles(index);
+            generator.digit_index[index] = (0..generator.valid_tiles_for[index].len()).position(|choice| generator.choice(index, choice) == tile)? + 1;
+            generator.place(index, tile);
+        }
+        generator.curr_tile = generator.vector_length;
+        generator.rightward = false;
+        generator.emitted = true;
+        generator.recount();
+        Some(generator)
+    }
+
+    /* Moves a new generator so its first mosaic is the first suitably connected mosaic at or after the given tiles in generation order
+    (which needn't be a mosaic themselves), or returns None if the tiles are the wrong length for a size x size mosaic. */
+    pub fn starting_at(self, tiles: &[usize]) -> Option<MosaicGenerator> {
+        let mut generator = self;
+        if tiles.len() != generator.tiles.len() {
+            return None;
+        }
+        let reverse = generator.order.reverse();
+        //Follows the tiles for as long as they're valid, then leaves the rest of the search to advance()
+        for index in 0..tiles.len() {
+            let (position, tile) = (generator.positions[index], tiles[generator.positions[index]]);
+            generator.valid_tiles_for[index] = generator.valid_tiles(index);
+            let at_or_after = |valid: usize| if reverse { valid <= tile } else { valid >= tile };
+            match (0..generator.valid_tiles_for[index].len()).position(|choice| at_or_after(generator.choice(index, choice))) {
+                Some(choice) => {
+                    generator.place(index, generator.choice(index, choice));
+                    generator.digit_index[index] = choice + 1;
+                    if generator.tile(position) == tile && index < generator.vector_length {
+                        continue;
+                    }
+                    //A later tile than asked for starts with the first mosaic after it, as does the complete mosaic itself
+                    generator.curr_tile = index;
+                    generator.rightward = false;
+                    if index < generator.vector_length {
+                        generator.curr_tile += 1;
+                        generator.rightward = true;
+                    }
+                }
+                //Every valid tile here comes too early, so the search carries on from the next tile before it
+                None if index == 0 => generator.finished = true,
+                None => {
+                    generator.curr_tile = index - 1;
+                    generator.rightward = false;
+                }
+            }
+            generator.emitted = false;
+            generator.recount();
+            return Some(generator);
+        }
+        unreachable!("the last tile always ends the loop")
+    }
+
+    //Determines the list of valid tiles for the tile of a step based on the tiles to its top/left, and whether it's on the right/bottom edge of the mosaic
+    fn valid_tiles(&self, step: usize) -> &'static [usize] {
+        let step = &self.steps[step];
+        let (above, left) = (self.tile(step.above), self.tile(step.left));
+        step.table[step.edges + 3 * (OPEN_BOTTOM >> above & 1) as usize + 9 * (OPEN_RIGHT >> left & 1) as usize]
+    }
+
+    //The tile at a position of the board
+    fn tile(&self, position: usize) -> usize {
+        (self.board[position / 16] >> (position % 16 * 4) & 0xf) as usize
+    }
+
+    //Sets the tile of a step on the board
+    fn place(&mut self, step: usize, tile: usize) {
+        let (position, shift) = (self.steps[step].position, self.steps[step].position % 16 * 4);
+        let word = &mut self.board[position / 16];
+        *word = *word & !(0xf << shift) | (tile as u64) << shift;
+        self.stale = self.stale.min(step);
+    }
+
+    //Unpacks the tiles of the steps up to and including `step` that have changed on the board since they were last unpacked
+    fn unpack_to(&mut self, step: usize) {
+        for index in self.stale..=step {
+            let position = self.steps[index].position;
+            self.tiles[position] = self.tile(position);
+        }
+        self.stale = self.stale.max(step + 1);
+    }
+
+    //The tiles of the current mosaic, packed as the batches of sharded runs pack them
+    fn packed(&self) -> &[u64] {
+        &self.board[..packed_words(self.size)]
+    }
+
+    //Moves to the next suitably connected mosaic, returning its tiles, or None once every mosaic has been generated
+    pub fn advance(&mut self) -> Option<&[usize]> {
+        if self.finished {
+            return None;
+        }
+        let vector_length = self.vector_length;
+        loop {
+            let curr_tile = self.curr_tile;
+            if self.rightward {
+                self.valid_tiles_for[curr_tile] = self.valid_tiles(curr_tile);
+                
+                //Determining if there are no valid tiles based on the current configuration
+                if self.valid_tiles_for[curr_tile].is_empty() {
+                    if curr_tile == 0 { //Only possible when tiles are forbidden
+                        self.finished = true;
+                        return None;
+                    }
+                    self.rightward = false;
+                    self.curr_tile -= 1;
+                    self.backtracks += 1;
+                    continue;
+                }
+
+                //Setting the current tile to the first valid tile
+                self.digit_index[curr_tile] = 1; //Note that digit index represents the index of the _next_ valid tile to be used for a given tile in the mosaic
+                self.place(curr_tile, self.choice(curr_tile, 0));
+                self.nodes += 1;
+                
+                if curr_tile == vector_length || self.pruned(curr_tile) {
+                    self.rightward = false;
+                    continue;
+                }
+                self.curr_tile += 1;
+                continue;
+            }
+
+            if curr_tile == vector_length && !self.emitted { //Returning complete mosaics
+                self.emitted = true;
+                self.unpack_to(vector_length);
+                if !self.pruned(curr_tile) && !self.rejected() {
+                    return Some(&self.tiles);
+                }
+            }
+            self.emitted = false;
+
+            //"Carrying" leftward when we've used every valid tile for the current tile
+            if self.digit_index[curr_tile] == self.valid_tiles_for[curr_tile].len() {
+                if curr_tile == 0 { //Ends generation
+                    self.finished = true;
+                    return None;
+                }
+                self.curr_tile -= 1;
+                self.backtracks += 1;
+                continue;
+            }
+
+            //Move to next tile in list of valid tiles
+            self.place(curr_tile, self.choice(curr_tile, self.digit_index[curr_tile]));
+            self.digit_index[curr_tile] += 1;
+            self.nodes += 1;
+            if curr_tile < vector_length && !self.pruned(curr_tile) {
+                self.curr_tile += 1;
+                self.rightward = true;
+            }
+        }
+    }
+}
+
+impl Iterator for MosaicGenerator {
+    type Item = Mosaic;
+
+    fn next(&mut self) -> Option<Mosaic> {
+        let size = self.size;
+        self.advance().map(|tiles| Mosaic::from_tiles(size, tiles.iter().map(|&tile| tile as u8).collect()).expect("generated tiles are always valid"))
+    }
+}
+
+/* Calls `visit` with every suitably connected size x size mosaic in generation order, until it breaks, for handling each mosaic without the iterator's
+ allocation per mosaic -- the same Mosaic is overwritten with each, so `visit` has to clone one to keep it.
+Returns what `visit` broke with, or the number of mosaics it was called with if it never broke. */
+pub fn generate_with<B>(size: usize, mut visit: impl FnMut(&Mosaic) -> ControlFlow<B>) -> ControlFlow<B, u64> {
+    let mut generator = MosaicGenerator::new(size);
+    let mut mosaic = Mosaic::from_tiles(size, vec![0; size * size]).expect("blank tiles are always valid");
+    let mut visited = 0;
+    while let Some(tiles) = generator.advance() {
+        mosaic.set_generated_tiles(tiles);
+        visit(&mosaic)?;
+        visited += 1;
+    }
+    ControlFlow::Continue(visited)
+}
+
+/* Where to pick up a run of mosaic_gen that was stopped early: the size, the number of mosaics generated so far, the code of the last mosaic generated
+ and the order of the run. Written as size:generated:code, e.g. 4:1000:0220255225661102, followed by :<order> for orders other than lex. */
+//The mosaic code of a mosaic's tiles
+fn code(tiles: &[usize]) -> String {
+    tiles.iter().map(|&tile| mosaic::tile_char(tile as u8)).collect()
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ResumeToken {
+    pub size: usize,
+    pub generated: u64,
+    pub last: Vec<usize>,
+    pub order: Order,
+}
+
+impl fmt::Display for ResumeToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.size, self.generated, code(&self.last))?;
+        if self.order != Order::Lex {
+            write!(f, ":{}", self.order)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ResumeToken {
+    type Err = Error;
+
+    fn from_str(token: &str) -> Result<ResumeToken> {
+        let invalid = || Error::Parse { what: "resume token", text: token.to_string(), reason: "resume tokens are size:generated:code, with the code of a mosaic of that size, followed by :order for orders other than lex".to_string() };
+        let (size, rest) = token.trim().split_once(':').ok_or_else(invalid)?;
+        let (generated, rest) = rest.split_once(':').ok_or_else(invalid)?;
+        let (code, order) = match rest.split_once(':') {
+            Some((code, order)) => (code, order.parse().map_err(|_| invalid())?),
+            None => (rest, Order::Lex),
+        };
+        let size: usize = size.parse().map_err(|_| invalid())?;
+        let last = code.chars().map(|tile| mosaic::parse_tile(tile).map(|tile| tile as usize)).collect::<Option<Vec<usize>>>().ok_or_else(invalid)?;
+        if MosaicGenerator::new(size).resume_after(&last).is_none() {
+            return Err(invalid());
+        }
+        Ok(ResumeToken { size, generated: generated.parse().map_err(|_| invalid())?, last, order })
+    }
+}
+
+//A place in the generation order: at a mosaic code (which needn't be suitably connected), just after a generated mosaic, or at the rank of a mosaic
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Position {
+    Mosaic(Vec<usize>),
+    After(Vec<usize>),
+    Rank(u64),
+}
+
+/* Where a run of mosaic_gen starts and stops -- by default a run generates every mosaic of its size.
+`start` and `end` restrict the run to the mosaics in [start, end) of the generation order, e.g. to shard a size by hand or replay part of a run,
+ and `prefix` to the mosaics whose first tiles (e.g. first rows) are the given tiles, where the generation order is `order` (see Order) --
+ so two runs can work through a size from both ends, a lex run and a revlex run stopped with --end at the first mosaic of the other between them.
+A run stopped by a limit finishes its output cleanly, and can be carried on by another run resuming from its token (with the same end). */
+#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bounds {
+    pub resume: Option<ResumeToken>,
+    pub start: Option<Position>,
+    pub end: Option<Position>,
+    pub prefix: Option<Vec<usize>>,
+    pub max_mosaics: Option<u64>,
+    pub max_seconds: Option<f64>,
+    pub order: Order,
+}
+
+impl Bounds {
+    /* The start and end of the range of the generation order to generate, with a prefix of fixed tiles (in the order's reading of the board)
+     taken as the range of mosaics starting with those tiles: from the prefix followed by the first tiles in order, to the next prefix in order (if there is one). */
+    fn range(&self, size: usize) -> Result<(Option<Position>, Option<Position>)> {
+        let Some(prefix) = &self.prefix else {
+            return Ok((self.start.clone(), self.end.clone()));
+        };
+        if self.start.is_some() || self.end.is_some() {
+            return Err(Error::Invalid("a prefix already fixes the range of mosaics generated, so can't be given a start or end".to_string()));
+        }
+        if prefix.len() > size * size {
+            return Err(Error::Invalid(format!("prefix has more than the {} tiles of a {}x{} mosaic", size * size, size, size)));
+        }
+        let (first, last) = if self.order.reverse() { (TILE_COUNT as usize - 1, 0) } else { (0, TILE_COUNT as usize - 1) };
+        let padded = |prefix: &[usize]| self.order.place(&prefix.iter().copied().chain(vec![first; size * size - prefix.len()]).collect::<Vec<usize>>(), size);
+        //The next prefix counts on by one, carrying past the last tile
+        let mut next = prefix.clone();
+        while next.last() == Some(&last) {
+            next.pop();
+        }
+        let end = match next.last_mut() {
+            Some(tile) => {
+                if self.order.reverse() {
+                    *tile -= 1;
+                } else {
+                    *tile += 1;
+                }
+                Some(Position::Mosaic(padded(&next)))
+            }
+            None => None,
+        };
+        Ok((Some(Position::Mosaic(padded(prefix))), end))
+    }
+}
+
+/* Which mosaics a run of mosaic_gen generates -- by default every suitably connected mosaic.
+`crossings` and `components` keep only mosaics with exactly that many crossing tiles and link components, `max_crossings`, `max_cusps` and `max_components` only those with at most that many,
+ and `pattern` only those containing the pattern, all pruned during generation (see pruning.rs).
+`reduced`, `space_efficient`, `connected`, `alternating`, `prime` and `linking_number` keep only mosaics passing those tests (see the MosaicGenerator methods setting them),
+ and `conditions` only those whose invariants meet every condition (see invariant.rs).
+Forbidden tiles are left out of the lists of valid tiles, either everywhere or at a (row, column) of the mosaic, so the search never visits them,
+ and a seed mosaic placed with its top left tile at a (row, column) fixes the tiles it covers, so only its completions are generated. */
+#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Constraints {
+    pub pattern: Option<Pattern>,
+    pub crossings: Option<usize>,
+    pub components: Option<usize>,
+    pub max_crossings: Option<usize>,
+    pub max_cusps: Option<usize>,
+    pub max_components: Option<usize>,
+    pub forbidden: Vec<usize>,
+    pub forbidden_at: Vec<(usize, usize, Vec<usize>)>,
+    pub seed: Option<(usize, usize, Mosaic)>,
+    pub reduced: bool,
+    pub space_efficient: bool,
+    pub connected: bool,
+    pub alternating: bool,
+    pub prime: bool,
+    pub linking_number: Option<i32>,
+    pub conditions: Vec<Condition>,
+}
+
+//Filters on the diagram of each complete mosaic
+#[derive(Clone, Copy, Default)]
+struct DiagramFilters {
+    reduced: bool, //No kinks (see Diagram::kinks)
+    connected: bool, //A connected diagram (see Diagram::is_connected)
+    alternating: bool, //An alternating diagram (see Diagram::is_alternating)
+    prime: bool, //A diagram that isn't visibly composite (see Diagram::is_composite)
+    linking_number: Option<i32>, //A total linking number of ± this (see Diagram::linking_number)
+}
+
+impl DiagramFilters {
+    fn any(&self) -> bool {
+        self.reduced || self.connected || self.alternating || self.prime || self.linking_number.is_some()
+    }
+
+    fn admit(&self, mosaic: &Mosaic) -> bool {
+        if !self.any() {
+            return true;
+        }
+        Diagram::trace(mosaic).is_none_or(|diagram| {
+            (!self.reduced || diagram.kinks().is_empty())
+                && (!self.connected || diagram.is_connected())
+                && (!self.alternating || diagram.is_alternating())
+                && (!self.prime || !diagram.is_composite())
+                && self.linking_number.is_none_or(|linking_number| diagram.linking_number().abs() == linking_number.abs())
+        })
+    }
+}
+
+impl Constraints {
+    //Whether every mosaic meets the constraints
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_none()
+            && self.crossings.is_none()
+            && self.components.is_none()
+            && self.max_crossings.is_none()
+            && self.max_cusps.is_none()
+            && self.max_components.is_none()
+            && self.forbidden.is_empty()
+            && self.forbidden_at.is_empty()
+            && self.seed.is_none()
+            && !(self.reduced || self.space_efficient || self.connected || self.alternating || self.prime)
+            && self.linking_number.is_none()
+            && self.conditions.is_empty()
+    }
+
+    /* Whether a complete mosaic meets the constraints that don't depend on the tiles allowed at each position:
+     the pattern, the counts of crossings, cusps and components (from their pruners), the filters on its diagram and the conditions on its invariants. */
+    pub fn admits(&self, mosaic: &Mosaic) -> bool {
+        let diagram_filters = DiagramFilters { reduced: self.reduced, connected: self.connected, alternating: self.alternating, prime: self.prime, linking_number: self.linking_number };
+        let (size, tiles) = (mosaic.size(), mosaic.tiles().iter().map(|&tile| tile as usize).collect::<Vec<usize>>());
+        self.pattern.as_ref().is_none_or(|pattern| pattern.is_contained_in(mosaic))
+            && self.pruners(size).iter_mut().all(|pruner| (0..tiles.len()).all(|tile| !pruner.prunes(&tiles, size, tile)))
+            && (!self.space_efficient || mosaic.is_space_efficient())
+            && diagram_filters.admit(mosaic)
+            && self.conditions.iter().all(|condition| condition.admits(mosaic))
+    }
+
+    //The pruners for the counts of crossings, cusps and components of size x size mosaics
+    fn pruners(&self, size: usize) -> Vec<Box<dyn Pruner>> {
+        let mut pruners: Vec<Box<dyn Pruner>> = Vec::new();
+        if self.crossings.is_some() || self.max_crossings.is_some() {
+            let max = self.crossings.unwrap_or(usize::MAX).min(self.max_crossings.unwrap_or(usize::MAX));
+            pruners.push(Box::new(Crossings::new(size, self.crossings.unwrap_or(0), max)));
+        }
+        if let Some(max) = self.max_cusps {
+            pruners.push(Box::new(Cusps::new(size, max)));
+        }
+        if self.components.is_some() || self.max_components.is_some() {
+            let max = self.components.unwrap_or(usize::MAX).min(self.max_components.unwrap_or(usize::MAX));
+            pruners.push(Box::new(Components::new(size, self.components.unwrap_or(0), max)));
+        }
+        pruners
+    }
+
+    //A generator of the size x size mosaics meeting the constraints
+    pub fn generator(&self, size: usize) -> Result<MosaicGenerator> {
+        let mut generator = MosaicGenerator::new(size);
+        if let Some(allowed) = self.allowed_tiles(size)? {
+            generator = generator.with_allowed_tiles(&allowed);
+        }
+        if let Some(pattern) = &self.pattern {
+            generator = generator.with_pattern(pattern.clone());
+        }
+        for pruner in self.pruners(size) {
+            generator = generator.with_pruner(pruner);
+        }
+        if self.reduced {
+            generator = generator.with_reduced();
+        }
+        if self.space_efficient {
+            generator = generator.with_space_efficient();
+        }
+        if self.connected {
+            generator = generator.with_connected();
+        }
+        if self.alternating {
+            generator = generator.with_alternating();
+        }
+        if self.prime {
+            generator = generator.with_prime();
+        }
+        if let Some(linking_number) = self.linking_number {
+            generator = generator.with_linking_number(linking_number);
+        }
+        if !self.conditions.is_empty() {
+            generator = generator.with_conditions(self.conditions.clone());
+        }
+        Ok(generator)
+    }
+
+    //Tiles allowed at each index of a size x size mosaic, or None if every tile is allowed everywhere
+    fn allowed_tiles(&self, size: usize) -> Result<Option<Vec<TileSet>>> {
+        if self.forbidden.is_empty() && self.forbidden_at.is_empty() && self.seed.is_none() {
+            return Ok(None);
+        }
+        let tile_set = |tiles: &[usize]| tiles.iter().fold(0, |set: TileSet, &tile| set | 1 << tile);
+        let mut allowed = vec![ALL_TILES & !tile_set(&self.forbidden); size * size];
+        for (row, col, tiles) in &self.forbidden_at {
+            if *row >= size || *col >= size {
+                return Err(Error::Invalid(format!("can't forbid tiles at {}:{}, outside a {}x{} mosaic", row, col, size, size)));
+            }
+            allowed[row * size + col] &= !tile_set(tiles);
+        }
+        if let Some((row, col, seed)) = &self.seed {
+            if row + seed.size() > size || col + seed.size() > size {
+                return Err(Error::Invalid(format!("a {}x{} seed at {}:{} doesn't fit in a {}x{} mosaic", seed.size(), seed.size(), row, col, size, size)));
+            }
+            for (index, &tile) in seed.tiles().iter().enumerate() {
+                allowed[(row + index / seed.size()) * size + col + index % seed.size()] &= 1 << tile;
+            }
+        }
+        Ok(Some(allowed))
+    }
+}
+
+//How a run of mosaic_gen writes its mosaics: each after its global rank in the generation order (see rank.rs), and checked again with some probability (see self_check.rs)
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmitOptions {
+    pub ranks: bool,
+    pub self_check: Option<f64>,
+}
+
+/* Everything about a run of mosaic_gen, built from its size and output with the with_ methods, and checked as a whole before the run starts (see validate):
+    tile set     the tiles its mosaics can use anywhere (every tile by default), on top of the constraints' forbidden tiles
+    constraints  which mosaics it generates (see Constraints), every suitably connected mosaic by default
+    bounds       where it starts and stops in the generation order, and the order itself (see Bounds)
+    threads      how many threads generate, splitting the run into shards by the mosaics' first rows (first columns, in column-major orders)
+                  that are generated at once and written in order, so the output is the same as one thread's
+    placement    which CPUs those threads are pinned to, if any (see affinity.rs)
+    polite       whether it makes way for other programs, at the lowest priorities and with its output throttled (see polite.rs)
+    output       where the mosaics are written and how the output is split into chunks (see Chunking)
+    emit         how each mosaic is written and checked as it's written (see EmitOptions)
+    metrics      where progress is published, if anywhere (see metrics.rs) */
+#[derive(Clone)]
+pub struct GeneratorConfig<'a> {
+    size: usize,
+    tile_set: TileSet,
+    constraints: Constraints,
+    bounds: Bounds,
+    threads: usize,
+    placement: Placement,
+    polite: bool,
+    output_path: String,
+    chunking: Chunking,
+    emit: EmitOptions,
+    metrics: Option<&'a Metrics>,
+}
+
+//A GeneratorConfig without its metrics, which it only borrows, owning everything else: the form a config is serialized in (see serialization.rs)
+#[cfg(feature = "serde")]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct GeneratorSettings {
+    pub size: usize,
+    pub tile_set: TileSet,
+    pub constraints: Constraints,
+    pub bounds: Bounds,
+    pub threads: usize,
+    pub placement: Placement,
+    pub polite: bool,
+    pub output_path: String,
+    pub chunking: Chunking,
+    pub emit: EmitOptions,
+}
+
+#[cfg(feature = "serde")]
+impl From<&GeneratorConfig<'_>> for GeneratorSettings {
+    fn from(config: &GeneratorConfig) -> GeneratorSettings {
+        GeneratorSettings {
+            size: config.size,
+            tile_set: config.tile_set,
+            constraints: config.constraints.clone(),
+            bounds: config.bounds.clone(),
+            threads: config.threads,
+            placement: config.placement,
+            polite: config.polite,
+            output_path: config.output_path.clone(),
+            chunking: config.chunking,
+            emit: config.emit,
+        }
+    }
+}
+
+//The config with these settings, publishing its progress nowhere until it's given metrics (see GeneratorConfig::with_metrics)
+#[cfg(feature = "serde")]
+impl From<GeneratorSettings> for GeneratorConfig<'_> {
+    fn from(settings: GeneratorSettings) -> Self {
+        let GeneratorSettings { size, tile_set, constraints, bounds, threads, placement, polite, output_path, chunking, emit } = settings;
+        GeneratorConfig { size, tile_set, constraints, bounds, threads, placement, polite, output_path, chunking, emit, metrics: None }
+    }
+}
+
+impl<'a> GeneratorConfig<'a> {
+    pub fn new(size: usize, output_path: &str) -> GeneratorConfig<'a> {
+        GeneratorConfig {
+            size,
+            tile_set: ALL_TILES,
+            constraints: Constraints::default(),
+            bounds: Bounds::default(),
+            threads: 1,
+            placement: Placement::Unpinned,
+            polite: false,
+            output_path: output_path.to_string(),
+            chunking: Chunking::default(),
+            emit: EmitOptions::default(),
+            metrics: None,
+        }
+    }
+
+    pub fn with_tile_set(mut self, tile_set: TileSet) -> GeneratorConfig<'a> {
+        self.tile_set = tile_set;
+        self
+    }
+
+    pub fn with_constraints(mut self, constraints: Constraints) -> GeneratorConfig<'a> {
+        self.constraints = constraints;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds) -> GeneratorConfig<'a> {
+        self.bounds = bounds;
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> GeneratorConfig<'a> {
+        self.threads = threads;
+        self
+    }
+
+    pub fn with_placement(mut self, placement: Placement) -> GeneratorConfig<'a> {
+        self.placement = placement;
+        self
+    }
+
+    pub fn with_polite(mut self, polite: bool) -> GeneratorConfig<'a> {
+        self.polite = polite;
+        self
+    }
+
+    pub fn with_chunking(mut self, chunking: Chunking) -> GeneratorConfig<'a> {
+        self.chunking = chunking;
+        self
+    }
+
+    pub fn with_emit(mut self, emit: EmitOptions) -> GeneratorConfig<'a> {
+        self.emit = emit;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Option<&'a Metrics>) -> GeneratorConfig<'a> {
+        self.metrics = metrics;
+        self
+    }
+
+    //The constraints with the tiles outside the tile set forbidden
+    pub fn constraints(&self) -> Constraints {
+        let mut constraints = self.constraints.clone();
+        let outside: Vec<usize> = (0..TILE_COUNT as usize).filter(|&tile| self.tile_set & 1 << tile == 0 && !constraints.forbidden.contains(&tile)).collect();
+        constraints.forbidden.extend(outside);
+        constraints
+    }
+
+    /* Checks that the settings make sense together, so that a run fails before writing anything rather than partway through:
+     a size with mosaics, tiles to make them from, a range of the generation order that fits the size, a resume token for the same size and order,
+     and threads, their placement and ranks only for runs they work for. */
+    pub fn validate(&self) -> Result<()> {
+        let (size, bounds) = (self.size, &self.bounds);
+        if size == 0 {
+            return Err(Error::UnsupportedSize { size, reason: "mosaics have at least one tile".to_string() });
+        }
+        if self.tile_set & ALL_TILES == 0 {
+            return Err(Error::Invalid("a tile set needs at least one tile".to_string()));
+        }
+        let (start, end) = bounds.range(size)?;
+        for (position, tiles) in [("start", &start), ("end", &end)] {
+            if let Some(Position::Mosaic(tiles) | Position::After(tiles)) = tiles {
+                if tiles.len() != size * size {
+                    return Err(Error::Invalid(format!("{} mosaic should have {} tiles for {}x{} mosaics", position, size * size, size, size)));
+                }
+            }
+        }
+        if let Some(token) = &bounds.resume {
+            let checkpoint = |reason: String| Err(Error::Checkpoint { token: token.to_string(), reason });
+            if start.is_some() {
+                return checkpoint("a run can't both resume and start from a given position".to_string());
+            }
+            if token.size != size {
+                return checkpoint(format!("it's for {}x{} mosaics, not {}x{}", token.size, token.size, size, size));
+            }
+            if token.order != bounds.order {
+                return checkpoint(format!("it's for a run in {} order, not {}", token.order, bounds.order));
+            }
+        }
+        self.constraints().generator(size)?;
+        match self.threads {
+            0 => return Err(Error::Invalid("a run needs at least one thread".to_string())),
+            1 => {}
+            _ if bounds.resume.is_some() || start.is_some() || end.is_some() => {
+                return Err(Error::Invalid("a run split between threads generates every mosaic of its size, so can't resume or be given a start, end or prefix".to_string()));
+            }
+            _ if size > rank::MAX_SIZE => return Err(Error::UnsupportedSize { size, reason: format!("runs are split between threads for sizes up to {}", rank::MAX_SIZE) }),
+            _ => {}
+        }
+        if self.placement != Placement::Unpinned {
+            if self.threads == 1 {
+                return Err(Error::Invalid("only runs split between threads have threads to pin to CPUs".to_string()));
+            }
+            affinity::cpus(self.placement, self.threads)?;
+        }
+        if self.emit.ranks {
+            if !self.constraints().is_empty() {
+                return Err(Error::Invalid("ranks count every mosaic of the size, so can't be written for a run generating only some of them".to_string()));
+            }
+            if size > rank::MAX_SIZE {
+                return Err(Error::UnsupportedSize { size, reason: format!("ranks are counted for sizes up to {}", rank::MAX_SIZE) });
+            }
+        }
+        Ok(())
+    }
+}
+
+//The outcome of a run of mosaic_gen: the number of mosaics it generated, a token to carry on from if it was stopped by a limit, and how many were self-checked
+pub struct Generated {
+    pub count: u64,
+    pub resume: Option<ResumeToken>,
+    pub self_checked: Option<u64>,
+}
+
+//Mosaics generated between updates of the metrics, so that publishing them doesn't slow generation
+const METRICS_INTERVAL: u64 = 1 << 14;
+//Mosaics generated between checks of the time limit
+const CLOCK_INTERVAL: u64 = 1 << 10;
+//Mosaics a shard's thread sends to the writing thread at a time, and the batches it can get ahead by
+const SHARD_BATCH: usize = 1 << 10;
+const SHARD_QUEUE: usize = 16;
+
+/* The words a size x size mosaic takes in a batch, its tiles packed four bits to a tile (every tile, the virtual crossing too, fits in four bits),
+ sixteen to a word -- 16 bytes a mosaic up to 5x5 rather than 8 a tile, so the batches waiting between the threads and the writer stay in cache. */
+fn packed_words(size: usize) -> usize {
+    (size * size).div_ceil(16)
+}
+
+//The tiles of a mosaic packed as a generator's board packs them
+fn unpack(words: &[u64], tiles: &mut [usize]) {
+    for (index, tile) in tiles.iter_mut().enumerate() {
+        *tile = (words[index / 16] >> (index % 16 * 4) & 0xf) as usize;
+    }
+}
+
+//Whether a mosaic is past the end of a range of the generation order
+fn past_end(order: Order, size: usize, mosaic: &[usize], end: &Option<Position>) -> bool {
+    match end {
+        Some(Position::Mosaic(end)) => order.compare(mosaic, end, size).is_ge(),
+        Some(Position::After(end)) => order.compare(mosaic, end, size).is_gt(),
+        _ => false,
+    }
+}
+
+//The search of a shard of a run split between threads (see GeneratorConfig), its mosaics with the given first tiles
+fn shard_generator(constraints: &Constraints, size: usize, order: Order, prefix: &[usize]) -> Result<(MosaicGenerator, Option<Position>)> {
+    let bounds = Bounds { prefix: Some(prefix.to_vec()), order, ..Bounds::default() };
+    let (Some(Position::Mosaic(start)), end) = bounds.range(size)? else {
+        unreachable!("the range of a prefix starts at a mosaic")
+    };
+    let generator = constraints.generator(size)?.with_order(order).starting_at(&start).expect("a prefix's range starts at a mosaic of its size");
+    Ok((generator, end))
+}
+
+/* The mosaics of a run split between threads, read back in order: a batch at a time from each shard's channel in turn, with an empty batch ending a shard.
+The threads add the backtracks and nodes of their searches (see MosaicGenerator) to the totals as they send each batch. */
+pub struct Shards<'a> {
+    size: usize,
+    receivers: VecDeque<Receiver<Vec<u64>>>,
+    count: usize,
+    batch: Vec<u64>,
+    position: usize,
+    tiles: Vec<usize>, //The mosaic last read from the batch
+    backtracks: &'a AtomicU64,
+    nodes: &'a AtomicU64,
+}
+
+impl Shards<'_> {
+    pub fn advance(&mut self) -> Option<&[usize]> {
+        let words = packed_words(self.size);
+        while self.position == self.batch.len() {
+            match self.receivers.front()?.recv() {
+                Ok(batch) if !batch.is_empty() => (self.batch, self.position) = (batch, 0),
+                _ => drop(self.receivers.pop_front()),
+            }
+        }
+        unpack(&self.batch[self.position..self.position + words], &mut self.tiles);
+        self.position += words;
+        Some(&self.tiles)
+    }
+}
+
+//Where a run's mosaics come from: a single search or the shards of several threads
+enum Source<'a> {
+    Single(MosaicGenerator),
+    Sharded(Shards<'a>),
+}
+
+impl Source<'_> {
+    fn advance(&mut self) -> Option<&[usize]> {
+        match self {
+            Source::Single(generator) => generator.advance(),
+            Source::Sharded(shards) => shards.advance(),
+        }
+    }
+
+    fn backtracks(&self) -> u64 {
+        match self {
+            Source::Single(generator) => generator.backtracks(),
+            Source::Sharded(shards) => shards.backtracks.load(AtomicOrdering::Relaxed),
+        }
+    }
+
+    fn nodes(&self) -> u64 {
+        match self {
+            Source::Single(generator) => generator.nodes(),
+            Source::Sharded(shards) => shards.nodes.load(AtomicOrdering::Relaxed),
+        }
+    }
+
+    //The fraction of the search done, which for shards is the fraction of the shards written
+    fn progress(&self) -> f64 {
+        match self {
+            Source::Single(generator) => generator.progress(),
+            Source::Sharded(shards) => 1.0 - shards.receivers.len() as f64 / shards.count.max(1) as f64,
+        }
+    }
+}
+
+/* Generates the shards of a run (see GeneratorConfig) with `threads` threads, each taking the next shard in turn and sending its mosaics
+ to that shard's channel, until every shard is done or the writing thread stops reading. The benchmark of dfs-parallel (see bench.rs) reads them back the same way.
+With a placement, each thread is pinned to its CPU (see affinity.rs) before it starts, and a thread that can't be pinned fails the run. */
+pub fn spawn_shards<'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    config: &'scope GeneratorConfig,
+    constraints: &'scope Constraints,
+    backtracks: &'scope AtomicU64,
+    nodes: &'scope AtomicU64,
+) -> Result<Shards<'scope>> {
+    let (size, order) = (config.size, config.bounds.order);
+    let prefixes = rank::prefixes(size, order, size);
+    let (senders, receivers): (Vec<_>, VecDeque<_>) = prefixes.iter().map(|_| sync_channel::<Vec<u64>>(SHARD_QUEUE)).unzip();
+    let work = Arc::new((prefixes, senders, AtomicUsize::new(0)));
+    let cpus = affinity::cpus(config.placement, config.threads)?;
+    let (pinned, pin_results) = channel();
+    for worker in 0..config.threads {
+        let (work, pinned, cpu) = (Arc::clone(&work), pinned.clone(), cpus.get(worker).copied());
+        scope.spawn(move || {
+            //Pinning comes first, so the thread's search and batches are allocated on its CPU's node
+            if let Some(cpu) = cpu {
+                let result = affinity::pin(cpu);
+                let failed = result.is_err();
+                let _ = pinned.send(result);
+                if failed {
+                    return;
+                }
+            }
+            let (prefixes, senders, next) = &*work;
+            loop {
+                let index = next.fetch_add(1, AtomicOrdering::Relaxed);
+                if index >= prefixes.len() {
+                    return;
+                }
+                let (mut generator, end) = shard_generator(constraints, size, order, &prefixes[index]).expect("the constraints were checked by GeneratorConfig::validate");
+                let (mut batch, mut counted) = (Vec::with_capacity(SHARD_BATCH * packed_words(size)), (0, 0));
+                loop {
+                    let done = match generator.advance() {
+                        Some(mosaic) if !past_end(order, size, mosaic, &end) => {
+                            batch.extend_from_slice(generator.packed());
+                            false
+                        }
+                        _ => true,
+                    };
+                    if batch.len() == SHARD_BATCH * packed_words(size) || done {
+                        backtracks.fetch_add(generator.backtracks() - counted.0, AtomicOrdering::Relaxed);
+                        nodes.fetch_add(generator.nodes() - counted.1, AtomicOrdering::Relaxed);
+                        counted = (generator.backtracks(), generator.nodes());
+                        //The writing thread only stops reading once it's done with every shard
+                        if !batch.is_empty() && senders[index].send(std::mem::take(&mut batch)).is_err() {
+                            return;
+                        }
+                    }
+                    if done {
+                        break;
+                    }
+                }
+                if senders[index].send(Vec::new()).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+    drop(pinned);
+    for result in pin_results.iter().take(cpus.len()) {
+        result?;
+    }
+    Ok(Shards { size, count: receivers.len(), receivers, batch: Vec::new(), position: 0, tiles: vec![0; size * size], backtracks, nodes })
+}
+
+/* Prints all the mosaics a GeneratorConfig describes (after checking it with validate) to its output, as they're iterated through, publishing progress to its metrics if given.
+With `emit.ranks`, each mosaic is written as <rank><tab><code>, its rank among every mosaic of the size in the generation order, however the run's range starts --
+ so it's only for runs that generate every mosaic in their range. With `emit.self_check`, each mosaic is checked again with that probability (see self_check.rs),
+ stopping with an error at the first that fails.
+A polite run lowers the priority of the calling thread for good (see polite.rs), along with those of the threads it splits the run between.
+If a limit stops the run before the last mosaic, its resume token is returned and (for outputs that aren't streams) written to <output>.resume. */
+pub fn mosaic_gen(config: &GeneratorConfig) -> Result<Generated> {
+    config.validate()?;
+    let (size, bounds, emit, metrics, output_path) = (config.size, &config.bounds, &config.emit, config.metrics, config.output_path.as_str());
+    let constraints = config.constraints();
+    let (start, end) = bounds.range(size)?;
+    let generator = constraints.generator(size)?.with_order(bounds.order);
+    let not_generated = |tiles: &[usize], reason: &str| {
+        Error::Invalid(format!("{} isn't a suitably connected {}x{} mosaic with the allowed tiles, so {}", code(tiles), size, size, reason))
+    };
+    let generator = match (&bounds.resume, &start) {
+        (Some(token), _) => generator.resume_after(&token.last).ok_or_else(|| Error::Checkpoint {
+            token: token.to_string(),
+            reason: format!("{} isn't a suitably connected {}x{} mosaic with the allowed tiles", code(&token.last), size, size),
+        })?,
+        (None, Some(Position::Mosaic(start))) => generator.starting_at(start).expect("validate checked the start mosaic's length"),
+        (None, Some(Position::After(start))) => generator.resume_after(start).ok_or_else(|| not_generated(start, "can't be started after"))?,
+        (None, Some(Position::Rank(start))) => {
+            let mut generator = generator;
+            for _ in 0..*start {
+                if generator.advance().is_none() {
+                    break;
+                }
+            }
+            generator
+        }
+        (None, None) => generator,
+    };
+    //Rank of the first mosaic of the run, where it's known
+    let previous = match (&bounds.resume, &start) {
+        (Some(token), _) => token.generated,
+        (None, Some(Position::Rank(start))) => *start,
+        _ => 0,
+    };
+    //Global rank of the first mosaic of the run, counting the mosaics before it from prefix counts
+    let first_rank = match (emit.ranks, &bounds.resume, &start) {
+        (false, _, _) => 0,
+        (true, Some(token), _) => rank::rank(&token.last, size, bounds.order) + 1,
+        (true, None, Some(Position::Mosaic(start))) => rank::rank(start, size, bounds.order),
+        (true, None, Some(Position::After(start))) => rank::rank(start, size, bounds.order) + 1,
+        (true, None, Some(Position::Rank(start))) => *start as u128,
+        (true, None, None) => 0,
+    };
+    let mut output_buffer = output::create_chunked(output_path, &config.chunking, &[("command", "generate".to_string()), ("size", size.to_string())])?;
+    if config.polite {
+        polite::lower_priority()?;
+        output_buffer = Box::new(ThrottledSink::new(output_buffer));
+    }
+
+    if let Some(metrics) = metrics {
+        metrics.start();
+    }
+    let mut self_check = emit.self_check.map(|probability| SelfCheck::new(size, probability));
+    let started = Instant::now();
+    let (mut generated, mut bytes_written) = (0, 0);
+    let (mut last, mut finished) = (None, false);
+    let (backtracks, nodes) = (AtomicU64::new(0), AtomicU64::new(0));
+    thread::scope(|scope| -> Result<()> {
+        let mut source = if config.threads > 1 {
+            Source::Sharded(spawn_shards(scope, config, &constraints, &backtracks, &nodes)?)
+        } else {
+            Source::Single(generator)
+        };
+        loop {
+            let limit_reached = bounds.max_mosaics.is_some_and(|limit| generated >= limit)
+                || bounds.max_seconds.is_some_and(|limit| generated > 0 && generated % CLOCK_INTERVAL == 0 && started.elapsed().as_secs_f64() >= limit);
+            if limit_reached {
+                break;
+            }
+            if let Some(Position::Rank(end)) = end {
+                if previous + generated >= end {
+                    finished = true;
+                    break;
+                }
+            }
+            let Some(mosaic) = source.advance() else {
+                finished = true;
+                break;
+            };
+            if past_end(bounds.order, size, mosaic, &end) {
+                finished = true;
+                break;
+            }
+            let line = code(mosaic);
+            if let Some(self_check) = self_check.as_mut() {
+                self_check.check(mosaic, size, &line)?;
+            }
+            if emit.ranks {
+                let rank = (first_rank + generated as u128).to_string();
+                write!(output_buffer, "{}\t", rank)?;
+                bytes_written += rank.len() as u64 + 1;
+            }
+            writeln!(output_buffer, "{}", line)?;
+            generated += 1;
+            bytes_written += line.len() as u64 + 1;
+            last = Some(mosaic.to_vec());
+            if let Some(metrics) = metrics.filter(|_| generated % METRICS_INTERVAL == 0) {
+                metrics.update(generated, bytes_written, source.backtracks(), source.nodes(), source.progress(), &line[..size * size.min(2)]);
+            }
+        }
+        if let Some(metrics) = metrics {
+            let prefix = last.as_ref().map_or(String::new(), |last: &Vec<usize>| code(&last[..size * size.min(2)]));
+            metrics.update(generated, bytes_written, source.backtracks(), source.nodes(), source.progress(), &prefix);
+        }
+        //Dropping the shards' channels stops their threads, if the run stopped early
+        Ok(())
+    })?;
+    output_buffer.finish()?;
+    if let Some(metrics) = metrics {
+        metrics.finish();
+    }
+
+    let resume = match last {
+        Some(last) if !finished => Some(ResumeToken { size, generated: previous + generated, last, order: bounds.order }),
+        _ => None,
+    };
+    if let Some(token) = resume.as_ref().filter(|_| !output::is_stdout(output_path) && !output_path.starts_with("unix:")) {
+        let mut token_output = output::create(&format!("{}.resume", output_path))?;
+        writeln!(token_output, "{}", token)?;
+        token_output.finish()?;
+    }
+    Ok(Generated { count: generated, resume, self_checked: self_check.map(|self_check| self_check.checked) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mosaic::{NONE, PARTNER};
+
+    const ORDERS: [Order; 4] = [Order::Lex, Order::Revlex, Order::Colex, Order::Revcolex];
+
+    //A mosaic's tiles as one number, four bits to a tile from the first, so that keys sort in lex order
+    fn key(tiles: &[usize]) -> u128 {
+        tiles.iter().fold(0, |key, &tile| key << 4 | tile as u128)
+    }
+
+    /* Every suitably connected size x size mosaic in lex order, found by trying every tile at each position against the faces of the tiles above and to its left
+     and the edges of the board, without the connection table. */
+    fn baseline(size: usize) -> Vec<u128> {
+        fn extend(size: usize, tiles: &mut Vec<usize>, found: &mut Vec<u128>) {
+            let index = tiles.len();
+            if index == size * size {
+                found.push(key(tiles));
+                return;
+            }
+            let (row, col) = (index / size, index % size);
+            let open = |tile: usize, face: usize| PARTNER[tile][face] != NONE;
+            for tile in 0..TILE_COUNT as usize {
+                let meets_above = open(tile, 1) == (row > 0 && open(tiles[index - size], 3));
+                let meets_left = open(tile, 2) == (col > 0 && open(tiles[index - 1], 0));
+                let closed_at_edges = (col + 1 < size || !open(tile, 0)) && (row + 1 < size || !open(tile, 3));
+                if meets_above && meets_left && closed_at_edges {
+                    tiles.push(tile);
+                    extend(size, tiles, found);
+                    tiles.pop();
+                }
+            }
+        }
+        let mut found = Vec::new();
+        extend(size, &mut Vec::with_capacity(size * size), &mut found);
+        found
+    }
+
+    //A mosaic's tiles as the generator holds them
+    fn tile_numbers(mosaic: &Mosaic) -> Vec<usize> {
+        mosaic.tiles().iter().map(|&tile| tile as usize).collect()
+    }
+
+    //The keys of some mosaics, in the order they come
+    fn keys(mosaics: impl Iterator<Item = Mosaic>) -> Vec<u128> {
+        mosaics.map(|mosaic| key(&tile_numbers(&mosaic))).collect()
+    }
+
+    //The tiles of a mosaic from its key
+    fn tiles(key: u128, size: usize) -> Vec<usize> {
+        (0..size * size).rev().map(|index| (key >> (4 * index) & 0xf) as usize).collect()
+    }
+
+    //The diagram of a mosaic from its key, if it's suitably connected
+    fn traced(key: u128, size: usize) -> Option<Diagram> {
+        Diagram::trace(&Mosaic::from_tiles(size, tiles(key, size).iter().map(|&tile| tile as u8).collect()).unwrap())
+    }
+
+    //The baseline's mosaics whose tiles pass a test
+    fn filtered(size: usize, keep: impl Fn(&[usize]) -> bool) -> Vec<u128> {
+        baseline(size).into_iter().filter(|&key| keep(&tiles(key, size))).collect()
+    }
+
+    #[test]
+    fn every_order_generates_the_baseline_in_order() {
+        for size in 2..=5 {
+            let baseline = baseline(size);
+            for order in ORDERS {
+                let mut generator = MosaicGenerator::new(size).with_order(order);
+                let (mut keys, mut previous) = (Vec::with_capacity(baseline.len()), Vec::new());
+                while let Some(tiles) = generator.advance() {
+                    assert!(previous.is_empty() || order.compare(&previous, tiles, size) == Ordering::Less, "{} out of order at size {}", order, size);
+                    previous.clear();
+                    previous.extend_from_slice(tiles);
+                    keys.push(key(tiles));
+                }
+                if order != Order::Lex {
+                    keys.sort_unstable();
+                }
+                assert!(keys == baseline, "{} generates different {}x{} mosaics from the baseline", order, size, size);
+            }
+        }
+    }
+
+    #[test]
+    fn resume_tokens_round_trip() {
+        let last = vec![0, 0, 2, 1, 0, 0, 3, 4, 2, 1, 0, 0, 3, 4, 0, 0];
+        let lex = ResumeToken { size: 4, generated: 1000, last: last.clone(), order: Order::Lex };
+        let revcolex = ResumeToken { order: Order::Revcolex, ..lex.clone() };
+        assert_eq!(lex.to_string(), "4:1000:0021003421003400");
+        assert_eq!(revcolex.to_string(), "4:1000:0021003421003400:revcolex");
+        assert_eq!(lex.to_string().parse::<ResumeToken>().unwrap(), lex);
+        assert_eq!(revcolex.to_string().parse::<ResumeToken>().unwrap(), revcolex);
+        //Not a mosaic, the wrong length for the size, an unknown order and a missing count
+        for token in ["4:1000:0021003421003401", "4:1000:002100342100340", "4:1000:0021003421003400:colexx", "4:0021003421003400"] {
+            assert!(matches!(token.parse::<ResumeToken>(), Err(Error::Parse { what: "resume token", .. })), "{}", token);
+        }
+    }
+
+    #[test]
+    fn resuming_after_a_mosaic_generates_the_rest() {
+        for order in ORDERS {
+            let all: Vec<Vec<usize>> = MosaicGenerator::new(4).with_order(order).map(|mosaic| tile_numbers(&mosaic)).collect();
+            for index in (0..all.len()).step_by(47).chain([all.len() - 1]) {
+                let mut generator = MosaicGenerator::new(4).with_order(order).resume_after(&all[index]).unwrap();
+                let rest: Vec<Vec<usize>> = std::iter::from_fn(|| generator.advance().map(<[usize]>::to_vec)).collect();
+                assert!(rest == all[index + 1..], "{} resumed after mosaic {}", order, index);
+            }
+        }
+    }
+
+    #[test]
+    fn stopped_runs_resume_to_the_whole_run() {
+        let path = std::env::temp_dir().join(format!("mosaic_gen-resume-test-{}.txt", std::process::id()));
+        let output_path = path.to_str().unwrap();
+        let read_lines = || std::fs::read_to_string(&path).unwrap().lines().map(str::to_string).collect::<Vec<String>>();
+        for order in [Order::Lex, Order::Colex] {
+            let bounds = Bounds { order, ..Bounds::default() };
+            mosaic_gen(&GeneratorConfig::new(4, output_path).with_bounds(bounds.clone())).unwrap();
+            let whole = read_lines();
+            let (mut resumed, mut resume) = (Vec::new(), None);
+            loop {
+                let bounds = Bounds { resume: resume.take(), max_mosaics: Some(400), ..bounds.clone() };
+                let generated = mosaic_gen(&GeneratorConfig::new(4, output_path).with_bounds(bounds)).unwrap();
+                resumed.extend(read_lines());
+                match generated.resume {
+                    Some(token) => {
+                        assert_eq!(token.generated, resumed.len() as u64);
+                        assert_eq!(std::fs::read_to_string(format!("{}.resume", output_path)).unwrap().trim().parse::<ResumeToken>().unwrap(), token);
+                        resume = Some(token);
+                    }
+                    None => break,
+                }
+            }
+            assert!(resumed == whole, "stopping and resuming a {} run changed its mosaics", order);
+        }
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(format!("{}.resume", output_path));
+    }
+
+    #[test]
+    fn limits_stop_runs_with_a_token() {
+        let path = std::env::temp_dir().join(format!("mosaic_gen-limits-test-{}.txt", std::process::id()));
+        let output_path = path.to_str().unwrap();
+        let run = |bounds: Bounds| mosaic_gen(&GeneratorConfig::new(4, output_path).with_bounds(bounds)).unwrap();
+        let lines = || std::fs::read_to_string(&path).unwrap().lines().count() as u64;
+        let all = baseline(4);
+        //A spent time limit is only checked every CLOCK_INTERVAL mosaics
+        let timed = run(Bounds { max_seconds: Some(0.0), ..Bounds::default() });
+        assert_eq!((timed.count, lines()), (CLOCK_INTERVAL, CLOCK_INTERVAL));
+        assert_eq!(timed.resume.map(|token| (token.generated, key(&token.last))), Some((CLOCK_INTERVAL, all[CLOCK_INTERVAL as usize - 1])));
+        let limited = run(Bounds { max_mosaics: Some(5), ..Bounds::default() });
+        assert_eq!((limited.count, lines(), limited.resume.map(|token| key(&token.last))), (5, 5, Some(all[4])));
+        //A limit the run doesn't reach leaves no token
+        let _ = std::fs::remove_file(format!("{}.resume", output_path));
+        let whole = run(Bounds { max_mosaics: Some(all.len() as u64 + 1), max_seconds: Some(3600.0), ..Bounds::default() });
+        assert_eq!((whole.count, whole.resume), (all.len() as u64, None));
+        assert!(!std::path::Path::new(&format!("{}.resume", output_path)).exists());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn packing_keeps_every_tile() {
+        for size in [4, 5] {
+            let mut generator = MosaicGenerator::new(size);
+            let mut tiles = vec![0; size * size];
+            while let Some(mosaic) = generator.advance() {
+                let mosaic = mosaic.to_vec();
+                assert_eq!(generator.packed().len(), packed_words(size));
+                unpack(generator.packed(), &mut tiles);
+                assert_eq!(tiles, mosaic);
+            }
+        }
+        //Every value a tile's four bits can take, over more words than any mosaic up to 5x5 takes
+        let mut generator = MosaicGenerator::new(7);
+        let tiles: Vec<usize> = (0..49).map(|index| index * 7 % 16).collect();
+        for step in 0..49 {
+            generator.place(step, tiles[generator.steps[step].position]);
+        }
+        let mut unpacked = vec![0; 49];
+        unpack(generator.packed(), &mut unpacked);
+        assert_eq!((generator.packed().len(), unpacked), (packed_words(7), tiles));
+    }
+
+    #[test]
+    fn shards_read_back_in_generation_order() {
+        for (size, order) in [(3, Order::Lex), (4, Order::Revcolex), (5, Order::Lex), (5, Order::Colex)] {
+            let single = keys(MosaicGenerator::new(size).with_order(order));
+            for threads in 1..=3 {
+                let config = GeneratorConfig::new(size, "-").with_threads(threads).with_bounds(Bounds { order, ..Bounds::default() });
+                let (constraints, backtracks, nodes) = (config.constraints(), AtomicU64::new(0), AtomicU64::new(0));
+                let sharded = thread::scope(|scope| {
+                    let mut shards = spawn_shards(scope, &config, &constraints, &backtracks, &nodes).unwrap();
+                    std::iter::from_fn(|| shards.advance().map(key)).collect::<Vec<u128>>()
+                });
+                assert!(sharded == single, "{} threads changed the {}x{} mosaics in {} order", threads, size, size, order);
+                assert!(nodes.load(AtomicOrdering::Relaxed) > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn prefixes_generate_the_mosaics_starting_with_them() {
+        let path = std::env::temp_dir().join(format!("mosaic_gen-prefix-test-{}.txt", std::process::id()));
+        let output_path = path.to_str().unwrap();
+        for order in ORDERS {
+            let all: Vec<Vec<usize>> = MosaicGenerator::new(4).with_order(order).map(|mosaic| order.read(&tile_numbers(&mosaic), 4)).collect();
+            //A first row (column, in column-major orders), a row and a half, a prefix ending in the last tile (whose range runs to the next prefix up) and one no mosaic starts with
+            let last = all.iter().find_map(|tiles| tiles.iter().position(|&tile| tile == TILE_COUNT as usize - 1).map(|index| tiles[..=index].to_vec())).unwrap();
+            for prefix in [all[100][..4].to_vec(), all[700][..6].to_vec(), last, vec![1]] {
+                let bounds = Bounds { prefix: Some(prefix.clone()), order, ..Bounds::default() };
+                mosaic_gen(&GeneratorConfig::new(4, output_path).with_bounds(bounds)).unwrap();
+                let mut generated = keys(std::fs::read_to_string(&path).unwrap().lines().map(|code| code.parse().unwrap()));
+                generated.sort_unstable();
+                assert_eq!(generated.is_empty(), prefix == [1]);
+                assert!(generated == filtered(4, |tiles| order.read(tiles, 4).starts_with(&prefix)), "{:?} in {} order", prefix, order);
+            }
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    //Whether a block of tiles (rows of tile characters, . for any tile) appears in a mosaic, trying it at every placement
+    fn contains_block(tiles: &[usize], size: usize, rows: &[&str]) -> bool {
+        let (height, width) = (rows.len(), rows[0].len());
+        let matches = |row: usize, col: usize| {
+            rows.iter().enumerate().all(|(i, block_row)| block_row.chars().enumerate().all(|(j, tile)| tile == '.' || tile == mosaic::tile_char(tiles[(row + i) * size + col + j] as u8)))
+        };
+        height <= size && width <= size && (0..=size - height).any(|row| (0..=size - width).any(|col| matches(row, col)))
+    }
+
+    #[test]
+    fn patterns_generate_the_mosaics_containing_them() {
+        //A block of crossings, two strands with any tile between them, and the same up to the symmetries of the square
+        let patterns = [
+            ("99/99", false, vec![vec!["99", "99"]]),
+            ("5.5", false, vec![vec!["5.5"]]),
+            ("5.5", true, vec![vec!["5.5"], vec!["6", ".", "6"]]),
+        ];
+        let mut expected = vec![Vec::new(); patterns.len()];
+        for key in baseline(5) {
+            let tiles = tiles(key, 5);
+            for ((_, _, blocks), expected) in patterns.iter().zip(&mut expected) {
+                if blocks.iter().any(|rows| contains_block(&tiles, 5, rows)) {
+                    expected.push(key);
+                }
+            }
+        }
+        for ((pattern, symmetric, blocks), expected) in patterns.iter().zip(expected) {
+            let pattern: Pattern = pattern.parse().unwrap();
+            let constraints = Constraints { pattern: Some(if *symmetric { pattern.symmetric() } else { pattern }), ..Constraints::default() };
+            let generated = keys(constraints.generator(5).unwrap());
+            assert!(!expected.is_empty() && generated == expected, "{:?}", blocks);
+        }
+    }
+
+    #[test]
+    fn forbidden_tiles_generate_the_mosaics_without_them() {
+        let blank_only: Vec<usize> = (1..TILE_COUNT as usize).collect();
+        for (forbidden, forbidden_at) in [
+            (vec![9], vec![]),
+            (vec![7, 8], vec![]),
+            (vec![], vec![(1, 1, vec![9]), (2, 2, vec![3, 9])]),
+            (vec![5], vec![(0, 1, vec![2]), (3, 3, vec![0])]),
+            (blank_only, vec![]),
+        ] {
+            let constraints = Constraints { forbidden: forbidden.clone(), forbidden_at: forbidden_at.clone(), ..Constraints::default() };
+            let generated = keys(constraints.generator(4).unwrap());
+            let allowed = |tiles: &[usize]| {
+                tiles.iter().all(|tile| !forbidden.contains(tile)) && forbidden_at.iter().all(|(row, col, tiles_there)| !tiles_there.contains(&tiles[row * 4 + col]))
+            };
+            assert!(generated == filtered(4, allowed), "{:?} and {:?}", forbidden, forbidden_at);
+        }
+        assert!(Constraints { forbidden_at: vec![(4, 0, vec![1])], ..Constraints::default() }.generator(4).is_err());
+    }
+
+    #[test]
+    fn crossing_counts_generate_the_mosaics_with_that_many() {
+        //Exactly so many crossing tiles, at most so many, and both at once
+        let counts = [(Some(0), None), (Some(3), None), (Some(9), None), (None, Some(2)), (Some(2), Some(4)), (Some(3), Some(2))];
+        let mut expected = vec![Vec::new(); counts.len()];
+        for key in baseline(5) {
+            let crossings = tiles(key, 5).iter().filter(|&&tile| tile == CROSSING).count();
+            for (&(exact, max), expected) in counts.iter().zip(&mut expected) {
+                if exact.is_none_or(|exact| crossings == exact) && max.is_none_or(|max| crossings <= max) {
+                    expected.push(key);
+                }
+            }
+        }
+        for (&(crossings, max_crossings), expected) in counts.iter().zip(expected) {
+            let constraints = Constraints { crossings, max_crossings, ..Constraints::default() };
+            let generated = keys(constraints.generator(5).unwrap());
+            assert_eq!(expected.is_empty(), crossings == Some(3) && max_crossings == Some(2));
+            assert!(generated == expected, "{:?} crossings, at most {:?}", crossings, max_crossings);
+        }
+    }
+
+    #[test]
+    fn reduced_generates_the_mosaics_without_kinks() {
+        let generated = keys(Constraints { reduced: true, ..Constraints::default() }.generator(4).unwrap());
+        assert!(generated == filtered(4, |tiles| traced(key(tiles), 4).is_none_or(|diagram| diagram.kinks().is_empty())));
+        //The unknot drawn as a figure eight, its one crossing a kink
+        let kinked: Mosaic = "2100391003400000".parse().unwrap();
+        assert_eq!(Diagram::trace(&kinked).unwrap().kinks(), [0]);
+        assert!(!generated.contains(&key(&tile_numbers(&kinked))) && generated.len() < baseline(4).len());
+    }
+
+    #[test]
+    fn space_efficient_generates_the_space_efficient_mosaics() {
+        let generated = keys(Constraints { space_efficient: true, ..Constraints::default() }.generator(4).unwrap());
+        let expected = filtered(4, |tiles| Mosaic::from_tiles(4, tiles.iter().map(|&tile| tile as u8).collect()).unwrap().is_space_efficient());
+        assert!(!expected.is_empty() && generated == expected);
+    }
+
+    #[test]
+    fn connected_generates_the_mosaics_with_connected_diagrams() {
+        let generated = keys(Constraints { connected: true, ..Constraints::default() }.generator(4).unwrap());
+        assert!(generated == filtered(4, |tiles| traced(key(tiles), 4).is_none_or(|diagram| diagram.is_connected())));
+        //Two unknots side by side are split, and a Hopf link isn't
+        let split: Mosaic = "2121343400000000".parse().unwrap();
+        let linked: Mosaic = "2510629139460354".parse().unwrap();
+        assert!(!Diagram::trace(&split).unwrap().is_connected() && Diagram::trace(&linked).unwrap().is_connected());
+        assert!(!generated.contains(&key(&tile_numbers(&split))) && generated.contains(&key(&tile_numbers(&linked))));
+    }
+
+    #[test]
+    fn component_counts_generate_the_mosaics_with_that_many() {
+        for (size, count) in [(4, 0), (4, 1), (4, 2), (4, 5), (4, 6), (5, 3)] {
+            let generated = keys(Constraints { components: Some(count), ..Constraints::default() }.generator(size).unwrap());
+            let expected = filtered(size, |tiles| traced(key(tiles), size).is_some_and(|diagram| diagram.component_count() == count));
+            assert_eq!(expected.is_empty(), count == 6);
+            assert!(generated == expected, "{}x{} mosaics with {} components", size, size, count);
+        }
+    }
+
+    #[test]
+    fn linking_numbers_generate_the_mosaics_with_that_linking_number() {
+        let hopf: Mosaic = "2510629139460354".parse().unwrap();
+        assert_eq!(Diagram::trace(&hopf).unwrap().linking_number().abs(), 1);
+        for linking_number in [0, 1, -1, 2] {
+            let generated = keys(Constraints { linking_number: Some(linking_number), ..Constraints::default() }.generator(4).unwrap());
+            let expected = filtered(4, |tiles| traced(key(tiles), 4).is_none_or(|diagram| diagram.linking_number().abs() == linking_number.abs()));
+            assert!(generated == expected, "linking number {}", linking_number);
+            assert_eq!(generated.contains(&key(&tile_numbers(&hopf))), linking_number.abs() == 1);
+        }
+    }
+
+    #[cfg(not(feature = "virtual"))]
+    #[test]
+    fn baseline_has_the_known_counts() {
+        assert_eq!([4, 5].map(|size| baseline(size).len()), [1504, 948032]);
+    }
+}
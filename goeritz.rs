@@ -0,0 +1,388 @@
+/*
+Goeritz matrices of mosaics, from the checkerboard coloring of the regions of their diagrams.
+Each tile is cut into four corners (corner q lying between faces q and q + 1, numbered as in mosaic.rs), which the tile's strands group into regions of the tile.
+The regions of the diagram are found with a union-find over the corners of every tile, joining the corners of neighbouring tiles that meet
+ and every corner along the edge of the mosaic to the unbounded region.
+Regions on either side of a strand get different colors, with the unbounded region white, and the Goeritz matrix is indexed by the other white regions:
+ at each crossing two white regions meet at opposite corners, giving η = 1 if they're the regions the over strand sweeps when rotated anticlockwise
+ (its A-regions, see invariants.rs) and -1 otherwise, and
+    G[i][j] = -Σ η over the crossings where regions i != j meet
+    G[i][i] = Σ η over the crossings where region i meets another white region (the unbounded one included)
+The determinant of the link of a connected diagram is |det G|, and by the theorem of Gordon and Litherland its signature is sign(G) - μ,
+ where μ is the sum of η over the crossings of type II, those where smoothing the crossing the way that respects the strands' orientations joins its black corners.
+Signatures are given in the convention where positive knots have negative signature (the right-handed trefoil has signature -2), so the signs above are reversed.
+Virtual crossings don't lie in the plane, so virtual mosaics have no Goeritz matrix.
+*/
+
+use crate::diagram::Diagram;
+use crate::invariants::{find, union};
+use crate::mosaic::{read_mosaics, Mosaic, NONE, PARTNER, VIRTUAL_CROSSING};
+use crate::output;
+use std::collections::VecDeque;
+use std::io::{Result, Write};
+
+const CROSSING: u8 = 9;
+
+//Whether corners p and q of a tile are in the same region of the tile
+fn joined(tile: u8, p: usize, q: usize) -> bool {
+    if tile == CROSSING {
+        return p == q;
+    }
+    //A strand between faces a < b separates corners a to b - 1 from the others
+    let partners = PARTNER[tile as usize];
+    (0..4).filter(|&a| partners[a] != NONE && (partners[a] as usize) > a).all(|a| {
+        let b = partners[a] as usize;
+        (a..b).contains(&p) == (a..b).contains(&q)
+    })
+}
+
+/* The region of each corner of each tile (at 4 * tile + corner) of a suitably connected mosaic, numbered from 0 for the unbounded region,
+and whether each region is white. Returns None if the mosaic has virtual crossings. */
+fn colored_regions(mosaic: &Mosaic) -> Option<(Vec<usize>, Vec<bool>)> {
+    let tiles = mosaic.tiles();
+    if tiles.contains(&VIRTUAL_CROSSING) {
+        return None;
+    }
+    let size = mosaic.size();
+    let outside = 4 * tiles.len();
+    let mut parent: Vec<usize> = (0..=outside).collect();
+    for (index, &tile) in tiles.iter().enumerate() {
+        for p in 0..4 {
+            for q in p + 1..4 {
+                if joined(tile, p, q) {
+                    union(&mut parent, 4 * index + p, 4 * index + q);
+                }
+            }
+        }
+        let (row, col) = (index / size, index % size);
+        //Corners meeting across the right and bottom faces, or the edge of the mosaic
+        match mosaic.neighbor(index, 0) {
+            Some(right) => {
+                union(&mut parent, 4 * index, 4 * right + 1);
+                union(&mut parent, 4 * index + 3, 4 * right + 2);
+            }
+            None => {
+                union(&mut parent, 4 * index, outside);
+                union(&mut parent, 4 * index + 3, outside);
+            }
+        }
+        match mosaic.neighbor(index, 3) {
+            Some(below) => {
+                union(&mut parent, 4 * index + 3, 4 * below);
+                union(&mut parent, 4 * index + 2, 4 * below + 1);
+            }
+            None => {
+                union(&mut parent, 4 * index + 2, outside);
+                union(&mut parent, 4 * index + 3, outside);
+            }
+        }
+        if row == 0 {
+            union(&mut parent, 4 * index, outside);
+            union(&mut parent, 4 * index + 1, outside);
+        }
+        if col == 0 {
+            union(&mut parent, 4 * index + 1, outside);
+            union(&mut parent, 4 * index + 2, outside);
+        }
+    }
+
+    //Numbers the regions, starting from the unbounded one
+    let mut number = vec![usize::MAX; outside + 1];
+    let root = find(&mut parent, outside);
+    number[root] = 0;
+    let mut regions = 1;
+    let mut region_of = Vec::with_capacity(outside);
+    for corner in 0..outside {
+        let root = find(&mut parent, corner);
+        if number[root] == usize::MAX {
+            number[root] = regions;
+            regions += 1;
+        }
+        region_of.push(number[root]);
+    }
+
+    //Regions meet across a strand where it leaves a tile, between the corners either side of the face
+    let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); regions];
+    for (index, &tile) in tiles.iter().enumerate() {
+        for face in 0..4 {
+            if PARTNER[tile as usize][face] != NONE {
+                let (a, b) = (region_of[4 * index + (face + 3) % 4], region_of[4 * index + face]);
+                neighbors[a].push(b);
+                neighbors[b].push(a);
+            }
+        }
+    }
+    let mut white: Vec<Option<bool>> = vec![None; regions];
+    white[0] = Some(true);
+    let mut queue = VecDeque::from([0]);
+    while let Some(region) = queue.pop_front() {
+        for &neighbor in &neighbors[region] {
+            if white[neighbor].is_none() {
+                white[neighbor] = white[region].map(|white| !white);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    Some((region_of, white.into_iter().map(|white| white.unwrap_or(true)).collect()))
+}
+
+/* The Goeritz matrix of a mosaic's diagram (see above), with its rows in order of the white regions' first corners.
+Returns None for mosaics that aren't suitably connected or have virtual crossings. */
+pub fn goeritz_matrix(mosaic: &Mosaic) -> Option<Vec<Vec<i64>>> {
+    if !mosaic.is_suitably_connected() {
+        return None;
+    }
+    let (region_of, white) = colored_regions(mosaic)?;
+    Some(matrix_of_regions(mosaic, &region_of, &white))
+}
+
+fn matrix_of_regions(mosaic: &Mosaic, region_of: &[usize], white: &[bool]) -> Vec<Vec<i64>> {
+    let mut row_of = vec![None; white.len()];
+    let mut rows = 0;
+    for region in 1..white.len() {
+        if white[region] {
+            row_of[region] = Some(rows);
+            rows += 1;
+        }
+    }
+    let mut matrix = vec![vec![0; rows]; rows];
+    for (index, &tile) in mosaic.tiles().iter().enumerate() {
+        if tile != CROSSING {
+            continue;
+        }
+        let (eta, corners) = if white[region_of[4 * index + 1]] { (1, (1, 3)) } else { (-1, (0, 2)) };
+        let (i, j) = (region_of[4 * index + corners.0], region_of[4 * index + corners.1]);
+        if i == j {
+            continue;
+        }
+        for (a, b) in [(i, j), (j, i)] {
+            if let Some(row) = row_of[a] {
+                matrix[row][row] += eta;
+                if let Some(col) = row_of[b] {
+                    matrix[row][col] -= eta;
+                }
+            }
+        }
+    }
+    matrix
+}
+
+//Determinant of a square integer matrix, by fraction-free (Bareiss) elimination
+pub fn integer_determinant(matrix: &[Vec<i64>]) -> i64 {
+    let mut matrix = matrix.to_vec();
+    let n = matrix.len();
+    let (mut sign, mut previous) = (1, 1);
+    for k in 0..n {
+        if matrix[k][k] == 0 {
+            match (k + 1..n).find(|&row| matrix[row][k] != 0) {
+                Some(row) => {
+                    matrix.swap(k, row);
+                    sign = -sign;
+                }
+                None => return 0,
+            }
+        }
+        for i in k + 1..n {
+            for j in k + 1..n {
+                matrix[i][j] = (matrix[i][j] * matrix[k][k] - matrix[i][k] * matrix[k][j]) / previous;
+            }
+        }
+        previous = matrix[k][k];
+    }
+    if n == 0 { 1 } else { sign * matrix[n - 1][n - 1] }
+}
+
+/* Determinant of the link represented by a diagram, |det G| for its Goeritz matrix G: 0 for split links and 1 for the unknot.
+Returns None for the empty diagram and for virtual mosaics. */
+pub fn determinant(diagram: &Diagram) -> Option<u64> {
+    if diagram.component_count() == 0 {
+        return None;
+    }
+    let matrix = goeritz_matrix(&diagram.mosaic)?;
+    Some(if diagram.is_connected() { integer_determinant(&matrix).unsigned_abs() } else { 0 })
+}
+
+//Signature of a symmetric integer matrix, diagonalizing it by congruences that keep its entries integers
+fn matrix_signature(matrix: &[Vec<i64>]) -> i32 {
+    let mut matrix: Vec<Vec<i128>> = matrix.iter().map(|row| row.iter().map(|&entry| entry as i128).collect()).collect();
+    let n = matrix.len();
+    let mut signature = 0;
+    for k in 0..n {
+        //Brings a nonzero entry to the pivot, from the diagonal or else from the pivot's row (adding that row and column to the pivot's)
+        if matrix[k][k] == 0 {
+            if let Some(j) = (k + 1..n).find(|&j| matrix[j][j] != 0) {
+                matrix.swap(k, j);
+                matrix.iter_mut().for_each(|row| row.swap(k, j));
+            } else if let Some(j) = (k + 1..n).find(|&j| matrix[k][j] != 0) {
+                let row = matrix[j].clone();
+                matrix[k].iter_mut().zip(row).for_each(|(entry, added)| *entry += added);
+                matrix.iter_mut().for_each(|row| row[k] += row[j]);
+            } else {
+                continue;
+            }
+        }
+        //Clears the pivot's row and column, scaling the rest by the pivot's magnitude (a congruence up to a positive factor)
+        let pivot = matrix[k][k];
+        signature += pivot.signum() as i32;
+        for i in k + 1..n {
+            for j in k + 1..n {
+                matrix[i][j] = pivot.signum() * (pivot * matrix[i][j] - matrix[i][k] * matrix[k][j]);
+            }
+        }
+        matrix.iter_mut().skip(k + 1).for_each(|row| row[k] = 0);
+        matrix[k][k + 1..].fill(0);
+        let divisor = (k + 1..n).flat_map(|i| (k + 1..n).map(move |j| (i, j))).fold(0, |divisor, (i, j)| gcd(divisor, matrix[i][j].abs()));
+        if divisor > 1 {
+            for row in matrix.iter_mut().skip(k + 1) {
+                row.iter_mut().skip(k + 1).for_each(|entry| *entry /= divisor);
+            }
+        }
+    }
+    signature
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/* Signature of the link represented by a diagram, in the traversed orientation of each component (see above): the sum of the signatures of its pieces, each found from its own Goeritz matrix.
+Returns None for the empty diagram and for virtual mosaics. */
+pub fn signature(diagram: &Diagram) -> Option<i32> {
+    if diagram.component_count() == 0 {
+        return None;
+    }
+    let mut signature = 0;
+    for piece in diagram.piece_mosaics() {
+        let (region_of, white) = colored_regions(&piece)?;
+        let mut mu = 0;
+        for crossing in diagram.crossings.iter().filter(|crossing| piece.tiles()[crossing.tile] == CROSSING) {
+            let (over, under) = (diagram.visit(crossing.over), diagram.visit(crossing.under));
+            let corner = |corner: usize| 4 * crossing.tile + corner;
+            let eta = if white[region_of[corner(1)]] { 1 } else { -1 };
+            //The oriented smoothing joins the entry of each strand to the exit of the other, which is tile 7 (joining corners 1 and 3) or tile 8 (corners 0 and 2)
+            let joined_corner = if matches!((over.entry.min(under.exit), over.entry.max(under.exit)), (0, 1) | (2, 3)) { 1 } else { 0 };
+            if !white[region_of[corner(joined_corner)]] {
+                mu += eta;
+            }
+        }
+        signature += mu - matrix_signature(&matrix_of_regions(&piece, &region_of, &white));
+    }
+    Some(signature)
+}
+
+/* Writes the Goeritz matrix of every nonempty mosaic in the input file, one per line as its mosaic code and the matrix's rows, e.g.
+    0021002891299463791603434 | [[-2, 1], [1, 1]]
+Mosaics with virtual crossings are skipped. Returns the number of matrices written. */
+pub fn goeritz_export(input_path: &str, output_path: &str) -> Result<usize> {
+    let mut output_buffer = output::create(output_path)?;
+    let mut count = 0;
+    for mosaic in read_mosaics(input_path)? {
+        let mosaic = mosaic?;
+        if mosaic.tiles().iter().all(|&tile| tile == 0) {
+            continue;
+        }
+        if let Some(matrix) = goeritz_matrix(&mosaic) {
+            writeln!(output_buffer, "{} | {:?}", mosaic, matrix)?;
+            count += 1;
+        }
+    }
+    output_buffer.finish()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::generate_with;
+    use crate::invariants::jones;
+    use std::ops::ControlFlow;
+
+    //The jones polynomial at t = -1, taking t^(1/2) to i, as its real and imaginary parts
+    fn jones_at_minus_one(diagram: &Diagram) -> (i64, i64) {
+        let jones = jones(diagram).unwrap();
+        jones.coefficients.iter().enumerate().fold((0, 0), |(re, im), (power, &coefficient)| match (jones.low + power as i32).rem_euclid(4) {
+            0 => (re + coefficient, im),
+            1 => (re, im + coefficient),
+            2 => (re - coefficient, im),
+            _ => (re, im - coefficient),
+        })
+    }
+
+    //The diagrams of every nonempty classical 4x4 mosaic and of every `stride`th 5x5 one
+    fn diagrams(stride: usize) -> Vec<Diagram> {
+        let mut diagrams = Vec::new();
+        for (size, stride) in [(4, 1), (5, stride)] {
+            let mut index = 0;
+            let _ = generate_with::<()>(size, |mosaic| {
+                index += 1;
+                if index % stride == 0 && !mosaic.tiles().contains(&VIRTUAL_CROSSING) {
+                    diagrams.extend(Diagram::trace(mosaic).filter(|diagram| diagram.component_count() > 0));
+                }
+                ControlFlow::Continue(())
+            });
+        }
+        diagrams
+    }
+
+    #[test]
+    fn integer_determinants() {
+        assert_eq!(integer_determinant(&[]), 1);
+        assert_eq!(integer_determinant(&[vec![2, 1], vec![1, 2]]), 3);
+        //A zero pivot, swapped for a later row
+        assert_eq!(integer_determinant(&[vec![0, 1, 2], vec![1, 0, 3], vec![4, -3, 8]]), -2);
+        assert_eq!(integer_determinant(&[vec![1, 2], vec![2, 4]]), 0);
+        assert_eq!(integer_determinant(&[vec![0, 0], vec![0, 5]]), 0);
+    }
+
+    #[test]
+    fn determinants_are_the_jones_polynomial_at_minus_one() {
+        for diagram in diagrams(97) {
+            let (re, im) = jones_at_minus_one(&diagram);
+            let determinant = determinant(&diagram).unwrap() as i64;
+            assert_eq!(determinant * determinant, re * re + im * im, "{}", diagram.mosaic);
+            //A knot's determinant is odd
+            assert!(diagram.component_count() > 1 || determinant % 2 == 1, "{}", diagram.mosaic);
+        }
+    }
+
+    #[test]
+    fn goeritz_matrices_are_symmetric() {
+        for diagram in diagrams(997) {
+            let matrix = goeritz_matrix(&diagram.mosaic).unwrap();
+            assert!((0..matrix.len()).all(|i| (0..matrix.len()).all(|j| matrix[i][j] == matrix[j][i])), "{}", diagram.mosaic);
+        }
+    }
+
+    #[test]
+    fn matrix_signatures() {
+        assert_eq!(matrix_signature(&[]), 0);
+        assert_eq!(matrix_signature(&[vec![1, 0, 0], vec![0, -1, 0], vec![0, 0, 2]]), 1);
+        assert_eq!(matrix_signature(&[vec![2, 1], vec![1, 2]]), 2);
+        assert_eq!(matrix_signature(&[vec![1, 2], vec![2, 1]]), 0);
+        //No pivot on the diagonal, and a zero row
+        assert_eq!(matrix_signature(&[vec![0, 1], vec![1, 0]]), 0);
+        assert_eq!(matrix_signature(&[vec![0, 1, 0], vec![1, 0, 0], vec![0, 0, -3]]), -1);
+        assert_eq!(matrix_signature(&[vec![0, 0], vec![0, 0]]), 0);
+        assert_eq!(matrix_signature(&[vec![-2, 1, 0], vec![1, -2, 1], vec![0, 1, -2]]), -3);
+    }
+
+    #[test]
+    fn trefoils_have_signature_two() {
+        //The max-tb right-handed and left-handed trefoils of the 5x5 census
+        for (code, expected) in [("0021025971629943943103554", -2), ("0021002971294663759403540", 2)] {
+            let diagram = Diagram::trace(&code.parse().unwrap()).unwrap();
+            assert_eq!((signature(&diagram), determinant(&diagram)), (Some(expected), Some(3)), "{}", code);
+        }
+    }
+
+    #[test]
+    fn knot_signatures_agree_with_the_determinant_and_genus() {
+        for diagram in diagrams(97).iter().filter(|diagram| diagram.component_count() == 1) {
+            let (signature, determinant) = (signature(diagram).unwrap(), determinant(diagram).unwrap() as i64);
+            //V(-1) = Δ(-1) = (-1)^(σ/2) det for knots, and |σ| is at most twice the genus
+            assert_eq!(signature % 2, 0, "{}", diagram.mosaic);
+            assert_eq!(jones_at_minus_one(diagram), (if signature % 4 == 0 { determinant } else { -determinant }, 0), "{}", diagram.mosaic);
+            assert!(signature.unsigned_abs() as usize <= 2 * diagram.genus_bound(), "{}", diagram.mosaic);
+        }
+    }
+}
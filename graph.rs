@@ -0,0 +1,267 @@
+/*
+Graphs of mosaics, for looking at how a set of mosaics hangs together under local changes. Both kinds have a node for each mosaic, joining
+    in the move graph, two mosaics whenever a single move (see moves.rs) turns one into the other, so its connected components are the Legendrian isotopy classes the moves can find within the set,
+    in the mutation graph, two mosaics differing in a single tile, which for suitably connected mosaics has to join the same faces -- so only the tiles 7, 8, 9 (and the virtual crossing) are ever swapped.
+Graphs come with statistics of their connected components: the number of components of each size, and their diameters,
+ found exactly for components of up to EXACT_DIAMETER_NODES mosaics and bounded below by a double sweep for bigger ones.
+Graphs are written in DOT (for Graphviz) or GraphML (for Gephi, graph-tool and the like), with each node labelled by its mosaic code and given its ID (see Mosaic::id),
+ the values of any invariants asked for (see invariant.rs) as node attributes, and each edge labelled by its move or mutation, from the earlier mosaic to the later.
+*/
+
+use crate::invariant::{Condition, Invariant, Value};
+use crate::mosaic::{read_mosaics, tile_char, Mosaic, NONE, PARTNER, TILE_COUNT};
+use crate::moves::moves;
+use crate::output;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Result, Write};
+use std::sync::Arc;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GraphFormat {
+    Dot,
+    GraphMl,
+}
+
+impl GraphFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::GraphMl => "graphml",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GraphFormat> {
+        [GraphFormat::Dot, GraphFormat::GraphMl].into_iter().find(|format| format.extension().eq_ignore_ascii_case(name) || (*format == GraphFormat::Dot && name == "gv"))
+    }
+}
+
+pub struct Graph {
+    pub mosaics: Vec<Mosaic>,
+    //Edges as the indices of the mosaics they join, the earlier first, and their labels
+    pub edges: Vec<(usize, usize, String)>,
+}
+
+//Components of over this many mosaics only get a lower bound on their diameter, as finding it exactly takes a search from every mosaic
+pub const EXACT_DIAMETER_NODES: usize = 5000;
+
+//A connected component of a graph, by its number of mosaics and its diameter (a lower bound, unless exact)
+pub struct Component {
+    pub nodes: usize,
+    pub diameter: usize,
+    pub exact: bool,
+}
+
+//Each of the mosaics once, in the order they're given, and the index of each
+fn index_mosaics(mosaics: impl IntoIterator<Item = Mosaic>) -> (Vec<Mosaic>, HashMap<Mosaic, usize>) {
+    let mut indices = HashMap::new();
+    let mut unique = Vec::new();
+    for mosaic in mosaics {
+        indices.entry(mosaic.clone()).or_insert_with(|| {
+            unique.push(mosaic);
+            unique.len() - 1
+        });
+    }
+    (unique, indices)
+}
+
+/* The mosaics of the component of `start` in the order a breadth-first search reaches them, each with its distance from `start`,
+ using `distance` (all usize::MAX, and left that way) to mark the mosaics reached, so each search only takes as long as its component */
+fn distances(adjacent: &[Vec<usize>], start: usize, distance: &mut [usize]) -> Vec<(usize, usize)> {
+    distance[start] = 0;
+    let mut reached = vec![(start, 0)];
+    let mut next = 0;
+    while let Some(&(node, from_start)) = reached.get(next) {
+        next += 1;
+        for &neighbor in &adjacent[node] {
+            if distance[neighbor] == usize::MAX {
+                distance[neighbor] = from_start + 1;
+                reached.push((neighbor, from_start + 1));
+            }
+        }
+    }
+    for &(node, _) in &reached {
+        distance[node] = usize::MAX;
+    }
+    reached
+}
+
+impl Graph {
+    //The move graph of the mosaics, with each mosaic once, in the order they're given
+    pub fn of_moves(mosaics: impl IntoIterator<Item = Mosaic>) -> Graph {
+        let (unique, indices) = index_mosaics(mosaics);
+        let mut edges = Vec::new();
+        for (index, mosaic) in unique.iter().enumerate() {
+            let mut neighbors = Vec::new();
+            for step in moves(mosaic) {
+                //Moves in different places can make the same mosaic, which only needs an edge once
+                match indices.get(&step.apply(mosaic)) {
+                    Some(&neighbor) if neighbor > index && !neighbors.contains(&neighbor) => {
+                        neighbors.push(neighbor);
+                        edges.push((index, neighbor, step.to_string()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Graph { mosaics: unique, edges }
+    }
+
+    //The mutation graph of the mosaics, with each edge labelled by the position of the tile that differs and the tile in each mosaic, like (1, 2) 7 -> 9
+    pub fn of_mutations(mosaics: impl IntoIterator<Item = Mosaic>) -> Graph {
+        let (unique, indices) = index_mosaics(mosaics);
+        let faces = |tile: u8| PARTNER[tile as usize].map(|partner| partner != NONE);
+        let mut edges = Vec::new();
+        for (index, mosaic) in unique.iter().enumerate() {
+            let size = mosaic.size();
+            let mut tiles = mosaic.tiles().to_vec();
+            for position in 0..tiles.len() {
+                let tile = tiles[position];
+                //Replacements joining other faces would leave loose ends, so aren't in a graph of suitably connected mosaics
+                for replacement in (0..TILE_COUNT).filter(|&replacement| replacement != tile && faces(replacement) == faces(tile)) {
+                    tiles[position] = replacement;
+                    if let Some(&neighbor) = indices.get(&Mosaic::from_tiles(size, tiles.clone()).expect("mutations replace tiles with tiles")) {
+                        if neighbor > index {
+                            edges.push((index, neighbor, format!("({}, {}) {} -> {}", position / size, position % size, tile_char(tile), tile_char(replacement))));
+                        }
+                    }
+                }
+                tiles[position] = tile;
+            }
+        }
+        Graph { mosaics: unique, edges }
+    }
+
+    //The connected components, in the order of their first mosaics
+    pub fn components(&self) -> Vec<Component> {
+        let mut adjacent = vec![Vec::new(); self.mosaics.len()];
+        for &(from, to, _) in &self.edges {
+            adjacent[from].push(to);
+            adjacent[to].push(from);
+        }
+        let mut components = Vec::new();
+        let mut seen = vec![false; self.mosaics.len()];
+        let mut distance = vec![usize::MAX; self.mosaics.len()];
+        for start in 0..self.mosaics.len() {
+            if seen[start] {
+                continue;
+            }
+            let members = distances(&adjacent, start, &mut distance);
+            for &(member, _) in &members {
+                seen[member] = true;
+            }
+            //The last mosaic a search reaches is the farthest from where it started
+            let eccentricity = |from: usize, distance: &mut [usize]| distances(&adjacent, from, distance).last().map_or(0, |&(_, from_start)| from_start);
+            let exact = members.len() <= EXACT_DIAMETER_NODES;
+            let diameter = if exact {
+                members.iter().map(|&(member, _)| eccentricity(member, &mut distance)).max().unwrap_or(0)
+            } else {
+                //Double sweep: the eccentricity of a mosaic farthest from the start
+                eccentricity(members.last().expect("components have a mosaic").0, &mut distance)
+            };
+            components.push(Component { nodes: members.len(), diameter, exact });
+        }
+        components
+    }
+
+    pub fn write(&self, output: &mut dyn Write, format: GraphFormat, columns: &[Arc<dyn Invariant>]) -> Result<()> {
+        let values: Vec<Vec<Value>> = self.mosaics.iter().map(|mosaic| columns.iter().map(|invariant| invariant.compute(mosaic)).collect()).collect();
+        match format {
+            GraphFormat::Dot => {
+                writeln!(output, "graph mosaics {{")?;
+                for (index, mosaic) in self.mosaics.iter().enumerate() {
+                    let attributes: String = columns.iter().zip(&values[index]).map(|(invariant, value)| format!(", {}={}", dot_string(invariant.name()), dot_string(&value.to_string()))).collect();
+                    writeln!(output, "  {} [label=\"{}\", id=\"{}\"{}];", index, mosaic, mosaic.id(), attributes)?;
+                }
+                for (from, to, label) in &self.edges {
+                    writeln!(output, "  {} -- {} [label={}];", from, to, dot_string(label))?;
+                }
+                writeln!(output, "}}")
+            }
+            GraphFormat::GraphMl => {
+                writeln!(output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+                writeln!(output, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">")?;
+                writeln!(output, "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>")?;
+                writeln!(output, "  <key id=\"id\" for=\"node\" attr.name=\"id\" attr.type=\"string\"/>")?;
+                for (column, invariant) in columns.iter().enumerate() {
+                    //Columns are typed by their defined values, with undefined values left out of the nodes
+                    let defined: Vec<&Value> = values.iter().map(|values| &values[column]).filter(|value| **value != Value::Undefined).collect();
+                    let attribute_type = if !defined.is_empty() && defined.iter().all(|value| matches!(value, Value::Integer(_))) {
+                        "long"
+                    } else if !defined.is_empty() && defined.iter().all(|value| matches!(value, Value::Boolean(_))) {
+                        "boolean"
+                    } else {
+                        "string"
+                    };
+                    writeln!(output, "  <key id=\"d{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>", column, xml_escape(invariant.name()), attribute_type)?;
+                }
+                writeln!(output, "  <key id=\"change\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>")?;
+                writeln!(output, "  <graph id=\"mosaics\" edgedefault=\"undirected\">")?;
+                for (index, mosaic) in self.mosaics.iter().enumerate() {
+                    write!(output, "    <node id=\"n{}\"><data key=\"label\">{}</data><data key=\"id\">{}</data>", index, mosaic, mosaic.id())?;
+                    for (column, value) in values[index].iter().enumerate().filter(|(_, value)| **value != Value::Undefined) {
+                        write!(output, "<data key=\"d{}\">{}</data>", column, xml_escape(&value.to_string()))?;
+                    }
+                    writeln!(output, "</node>")?;
+                }
+                for (from, to, label) in &self.edges {
+                    writeln!(output, "    <edge source=\"n{}\" target=\"n{}\"><data key=\"change\">{}</data></edge>", from, to, xml_escape(label))?;
+                }
+                writeln!(output, "  </graph>")?;
+                writeln!(output, "</graphml>")
+            }
+        }
+    }
+}
+
+fn dot_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+//The suitably connected mosaics in a file meeting the conditions (see invariant.rs)
+pub fn read_graph_mosaics(input_path: &str, conditions: &[Condition]) -> Result<Vec<Mosaic>> {
+    let mut mosaics = Vec::new();
+    for mosaic in read_mosaics(input_path)? {
+        let mosaic = mosaic?;
+        if mosaic.is_suitably_connected() && conditions.iter().all(|condition| condition.admits(&mosaic)) {
+            mosaics.push(mosaic);
+        }
+    }
+    Ok(mosaics)
+}
+
+//Writes a graph to a file, with the values of the invariants in `columns` as node attributes
+pub fn write_graph(graph: &Graph, output_path: &str, format: GraphFormat, columns: &[Arc<dyn Invariant>]) -> Result<()> {
+    let mut output = output::create(output_path)?;
+    graph.write(&mut output, format, columns)?;
+    output.finish()
+}
+
+/* A table of a graph's components: the numbers of mosaics, edges, components and isolated mosaics, the size and diameter of the largest component and the greatest diameter,
+ then the number of components of each size, with their greatest diameter (after >= if it's only a lower bound) */
+pub fn format_components(graph: &Graph, components: &[Component]) -> String {
+    let format_diameter = |component: &Component| format!("{}{}", if component.exact { "" } else { ">=" }, component.diameter);
+    let mut table = format!("mosaics\t{}\nedges\t{}\ncomponents\t{}\nisolated\t{}\n", graph.mosaics.len(), graph.edges.len(), components.len(), components.iter().filter(|component| component.nodes == 1).count());
+    if let Some(largest) = components.iter().max_by_key(|component| component.nodes) {
+        table += &format!("largest\t{} mosaics (diameter {})\n", largest.nodes, format_diameter(largest));
+    }
+    if let Some(widest) = components.iter().max_by_key(|component| (component.diameter, component.exact)) {
+        table += &format!("diameter\t{} (of a component of {} mosaics)\n", format_diameter(widest), widest.nodes);
+    }
+    let mut by_size: BTreeMap<usize, (usize, &Component)> = BTreeMap::new();
+    for component in components {
+        let (count, widest) = by_size.entry(component.nodes).or_insert((0, component));
+        *count += 1;
+        if (component.diameter, component.exact) > (widest.diameter, widest.exact) {
+            *widest = component;
+        }
+    }
+    table += "size\tcomponents\tdiameter";
+    for (nodes, (count, widest)) in by_size {
+        table += &format!("\n{}\t{}\t{}", nodes, count, format_diameter(widest));
+    }
+    table
+}
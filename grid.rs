@@ -0,0 +1,465 @@
+/*
+Grid diagrams of mosaics, and the GRID invariants λ+ and λ- of Ozsváth, Szabó and Thurston.
+The strands of a mosaic already run along its rows and columns, so a mosaic is nearly a grid diagram once it's turned a quarter turn anticlockwise:
+ mosaic rows become grid columns and mosaic columns become grid rows, and the corners of the strands become the markings.
+Grids have a single segment in each row and column, so where a row or column of the mosaic holds several segments, they're moved slightly apart,
+ segments of a mosaic row in order from left to right and segments of a mosaic column from bottom to top, which keeps the two strands of a tile 7 or 8 from crossing.
+Travelling along a knot, grid rows run from O to X and grid columns from X to O. Grid crossings have their vertical strand over, and front crossings their 1-3 strand,
+ so the grid is a diagram of the mirror m(K) of the knot K a mosaic represents, which is the grid Ozsváth, Szabó and Thurston associate with a Legendrian front of K.
+The resulting grid has a row and column for every two corners, so it's made smaller by the destabilizations that don't change its Legendrian type
+ (those keeping tb the same, see Grid::destabilized_at), along with the commutations and translations around the torus that make room for them.
+
+The invariants are the classes of the generators x+ (the upper right corners of the X's) and x- (their lower left corners) in grid homology, which lie in gradings
+ (M, A) = (tb - rot + 1, (tb - rot + 1) / 2) and (tb + rot + 1, (tb + rot + 1) / 2) of the knot Floer homology of m(K). Since they're invariant under Legendrian isotopy
+ yet aren't determined by tb, rot or the knot type, whether they vanish tells apart Legendrian knots the classical invariants can't, and λ+ vanishes for positive stabilizations
+ (as does λ- for negative ones). They're computed here over F2 in the fully blocked complex, whose differential counts empty rectangles on the torus containing no markings:
+ its homology is that of the hat complex tensored with a vector space of dimension 2^(n-1), and the class of x± vanishes in it exactly when λ̂± does.
+That takes every one of the n! generators of an n x n grid, so it's only done for grids up to MAX_GRID_SIZE.
+*/
+
+use crate::diagram::Diagram;
+use crate::mosaic::VIRTUAL_CROSSING;
+use std::collections::HashMap;
+
+//The largest grids the invariants are computed for
+pub const MAX_GRID_SIZE: usize = 8;
+
+//A grid diagram, as the rows of the X and O in each column (counting from the bottom)
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Grid {
+    pub x: Vec<usize>,
+    pub o: Vec<usize>,
+}
+
+//Whether the two faces of a visit are on opposite sides of the tile (rather than making a corner)
+fn is_straight(entry: u8, exit: u8) -> bool {
+    entry % 2 == exit % 2
+}
+
+//A marking, as (column, row, whether it's an X)
+type Marking = (usize, usize, bool);
+
+impl Grid {
+    //The grid of a knot or link diagram (see above), or None for diagrams without strands or with virtual crossings
+    pub fn new(diagram: &Diagram) -> Option<Grid> {
+        if diagram.components.is_empty() || diagram.mosaic.tiles().contains(&VIRTUAL_CROSSING) {
+            return None;
+        }
+        let size = diagram.mosaic.size();
+        //(tile, whether the strand arrives along a mosaic column) of each corner, component by component
+        let corners: Vec<Vec<(usize, bool)>> = diagram
+            .components
+            .iter()
+            .map(|component| component.iter().filter(|visit| !is_straight(visit.entry, visit.exit)).map(|visit| (visit.tile, visit.entry % 2 == 1)).collect())
+            .collect();
+        //Segments are keyed by (mosaic row, leftmost column) along rows and (mosaic column, bottom row reversed) along columns, so sorting them orders the grid
+        let mut columns: Vec<((usize, usize), usize, usize)> = Vec::new();
+        let mut rows: Vec<((usize, usize), usize, usize)> = Vec::new();
+        for (component, corners) in corners.iter().enumerate() {
+            for (index, &(tile, _)) in corners.iter().enumerate() {
+                let (next, arrives_vertically) = corners[(index + 1) % corners.len()];
+                let ((row, col), (next_row, next_col)) = ((tile / size, tile % size), (next / size, next % size));
+                if arrives_vertically {
+                    rows.push(((col, size - row.max(next_row)), component, index));
+                } else {
+                    columns.push(((row, col.min(next_col)), component, index));
+                }
+            }
+        }
+        columns.sort();
+        rows.sort();
+        //Each segment is identified by the corner it leaves from
+        let rank = |segments: &[((usize, usize), usize, usize)]| -> HashMap<(usize, usize), usize> {
+            segments.iter().enumerate().map(|(rank, &(_, component, index))| ((component, index), rank)).collect()
+        };
+        let (column_of, row_of) = (rank(&columns), rank(&rows));
+        let mut grid = Grid { x: vec![0; columns.len()], o: vec![0; columns.len()] };
+        for (component, corners) in corners.iter().enumerate() {
+            for (index, &(_, arrives_vertically)) in corners.iter().enumerate() {
+                let (arriving, leaving) = ((component, (index + corners.len() - 1) % corners.len()), (component, index));
+                if arrives_vertically {
+                    grid.x[column_of[&leaving]] = row_of[&arriving];
+                } else {
+                    grid.o[column_of[&arriving]] = row_of[&leaving];
+                }
+            }
+        }
+        Some(grid.destabilized())
+    }
+
+    pub fn size(&self) -> usize {
+        self.x.len()
+    }
+
+    fn markings(&self) -> Vec<Marking> {
+        (0..self.size()).flat_map(|col| [(col, self.x[col], true), (col, self.o[col], false)]).collect()
+    }
+
+    //The columns of the X and O in each row
+    fn row_markings(&self) -> (Vec<usize>, Vec<usize>) {
+        let (mut x, mut o) = (vec![0; self.size()], vec![0; self.size()]);
+        for col in 0..self.size() {
+            x[self.x[col]] = col;
+            o[self.o[col]] = col;
+        }
+        (x, o)
+    }
+
+    /* The Thurston-Bennequin number of the front: cusps are the markings whose strands turn up and right or down and left,
+    and the front's crossings have the grid's horizontal strand over, so they're the grid's crossings with opposite signs. */
+    pub fn tb(&self) -> i32 {
+        let n = self.size();
+        let (row_x, row_o) = self.row_markings();
+        let between = |value: usize, a: usize, b: usize| a.min(b) < value && value < a.max(b);
+        let sign = |from: usize, to: usize| if to > from { 1 } else { -1 };
+        let mut writhe = 0;
+        for col in 0..n {
+            for row in (0..n).filter(|&row| between(row, self.x[col], self.o[col]) && between(col, row_o[row], row_x[row])) {
+                writhe += sign(self.x[col], self.o[col]) * sign(row_o[row], row_x[row]);
+            }
+        }
+        let turns = |col: usize, row: usize, partner_row: usize, partner_col: usize| (partner_row as i64 - row as i64) * (partner_col as i64 - col as i64) > 0;
+        let cusps = (0..n).filter(|&col| turns(col, self.x[col], self.o[col], row_o[self.x[col]])).count()
+            + (0..n).filter(|&col| turns(col, self.o[col], self.x[col], row_x[self.o[col]])).count();
+        writhe - cusps as i32 / 2
+    }
+
+    //The grid moved cols columns right and rows rows up on the torus
+    fn translated(&self, cols: usize, rows: usize) -> Grid {
+        let n = self.size();
+        let mut grid = self.clone();
+        for col in 0..n {
+            grid.x[(col + cols) % n] = (self.x[col] + rows) % n;
+            grid.o[(col + cols) % n] = (self.o[col] + rows) % n;
+        }
+        grid
+    }
+
+    /* The grids given by the commutations of the grid: swapping two neighbouring columns (or rows) whose segments are disjoint or nested,
+    which moves one segment past the other without changing any crossings. */
+    fn commutations(&self) -> Vec<Grid> {
+        let n = self.size();
+        let commute = |a: (usize, usize), b: (usize, usize)| {
+            let ((a_low, a_high), (b_low, b_high)) = ((a.0.min(a.1), a.0.max(a.1)), (b.0.min(b.1), b.0.max(b.1)));
+            a_high < b_low || b_high < a_low || (a_low < b_low && b_high < a_high) || (b_low < a_low && a_high < b_high)
+        };
+        let mut grids = Vec::new();
+        for i in 0..n {
+            //Neighbours across the edge of the grid are next to each other once it's moved around the torus
+            let (grid, i) = if i + 1 < n { (self.clone(), i) } else { (self.translated(1, 1), 0) };
+            let (row_x, row_o) = grid.row_markings();
+            if commute((grid.x[i], grid.o[i]), (grid.x[i + 1], grid.o[i + 1])) {
+                let mut commuted = grid.clone();
+                commuted.x.swap(i, i + 1);
+                commuted.o.swap(i, i + 1);
+                grids.push(commuted);
+            }
+            if commute((row_x[i], row_o[i]), (row_x[i + 1], row_o[i + 1])) {
+                let swap = |row: usize| if row == i { i + 1 } else if row == i + 1 { i } else { row };
+                grids.push(Grid { x: grid.x.iter().map(|&row| swap(row)).collect(), o: grid.o.iter().map(|&row| swap(row)).collect() });
+            }
+        }
+        grids
+    }
+
+    /* Removes an L shaped step of markings p - q - r, with q the marking at (col, row), p beside it and r just above or below it, merging p and r into one marking
+    at p's column and r's row. That's always a Legendrian isotopy or a stabilization, and stabilizations change tb, so it's only done if tb stays the same. */
+    fn destabilized_at(&self, col: usize, row: usize, is_x: bool) -> Option<Grid> {
+        let (row_x, row_o) = self.row_markings();
+        let (p_col, r_row) = if is_x { (row_o[row], self.o[col]) } else { (row_x[row], self.x[col]) };
+        //A step whose fourth corner is p's partner (and r's) would leave a component without markings
+        let p_partner_row = if is_x { self.x[p_col] } else { self.o[p_col] };
+        if p_col.abs_diff(col) != 1 || r_row.abs_diff(row) != 1 || p_partner_row == r_row {
+            return None;
+        }
+        let mut grid = self.clone();
+        if is_x { grid.o[p_col] = r_row } else { grid.x[p_col] = r_row }
+        grid.x.remove(col);
+        grid.o.remove(col);
+        for marking_row in grid.x.iter_mut().chain(grid.o.iter_mut()) {
+            if *marking_row > row {
+                *marking_row -= 1;
+            }
+        }
+        Some(grid).filter(|grid| grid.tb() == self.tb())
+    }
+
+    //Finds a destabilization keeping the Legendrian type, moving the grid around the torus so the step doesn't wrap around its edges
+    fn destabilization(&self) -> Option<Grid> {
+        let n = self.size();
+        if n <= 2 {
+            return None;
+        }
+        let (row_x, row_o) = self.row_markings();
+        let neighbours = |a: usize, b: usize| (a + n - b) % n == 1 || (b + n - a) % n == 1;
+        self.markings().into_iter().find_map(|(col, row, is_x)| {
+            let (p_col, r_row) = if is_x { (row_o[row], self.o[col]) } else { (row_x[row], self.x[col]) };
+            if !neighbours(p_col, col) || !neighbours(r_row, row) {
+                return None;
+            }
+            self.translated((n + 1 - col) % n, (n + 1 - row) % n).destabilized_at(1, 1, is_x)
+        })
+    }
+
+    //Destabilizes the grid as far as it goes, commuting columns or rows where that makes room for another destabilization
+    fn destabilized(self) -> Grid {
+        let mut grid = self;
+        while let Some(smaller) = grid.destabilization().or_else(|| {
+            let commuted = grid.commutations();
+            commuted.iter().find_map(Grid::destabilization).or_else(|| commuted.iter().flat_map(Grid::commutations).find_map(|grid| grid.destabilization()))
+        }) {
+            grid = smaller;
+        }
+        grid
+    }
+
+    //The grid of the knot with its orientation reversed, which swaps the X's and O's
+    pub fn reversed(&self) -> Grid {
+        Grid { x: self.o.clone(), o: self.x.clone() }
+    }
+
+    /* The Maslov grading of a generator relative to a set of markings m is J(x - m, x - m) + 1, with J(P, Q) the average of I(P, Q) and I(Q, P)
+    and I(P, Q) = #{p in P, q in Q: p < q in both coordinates}. Generator points are at the lattice points (i, x[i]) and markings at the centers of squares,
+    so it's I(m, m) + 1 + I(x, x) minus the sum over i of weight[i][x[i]], the number of markings pairing with the point (i, x[i]). */
+    fn weights(markings: &[usize]) -> (i64, Vec<Vec<i64>>) {
+        let n = markings.len();
+        let pairs = (0..n).flat_map(|i| (i + 1..n).map(move |k| (i, k))).filter(|&(i, k)| markings[i] < markings[k]).count() as i64;
+        let weight = (0..n).map(|i| (0..n).map(|row| (0..n).filter(|&k| if k >= i { row <= markings[k] } else { markings[k] < row }).count() as i64).collect()).collect();
+        (pairs + 1, weight)
+    }
+
+    /* Calls visit with every generator whose (twice) Alexander grading is one of alexanders, along with its (Maslov grading, twice its Alexander grading).
+    The Alexander grading is the Maslov grading relative to the O's less that relative to the X's, less n - 1, over 2, which is a sum over the columns,
+    so searches are cut short once the columns left can't bring it to a wanted value. */
+    fn visit_generators(&self, alexanders: &[i64], visit: &mut dyn FnMut(Generator, (i64, i64))) {
+        struct Search<'a> {
+            n: usize,
+            o: (i64, Vec<Vec<i64>>),
+            x: (i64, Vec<Vec<i64>>),
+            //The least and greatest the columns from i on can add to the Alexander grading
+            reach: Vec<(i64, i64)>,
+            alexanders: &'a [i64],
+            visit: &'a mut dyn FnMut(Generator, (i64, i64)),
+        }
+        //Places a point in column i, given the rows already used, the pairs of points so far and the sums of weights relative to the O's and X's
+        fn extend(search: &mut Search, i: usize, generator: Generator, used: u32, pairs: i64, o_weight: i64, x_weight: i64) {
+            let alexander = search.o.0 - search.x.0 - o_weight + x_weight - (search.n as i64 - 1);
+            let (low, high) = search.reach[i];
+            if !search.alexanders.iter().any(|&wanted| alexander + low <= wanted && wanted <= alexander + high) {
+                return;
+            }
+            if i == search.n {
+                (search.visit)(generator, (search.o.0 + pairs - o_weight, alexander));
+                return;
+            }
+            for row in (0..search.n).filter(|&row| used & (1 << row) == 0) {
+                let below = (used & ((1 << row) - 1)).count_ones() as i64;
+                let (o_weight, x_weight) = (o_weight + search.o.1[i][row], x_weight + search.x.1[i][row]);
+                extend(search, i + 1, generator | (row as Generator) << (4 * i), used | (1 << row), pairs + below, o_weight, x_weight);
+            }
+        }
+        let n = self.size();
+        let (o, x) = (Grid::weights(&self.o), Grid::weights(&self.x));
+        let mut reach = vec![(0, 0); n + 1];
+        for i in (0..n).rev() {
+            let added: Vec<i64> = (0..n).map(|row| x.1[i][row] - o.1[i][row]).collect();
+            reach[i] = (reach[i + 1].0 + added.iter().min().unwrap(), reach[i + 1].1 + added.iter().max().unwrap());
+        }
+        let mut search = Search { n, o, x, reach, alexanders, visit };
+        extend(&mut search, 0, 0, 0, 0, 0, 0);
+    }
+
+    //(Maslov grading, twice the Alexander grading) of a single generator
+    fn grading(&self, generator: &[usize]) -> (i64, i64) {
+        let ((o_base, o_weight), (x_base, x_weight)) = (Grid::weights(&self.o), Grid::weights(&self.x));
+        let n = self.size();
+        let pairs = (0..n).flat_map(|i| (i + 1..n).map(move |k| (i, k))).filter(|&(i, k)| generator[i] < generator[k]).count() as i64;
+        let maslov_o = o_base + pairs - (0..n).map(|i| o_weight[i][generator[i]]).sum::<i64>();
+        let maslov_x = x_base + pairs - (0..n).map(|i| x_weight[i][generator[i]]).sum::<i64>();
+        (maslov_o, maslov_o - maslov_x - (n as i64 - 1))
+    }
+
+    /* The generators reached from a generator by empty rectangles on the torus containing no markings, each counted mod 2.
+    Each rectangle is found from its lower left corner (a, generator[a]) by widening it a column at a time: its height (relative to its bottom) can be at most
+    the lowest of the points and markings it has passed over, so the upper right corner (c, generator[c]) is reachable while it's no higher than that. */
+    fn boundary(&self, generator: Generator) -> Vec<Generator> {
+        let n = self.size();
+        let rows: Vec<usize> = (0..n).map(|i| (generator >> (4 * i) & 15) as usize).collect();
+                let mut targets: Vec<Generator> = Vec::new();
+        for a in 0..n {
+            let height = |row: usize| (row + n - rows[a]) % n;
+            let mut limit = height(self.x[a]).min(height(self.o[a]));
+            for c in (1..n).map(|step| (a + step) % n) {
+                if limit == 0 {
+                    break;
+                }
+                if height(rows[c]) <= limit {
+                    let swapped = (rows[a] ^ rows[c]) as Generator;
+                    let target = generator ^ swapped << (4 * a) ^ swapped << (4 * c);
+                    match targets.iter().position(|&existing| existing == target) {
+                        Some(index) => {
+                            targets.swap_remove(index);
+                        }
+                        None => targets.push(target),
+                    }
+                }
+                limit = limit.min(height(rows[c])).min(height(self.x[c])).min(height(self.o[c]));
+            }
+        }
+        targets
+    }
+
+    /* Whether λ+ and λ- are nonzero (see above), for knot grids up to MAX_GRID_SIZE.
+    x+ and x- are cycles, so each vanishes when it's the boundary of a sum of generators one Maslov grading higher in the same Alexander grading. */
+    pub fn lambdas(&self) -> Option<(bool, bool)> {
+        let n = self.size();
+        if n > MAX_GRID_SIZE {
+            return None;
+        }
+        let x_plus: Vec<usize> = (0..n).map(|i| (self.x[(i + n - 1) % n] + 1) % n).collect();
+        let x_minus = self.x.clone();
+        let bigradings = [self.grading(&x_plus), self.grading(&x_minus)];
+        //Generators in the gradings of x± and one Maslov grading above them
+        let mut graded: HashMap<(i64, i64), Vec<Generator>> = HashMap::new();
+        let wanted = |grading: (i64, i64)| bigradings.iter().any(|&(maslov, alexander)| grading == (maslov, alexander) || grading == (maslov + 1, alexander));
+        self.visit_generators(&[bigradings[0].1, bigradings[1].1], &mut |generator, grading| {
+            if wanted(grading) {
+                graded.entry(grading).or_default().push(generator);
+            }
+        });
+        //Row reduces the boundaries into a grading over F2, as sorted lists of generator indices keyed by their first index
+        let reduce = |pivots: &HashMap<usize, Vec<usize>>, mut vector: Vec<usize>| {
+            while let Some(row) = vector.first().and_then(|first| pivots.get(first)) {
+                vector = symmetric_difference(&vector, row);
+            }
+            vector
+        };
+        let boundaries = |(maslov, alexander): (i64, i64)| {
+            let index: HashMap<Generator, usize> = graded.get(&(maslov, alexander)).into_iter().flatten().enumerate().map(|(index, &generator)| (generator, index)).collect();
+            let mut pivots: HashMap<usize, Vec<usize>> = HashMap::new();
+            for &source in graded.get(&(maslov + 1, alexander)).into_iter().flatten() {
+                let mut vector: Vec<usize> = self.boundary(source).iter().map(|target| index[target]).collect();
+                vector.sort_unstable();
+                let vector = reduce(&pivots, vector);
+                if let Some(&first) = vector.first() {
+                    pivots.insert(first, vector);
+                }
+            }
+            (index, pivots)
+        };
+        let plus = boundaries(bigradings[0]);
+        //x+ and x- share a grading when rot = 0
+        let minus = if bigradings[1] == bigradings[0] { None } else { Some(boundaries(bigradings[1])) };
+        let nonzero = |cycle: &[usize], (index, pivots): &(HashMap<Generator, usize>, HashMap<usize, Vec<usize>>)| !reduce(pivots, vec![index[&pack(cycle)]]).is_empty();
+        Some((nonzero(&x_plus, &plus), nonzero(&x_minus, minus.as_ref().unwrap_or(&plus))))
+    }
+}
+
+//A generator as the rows of its points in each column, packed four bits to a column (grids are at most 16 x 16 here)
+type Generator = u64;
+
+fn pack(rows: &[usize]) -> Generator {
+    rows.iter().enumerate().map(|(i, &row)| (row as Generator) << (4 * i)).sum()
+}
+
+//The symmetric difference of two sorted lists, i.e. their sum over F2
+fn symmetric_difference(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let (mut i, mut j, mut sum) = (0, 0, Vec::with_capacity(a.len() + b.len()));
+    while i < a.len() || j < b.len() {
+        if j == b.len() || (i < a.len() && a[i] < b[j]) {
+            sum.push(a[i]);
+            i += 1;
+        } else if i == a.len() || b[j] < a[i] {
+            sum.push(b[j]);
+            j += 1;
+        } else {
+            i += 1;
+            j += 1;
+        }
+    }
+    sum
+}
+
+/* Whether λ+ and λ- of a knot diagram are nonzero (see above), oriented so its rotation number is at least 0 (as in the census),
+or None for links, virtual knots and grids larger than MAX_GRID_SIZE. */
+pub fn grid_invariants(diagram: &Diagram) -> Option<(bool, bool)> {
+    if diagram.component_count() != 1 {
+        return None;
+    }
+    let grid = Grid::new(diagram)?;
+    let grid = if diagram.rot() < 0 { grid.reversed() } else { grid };
+    grid.lambdas()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atlas::unknot_stabilizations;
+    use crate::generator::generate_with;
+    use crate::knots::knot_name;
+    use crate::invariants::jones;
+    use std::ops::ControlFlow;
+
+    //The diagrams of every nonempty 4x4 mosaic and of every `stride`th 5x5 one
+    fn diagrams(stride: usize) -> Vec<Diagram> {
+        let mut diagrams = Vec::new();
+        for (size, stride) in [(4, 1), (5, stride)] {
+            let mut index = 0;
+            let _ = generate_with::<()>(size, |mosaic| {
+                index += 1;
+                if index % stride == 0 {
+                    diagrams.extend(Diagram::trace(mosaic).filter(|diagram| diagram.component_count() > 0));
+                }
+                ControlFlow::Continue(())
+            });
+        }
+        diagrams
+    }
+
+    #[test]
+    fn grids_are_grids_of_the_front() {
+        for diagram in diagrams(97) {
+            let Some(grid) = Grid::new(&diagram) else {
+                assert!(diagram.mosaic.tiles().contains(&VIRTUAL_CROSSING));
+                continue;
+            };
+            let is_permutation = |rows: &[usize]| {
+                let mut sorted = rows.to_vec();
+                sorted.sort_unstable();
+                sorted == (0..rows.len()).collect::<Vec<usize>>()
+            };
+            assert!(is_permutation(&grid.x) && is_permutation(&grid.o), "{}", diagram.mosaic);
+            assert!((0..grid.size()).all(|col| grid.x[col] != grid.o[col]), "{}", diagram.mosaic);
+            //Destabilizing and reversing orientations keep tb
+            assert_eq!(grid.tb(), diagram.tb(), "{}", diagram.mosaic);
+            assert_eq!(grid.reversed().tb(), diagram.tb(), "{}", diagram.mosaic);
+        }
+    }
+
+    #[test]
+    fn lambdas_vanish_for_stabilized_unknots() {
+        let mut checked = 0;
+        for diagram in diagrams(499).iter().filter(|diagram| diagram.component_count() == 1) {
+            let Some(lambdas) = grid_invariants(diagram) else {
+                continue;
+            };
+            if diagram.crossings.is_empty() || jones(diagram).and_then(|jones| knot_name(&jones)).as_deref() == Some("0_1") {
+                //λ+ vanishes for positive stabilizations and λ- for negative ones (and the unknot is determined by them)
+                let (positive, negative) = unknot_stabilizations(diagram.tb(), diagram.rot()).unwrap();
+                assert_eq!(lambdas, (positive == 0, negative == 0), "{}", diagram.mosaic);
+                checked += 1;
+            }
+        }
+        assert!(checked > 100);
+    }
+
+    #[test]
+    fn lambdas_of_trefoils() {
+        //The max-tb right-handed trefoil of the 5x5 census, where tb + |rot| = 2τ - 1 so both are nonzero, and a positive stabilization of it, which keeps only λ-
+        for (code, lambdas) in [("0021025971629943943103554", (true, true)), ("0021002891299463791603434", (false, true))] {
+            let diagram = Diagram::trace(&code.parse().unwrap()).unwrap();
+            assert_eq!(grid_invariants(&diagram), Some(lambdas), "{}", code);
+            assert_eq!(grid_invariants(&Diagram::trace(&diagram.mosaic.flip_x()).unwrap()), Some(lambdas), "{}", code);
+        }
+    }
+}
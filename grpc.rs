@@ -0,0 +1,96 @@
+/*
+gRPC generation service (enabled by the `grpc` feature), so mosaics can be generated on one machine and consumed by invariant computations on others.
+The interface is defined in mosaics.proto: Generate streams the mosaics of a given size (optionally filtered by their traced diagrams) in generation order.
+
+Each stream is generated on its own blocking thread, which sends into a bounded channel -- so a slow client (through HTTP/2 flow control) pauses its generator
+ rather than letting mosaics pile up in memory, and a client that disconnects stops its generator.
+*/
+
+use crate::diagram::Diagram;
+use crate::generator::MosaicGenerator;
+use proto::mosaic_generator_server::{MosaicGenerator as GeneratorService, MosaicGeneratorServer};
+use proto::{GenerateRequest, GeneratedMosaic, Invariants};
+use std::io::{Error, ErrorKind, Result};
+use std::net::{SocketAddr, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{transport::Server, Request, Response, Status};
+
+mod proto {
+    tonic::include_proto!("legendrian_mosaics");
+}
+
+//Mosaics buffered per stream before generation waits for the client
+const STREAM_BUFFER: usize = 256;
+
+struct GenerationService;
+
+impl GenerateRequest {
+    fn has_filters(&self) -> bool {
+        self.components.is_some() || self.min_crossings.is_some() || self.max_crossings.is_some()
+    }
+
+    fn accepts(&self, diagram: &Diagram) -> bool {
+        let (components, crossings) = (diagram.component_count() as u32, diagram.crossings.len() as u32);
+        self.components.is_none_or(|wanted| components == wanted)
+            && self.min_crossings.is_none_or(|min| crossings >= min)
+            && self.max_crossings.is_none_or(|max| crossings <= max)
+    }
+}
+
+//Streams the requested mosaics into `sender` until generation finishes, the limit is reached, or the client goes away
+fn stream_mosaics(request: GenerateRequest, sender: mpsc::Sender<std::result::Result<GeneratedMosaic, Status>>) {
+    let mut sent = 0;
+    for (rank, mosaic) in MosaicGenerator::new(request.size as usize).enumerate() {
+        let mut invariants = None;
+        if request.has_filters() || request.with_invariants {
+            //Generated mosaics are always suitably connected
+            let diagram = Diagram::trace(&mosaic).unwrap();
+            if !request.accepts(&diagram) {
+                continue;
+            }
+            invariants = request.with_invariants.then(|| Invariants {
+                components: diagram.component_count() as u32,
+                crossings: diagram.crossings.len() as u32,
+                tb: diagram.tb(),
+                rot: diagram.rot(),
+            });
+        }
+        let message = GeneratedMosaic { code: mosaic.to_string(), rank: rank as u64, invariants, id: mosaic.id().to_string() };
+        if sender.blocking_send(Ok(message)).is_err() {
+            return;
+        }
+        sent += 1;
+        if sent == request.limit {
+            return;
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl GeneratorService for GenerationService {
+    type GenerateStream = ReceiverStream<std::result::Result<GeneratedMosaic, Status>>;
+
+    async fn generate(&self, request: Request<GenerateRequest>) -> std::result::Result<Response<Self::GenerateStream>, Status> {
+        let request = request.into_inner();
+        if request.size == 0 {
+            return Err(Status::invalid_argument("mosaic size must be at least 1"));
+        }
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        tokio::task::spawn_blocking(move || stream_mosaics(request, sender));
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+//Runs the generation service on the given address until the process is killed
+pub fn serve(address: &str) -> Result<()> {
+    let address: SocketAddr = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("could not resolve {}", address)))?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    println!("Serving gRPC generation on {}", address);
+    runtime
+        .block_on(Server::builder().add_service(MosaicGeneratorServer::new(GenerationService)).serve(address))
+        .map_err(Error::other)
+}
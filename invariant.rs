@@ -0,0 +1,241 @@
+/*
+Invariants by name, for adding columns to the census and filtering mosaics on their values.
+An Invariant has a name and computes a Value from a mosaic; a Registry holds the invariants that can be asked for by name,
+ starting with the built-in ones below, and others (e.g. from crates building on this one) can be registered alongside them.
+Census runs add a column for each invariant named with --columns (see census.rs), and --where keeps only the mosaics meeting conditions like
+    tb>=1;name=3_1;alternating=true
+ on their values, comparing numbers as numbers and everything else as text, with - for an undefined value (e.g. the genus bound of a virtual mosaic).
+*/
+
+use crate::dga::augmentation_number;
+use crate::diagram::Diagram;
+use crate::goeritz::{determinant, signature};
+use crate::invariants::jones;
+use crate::knots::knot_name;
+use crate::mosaic::{Mosaic, VIRTUAL_CROSSING};
+use crate::ruling::ruling_polynomial;
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::Arc;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Value {
+    Integer(i64),
+    Boolean(bool),
+    Text(String),
+    //Not defined for the mosaic, like the knot name of a link
+    Undefined,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Text(value) => write!(f, "{}", value),
+            Value::Undefined => write!(f, "-"),
+        }
+    }
+}
+
+impl Value {
+    //How the value compares to a value written in a condition, or None if they can't be compared
+    fn compare(&self, literal: &str) -> Option<Ordering> {
+        match self {
+            Value::Integer(value) => literal.parse::<i64>().ok().map(|literal| value.cmp(&literal)),
+            Value::Boolean(value) => literal.parse::<bool>().ok().map(|literal| value.cmp(&literal)),
+            Value::Text(value) => Some(value.as_str().cmp(literal)),
+            Value::Undefined => (literal == "-").then_some(Ordering::Equal),
+        }
+    }
+}
+
+pub trait Invariant: Send + Sync {
+    //The name of the invariant, as its column is headed and as it's written in conditions
+    fn name(&self) -> &str;
+
+    fn compute(&self, mosaic: &Mosaic) -> Value;
+}
+
+//A built-in invariant, computed by a function of the mosaic
+struct Builtin {
+    name: &'static str,
+    compute: fn(&Mosaic) -> Value,
+}
+
+impl Invariant for Builtin {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn compute(&self, mosaic: &Mosaic) -> Value {
+        (self.compute)(mosaic)
+    }
+}
+
+//The value of an invariant of the mosaic's diagram, which is undefined for mosaics that aren't suitably connected
+fn of_diagram(mosaic: &Mosaic, compute: impl Fn(&Diagram) -> Value) -> Value {
+    Diagram::trace(mosaic).map_or(Value::Undefined, |diagram| compute(&diagram))
+}
+
+fn integer(value: Option<impl Into<i64>>) -> Value {
+    value.map_or(Value::Undefined, |value| Value::Integer(value.into()))
+}
+
+fn text(value: Option<String>) -> Value {
+    value.map_or(Value::Undefined, Value::Text)
+}
+
+//Whether the mosaic has no virtual crossings
+fn classical(mosaic: &Mosaic) -> bool {
+    !mosaic.tiles().contains(&VIRTUAL_CROSSING)
+}
+
+//The built-in invariants, with the same values as their census columns (see census.rs)
+const BUILTINS: &[Builtin] = &[
+    Builtin { name: "size", compute: |mosaic| Value::Integer(mosaic.size() as i64) },
+    Builtin { name: "id", compute: |mosaic| Value::Text(mosaic.id().to_string()) },
+    Builtin { name: "components", compute: |mosaic| of_diagram(mosaic, |diagram| Value::Integer(diagram.component_count() as i64)) },
+    Builtin { name: "crossings", compute: |mosaic| of_diagram(mosaic, |diagram| Value::Integer(diagram.crossings.len() as i64)) },
+    Builtin { name: "seifert_circles", compute: |mosaic| of_diagram(mosaic, |diagram| Value::Integer(diagram.seifert_circles() as i64)) },
+    Builtin {
+        name: "genus_bound",
+        compute: |mosaic| of_diagram(mosaic, |diagram| integer(classical(mosaic).then(|| diagram.genus_bound() as u32))),
+    },
+    Builtin { name: "bridge_number", compute: |mosaic| of_diagram(mosaic, |diagram| Value::Integer(diagram.bridge_number() as i64)) },
+    Builtin { name: "alternating", compute: |mosaic| of_diagram(mosaic, |diagram| Value::Boolean(diagram.is_alternating())) },
+    Builtin { name: "composite", compute: |mosaic| of_diagram(mosaic, |diagram| Value::Boolean(diagram.is_composite())) },
+    Builtin { name: "linking_number", compute: |mosaic| of_diagram(mosaic, |diagram| Value::Integer(diagram.linking_number() as i64)) },
+    Builtin { name: "determinant", compute: |mosaic| of_diagram(mosaic, |diagram| integer(determinant(diagram).and_then(|determinant| i64::try_from(determinant).ok()))) },
+    Builtin { name: "signature", compute: |mosaic| of_diagram(mosaic, |diagram| integer(signature(diagram))) },
+    Builtin { name: "ruling_polynomial", compute: |mosaic| of_diagram(mosaic, |diagram| text(ruling_polynomial(diagram).map(|ruling| ruling.format("z", 1)))) },
+    Builtin { name: "augmentation_number", compute: |mosaic| of_diagram(mosaic, |diagram| text(augmentation_number(diagram))) },
+    Builtin { name: "tb", compute: |mosaic| of_diagram(mosaic, |diagram| Value::Integer(diagram.tb() as i64)) },
+    //Up to orientation for knots, as in the census
+    Builtin {
+        name: "rot",
+        compute: |mosaic| of_diagram(mosaic, |diagram| Value::Integer((if diagram.component_count() == 1 { diagram.rot().abs() } else { diagram.rot() }) as i64)),
+    },
+    Builtin {
+        name: "name",
+        compute: |mosaic| {
+            of_diagram(mosaic, |diagram| text(jones(diagram).filter(|_| diagram.component_count() == 1 && classical(mosaic)).and_then(|jones| knot_name(&jones))))
+        },
+    },
+    Builtin { name: "jones", compute: |mosaic| of_diagram(mosaic, |diagram| text(jones(diagram).map(|jones| jones.format("t", 2)))) },
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+//Operators in conditions, longest first so that <= isn't read as <
+const COMPARISONS: [(&str, Comparison); 6] = [
+    ("<=", Comparison::LessOrEqual),
+    (">=", Comparison::GreaterOrEqual),
+    ("!=", Comparison::NotEqual),
+    ("=", Comparison::Equal),
+    ("<", Comparison::Less),
+    (">", Comparison::Greater),
+];
+
+//A condition on the value of an invariant, like tb>=1
+#[derive(Clone)]
+pub struct Condition {
+    invariant: Arc<dyn Invariant>,
+    comparison: Comparison,
+    literal: String,
+}
+
+//The condition as it's written, like tb>=1
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operator = COMPARISONS.iter().find(|(_, comparison)| *comparison == self.comparison).map_or("?", |(operator, _)| operator);
+        write!(f, "{}{}{}", self.invariant.name(), operator, self.literal)
+    }
+}
+
+impl fmt::Debug for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Condition {
+    //Whether the mosaic meets the condition -- never, if its value can't be compared with the condition's
+    pub fn admits(&self, mosaic: &Mosaic) -> bool {
+        self.invariant.compute(mosaic).compare(&self.literal).is_some_and(|ordering| match self.comparison {
+            Comparison::Equal => ordering == Ordering::Equal,
+            Comparison::NotEqual => ordering != Ordering::Equal,
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Greater => ordering == Ordering::Greater,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less,
+        })
+    }
+}
+
+//The invariants that can be asked for by name
+#[derive(Clone, Default)]
+pub struct Registry {
+    invariants: Vec<Arc<dyn Invariant>>,
+}
+
+impl Registry {
+    //A registry of the built-in invariants
+    pub fn builtin() -> Registry {
+        let mut registry = Registry::default();
+        for builtin in BUILTINS {
+            registry.register(Builtin { name: builtin.name, compute: builtin.compute });
+        }
+        registry
+    }
+
+    //Adds an invariant, replacing any already registered under its name
+    pub fn register(&mut self, invariant: impl Invariant + 'static) {
+        self.invariants.retain(|registered| registered.name() != invariant.name());
+        self.invariants.push(Arc::new(invariant));
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.invariants.iter().map(|invariant| invariant.name()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<Arc<dyn Invariant>> {
+        self.invariants.iter().find(|invariant| invariant.name() == name).cloned().ok_or_else(|| {
+            Error::new(ErrorKind::InvalidInput, format!("unknown invariant {} (the invariants are {})", name, self.names().join(", ")))
+        })
+    }
+
+    //The invariants in a comma separated list of names, e.g. for the columns of a census
+    pub fn columns(&self, names: &str) -> Result<Vec<Arc<dyn Invariant>>> {
+        names.split(',').map(|name| self.get(name.trim())).collect()
+    }
+
+    //The conditions in a semicolon separated list (see above)
+    pub fn conditions(&self, conditions: &str) -> Result<Vec<Condition>> {
+        conditions
+            .split(';')
+            .filter(|condition| !condition.trim().is_empty())
+            .map(|condition| {
+                let (at, operator, comparison) = COMPARISONS
+                    .iter()
+                    .filter_map(|&(operator, comparison)| condition.find(operator).map(|at| (at, operator, comparison)))
+                    .min_by_key(|&(at, operator, _)| (at, usize::MAX - operator.len()))
+                    .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("condition {} should compare an invariant with a value, like tb>=1", condition)))?;
+                Ok(Condition {
+                    invariant: self.get(condition[..at].trim())?,
+                    comparison,
+                    literal: condition[at + operator.len()..].trim().to_string(),
+                })
+            })
+            .collect()
+    }
+}
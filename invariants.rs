@@ -0,0 +1,190 @@
+/*
+Polynomial invariants of traced mosaics.
+
+The Kauffman bracket is computed directly on the mosaic: the two smoothings of a crossing tile (9) are exactly tiles 7 and 8,
+ so each state of the bracket is a crossingless mosaic, and we only need to count its loops.
+Rotating the over strand (faces 1-3) counterclockwise sweeps the regions between faces 1/2 and 3/0, which tile 7 joins, so tile 7 is the A-smoothing.
+*/
+
+use crate::diagram::Diagram;
+use crate::mosaic::{NONE, PARTNER};
+use std::fmt;
+
+//Laurent polynomial: coefficients[i] is the coefficient of x^(low + i)
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polynomial {
+    pub low: i32,
+    pub coefficients: Vec<i64>,
+}
+
+impl Polynomial {
+    pub fn monomial(exponent: i32, coefficient: i64) -> Polynomial {
+        Polynomial { low: exponent, coefficients: vec![coefficient] }.trimmed()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    pub fn high(&self) -> i32 {
+        self.low + self.coefficients.len() as i32 - 1
+    }
+
+    //Removes leading and trailing zero coefficients, so equal polynomials have equal representations
+    fn trimmed(mut self) -> Polynomial {
+        while self.coefficients.last() == Some(&0) {
+            self.coefficients.pop();
+        }
+        let leading_zeros = self.coefficients.iter().take_while(|&&c| c == 0).count();
+        self.coefficients.drain(..leading_zeros);
+        self.low = if self.coefficients.is_empty() { 0 } else { self.low + leading_zeros as i32 };
+        self
+    }
+
+    pub fn add(&self, other: &Polynomial) -> Polynomial {
+        if self.is_zero() {
+            return other.clone();
+        }
+        if other.is_zero() {
+            return self.clone();
+        }
+        let low = self.low.min(other.low);
+        let high = self.high().max(other.high());
+        let mut coefficients = vec![0; (high - low + 1) as usize];
+        for p in [self, other] {
+            for (i, c) in p.coefficients.iter().enumerate() {
+                coefficients[(p.low - low) as usize + i] += c;
+            }
+        }
+        Polynomial { low, coefficients }.trimmed()
+    }
+
+    pub fn mul(&self, other: &Polynomial) -> Polynomial {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial { low: 0, coefficients: Vec::new() };
+        }
+        let mut coefficients = vec![0; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                coefficients[i + j] += a * b;
+            }
+        }
+        Polynomial { low: self.low + other.low, coefficients }.trimmed()
+    }
+
+    //p(x^-1)
+    pub fn mirror(&self) -> Polynomial {
+        let mut coefficients = self.coefficients.clone();
+        coefficients.reverse();
+        Polynomial { low: -self.high(), coefficients }.trimmed()
+    }
+
+    /* Writes the polynomial in terms of `variable`, with every exponent divided by `denominator`
+    e.g. format("t", 2) writes x^-3 + x as "t^(-3/2) + t^(1/2)" */
+    pub fn format(&self, variable: &str, denominator: i32) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let mut result = String::new();
+        for (i, &c) in self.coefficients.iter().enumerate() {
+            if c == 0 {
+                continue;
+            }
+            let exponent = self.low + i as i32;
+            if result.is_empty() {
+                result.push_str(if c < 0 { "-" } else { "" });
+            } else {
+                result.push_str(if c < 0 { " - " } else { " + " });
+            }
+            if c.abs() != 1 || exponent == 0 {
+                result.push_str(&c.abs().to_string());
+            }
+            if exponent != 0 {
+                result.push_str(variable);
+                if exponent % denominator != 0 {
+                    result.push_str(&format!("^({}/{})", exponent, denominator));
+                } else if exponent != denominator {
+                    result.push_str(&format!("^{}", exponent / denominator));
+                }
+            }
+        }
+        result
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format("x", 1))
+    }
+}
+
+/* Kauffman bracket <D> = sum over states of A^(#A smoothings - #B smoothings) (-A^2 - A^-2)^(loops - 1), as a polynomial in A
+Loops are counted with a union-find over the (tile, face) connection points of the mosaic. */
+pub fn kauffman_bracket(diagram: &Diagram) -> Polynomial {
+    let tiles = diagram.mosaic.tiles();
+    let crossing_tiles: Vec<usize> = diagram.crossings.iter().map(|crossing| crossing.tile).collect();
+    let mut base_parent: Vec<usize> = (0..4 * tiles.len()).collect();
+
+    //Joining connection points along strands of non-crossing tiles and across the edges between tiles
+    for visit in diagram.components.iter().flatten() {
+        if tiles[visit.tile] != 9 {
+            union(&mut base_parent, 4 * visit.tile + visit.entry as usize, 4 * visit.tile + visit.exit as usize);
+        }
+        if let Some(next) = diagram.mosaic.neighbor(visit.tile, visit.exit) {
+            union(&mut base_parent, 4 * visit.tile + visit.exit as usize, 4 * next + ((visit.exit + 2) % 4) as usize);
+        }
+    }
+    let used_points: Vec<usize> = (0..4 * tiles.len()).filter(|&point| PARTNER[tiles[point / 4] as usize][point % 4] != NONE).collect();
+
+    let loop_factor = Polynomial { low: -2, coefficients: vec![-1, 0, 0, 0, -1] };
+    let mut bracket = Polynomial { low: 0, coefficients: Vec::new() };
+    for state in 0..1u64 << crossing_tiles.len() {
+        let mut parent = base_parent.clone();
+        let mut a_smoothings = 0;
+        for (k, &tile) in crossing_tiles.iter().enumerate() {
+            let smoothing = if state & (1 << k) == 0 { 7 } else { 8 };
+            if smoothing == 7 {
+                a_smoothings += 1;
+            }
+            for face in [0, 2] {
+                union(&mut parent, 4 * tile + face, 4 * tile + PARTNER[smoothing][face] as usize);
+            }
+        }
+        let loops = used_points.iter().filter(|&&point| find(&mut parent, point) == point).count();
+        let mut term = Polynomial::monomial(2 * a_smoothings - crossing_tiles.len() as i32, 1);
+        for _ in 1..loops {
+            term = term.mul(&loop_factor);
+        }
+        bracket = bracket.add(&term);
+    }
+    bracket
+}
+
+/* Jones polynomial V(t) = (-A^3)^(-writhe) <D> with A = t^(-1/4), as a polynomial in t^(1/2) (exponents are only odd for links with an even number of components)
+Returns None for the empty diagram. */
+pub fn jones(diagram: &Diagram) -> Option<Polynomial> {
+    if diagram.component_count() == 0 {
+        return None;
+    }
+    let writhe = diagram.writhe();
+    let normalization = Polynomial::monomial(-3 * writhe, if writhe % 2 == 0 { 1 } else { -1 });
+    let in_a = kauffman_bracket(diagram).mul(&normalization);
+    //A^e = t^(-e/4) = (t^(1/2))^(-e/2), where e is always even
+    let mut coefficients = in_a.coefficients.iter().step_by(2).copied().collect::<Vec<i64>>();
+    coefficients.reverse();
+    Some(Polynomial { low: -in_a.high() / 2, coefficients })
+}
+
+pub fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+pub fn union(parent: &mut [usize], x: usize, y: usize) {
+    let (x, y) = (find(parent, x), find(parent, y));
+    parent[x] = y;
+}
@@ -0,0 +1,182 @@
+/*
+Names of low-crossing knots, looked up by jones polynomial.
+
+KNOT_TABLE holds the jones polynomials of every prime knot through 9 crossings, as file_cat.py (through sage's knot table) names them.
+Those through 7 crossings and the 8 crossing knots of braid index 3 (8_2, 8_5, 8_7, 8_9, 8_10, 8_16 - 8_21) are computed from the closures of
+ their KnotInfo braid representatives (with positive braid generators as positive crossings, so 3_1 is the right handed trefoil).
+The rest are computed from their Conway notations (8_1 is 62, 8_3 44, 8_4 413, 8_6 332, 8_8 2312, 8_11 3212, 8_12 2222, 8_13 31112, 8_14 22112 and 8_15 21,21,2,
+ and the 9 crossing knots from 9_1 9 through 9_49 -20:-20:-20), each in the chirality whose polynomial has its larger degrees positive, as every
+ alternating knot's from a braid has -- so 8_15 is the positive one. The polyhedral 9 crossing knots (9_29, 9_32 - 9_34, 9_38 - 9_41, 9_47 and 9_49) are
+ each the only knot of its determinant among the diagrams on the basic polyhedra 6*, 8* and 9* whose polynomial isn't another's.
+Entries are (KnotInfo name, lowest exponent of t, coefficients) -- mirror images are matched by reversing the polynomial and named m(K),
+ and amphichiral knots (4_1, 6_3, 8_3, 8_9, 8_12, 8_17, 8_18) have symmetric polynomials and so only one name, as does 9_42, whose polynomial is
+ symmetric though it's chiral.
+
+The jones polynomial doesn't distinguish every pair of knots (e.g. 5_1 and 10_132 share theirs),
+ so names for mosaics large enough to hold 10+ crossing knots are only a best guess.
+*/
+
+use crate::invariants::Polynomial;
+
+const KNOT_TABLE: &[(&str, i32, &[i64])] = &[
+    ("0_1", 0, &[1]),
+    ("3_1", 1, &[1, 0, 1, -1]),
+    ("4_1", -2, &[1, -1, 1, -1, 1]),
+    ("5_1", 2, &[1, 0, 1, -1, 1, -1]),
+    ("5_2", 1, &[1, -1, 2, -1, 1, -1]),
+    ("6_1", -2, &[1, -1, 2, -2, 1, -1, 1]),
+    ("6_2", -1, &[1, -1, 2, -2, 2, -2, 1]),
+    ("6_3", -3, &[-1, 2, -2, 3, -2, 2, -1]),
+    ("7_1", 3, &[1, 0, 1, -1, 1, -1, 1, -1]),
+    ("7_2", 1, &[1, -1, 2, -2, 2, -1, 1, -1]),
+    ("7_3", 2, &[1, -1, 2, -2, 3, -2, 1, -1]),
+    ("7_4", 1, &[1, -2, 3, -2, 3, -2, 1, -1]),
+    ("7_5", 2, &[1, -1, 3, -3, 3, -3, 2, -1]),
+    ("7_6", -1, &[1, -2, 3, -3, 4, -3, 2, -1]),
+    ("7_7", -3, &[-1, 3, -3, 4, -4, 3, -2, 1]),
+    ("8_1", -2, &[1, -1, 2, -2, 2, -2, 1, -1, 1]),
+    ("8_2", 0, &[1, -1, 2, -2, 3, -3, 2, -2, 1]),
+    ("8_3", -4, &[1, -1, 2, -3, 3, -3, 2, -1, 1]),
+    ("8_4", -3, &[1, -1, 2, -3, 3, -3, 3, -2, 1]),
+    ("8_5", 0, &[1, -1, 3, -3, 3, -4, 3, -2, 1]),
+    ("8_6", -1, &[1, -1, 3, -4, 4, -4, 3, -2, 1]),
+    ("8_7", -2, &[-1, 2, -2, 4, -4, 4, -3, 2, -1]),
+    ("8_8", -3, &[-1, 2, -3, 5, -4, 4, -3, 2, -1]),
+    ("8_9", -4, &[1, -2, 3, -4, 5, -4, 3, -2, 1]),
+    ("8_10", -2, &[-1, 2, -3, 5, -4, 5, -4, 2, -1]),
+    ("8_11", -1, &[1, -2, 4, -4, 5, -5, 3, -2, 1]),
+    ("8_12", -4, &[1, -2, 4, -5, 5, -5, 4, -2, 1]),
+    ("8_13", -3, &[-1, 3, -4, 5, -5, 5, -3, 2, -1]),
+    ("8_14", -1, &[1, -2, 4, -5, 6, -5, 4, -3, 1]),
+    ("8_15", 2, &[1, -2, 5, -5, 6, -6, 4, -3, 1]),
+    ("8_16", -2, &[-1, 3, -4, 6, -6, 6, -5, 3, -1]),
+    ("8_17", -4, &[1, -3, 5, -6, 7, -6, 5, -3, 1]),
+    ("8_18", -4, &[1, -4, 6, -7, 9, -7, 6, -4, 1]),
+    ("8_19", 3, &[1, 0, 1, 0, 0, -1]),
+    ("8_20", -5, &[-1, 1, -1, 2, -1, 2, -1]),
+    ("8_21", 1, &[2, -2, 3, -3, 2, -2, 1]),
+    ("9_1", 4, &[1, 0, 1, -1, 1, -1, 1, -1, 1, -1]),
+    ("9_2", 1, &[1, -1, 2, -2, 2, -2, 2, -1, 1, -1]),
+    ("9_3", 3, &[1, -1, 2, -2, 3, -3, 3, -2, 1, -1]),
+    ("9_4", 2, &[1, -1, 2, -3, 4, -3, 3, -2, 1, -1]),
+    ("9_5", 1, &[1, -2, 3, -3, 4, -3, 3, -2, 1, -1]),
+    ("9_6", 3, &[1, -1, 3, -3, 4, -5, 4, -3, 2, -1]),
+    ("9_7", 2, &[1, -1, 3, -4, 5, -5, 4, -3, 2, -1]),
+    ("9_8", -3, &[1, -2, 3, -4, 5, -5, 5, -3, 2, -1]),
+    ("9_9", 3, &[1, -1, 3, -4, 5, -5, 5, -4, 2, -1]),
+    ("9_10", 2, &[1, -2, 4, -5, 6, -5, 5, -3, 1, -1]),
+    ("9_11", 0, &[1, -2, 3, -4, 6, -5, 5, -4, 2, -1]),
+    ("9_12", -1, &[1, -2, 4, -5, 6, -6, 5, -3, 2, -1]),
+    ("9_13", 2, &[1, -2, 4, -5, 7, -6, 5, -4, 2, -1]),
+    ("9_14", -3, &[-1, 3, -4, 6, -6, 6, -5, 3, -2, 1]),
+    ("9_15", -1, &[1, -2, 4, -6, 7, -6, 6, -4, 2, -1]),
+    ("9_16", 3, &[1, -1, 4, -5, 6, -7, 6, -5, 3, -1]),
+    ("9_17", -3, &[1, -2, 4, -5, 6, -7, 6, -4, 3, -1]),
+    ("9_18", 2, &[1, -2, 5, -6, 7, -7, 6, -4, 2, -1]),
+    ("9_19", -4, &[1, -2, 4, -6, 7, -7, 6, -4, 3, -1]),
+    ("9_20", 0, &[1, -2, 4, -5, 7, -7, 6, -5, 3, -1]),
+    ("9_21", -1, &[1, -3, 5, -6, 8, -7, 6, -4, 2, -1]),
+    ("9_22", -3, &[1, -2, 4, -6, 7, -7, 7, -5, 3, -1]),
+    ("9_23", 2, &[1, -2, 5, -6, 8, -8, 6, -5, 3, -1]),
+    ("9_24", -4, &[1, -3, 5, -7, 8, -7, 7, -4, 2, -1]),
+    ("9_25", -1, &[1, -2, 5, -7, 8, -8, 7, -5, 3, -1]),
+    ("9_26", -2, &[-1, 3, -4, 7, -8, 8, -7, 5, -3, 1]),
+    ("9_27", -4, &[1, -3, 5, -7, 9, -8, 7, -5, 3, -1]),
+    ("9_28", -2, &[-1, 3, -5, 8, -8, 9, -8, 5, -3, 1]),
+    ("9_29", -3, &[1, -3, 5, -7, 9, -8, 8, -6, 3, -1]),
+    ("9_30", -4, &[1, -3, 6, -8, 9, -9, 8, -5, 3, -1]),
+    ("9_31", -2, &[-1, 3, -5, 8, -9, 10, -8, 6, -4, 1]),
+    ("9_32", -2, &[-1, 4, -6, 9, -10, 10, -9, 6, -3, 1]),
+    ("9_33", -4, &[1, -4, 7, -9, 11, -10, 9, -6, 3, -1]),
+    ("9_34", -4, &[1, -4, 8, -10, 12, -12, 10, -7, 4, -1]),
+    ("9_35", 1, &[1, -2, 3, -4, 5, -3, 4, -3, 1, -1]),
+    ("9_36", 0, &[1, -2, 4, -5, 6, -6, 6, -4, 2, -1]),
+    ("9_37", -4, &[1, -2, 5, -7, 7, -8, 7, -4, 3, -1]),
+    ("9_38", 2, &[1, -3, 7, -8, 10, -10, 8, -6, 3, -1]),
+    ("9_39", -1, &[1, -3, 6, -8, 10, -9, 8, -6, 3, -1]),
+    ("9_40", -2, &[-1, 5, -8, 11, -13, 13, -11, 8, -4, 1]),
+    ("9_41", -3, &[-1, 3, -5, 8, -8, 8, -7, 5, -3, 1]),
+    ("9_42", -3, &[1, -1, 1, -1, 1, -1, 1]),
+    ("9_43", 0, &[1, -1, 2, -2, 2, -2, 2, -1]),
+    ("9_44", -2, &[1, -2, 3, -3, 3, -2, 2, -1]),
+    ("9_45", 1, &[2, -3, 4, -4, 4, -3, 2, -1]),
+    ("9_46", 0, &[2, -1, 1, -2, 1, -1, 1]),
+    ("9_47", -2, &[-1, 3, -3, 5, -5, 4, -4, 2]),
+    ("9_48", -1, &[1, -3, 4, -4, 6, -4, 3, -2]),
+    ("9_49", 2, &[1, -2, 4, -4, 5, -4, 3, -2]),
+];
+
+//The smooth 4-genus of each knot in KNOT_TABLE through 8 crossings (from KnotInfo), the same for its mirror image, with 6_1, 8_8, 8_9 and 8_20 slice
+const SLICE_GENUS: &[(&str, usize)] = &[
+    ("0_1", 0),
+    ("3_1", 1),
+    ("4_1", 1),
+    ("5_1", 2),
+    ("5_2", 1),
+    ("6_1", 0),
+    ("6_2", 1),
+    ("6_3", 1),
+    ("7_1", 3),
+    ("7_2", 1),
+    ("7_3", 2),
+    ("7_4", 1),
+    ("7_5", 2),
+    ("7_6", 1),
+    ("7_7", 1),
+    ("8_1", 1),
+    ("8_2", 2),
+    ("8_3", 1),
+    ("8_4", 1),
+    ("8_5", 2),
+    ("8_6", 1),
+    ("8_7", 1),
+    ("8_8", 0),
+    ("8_9", 0),
+    ("8_10", 1),
+    ("8_11", 1),
+    ("8_12", 1),
+    ("8_13", 1),
+    ("8_14", 1),
+    ("8_15", 2),
+    ("8_16", 1),
+    ("8_17", 1),
+    ("8_18", 1),
+    ("8_19", 3),
+    ("8_20", 0),
+    ("8_21", 1),
+];
+
+//Orders knot names by crossing number then index, each knot before its mirror, and names not like 3_1 or m(3_1) last
+pub fn table_order(name: &str) -> (usize, usize, bool, String) {
+    let mirrored = name.starts_with("m(");
+    let knot = name.trim_start_matches("m(").trim_end_matches(')');
+    let number = |part: Option<&str>| part.and_then(|part| part.parse().ok()).unwrap_or(usize::MAX);
+    let mut parts = knot.splitn(2, '_');
+    (number(parts.next()), number(parts.next()), mirrored, name.to_string())
+}
+
+//The slice genus of a knot named by knot_name
+pub fn slice_genus(name: &str) -> Option<usize> {
+    let name = name.strip_prefix("m(").and_then(|name| name.strip_suffix(')')).unwrap_or(name);
+    SLICE_GENUS.iter().find(|&&(knot, _)| knot == name).map(|&(_, genus)| genus)
+}
+
+/* KnotInfo name of the knot with the given jones polynomial (in t^(1/2), as returned by invariants::jones), if it's in the table
+Composite knots and links are never named. */
+pub fn knot_name(jones: &Polynomial) -> Option<String> {
+    //Knots have integral powers of t
+    if jones.low % 2 != 0 || jones.coefficients.iter().skip(1).step_by(2).any(|&c| c != 0) {
+        return None;
+    }
+    let in_t = Polynomial { low: jones.low / 2, coefficients: jones.coefficients.iter().step_by(2).copied().collect() };
+    let mirror = in_t.mirror();
+    KNOT_TABLE.iter().find_map(|&(name, low, coefficients)| {
+        if in_t.low == low && in_t.coefficients == coefficients {
+            Some(name.to_string())
+        } else if mirror.low == low && mirror.coefficients == coefficients {
+            Some(format!("m({})", name))
+        } else {
+            None
+        }
+    })
+}
@@ -0,0 +1,106 @@
+#ifndef LEGENDRIAN_MOSAICS_H
+#define LEGENDRIAN_MOSAICS_H
+
+/* Generated from ffi.rs with cbindgen -- do not edit by hand */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef enum LmStatus {
+  LM_STATUS_OK = 0,
+  LM_STATUS_NULL_POINTER = -1,
+  LM_STATUS_NOT_SUITABLY_CONNECTED = -2,
+  LM_STATUS_INVALID_SIZE = -3,
+} LmStatus;
+
+typedef struct LmMosaic LmMosaic;
+
+typedef int32_t (*LmMosaicCallback)(const uint8_t *tiles, size_t tile_count, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Parses a null terminated mosaic code, returning NULL if it isn't a valid mosaic code.
+
+ # Safety
+ `code` must be NULL or point to a null terminated string.
+ */
+struct LmMosaic *lm_mosaic_parse(const char *code);
+
+/*
+ Releases a mosaic returned by lm_mosaic_parse.
+
+ # Safety
+ `mosaic` must be NULL or a pointer returned by lm_mosaic_parse that hasn't already been freed.
+ */
+void lm_mosaic_free(struct LmMosaic *mosaic);
+
+/*
+ Size n of an n x n mosaic, or 0 for NULL.
+
+ # Safety
+ `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse.
+ */
+size_t lm_mosaic_size(const struct LmMosaic *mosaic);
+
+/*
+ Whether the mosaic is suitably connected (false for NULL).
+
+ # Safety
+ `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse.
+ */
+bool lm_mosaic_is_suitably_connected(const struct LmMosaic *mosaic);
+
+/*
+ Writes the Thurston-Bennequin number of the mosaic to `tb`.
+
+ # Safety
+ `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse, and `tb` must be NULL or valid for writes.
+ */
+enum LmStatus lm_mosaic_tb(const struct LmMosaic *mosaic,
+                           int32_t *tb);
+
+/*
+ Writes the rotation number of the mosaic (with each component oriented in the direction it's traversed) to `rot`.
+
+ # Safety
+ `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse, and `rot` must be NULL or valid for writes.
+ */
+enum LmStatus lm_mosaic_rot(const struct LmMosaic *mosaic,
+                            int32_t *rot);
+
+/*
+ Writes the number of link components of the mosaic to `components`.
+
+ # Safety
+ `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse, and `components` must be NULL or valid for writes.
+ */
+enum LmStatus lm_mosaic_components(const struct LmMosaic *mosaic,
+                                   size_t *components);
+
+/*
+ Writes the stable ID of the mosaic (see Mosaic::id) to `id`, as 16 bytes, most significant first.
+
+ # Safety
+ `mosaic` must be NULL or a valid pointer returned by lm_mosaic_parse, and `id` must be NULL or valid for writes of 16 bytes.
+ */
+enum LmStatus lm_mosaic_id(const struct LmMosaic *mosaic,
+                           uint8_t *id);
+
+/*
+ Calls `callback` with every suitably connected size x size mosaic, in the same order as mosaic_gen,
+ writing the number of mosaics passed to the callback to `count` (if it isn't NULL).
+
+ # Safety
+ `count` must be NULL or valid for writes, and `callback` must be safe to call with `user_data`.
+ */
+enum LmStatus lm_generate(size_t size, LmMosaicCallback callback, void *user_data, uint64_t *count);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LEGENDRIAN_MOSAICS_H */
@@ -0,0 +1,145 @@
+/*
+Conversions between classical knot mosaics and legendrian mosaics: legendrianization, and smoothing back to classical mosaics.
+Classical mosaics use the 11 tiles of Lomonaco and Kauffman: our tiles 0 to 8, the crossing 9 (whose over strand runs between faces 1 and 3), and the opposite crossing 10
+ (over strand between faces 0 and 2), written a in classical mosaic codes as in base 11.
+Read as a front (rotated 45°), every classical tile but the opposite crossing is already a legendrian tile -- corners become cusps or smooth turns --
+ but a front crossing always has its 1-3 strand over, so each opposite crossing has to be replaced. Following the standard construction, the rows and columns
+ through opposite crossings are stretched (adding a row or column of straight strands on either side), and each opposite crossing, now alone in the middle of
+    . 6 .           2 8 1
+    5 a 5   with    7 9 7
+    . 6 .           3 8 4
+ is replaced by the block on the right, which reroutes its strands so the horizontal one is over at a crossing tile. The mosaic is padded with blank rows or columns to stay square.
+Smoothing goes the other way, forgetting the legendrian structure (cusps are just corners of a classical diagram) and writing each tile as its classical tile.
+Classical mosaic tools don't all agree on which crossing is tile 9, so our crossing can be written as either classical crossing.
+*/
+
+use crate::mosaic::{read_mosaics, Mosaic, NONE, PARTNER, VIRTUAL_CROSSING};
+use crate::output;
+use std::io::{Error, ErrorKind, Result, Write};
+
+const CROSSING: u8 = 9;
+const OPPOSITE_CROSSING: u8 = 10;
+
+//The legendrian block replacing an opposite crossing
+const REROUTED_CROSSING: [[u8; 3]; 3] = [[2, 8, 1], [7, 9, 7], [3, 8, 4]];
+
+//Connections within a classical tile, as in PARTNER
+fn classical_partner(tile: u8) -> [u8; 4] {
+    if tile == OPPOSITE_CROSSING { PARTNER[CROSSING as usize] } else { PARTNER[tile as usize] }
+}
+
+//Converts a classical mosaic code into a legendrian mosaic, returning it along with the size of the classical mosaic
+pub fn legendrianize(code: &str) -> Result<(Mosaic, usize)> {
+    let code = code.trim();
+    let invalid = |reason: &str| Error::new(ErrorKind::InvalidInput, format!("\"{}\" isn't a classical mosaic code ({})", code, reason));
+    let tiles = code
+        .chars()
+        .map(|tile| match tile {
+            'a' | 'A' => Some(OPPOSITE_CROSSING),
+            _ => tile.to_digit(10).map(|tile| tile as u8),
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| invalid("tiles are 0 to 9 and a"))?;
+    let size = (tiles.len() as f64).sqrt().round() as usize;
+    if size == 0 || size * size != tiles.len() {
+        return Err(invalid("not a square number of tiles"));
+    }
+    let rows: Vec<Vec<u8>> = tiles.chunks(size).map(|row| row.to_vec()).collect();
+    let suitably_connected = (0..size).all(|row| {
+        (0..size).all(|col| {
+            let partners = classical_partner(rows[row][col]);
+            let right = col + 1 < size && classical_partner(rows[row][col + 1])[2] != NONE;
+            let below = row + 1 < size && classical_partner(rows[row + 1][col])[1] != NONE;
+            (partners[0] != NONE) == right
+                && (partners[3] != NONE) == below
+                && (col > 0 || partners[2] == NONE)
+                && (row > 0 || partners[1] == NONE)
+        })
+    });
+    if !suitably_connected {
+        return Err(invalid("not suitably connected"));
+    }
+
+    //Stretches the columns, then the rows, through opposite crossings
+    let stretched_cols: Vec<bool> = (0..size).map(|col| (0..size).any(|row| rows[row][col] == OPPOSITE_CROSSING)).collect();
+    let stretched_rows: Vec<bool> = (0..size).map(|row| rows[row].contains(&OPPOSITE_CROSSING)).collect();
+    let mut wide: Vec<Vec<u8>> = Vec::new();
+    for row in &rows {
+        let mut wide_row = Vec::new();
+        for (col, &tile) in row.iter().enumerate() {
+            if stretched_cols[col] {
+                let strand_left = col > 0 && classical_partner(row[col - 1])[0] != NONE;
+                wide_row.push(if strand_left { 5 } else { 0 });
+            }
+            wide_row.push(tile);
+            if stretched_cols[col] {
+                wide_row.push(if classical_partner(tile)[0] != NONE { 5 } else { 0 });
+            }
+        }
+        wide.push(wide_row);
+    }
+    let mut stretched: Vec<Vec<u8>> = Vec::new();
+    for (row, wide_row) in wide.iter().enumerate() {
+        if stretched_rows[row] {
+            let strands_above: Vec<u8> = match stretched.last() {
+                Some(above) => above.iter().map(|&tile| if classical_partner(tile)[3] != NONE { 6 } else { 0 }).collect(),
+                None => vec![0; wide_row.len()],
+            };
+            stretched.push(strands_above);
+        }
+        stretched.push(wide_row.clone());
+        if stretched_rows[row] {
+            stretched.push(wide_row.iter().map(|&tile| if classical_partner(tile)[3] != NONE { 6 } else { 0 }).collect());
+        }
+    }
+
+    //Reroutes each opposite crossing, then pads the mosaic out to a square
+    let (height, width) = (stretched.len(), stretched[0].len());
+    for row in 0..height {
+        for col in 0..width {
+            if stretched[row][col] == OPPOSITE_CROSSING {
+                for (block_row, block) in REROUTED_CROSSING.iter().enumerate() {
+                    stretched[row + block_row - 1][col - 1..col + 2].copy_from_slice(block);
+                }
+            }
+        }
+    }
+    let new_size = height.max(width);
+    let mut legendrian = Vec::with_capacity(new_size * new_size);
+    for row in 0..new_size {
+        for col in 0..new_size {
+            legendrian.push(stretched.get(row).and_then(|row| row.get(col)).copied().unwrap_or(0));
+        }
+    }
+    let mosaic = Mosaic::from_tiles(new_size, legendrian)?;
+    debug_assert!(mosaic.is_suitably_connected());
+    Ok((mosaic, size))
+}
+
+/* The classical mosaic code of a legendrian mosaic, with its crossings written as `crossing_tile` (9 or 10, written a): the classical tile number of the crossing
+whose over strand runs between faces 1 and 3 in the classical tool the code is meant for. Returns None for mosaics with virtual crossings, which have no classical tile. */
+pub fn smooth(mosaic: &Mosaic, crossing_tile: u8) -> Option<String> {
+    mosaic
+        .tiles()
+        .iter()
+        .map(|&tile| match tile {
+            VIRTUAL_CROSSING => None,
+            CROSSING if crossing_tile == OPPOSITE_CROSSING => Some('a'),
+            _ => Some((b'0' + tile) as char),
+        })
+        .collect()
+}
+
+//Writes the classical mosaic code of every mosaic in the input file (see smooth), returning the number written
+pub fn smooth_export(input_path: &str, output_path: &str, crossing_tile: u8) -> Result<usize> {
+    let mut output_buffer = output::create(output_path)?;
+    let mut count = 0;
+    for mosaic in read_mosaics(input_path)? {
+        let mosaic = mosaic?;
+        let classical = smooth(&mosaic, crossing_tile).ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("{} has virtual crossings, which classical mosaics don't", mosaic)))?;
+        writeln!(output_buffer, "{}", classical)?;
+        count += 1;
+    }
+    output_buffer.finish()?;
+    Ok(count)
+}
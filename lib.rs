@@ -0,0 +1,72 @@
+/*
+The library behind mosaic_gen, for programs that generate Legendrian mosaics and compute their invariants themselves rather than through the command line.
+The same crate builds as the python module (with the `python` feature and maturin, see python.rs), the C library (with the `ffi` feature, see ffi.rs)
+ and the WASM module (with the `wasm` feature, see wasm.rs), and main.rs is its command line driver.
+*/
+
+pub mod affinity;
+pub mod anneal;
+pub mod atlas;
+pub mod atlas_report;
+pub mod bench;
+pub mod bennequin;
+pub mod cache;
+pub mod census;
+pub mod config;
+pub mod crossingless;
+pub mod dedup;
+pub mod dga;
+pub mod diagram;
+pub mod error;
+pub mod export;
+pub mod extend;
+pub mod external;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod generator;
+pub mod goeritz;
+pub mod graph;
+pub mod grid;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod invariant;
+pub mod invariants;
+pub mod knots;
+pub mod legendrianize;
+pub mod manifest;
+pub mod metrics;
+pub mod minimize;
+pub mod mosaic;
+pub mod moves;
+pub mod oriented;
+pub mod output;
+pub mod pattern;
+pub mod pipe;
+pub mod pipeline;
+pub mod polite;
+pub mod pruning;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rank;
+pub mod reidemeister;
+pub mod render;
+pub mod ruling;
+pub mod sample;
+pub mod stabilization;
+#[cfg(feature = "s3")]
+pub mod s3;
+pub mod satellite;
+pub mod self_check;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod server;
+pub mod sink;
+pub mod sort;
+pub mod store;
+pub mod symmetry;
+pub mod tangle;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod type_table;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -8,141 +8,938 @@ See the associated python file for information on how these mosaics are catalogu
 This material is based upon work supported by the National Science Foundation under Grant No. MPS-2150299
 */
 
+use legendrian_mosaics::{anneal, atlas, atlas_report, bench, bennequin, cache, census, config, crossingless, dedup, dga, diagram, export, extend, external, generator, goeritz, graph, grid, invariant, legendrianize, manifest, metrics, minimize, mosaic, oriented, output, pattern, pipe, pipeline, render, sample, stabilization, satellite, server, sort, store, symmetry, tangle, type_table};
+
+#[cfg(feature = "grpc")]
+use legendrian_mosaics::grpc;
+#[cfg(feature = "interactive")]
+use legendrian_mosaics::moves;
+#[cfg(feature = "tui")]
+use legendrian_mosaics::tui;
+#[cfg(feature = "interactive")]
 use dialoguer::Input; //For driver function
-use std::fs::File;
-use std::io::{BufWriter, Write, Result};
+use std::env;
+use anneal::AnnealOptions;
+use bench::BenchOptions;
+use cache::InvariantCache;
+use census::CensusRecord;
+use dedup::DedupOptions;
+use dga::{format_augmentation_number, Dga, MAX_AUGMENTATION_GENERATORS};
+use diagram::Diagram;
+use export::GroupSyntax;
+use extend::Extended;
+use graph::{Graph, GraphFormat};
+use grid::{grid_invariants, Grid, MAX_GRID_SIZE};
+use generator::{mosaic_gen, Bounds, Constraints, EmitOptions, Generated, GeneratorConfig, Order, Position, ResumeToken, TileSet, ALL_TILES};
+use invariant::Registry;
+use metrics::Metrics;
+use minimize::Objective;
+use mosaic::Mosaic;
+use output::Chunking;
+use pattern::Pattern;
+use pipeline::Stages;
+use render::Format;
+use sample::SamplerOptions;
+use store::ClassStore;
+use symmetry::{class_symmetries, GROUP};
+use tangle::Rect;
+use type_table::{TableFormat, TypeTable};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
 use std::time::Instant;
 
-/* 
-Connection table for mosaic generation
-Essentially a hash map, states of surrounding tiles are converted to a base-3 number,
- and the list of tiles at that index in CONNECTION_TABLE represents all tiles that would be suitably connected given these surroundings
- 0: no connection to tile
- 1: must connect to tile
- 2: undecided, may or may not connect
-
- Digits in the number are assigned as below:
-    1
- 2 ▇▇ 0
-    3
- e.g. 0201 would indicate that the tile:
- must connect to the tile to its right,
- mustn't connect to the tiles above/below it,
- may or may not connect to the tile to its left
+
+/* Basic driver function
+With no arguments (and the `interactive` feature), prompts for a size and output path and generates mosaics.
+Outputs can be files, - for stdout, unix:<path> for a Unix socket, or (with the `s3` feature) s3://bucket/key (see output.rs).
+Output paths can contain the placeholders {size}, {date}, {time} and {ext}, which are filled in when the output is created (see output::expand_path).
+Other tasks are run as subcommands:
+ generate <size> <output> [--metrics <address>] [chunking]
+                            generates mosaics without prompting, optionally serving Prometheus metrics on <address> (see metrics.rs).
+                            The size and output can also be given as --size and --output
+ generate --sizes <sizes> --output <output> [--parallel] [--summary <path>] [--metrics <address>] [chunking]
+                            generates each of several sizes (like 3..=6, or 3,5) to its own output, <output> with NxN before its extensions
+                            (unless it names outputs with {size}),
+                            one after another or all at once with --parallel, then prints a summary of counts and timings (also written to <path>)
+ generate ... --start <mosaic> | --start-rank <rank>, --end <mosaic> | --end-rank <rank>
+                            generates only the mosaics from the start (inclusive) to the end (exclusive) of a range of the generation order
+ generate ... --order lex|revlex|colex|revcolex
+                            generates the mosaics in another order: reading their tiles by rows (lex, the default) or by columns (colex),
+                            and from the smallest tiles up or the largest down (revlex, revcolex), which --start, --end, --prefix and resume tokens follow
+ generate ... --prefix <tiles>
+                            generates only the mosaics starting with the given tiles, e.g. a fixed first row
+ generate ... --pattern <pattern> [--pattern-symmetric]
+                            generates only the mosaics containing a block of tiles like 99/99 (see pattern.rs), in any orientation with --pattern-symmetric
+ generate ... --components <count>
+                            generates only the mosaics of links with exactly <count> components, abandoning partial mosaics that can't have that many
+ generate ... --linking-number <lk>
+                            generates only the mosaics of links with total linking number ±<lk>, e.g. --components 2 --linking-number 0
+ generate ... --reduced     generates only reduced mosaics, leaving out those with a kink that a Reidemeister I move would untwist
+ generate ... --space-efficient
+                            generates only space-efficient mosaics, which fill their board and have no strand that could be pulled tight onto fewer tiles
+ generate ... --connected   generates only mosaics with connected diagrams, leaving out split links
+ generate ... --alternating generates only mosaics with alternating diagrams, whose strands pass alternately over and under crossings
+ generate ... --prime       generates only mosaics whose diagrams aren't visibly connected sums (see Diagram::is_composite)
+ generate ... --where <conditions>
+                            generates only the mosaics whose invariants meet every condition, like tb>=1;name=3_1 (see invariant.rs)
+ generate ... --crossings <count>
+                            generates only the mosaics with exactly <count> crossing tiles
+ generate ... --max-crossings <count> | --max-cusps <count> | --max-components <count>
+                            generates only the mosaics with at most <count> crossing tiles, cusps or link components,
+                            abandoning partial mosaics with more as they're generated (see pruning.rs)
+ generate ... --forbid-tiles <tiles> | --forbid-at <row>:<col>=<tiles>;...
+                            generates only the mosaics without the given tiles (like 9 for crossingless mosaics), anywhere or at the given positions
+ generate ... --tiles <tiles>
+                            generates only the mosaics made of the given tiles (like 0,1,2,3,4,5,6 for mosaics without crossings), the tile set of the run
+ generate ... --threads <count>
+                            generates with <count> threads, splitting the run by the mosaics' first rows (first columns in column-major orders),
+                            which are written in order, so the output is the same as a single thread's -- only for runs of every mosaic of the size
+ generate ... --threads <count> --pin compact|spread
+                            pins each thread to a CPU, filling one NUMA node (socket) before the next or spreading them across the nodes,
+                            each thread allocating its buffers on its own node (see affinity.rs)
+ generate ... --polite
+                            makes way for other programs, for long runs in the background of a workstation: generates at the lowest CPU priority
+                            (and idle I/O priority on Linux), writing in bursts spaced out to take at most a tenth of the time (see polite.rs)
+ generate ... --seed [<row>:<col>=]<mosaic>
+                            generates only the completions of a smaller mosaic placed with its top left tile at <row>:<col> (0:0 by default),
+                            whose strands may run off its edges into the rest, e.g. --seed 1:1=2134 for the mosaics containing a loop there
+ generate ... --start-after <mosaic>
+                            carries on generating just after a previously generated mosaic, e.g. the last line to survive of a lost run's output
+                            (and --end-after <mosaic> ends a range just after a generated mosaic, so it's included)
+ generate ... --max-mosaics <count> | --max-seconds <seconds>
+                            stops each size cleanly after <count> mosaics or <seconds> of generation, printing a resume token (also written to <output>.resume)
+ generate ... --verbose [--log-interval <seconds>]
+                            logs the progress of each size as a JSON object on stderr every <seconds> (10 by default): the mosaics, nodes of the search
+                            and bytes so far, their rates and the first two rows of the last mosaic, and a last line once it's done (see metrics.rs)
+ generate ... --self-check <probability> | --self-check all
+                            checks each mosaic generated again with probability <probability> (or every one), by a separate checker of
+                            suitable connectedness, stopping with an error at the first the generator got wrong (see self_check.rs)
+ generate ... --ranks
+                            writes each mosaic as <rank><tab><code>, with its rank among all the mosaics of the size in the generation order, counted from
+                            prefix counts (see rank.rs), so the outputs of shards and resumed runs can be merged with sort -n and checked against a single run
+ generate <size> <output> --resume <token>
+                            carries on a stopped run from its resume token (or the .resume file holding it), writing the remaining mosaics to <output>
+ census <input> <output> [--pattern <pattern> [--pattern-symmetric]] [--crossings <count>] [--components <count>] [--max-crossings <count>] [--max-cusps <count>] [--max-components <count>] [--reduced] [--space-efficient] [--connected] [--alternating] [--prime] [--linking-number <lk>] [--where <conditions>] [--columns <invariants>] [--cache <path>] [stages] [chunking]
+                            writes the invariants and knot name (where known) of every mosaic in <input> meeting the given filters, with a column for each of the named invariants,
+                            then counts the unknots among them by their stabilizations of the standard unknot,
+                            keeping the most expensive invariants in a cache file shared by equivalent mosaics and runs with --cache (see cache.rs).
+                            An <input> of generate:<size> generates the mosaics of that size meeting the filters as the census is taken, instead of reading them
+ extend <input> <output> [--open]
+                            writes the (n+1)x(n+1) mosaics extending the open nxn blocks in <input> (strands may leave their right and bottom edges) with a column and a row,
+                            or with --open the open (n+1)x(n+1) blocks, finding each boundary profile's extensions once for all its blocks (see extend.rs).
+                            An <input> of blocks:<n> finds the open blocks by extending from nothing, and a summary file of the blocks with each profile
+                            (or profiles:<n>) is extended into the summary of the next size, counting its mosaics
+ crossingless <size> [<output>]
+                            counts the crossingless mosaics (Legendrian unknots and unlinks) by components and (tb, rot), also writing them to <output>
+ satellite <mosaic> [<pattern>]
+                            prints the Legendrian satellite of a mosaic with a pattern block (see satellite.rs), by default its Whitehead double
+ legendrianize <classical mosaic>
+                            converts a classical mosaic (with the opposite crossing written a) into a legendrian mosaic, enlarging it if it has to (see legendrianize.rs)
+ smooth <input> <output> [--crossing-tile 9|10]
+                            writes every mosaic in <input> as a classical mosaic code, for classical mosaic tools whose crossing tile with the 1-3 strand over is numbered 9 (or 10)
+ render <input> <output> [--format svg|png|tikz|ascii|unicode] [--index <ranks>]
+                            renders every mosaic in <input> (or stdin, for -), or those at the given ranks counting from 0 (like 0,5,10..20),
+                            as TikZ pictures or ASCII or Unicode art one after another in <output>, or as SVG or PNG images, a file each named by <output> with the rank
+                            before its extensions (or <output> itself for a single rank). The format is taken from <output>'s extension by default (see render.rs)
+ pipe [--store <path>] [--dedup <expected lines> [--false-positive-rate <rate>] [--exact]] [--memory-limit <size>] <step>...
+                            reads mosaic codes (or census records) from stdin and writes what the steps make of them to stdout, for shell pipelines:
+                            canonicalize, corner, trim, embed <size>, mirror and flip transform mosaics, valid and --where <conditions> filter them,
+                            simplify writes their simplified PD codes instead and id adds their IDs, and by-type counts them by knot type with the
+                            smallest mosaic of each, e.g. pipe valid canonicalize --where 'tb>=1' (see pipe.rs).
+                            Canonical forms are kept in a class store, in the file <path> with --store (see store.rs).
+                            --dedup drops repeated lines by a Bloom filter sized for the expected number of distinct lines, with an exact second pass
+                            restoring the lines it mistakes for repeats with --exact (see dedup.rs). --memory-limit bounds the memory the class store,
+                            by-type and the exact pass use, spilling to temporary files past it (see store.rs, pipe.rs and sort.rs)
+ identify <mosaic>          prints whether a mosaic is suitably connected (or where it isn't), and its components, crossings, cusps, tb, rot,
+                            polynomial invariants and knot name, if it's in the knot table
+ submosaic <mosaic> <rect>  prints the tiles of a rectangle of a mosaic, like 1:2+3x4 (at row 1, column 2, 3 rows high and 4 columns wide),
+                            and the tangle they make: the ends of the strands leaving the rectangle, which of them are joined inside it and its loops (see tangle.rs)
+ translate <mosaic> [<rows>:<cols>]
+                            prints a mosaic moved down <rows> and right <cols> on its board (negative to move it up or left), or every translate of it
+ embed <mosaic> <size> [<row>:<col>]
+                            prints a mosaic placed on a blank <size>x<size> board with its top left tile at <row>:<col> (0:0 by default)
+ occurrences <mosaic> <pattern> [--symmetric]
+                            prints each rectangle of a mosaic where a pattern (see pattern.rs) or a smaller mosaic's code appears, in any orientation with --symmetric,
+                            and the tangle of its tiles there
+ explore <mosaic>           lists the Legendrian planar isotopy moves of a mosaic for one to be picked, then the moves of the result, with u to undo,
+                            and on quitting prints the mosaics on the way, each with its move (see moves.rs), with the `interactive` feature
+ sample <mosaic> <output> [--samples <count>] [--burn-in <steps>] [--thin <steps>] [--weights <weights>] [--seed <number>] [--size <size>]
+                            writes <count> mosaics (100 by default) sampled by a random walk through the moves keeping a mosaic's Legendrian class,
+                            after <steps> steps of burn-in (10000) and <steps> between samples (100), picking block moves, translations, flips and
+                            Reidemeister moves by their weights (block=1,translate=0.1,flip=0.01,reidemeister=0), from the mosaic put on a <size>x<size>
+                            board first if given (see sample.rs)
+ anneal <mosaic> [--cost tiles|size|crossings] [--steps <count>] [--start-temperature <t>] [--end-temperature <t>] [--weights <weights>] [--seed <number>] [--size <size>]
+                            prints the cheapest mosaic of a mosaic's Legendrian class found by simulated annealing over the moves of sample, by the number of
+                            nonblank tiles (by default), the size it trims to or its crossings, in <count> steps (100000) cooling from 2 to 0.01 (see anneal.rs)
+ tile-number <mosaic> [--depth <moves>] [--max-mosaics <count>] [--size <size>]
+                            prints the mosaic with the fewest nonblank tiles within <moves> moves (20 by default) of a mosaic keeping its Legendrian class,
+                            searching at most <count> mosaics (100000), on a <size>x<size> board if given (see minimize.rs)
+ fewest-crossings <mosaic> [--depth <moves>] [--max-mosaics <count>] [--size <size>]
+                            prints the mosaic with the fewest crossings found the same way, with the Legendrian Reidemeister moves of reidemeister.rs
+ stabilization-distance <mosaic> <mosaic> [--max-stabilizations <count>] [--max-mosaics <count>] [--size <size>]
+                            prints the fewest stabilizations and destabilizations found connecting two knot mosaics of a knot type (at most <count>, 6
+                            by default, searching at most <count> mosaics, 200000), as the mosaics on the way with their tb and |rot| (see stabilization.rs)
+ move-graph <input> <output> [--format dot|graphml] [--where <conditions>] [--columns <invariants>]
+                            writes the graph of the suitably connected mosaics in <input> meeting the conditions, with an edge for each pair a single move apart,
+                            as DOT or GraphML (by <output>'s extension by default), with the named invariants as node attributes,
+                            and prints the number of its components of each size and their diameters (see graph.rs)
+ mutation-graph <input> <output> [--format dot|graphml] [--where <conditions>] [--columns <invariants>]
+                            does the same for the graph with an edge for each pair of mosaics differing in a single tile
+ orientations <mosaic>      prints the writhe, rotation number and self-linking number of each orientation of a mosaic's components
+ split <mosaic>             prints the pieces of a split diagram as separate mosaics, one per line (see Diagram::split)
+ grid <mosaic>              prints the grid diagram of a knot mosaic and whether its GRID invariants λ+ and λ- vanish (see grid.rs)
+ dga <mosaic>               prints the Chekanov-Eliashberg algebra of a mosaic's front over F2 and counts its augmentations (see dga.rs)
+ symmetries <mosaic>        prints the Legendrian symmetries fixing a mosaic exactly and up to translation, and the size of its orbit (see symmetry.rs)
+ transforms <mosaic>        prints a mosaic's images under the Legendrian symmetries with their tb and rot, failing unless the symmetries keep tb and keep (x)
+                             or negate (z and xz) rotation numbers
+ oriented <size> <output>   generates every oriented mosaic, from tiles whose strands carry directions, as its mosaic code and strand directions (see oriented.rs)
+ compute-external <census> <output> --command <command> [--batch-size <count>]
+                            appends a column computed by an external program (e.g. a Khovanov homology engine) from the simplified PD codes of the census records,
+                            sending them <count> at a time (1000 by default, see external.rs)
+ goeritz <input> <output>   writes the Goeritz matrix of every mosaic in <input>, from the checkerboard coloring of its regions (see goeritz.rs)
+ regina <input> <output>    writes a python script building every mosaic in <input> as a Regina link
+ wirtinger <input> <output> [--syntax gap|magma]
+                            writes a GAP (or Magma) script building the link group of every mosaic in <input> from its Wirtinger presentation
+ sort <input> <output> [--unique] [--memory-limit <size>]
+                            sorts (and with --unique, deduplicates) the lines of <input>, spilling to temporary files beyond <size> bytes (see sort.rs)
+ type-table <input>... [--output <path>] [--format tsv|markdown|latex]
+                            writes a table of how many suitably connected mosaics of each size in the inputs realize each knot type, with rows for
+                            unnamed knots, virtual knots, links and the total, to <path> (stdout by default) in the format of its extension (see type_table.rs)
+ atlas <census> <output> [--representatives <count>]
+                            writes an HTML atlas of the knot types in a census, a page each with its mountain range of realized (tb, rot) as an SVG plot
+                            and images of the smallest mosaics of up to <count> of its classes (8 by default) (see atlas_report.rs)
+ bench <size> [--engines <engines>] [--warm-up <count>] [--repetitions <count>] [--threads <count>] [--pin compact|spread] [--output <path>]
+                            times the engines enumerating <size>x<size> mosaics (dfs, dfs-parallel, visit, extend and profiles by default) after <count> unmeasured
+                            runs (1) over <count> repetitions (5), writing a table comparing them to <path> (stdout by default) (see bench.rs),
+                            with the threads of dfs-parallel pinned to CPUs as for generate with --pin
+ bennequin <input>          checks the Bennequin inequality tb + |rot| ≤ -χ for every classical mosaic in <input> (or census of them), printing any violations (see bennequin.rs)
+ invariants                 lists the invariants that can be named in --where conditions and --columns (see invariant.rs)
+ verify-manifest <manifest> checks the chunks listed in a manifest against their record counts, sizes and checksums
+ serve <address> <input>    answers HTTP queries on the mosaics in <input> (see server.rs)
+ grpc <address>             runs the gRPC generation service (see grpc.rs, requires the `grpc` feature)
+ browse <census>            browses the records of a census in a terminal UI, drawing each mosaic beside its invariants,
+                            with searching, filtering on invariants and jumping by rank (see tui.rs, requires the `tui` feature)
+ edit <size> | <mosaic> [<output>]
+                            edits a blank mosaic (or the given one) in a terminal UI, highlighting loose ends as tiles are placed,
+                            appending it to <output> when saved and printing its code on quitting (see tui.rs, requires the `tui` feature)
+Generated mosaics and census records can be split into numbered chunks, listed in <output>.manifest, with
+ --chunk-records <count>    starting a new chunk after every <count> records
+ --chunk-size <size>        starting a new chunk once a chunk reaches <size> bytes (with an optional K, M, G or T suffix for powers of 1024)
+ --manifest                 writing a manifest (with checksums, see manifest.rs) even if the output isn't split into chunks
+Census records are computed in a pipeline of stages (see pipeline.rs), each with its own threads, set with
+ --filter-threads <count>   the number of threads filtering mosaics (1 by default)
+ --compute-threads <count>  the number of threads computing records (one per core by default)
+ --queued-batches <count>   the number of batches of mosaics that can wait between one stage and the next (16 by default)
+Options can also be read from a TOML file with --config <path> (see config.rs).
+Errors are printed to stderr, exiting with status 1.
 */
-const CONNECTION_TABLE: &[&[usize]]= &[ 
-    //000x
-    &[0],        
-    &[],
-    &[0],
-    //001x
-    &[],       
-    &[3],
-    &[3],
-    //002x
-    &[0],        
-    &[3],
-    &[0,3],
-    //010x
-    &[],        
-    &[5],
-    &[5],
-    //011x
-    &[4],       
-    &[],
-    &[4],
-    //012x
-    &[4],        
-    &[5],
-    &[4,5],
-    //020x
-    &[0],        
-    &[5],
-    &[0,5],
-    //021x
-    &[4],       
-    &[3],
-    &[3,4],
-    //022x
-    &[0,4],        
-    &[3,5],
-    &[0,3,4,5],
-    //100x
-    &[],        
-    &[2],
-    &[2],
-    //101x
-    &[6],       
-    &[],
-    &[6],
-    //102x
-    &[6],        
-    &[2],
-    &[2,6],
-    //110x
-    &[1],        
-    &[],
-    &[1],
-    //111x
-    &[],       
-    &[7,8,9],
-    &[7,8,9],
-    //112x
-    &[1],        
-    &[7,8,9],
-    &[1,7,8,9],
-    //120x
-    &[1],        
-    &[2],
-    &[1,2],
-    //121x
-    &[6],       
-    &[7,8,9],
-    &[6,7,8,9],
-    //122x
-    &[6],        
-    &[2,7,8,9],
-    &[2,6,7,8,9],
-    //200x
-    &[0],        
-    &[2],
-    &[0,2],
-    //201x
-    &[6],       
-    &[3],
-    &[3,6],
-    //202x
-    &[0,6],        
-    &[2,3],
-    &[0,2,3,6],
-    //210x
-    &[1],        
-    &[5],
-    &[1,5],
-    //211x
-    &[4],       
-    &[7,8,9],
-    &[4,7,8,9],
-    //212x
-    &[1,4],        
-    &[5,7,8,9],
-    &[1,5,7,8,9],
-    //220x
-    &[0,1],        
-    &[2,5],
-    &[0,1,2,5],
-    //221x
-    &[4,6],       
-    &[3,7,8,9],
-    &[3,4,5,7,8,9],
-    //222x
-    &[0,1,4,6],        
-    &[2,3,5,7,8,9],
-    &[0,1,2,3,4,5,6,7,8,9]
-];
-
-//Basic driver function
-fn main() -> Result<()> {
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.iter().map(String::as_str).collect::<Vec<&str>>().as_slice() {
+        #[cfg(feature = "interactive")]
+        [] => generate_interactive(),
+        ["generate", arguments @ ..] => generate(arguments),
+        ["census", input_path, output_path, options @ ..] => output::expand_path(output_path, None, "txt").and_then(|output_path| {
+            parse_options(options, &["--pattern", "--crossings", "--components", "--max-crossings", "--max-cusps", "--max-components", "--linking-number", "--where", "--columns", "--cache", "--filter-threads", "--compute-threads", "--queued-batches", "--chunk-records", "--chunk-size"], &["--pattern-symmetric", "--reduced", "--space-efficient", "--connected", "--alternating", "--prime", "--manifest"])
+                .and_then(|options| {
+                    let columns = options.get("--columns").map(|names| Registry::builtin().columns(names)).transpose()?.unwrap_or_default();
+                    let cache = options.get("--cache").map(|path| InvariantCache::open(path)).transpose()?;
+                    let (count, unknots) =
+                        census::write_census(&census_source(input_path)?, &output_path, &chunking(&options)?, &constraints(&options)?, &columns, cache.as_ref(), &stages(&options)?)?;
+                    report_census(&output_path, count, &unknots);
+                    if let Some(cache) = cache {
+                        report(&output_path, format!("Took {} records' polynomials from the cache, adding {}", cache.hits(), cache.misses()));
+                    }
+                    Ok(())
+                })
+        }),
+        ["extend", input_path, output_path, options @ ..] => output::expand_path(output_path, None, "txt").and_then(|output_path| {
+            let options = parse_options(options, &[], &["--open"])?;
+            let message = match extend::extend(&extend_source(input_path)?, &output_path, options.contains_key("--open"))? {
+                Extended::Mosaics { size, count } => format!("Wrote {} {}x{} mosaics to {}", count, size, size, output_path),
+                Extended::Blocks { size, count } => format!("Wrote {} open {}x{} blocks to {}", count, size, size, output_path),
+                Extended::Profiles { size, profiles, mosaics } => {
+                    format!("Wrote the counts of open {}x{} blocks with each of {} boundary profiles to {}, counting {} {}x{} mosaics", size, size, profiles, output_path, mosaics, size, size)
+                }
+            };
+            report(&output_path, message);
+            Ok(())
+        }),
+        ["crossingless", size] => tally_crossingless(size, None),
+        ["crossingless", size, output_path] => output::expand_path(output_path, None, "txt").and_then(|output_path| tally_crossingless(size, Some(&output_path))),
+        ["smooth", input_path, output_path, options @ ..] => output::expand_path(output_path, None, "txt").and_then(|output_path| {
+            parse_options(options, &["--crossing-tile"], &[])
+                .and_then(|options| {
+                    let crossing_tile = match options.get("--crossing-tile").map(String::as_str) {
+                        None | Some("9") => 9,
+                        Some("10") => 10,
+                        Some(value) => return Err(invalid_value("--crossing-tile", value)),
+                    };
+                    legendrianize::smooth_export(input_path, &output_path, crossing_tile)
+                })
+                .map(|count| report(&output_path, format!("Wrote {} classical mosaics to {}", count, output_path)))
+        }),
+        ["render", input_path, output_path, options @ ..] => output::expand_path(output_path, None, "svg").and_then(|output_path| {
+            parse_options(options, &["--format", "--index"], &[])
+                .and_then(|options| {
+                    let format = match options.get("--format") {
+                        Some(name) => Format::from_name(name).ok_or_else(|| invalid_value("--format", name))?,
+                        None if output::is_stdout(&output_path) => Format::Ascii,
+                        None => output_path.rsplit_once('.').and_then(|(_, extension)| Format::from_name(extension)).unwrap_or(Format::Svg),
+                    };
+                    let ranks = options.get("--index").map(|ranks| parse_list("--index", ranks, 0)).transpose()?;
+                    render::render_file(input_path, &output_path, format, ranks.map(BTreeSet::from_iter).as_ref())
+                })
+                .map(|count| report(&output_path, format!("Rendered {} mosaics to {}", count, output_path)))
+        }),
+        ["pipe", arguments @ ..] => run_pipe(arguments),
+        ["identify", code] => print_identify(code),
+        ["submosaic", code, rect] => print_submosaic(code, rect),
+        ["translate", code, shift @ ..] if shift.len() <= 1 => print_translate(code, shift.first().copied()),
+        ["embed", code, size, offset @ ..] if offset.len() <= 1 => print_embed(code, size, offset.first().copied()),
+        ["occurrences", code, pattern, options @ ..] => parse_options(options, &[], &["--symmetric"]).and_then(|options| print_occurrences(code, pattern, options.contains_key("--symmetric"))),
+        ["sample", code, output_path, options @ ..] => write_samples(code, output_path, options),
+        ["anneal", code, options @ ..] => print_annealed(code, options),
+        ["tile-number", code, options @ ..] => print_smallest(code, options, Objective::Tiles),
+        ["fewest-crossings", code, options @ ..] => print_smallest(code, options, Objective::Crossings),
+        ["stabilization-distance", from, to, options @ ..] => print_stabilization_distance(from, to, options),
+        #[cfg(feature = "interactive")]
+        ["explore", code] => explore(code),
+        ["move-graph", input_path, output_path, options @ ..] => write_graph(input_path, output_path, options, "move", Graph::of_moves),
+        ["mutation-graph", input_path, output_path, options @ ..] => write_graph(input_path, output_path, options, "mutation", Graph::of_mutations),
+        ["orientations", code] => print_orientations(code),
+        ["split", code] => print_split(code),
+        ["symmetries", code] => print_symmetries(code),
+        ["transforms", code] => print_transforms(code),
+        ["grid", code] => print_grid(code),
+        ["dga", code] => print_dga(code),
+        ["satellite", code, pattern @ ..] if pattern.len() <= 1 => print_satellite(code, pattern.first().copied()),
+        ["legendrianize", code] => legendrianize::legendrianize(code).map(|(mosaic, size)| {
+            println!("{}", mosaic);
+            match mosaic.size() - size {
+                0 => eprintln!("Already a legendrian mosaic, so kept at {}x{}", size, size),
+                increase => eprintln!("Enlarged from {}x{} to {}x{} (+{}) to reroute its crossings", size, size, mosaic.size(), mosaic.size(), increase),
+            }
+        }),
+        ["oriented", size, output_path] => output::expand_path(output_path, None, "txt").and_then(|output_path| {
+            let size = size.parse::<usize>().ok().filter(|&size| size > 0).ok_or_else(|| invalid_value("<size>", size))?;
+            oriented::write_oriented(size, &output_path).map(|count| report(&output_path, format!("Wrote {} oriented mosaics to {}", count, output_path)))
+        }),
+        ["wirtinger", input_path, output_path, options @ ..] => parse_options(options, &["--syntax"], &[]).and_then(|options| {
+            let syntax = match options.get("--syntax").map(String::as_str) {
+                None | Some("gap") => GroupSyntax::Gap,
+                Some("magma") => GroupSyntax::Magma,
+                Some(value) => return Err(invalid_value("--syntax", value)),
+            };
+            let output_path = output::expand_path(output_path, None, syntax.extension())?;
+            export::wirtinger_export(input_path, &output_path, syntax).map(|count| report(&output_path, format!("Exported {} link groups to {}", count, output_path)))
+        }),
+        ["goeritz", input_path, output_path] => output::expand_path(output_path, None, "txt").and_then(|output_path| {
+            goeritz::goeritz_export(input_path, &output_path).map(|count| report(&output_path, format!("Wrote {} Goeritz matrices to {}", count, output_path)))
+        }),
+        ["compute-external", input_path, output_path, options @ ..] => output::expand_path(output_path, None, "txt").and_then(|output_path| {
+            let options = parse_options(options, &["--command", "--batch-size"], &[])?;
+            let command = options.get("--command").ok_or_else(|| Error::new(ErrorKind::InvalidInput, "compute-external needs a --command to run"))?;
+            let batch_size = match options.get("--batch-size") {
+                Some(value) => value.parse::<usize>().ok().filter(|&count| count > 0).ok_or_else(|| invalid_value("--batch-size", value))?,
+                None => 1000,
+            };
+            external::compute_external(input_path, &output_path, command, batch_size)
+                .map(|(count, answered)| report(&output_path, format!("Wrote {} census records to {}, {} with external results", count, output_path, answered)))
+        }),
+        ["regina", input_path, output_path] => output::expand_path(output_path, None, "py").and_then(|output_path| {
+            export::regina_export(input_path, &output_path).map(|count| report(&output_path, format!("Exported {} links to {}", count, output_path)))
+        }),
+        ["sort", input_path, output_path, options @ ..] => output::expand_path(output_path, None, "txt").and_then(|output_path| {
+            parse_options(options, &["--memory-limit"], &["--unique"])
+                .and_then(|options| {
+                    let memory_limit = options.get("--memory-limit").map(|value| parse_size("--memory-limit", value)).transpose()?;
+                    Ok(sort::sort_lines(input_path, &output_path, memory_limit, options.contains_key("--unique"))?)
+                })
+                .map(|count| report(&output_path, format!("Wrote {} sorted lines to {}", count, output_path)))
+        }),
+        ["type-table", arguments @ ..] => write_type_table(arguments),
+        ["atlas", input_path, output_path, options @ ..] => output::expand_path(output_path, None, "html").and_then(|output_path| {
+            let options = parse_options(options, &["--representatives"], &[])?;
+            let representatives = options.get("--representatives").map_or(Ok(8), |value| value.parse::<usize>().map_err(|_| invalid_value("--representatives", value)))?;
+            atlas_report::write_atlas(input_path, &output_path, representatives)
+                .map(|(knot_types, records)| report(&output_path, format!("Wrote an atlas of {} knot types from {} census records to {}", knot_types, records, output_path)))
+        }),
+        ["bench", size, options @ ..] => run_bench(size, options),
+        ["bennequin", input_path] => bennequin::check_bennequin(input_path).map(|checked| println!("Checked {} mosaics, no violations", checked)),
+        ["invariants"] => {
+            println!("{}", Registry::builtin().names().join("\n"));
+            Ok(())
+        }
+        ["verify-manifest", manifest_path] => manifest::verify_manifest(manifest_path).map(|records| println!("Verified {} records", records)),
+        ["serve", address, input_path] => server::serve(address, input_path),
+        #[cfg(feature = "grpc")]
+        ["grpc", address] => grpc::serve(address),
+        #[cfg(feature = "tui")]
+        ["browse", census_path] => tui::browse(census_path),
+        #[cfg(feature = "tui")]
+        ["edit", start, output_path @ ..] if output_path.len() <= 1 => edit(start, output_path.first().copied()),
+        _ => {
+            eprintln!("Usage: mosaic_gen [generate [<size> <output>] [options] | census <input> <output> [options] | extend <input> <output> [--open] | crossingless <size> [<output>] | satellite <mosaic> [<pattern>] | legendrianize <classical mosaic> | smooth <input> <output> [options] | render <input> <output> [options] | pipe [options] <step>... | identify <mosaic> | submosaic <mosaic> <rect> | translate <mosaic> [<rows>:<cols>] | embed <mosaic> <size> [<row>:<col>] | occurrences <mosaic> <pattern> [--symmetric] | explore <mosaic> | sample <mosaic> <output> [options] | anneal <mosaic> [options] | tile-number <mosaic> [options] | fewest-crossings <mosaic> [options] | stabilization-distance <mosaic> <mosaic> [options] | move-graph <input> <output> [options] | mutation-graph <input> <output> [options] | orientations <mosaic> | split <mosaic> | symmetries <mosaic> | transforms <mosaic> | grid <mosaic> | dga <mosaic> | oriented <size> <output> | goeritz <input> <output> | compute-external <census> <output> [options] | regina <input> <output> | wirtinger <input> <output> [options] | sort <input> <output> [options] | type-table <input>... [options] | atlas <census> <output> [options] | bench <size> [options] | bennequin <input> | invariants | verify-manifest <manifest> | serve <address> <input>]");
+            std::process::exit(2);
+        }
+    };
+    match result {
+        Ok(()) => {}
+        //A closed pipe just means whatever we were writing to has seen enough
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => {}
+        Err(error) => {
+            eprintln!("mosaic_gen: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+//Status messages go to stderr when the output itself is written to stdout
+fn report(output_path: &str, message: String) {
+    if output::is_stdout(output_path) {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+//Prints the counts of crossingless mosaics as a table (on stderr if the mosaics themselves are going to stdout)
+fn tally_crossingless(size: &str, output_path: Option<&str>) -> Result<()> {
+    let size = size.parse::<usize>().ok().filter(|&size| size > 0).ok_or_else(|| invalid_value("<size>", size))?;
+    let (tally, count) = crossingless::crossingless(size, output_path)?;
+    let mut table = String::from("components\ttb\trot\tmosaics\n");
+    for ((components, tb, rot), mosaics) in &tally {
+        table += &format!("{}\t{}\t{}\t{}\n", components, tb, rot, mosaics);
+    }
+    table += &format!("Counted {} crossingless {}x{} mosaics", count, size, size);
+    report(output_path.unwrap_or(""), table);
+    Ok(())
+}
+
+//Prints the satellite of a mosaic with a pattern (the Whitehead clasp by default)
+fn print_satellite(code: &str, pattern: Option<&str>) -> Result<()> {
+    let mosaic: Mosaic = code.parse()?;
+    let pattern: Mosaic = pattern.unwrap_or(satellite::WHITEHEAD_CLASP).parse()?;
+    let satellite = satellite::satellite(&mosaic, &pattern)?;
+    println!("{}", satellite);
+    eprintln!("Built the satellite on a {}x{} mosaic", satellite.size(), satellite.size());
+    Ok(())
+}
+
+//Reports the unknots of a census by class, as a table of their (tb, rot) and stabilizations of the standard unknot
+fn report_census(output_path: &str, count: usize, unknots: &census::UnknotTally) {
+    let mut table = String::from("tb\trot\tstabilizations\tclass\tmosaics\n");
+    for (&(positive, negative), mosaics) in unknots {
+        let stabilizations = positive + negative;
+        table += &format!("{}\t{}\t{}\t{}\t{}\n", -1 - stabilizations as i32, positive as i32 - negative as i32, stabilizations, atlas::unknot_label((positive, negative)), mosaics);
+    }
+    table += &format!("Wrote {} census records to {}", count, output_path);
+    report(output_path, table);
+}
+
+/* Prints what's known about a single mosaic, one property to a line: whether it's suitably connected (and if not, the first strand that isn't matched),
+ then its census invariants (see census.rs) and its knot name, for classical knots in the knot table. */
+fn print_identify(code: &str) -> Result<()> {
+    let mosaic: Mosaic = code.trim().parse()?;
+    println!("mosaic\t{} ({}x{})", mosaic, mosaic.size(), mosaic.size());
+    println!("id\t{}", mosaic.id());
+    if let Some(bounds) = mosaic.bounds() {
+        println!("bounds\t{} (trims to {}x{})", bounds, mosaic.trim().size(), mosaic.trim().size());
+    }
+    if let Some(loose_end) = mosaic.loose_end() {
+        println!("valid\tno: {}", mosaic.describe_loose_end(loose_end));
+        return Ok(());
+    }
+    let (Some(diagram), Some(record)) = (Diagram::trace(&mosaic), CensusRecord::new(&mosaic)) else {
+        println!("valid\tyes, but empty");
+        return Ok(());
+    };
+    println!("valid\tyes");
+    println!("components\t{}", record.components);
+    println!("crossings\t{}", record.crossings);
+    println!("cusps\t{} ({} up, {} down)", diagram.up_cusps() + diagram.down_cusps(), diagram.up_cusps(), diagram.down_cusps());
+    println!("tb\t{}", record.tb);
+    println!("rot\t{}{}", record.rot, if record.components == 1 { " (up to orientation)" } else { "" });
+    println!("jones\t{}", record.jones.format("t", 2));
+    println!("ruling\t{}", record.ruling.as_ref().map_or("-".to_string(), |ruling| ruling.format("z", 1)));
+    println!("augmentations\t{}", record.augmentations.as_deref().unwrap_or("-"));
+    println!("determinant\t{}", record.determinant.map_or("-".to_string(), |determinant| determinant.to_string()));
+    println!("signature\t{}", record.signature.map_or("-".to_string(), |signature| signature.to_string()));
+    let name = match &record.name {
+        Some(name) => format!("{} (atlas: {})", name, record.atlas.label()),
+        None if record.components > 1 => format!("- (a {}-component link)", record.components),
+        None if mosaic.tiles().contains(&mosaic::VIRTUAL_CROSSING) => "- (a virtual knot)".to_string(),
+        None => "- (not in the knot table)".to_string(),
+    };
+    println!("knot\t{}", name);
+    if let Some(class) = record.unknot {
+        println!("unknot class\t{}", atlas::unknot_label(class));
+    }
+    Ok(())
+}
+
+//Prints the tangle of the tiles in a rectangle of a mosaic, one part to a line
+fn print_submosaic(code: &str, rect: &str) -> Result<()> {
+    let mosaic: Mosaic = code.trim().parse()?;
+    let tangle = mosaic.submosaic(rect.parse()?)?;
+    println!("tiles\t{} ({}x{})", tangle.rows(), tangle.height, tangle.width);
+    println!("ends\t{}", tangle.ends.iter().map(|end| end.to_string()).collect::<Vec<String>>().join(" "));
+    println!("arcs\t{}", tangle.arcs.iter().map(|(first, second)| format!("{}-{}", first, second)).collect::<Vec<String>>().join(" "));
+    println!("loops\t{}", tangle.loops);
+    Ok(())
+}
+
+/* Writes the table of how many mosaics of each size in the inputs (the arguments before the options) realize each knot type (see type_table.rs),
+ to --output (stdout by default) in --format, or the format its extension names */
+fn write_type_table(arguments: &[&str]) -> Result<()> {
+    let split = arguments.iter().position(|argument| argument.starts_with("--")).unwrap_or(arguments.len());
+    let (input_paths, options) = arguments.split_at(split);
+    if input_paths.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "type-table needs files of mosaics to count"));
+    }
+    let options = parse_options(options, &["--output", "--format"], &[])?;
+    let output_path = output::expand_path(options.get("--output").map_or("-", String::as_str), None, "txt")?;
+    let format = match options.get("--format") {
+        Some(name) => TableFormat::from_name(name).ok_or_else(|| invalid_value("--format", name))?,
+        None => output_path.rsplit_once('.').and_then(|(_, extension)| TableFormat::from_name(extension)).unwrap_or(TableFormat::Tsv),
+    };
+    let table = TypeTable::count(input_paths, &Stages::default())?;
+    let mut output = output::create(&output_path)?;
+    table.write(&mut output, format)?;
+    output.finish()?;
+    let mosaics: u64 = table.counts.values().flat_map(|counts| counts.values()).sum();
+    report(&output_path, format!("Wrote the knot types of {} mosaics of {} sizes to {}", mosaics, table.sizes.len(), output_path));
+    Ok(())
+}
+
+//Benchmarks the engines enumerating mosaics of a size against each other (see bench.rs) with the options of bench
+fn run_bench(size: &str, options: &[&str]) -> Result<()> {
+    let size = size.parse().map_err(|_| invalid_value("<size>", size))?;
+    let options = parse_options(options, &["--engines", "--warm-up", "--repetitions", "--threads", "--pin", "--output"], &[])?;
+    let defaults = BenchOptions::default();
+    let count = |option: &str, default: usize| options.get(option).map_or(Ok(default), |value| value.parse::<usize>().map_err(|_| invalid_value(option, value)));
+    let bench_options = BenchOptions {
+        engines: options.get("--engines").map_or(Ok(defaults.engines.clone()), |engines| engines.split(',').map(|engine| engine.trim().parse()).collect::<Result<_>>())?,
+        warm_up: count("--warm-up", defaults.warm_up)?,
+        repetitions: count("--repetitions", defaults.repetitions)?,
+        threads: count("--threads", defaults.threads)?.max(1),
+        placement: options.get("--pin").map(|placement| placement.parse()).transpose()?.unwrap_or_default(),
+    };
+    let output_path = output::expand_path(options.get("--output").map_or("-", String::as_str), None, "tsv")?;
+    let timings = bench::bench(size, &bench_options)?;
+    let mut output = output::create(&output_path)?;
+    bench::write_table(&mut output, size, &timings)?;
+    output.finish()
+}
+
+//Writes samples of the random walk through the moves of a mosaic's Legendrian class (see sample.rs) with the options of sample
+fn write_samples(code: &str, output_path: &str, options: &[&str]) -> Result<()> {
+    let output_path = output::expand_path(output_path, None, "txt")?;
+    let options = parse_options(options, &["--samples", "--burn-in", "--thin", "--weights", "--seed", "--size"], &[])?;
+    let number = |option: &str, default: u64| options.get(option).map_or(Ok(default), |value| value.parse::<u64>().map_err(|_| invalid_value(option, value)));
+    let defaults = SamplerOptions::default();
+    let sampler_options = SamplerOptions {
+        weights: options.get("--weights").map(|weights| weights.parse()).transpose()?.unwrap_or_default(),
+        burn_in: number("--burn-in", defaults.burn_in)?,
+        thin: number("--thin", defaults.thin)?,
+        seed: number("--seed", defaults.seed)?,
+    };
+    let mut seed: Mosaic = code.trim().parse()?;
+    if let Some(size) = options.get("--size") {
+        seed = seed.embed(size.parse().map_err(|_| invalid_value("--size", size))?, (0, 0))?;
+    }
+    let count = number("--samples", 100)?;
+    let sampler = sample::sample(seed, &output_path, count, sampler_options)?;
+    let message = format!(
+        "Wrote {} samples to {} after {} steps with move weights {} ({:.1}% of them moving)",
+        count,
+        output_path,
+        sampler.steps,
+        sampler_options.weights,
+        100.0 * sampler.acceptance_rate()
+    );
+    report(&output_path, message);
+    Ok(())
+}
+
+//Prints the cheapest mosaic found by annealing a mosaic with the options of anneal (see anneal.rs), and how it was found on stderr
+fn print_annealed(code: &str, options: &[&str]) -> Result<()> {
+    let options = parse_options(options, &["--cost", "--steps", "--start-temperature", "--end-temperature", "--weights", "--seed", "--size"], &[])?;
+    let defaults = AnnealOptions::default();
+    let temperature = |option: &str, default: f64| options.get(option).map_or(Ok(default), |value| value.parse::<f64>().map_err(|_| invalid_value(option, value)));
+    let number = |option: &str, default: u64| options.get(option).map_or(Ok(default), |value| value.parse::<u64>().map_err(|_| invalid_value(option, value)));
+    let anneal_options = AnnealOptions {
+        cost: options.get("--cost").map(|cost| cost.parse()).transpose()?.unwrap_or(defaults.cost),
+        steps: number("--steps", defaults.steps)?,
+        start_temperature: temperature("--start-temperature", defaults.start_temperature)?,
+        end_temperature: temperature("--end-temperature", defaults.end_temperature)?,
+        weights: options.get("--weights").map(|weights| weights.parse()).transpose()?.unwrap_or_default(),
+        seed: number("--seed", defaults.seed)?,
+    };
+    let mut mosaic: Mosaic = code.trim().parse()?;
+    if let Some(size) = options.get("--size") {
+        mosaic = mosaic.embed(size.parse().map_err(|_| invalid_value("--size", size))?, (0, 0))?;
+    }
+    let start = anneal_options.cost.costs(&mosaic);
+    let annealed = anneal::anneal(mosaic, &anneal_options)?;
+    println!("{}", annealed.best);
+    let [cost, ..] = annealed.costs;
+    eprintln!(
+        "Lowered {} from {} to {} (trimming to {}) at step {} of {}, with {} steps moving",
+        anneal_options.cost,
+        start[0],
+        cost,
+        annealed.best.trim(),
+        annealed.found_at,
+        anneal_options.steps,
+        annealed.accepted
+    );
+    Ok(())
+}
+
+/* Prints the mosaic with the fewest tiles or crossings (as `objective` says) found by searching the moves of a mosaic with the options of tile-number
+ and fewest-crossings (see minimize.rs), and how the search went on stderr */
+fn print_smallest(code: &str, options: &[&str], objective: Objective) -> Result<()> {
+    let options = parse_options(options, &["--depth", "--max-mosaics", "--size"], &[])?;
+    let number = |option: &str, default: usize| options.get(option).map_or(Ok(default), |value| value.parse::<usize>().map_err(|_| invalid_value(option, value)));
+    let mut mosaic: Mosaic = code.trim().parse()?;
+    if let Some(size) = options.get("--size") {
+        mosaic = mosaic.embed(size.parse().map_err(|_| invalid_value("--size", size))?, (0, 0))?;
+    }
+    let smallest = minimize::smallest(&mosaic, objective, number("--depth", 20)?, number("--max-mosaics", 100000)?)?;
+    println!("{}", smallest.best);
+    let extent = if smallest.exhaustive { "every mosaic the moves reach" } else { "the mosaics searched" };
+    eprintln!(
+        "{} {}, down from {}, {} moves from the mosaic -- the fewest of {} ({}) on the {}x{} board",
+        smallest.value,
+        objective.name(),
+        objective.measure(&mosaic),
+        smallest.depth,
+        extent,
+        smallest.explored,
+        mosaic.size(),
+        mosaic.size()
+    );
+    Ok(())
+}
+
+/* Prints the fewest stabilizations and destabilizations found connecting two knot mosaics (see stabilization.rs), as the mosaics on the way with their tb and |rot|,
+ both put on a board of the larger size (or `--size`) first, and how the search went on stderr */
+fn print_stabilization_distance(from: &str, to: &str, options: &[&str]) -> Result<()> {
+    let options = parse_options(options, &["--max-stabilizations", "--max-mosaics", "--size"], &[])?;
+    let number = |option: &str, default: usize| options.get(option).map_or(Ok(default), |value| value.parse::<usize>().map_err(|_| invalid_value(option, value)));
+    let (from, to): (Mosaic, Mosaic) = (from.trim().parse()?, to.trim().parse()?);
+    let size = number("--size", from.size().max(to.size()))?;
+    let (from, to) = (from.embed(size, (0, 0))?, to.embed(size, (0, 0))?);
+    let (Some(start), Some(end)) = (stabilization::classical(&from), stabilization::classical(&to)) else {
+        return Err(Error::new(ErrorKind::InvalidInput, "stabilization distances are between suitably connected knot mosaics"));
+    };
+    let lower_bound = stabilization::lower_bound(start, end);
+    let Some(connection) = stabilization::connect(&from, &to, number("--max-stabilizations", 6)?, number("--max-mosaics", 200000)?)? else {
+        eprintln!("Not connected within the search's limits on the {}x{} board (they're at least {} (de)stabilizations apart by tb and rot)", size, size, lower_bound);
+        return Ok(());
+    };
+    for (index, (mosaic, step)) in connection.path.iter().enumerate() {
+        let (tb, rot) = stabilization::classical(mosaic).expect("stabilizations and moves keep knots knots");
+        let step = match step {
+            Some(true) => "stabilization",
+            Some(false) => "destabilization",
+            None if index == 0 => "start",
+            None => "move",
+        };
+        println!("{}	{}	tb {}	|rot| {}", mosaic, step, tb, rot);
+    }
+    eprintln!(
+        "Connected by {} stabilizations and {} destabilizations{} in {} steps, searching {} mosaics on the {}x{} board",
+        connection.stabilizations,
+        connection.destabilizations,
+        if connection.stabilizations + connection.destabilizations == lower_bound { ", the fewest tb and rot allow," } else { "" },
+        connection.path.len() - 1,
+        connection.explored,
+        size,
+        size
+    );
+    Ok(())
+}
+
+//Prints a mosaic moved by whole rows and columns on its board (see Mosaic::translate), or every translate of it
+fn print_translate(code: &str, shift: Option<&str>) -> Result<()> {
+    let mosaic: Mosaic = code.trim().parse()?;
+    let Some(shift) = shift else {
+        for translate in mosaic.translations() {
+            println!("{}", translate);
+        }
+        return Ok(());
+    };
+    let (rows, cols) = shift.split_once(':').ok_or_else(|| invalid_value("<rows>:<cols>", shift))?;
+    let number = |number: &str| number.trim().parse::<isize>().map_err(|_| invalid_value("<rows>:<cols>", shift));
+    println!("{}", mosaic.translate(number(rows)?, number(cols)?)?);
+    Ok(())
+}
+
+//Prints a mosaic embedded in a larger one (see Mosaic::embed)
+fn print_embed(code: &str, size: &str, offset: Option<&str>) -> Result<()> {
+    let mosaic: Mosaic = code.trim().parse()?;
+    let size = size.parse::<usize>().map_err(|_| invalid_value("<size>", size))?;
+    let offset = match offset {
+        Some(offset) => {
+            let (row, col) = offset.split_once(':').ok_or_else(|| invalid_value("<row>:<col>", offset))?;
+            let index = |index: &str| index.trim().parse::<usize>().map_err(|_| invalid_value("<row>:<col>", offset));
+            (index(row)?, index(col)?)
+        }
+        None => (0, 0),
+    };
+    println!("{}", mosaic.embed(size, offset)?);
+    Ok(())
+}
+
+/* Prints each rectangle of a mosaic where a pattern appears, with the tangle of its tiles there.
+A pattern without / or . that's a mosaic code is taken as that mosaic's square block of tiles. */
+fn print_occurrences(code: &str, pattern: &str, symmetric: bool) -> Result<()> {
+    let mosaic: Mosaic = code.trim().parse()?;
+    let inner = Some(pattern).filter(|pattern| !pattern.contains(['/', '.'])).and_then(|pattern| pattern.parse::<Mosaic>().ok());
+    let occurrences: Vec<Rect> = match inner {
+        Some(inner) if !symmetric => mosaic.occurrences(&inner),
+        Some(inner) => Pattern::from(&inner).symmetric().occurrences(&mosaic),
+        None if symmetric => pattern.parse::<Pattern>()?.symmetric().occurrences(&mosaic),
+        None => pattern.parse::<Pattern>()?.occurrences(&mosaic),
+    };
+    for &rect in &occurrences {
+        println!("{}\t{}", rect, mosaic.submosaic(rect)?);
+    }
+    eprintln!("Found {} occurrences", occurrences.len());
+    Ok(())
+}
+
+//Prints the invariants of each orientation of a mosaic, as a table with a + for each component kept as traversed and a - for each reversed
+fn print_orientations(code: &str) -> Result<()> {
+    let mosaic: Mosaic = code.parse()?;
+    let diagram = Diagram::trace(&mosaic).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} isn't a suitably connected mosaic", code)))?;
+    println!("orientation\twrithe\trot\tsl");
+    for orientation in diagram.orientations() {
+        let signs: String = orientation.reversed.iter().map(|&reversed| if reversed { '-' } else { '+' }).collect();
+        println!("{}\t{}\t{}\t{}", signs, orientation.writhe, orientation.rot, orientation.sl);
+    }
+    Ok(())
+}
+
+//Prints the mosaic of each piece of a mosaic's diagram, noting on stderr how it splits
+fn print_split(code: &str) -> Result<()> {
+    let mosaic: Mosaic = code.parse()?;
+    let diagram = Diagram::trace(&mosaic).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} isn't a suitably connected mosaic", code)))?;
+    let pieces = diagram.split();
+    for piece in &pieces {
+        println!("{}", piece);
+    }
+    match pieces.len() {
+        0 | 1 => eprintln!("The diagram isn't split"),
+        count if mosaic.has_corridor() => eprintln!("The diagram splits into {} pieces, with a blank row or column between some of them", count),
+        count => eprintln!("The diagram splits into {} pieces", count),
+    }
+    Ok(())
+}
+
+//Prints the symmetries fixing a mosaic -- exactly, with the size of its orbit, then up to translation (see symmetry::class_symmetries)
+fn print_symmetries(code: &str) -> Result<()> {
+    let mosaic: Mosaic = code.parse()?;
+    let diagram = Diagram::trace(&mosaic).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} isn't a suitably connected mosaic", code)))?;
+    let labels: Vec<&str> = mosaic.symmetries().iter().map(|symmetry| symmetry.label()).collect();
+    println!("fixed by {} (orbit of {})", labels.join(", "), mosaic.orbit_size());
+    for fixed in class_symmetries(&diagram) {
+        let orientation = match fixed.reversing {
+            Some(true) => ", reversing its orientation",
+            Some(false) => ", preserving its orientation",
+            None => "",
+        };
+        println!("{} fixes it up to translation{}", fixed.symmetry.label(), orientation);
+    }
+    Ok(())
+}
+
+/* Prints a mosaic's image under each Legendrian symmetry with its tb and rotation numbers, checking that tb is unchanged and the rotation numbers
+ are kept or negated as they should be (see Symmetry::rot_sign). The rotation numbers are those of every orientation, sorted, since the symmetries
+ don't keep the orientation each component is traversed in. */
+fn print_transforms(code: &str) -> Result<()> {
+    let mosaic: Mosaic = code.parse()?;
+    let invariants = |mosaic: &Mosaic| {
+        let diagram = Diagram::trace(mosaic).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} isn't a suitably connected mosaic", mosaic)))?;
+        let mut rots: Vec<i32> = diagram.orientations().iter().map(|orientation| orientation.rot).collect();
+        rots.sort_unstable();
+        Ok::<_, Error>((diagram.tb(), rots))
+    };
+    let (tb, rots) = invariants(&mosaic)?;
+    println!("symmetry	mosaic	tb	rot");
+    let mut mismatched = Vec::new();
+    for symmetry in GROUP {
+        let image = symmetry.apply(&mosaic);
+        let (image_tb, image_rots) = invariants(&image)?;
+        let mut expected: Vec<i32> = rots.iter().map(|rot| rot * symmetry.rot_sign()).collect();
+        expected.sort_unstable();
+        let rot_list: Vec<String> = image_rots.iter().map(i32::to_string).collect();
+        println!("{}\t{}\t{}\t{}", symmetry.label(), image, image_tb, rot_list.join(","));
+        if image_tb != tb || image_rots != expected {
+            mismatched.push(symmetry.label());
+        }
+    }
+    if !mismatched.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidData, format!("tb or rot of {} changed unexpectedly under {}", code, mismatched.join(", "))));
+    }
+    Ok(())
+}
+
+//Prints the grid as rows of X's and O's from the top, and whether λ+ and λ- vanish
+fn print_grid(code: &str) -> Result<()> {
+    let mosaic: Mosaic = code.parse()?;
+    let diagram = Diagram::trace(&mosaic).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} isn't a suitably connected mosaic", code)))?;
+    let grid = Grid::new(&diagram).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} has no grid diagram (it's empty or has virtual crossings)", code)))?;
+    for row in (0..grid.size()).rev() {
+        let cells: String = (0..grid.size()).map(|col| if grid.x[col] == row { 'X' } else if grid.o[col] == row { 'O' } else { '.' }).collect();
+        println!("{}", cells);
+    }
+    let status = |nonzero: bool| if nonzero { "nonzero" } else { "vanishes" };
+    match grid_invariants(&diagram) {
+        Some((plus, minus)) => println!("λ+ {}, λ- {} (with rot >= 0)", status(plus), status(minus)),
+        None if diagram.component_count() != 1 => println!("λ± are only computed for knots"),
+        None => println!("λ± aren't computed for grids larger than {}x{}", MAX_GRID_SIZE, MAX_GRID_SIZE),
+    }
+    Ok(())
+}
+
+//Prints the differential of every generator of the Chekanov-Eliashberg algebra, and the number of augmentations
+fn print_dga(code: &str) -> Result<()> {
+    let mosaic: Mosaic = code.parse()?;
+    let diagram = Diagram::trace(&mosaic).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} isn't a suitably connected mosaic", code)))?;
+    let dga = Dga::new(&diagram).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} has virtual crossings", code)))?;
+    for (generator, (_, (row, col))) in dga.generators.iter().enumerate() {
+        println!("∂{} = {} (tile {}, {})", dga.label(generator), dga.format_differential(generator), row, col);
+    }
+    match dga.augmentation_count() {
+        Some(count) => println!("{} augmentations, augmentation number {}", count, format_augmentation_number(count, dga.generators.len())),
+        None => println!("augmentations aren't counted for more than {} generators", MAX_AUGMENTATION_GENERATORS),
+    }
+    Ok(())
+}
+
+//Runs the pipe command: its options (see pipe.rs), then its steps
+fn run_pipe(arguments: &[&str]) -> Result<()> {
+    const WITH_VALUES: [&str; 4] = ["--store", "--dedup", "--false-positive-rate", "--memory-limit"];
+    let mut steps_start = 0;
+    while let Some(argument) = arguments.get(steps_start) {
+        match *argument {
+            "--exact" => steps_start += 1,
+            argument if WITH_VALUES.contains(&argument) => steps_start += 2,
+            _ => break,
+        }
+    }
+    let options = parse_options(&arguments[..steps_start.min(arguments.len())], &WITH_VALUES, &["--exact"])?;
+    let steps = pipe::Step::parse_all(&arguments[steps_start.min(arguments.len())..], &Registry::builtin())?;
+    let memory_limit = options.get("--memory-limit").map(|value| parse_size("--memory-limit", value)).transpose()?;
+    let dedup = match options.get("--dedup") {
+        Some(expected) => Some(DedupOptions {
+            expected: expected.parse().ok().filter(|&expected| expected > 0).ok_or_else(|| invalid_value("--dedup", expected))?,
+            false_positive_rate: match options.get("--false-positive-rate") {
+                Some(rate) => rate.parse().ok().filter(|rate| 0.0 < *rate && *rate < 1.0).ok_or_else(|| invalid_value("--false-positive-rate", rate))?,
+                None => dedup::DEFAULT_FALSE_POSITIVE_RATE,
+            },
+            exact: options.contains_key("--exact"),
+            memory_limit,
+        }),
+        None if options.contains_key("--false-positive-rate") || options.contains_key("--exact") => return Err(Error::new(ErrorKind::InvalidInput, "--false-positive-rate and --exact need --dedup")),
+        None => None,
+    };
+    let store = match options.get("--store") {
+        Some(store_path) => ClassStore::open(store_path, memory_limit)?,
+        None => ClassStore::in_memory(memory_limit),
+    };
+    let counts = pipe::run(&steps, &Stages::default(), &store, dedup.as_ref(), memory_limit)?;
+    eprintln!("Read {} mosaics from stdin, writing {} to stdout", counts.read, counts.written);
+    if dedup.is_some() {
+        eprintln!("Dropped {} duplicate lines{}", counts.dropped - counts.restored, if options.contains_key("--exact") { format!(" ({} more restored by the exact pass)", counts.restored) } else { String::new() });
+    }
+    if let Some(store_path) = options.get("--store") {
+        eprintln!("The class store {} holds {} classes", store_path, store.class_count());
+    }
+    Ok(())
+}
+
+/* Writes the move or mutation graph (as `kind` says, see graph.rs) of the mosaics in a file with the options of move-graph, in the format named by --format
+ or the output's extension, and reports the statistics of its components */
+fn write_graph(input_path: &str, output_path: &str, options: &[&str], kind: &str, build: fn(Vec<Mosaic>) -> Graph) -> Result<()> {
+    let output_path = output::expand_path(output_path, None, "dot")?;
+    let options = parse_options(options, &["--format", "--where", "--columns"], &[])?;
+    let format = match options.get("--format") {
+        Some(name) => GraphFormat::from_name(name).ok_or_else(|| invalid_value("--format", name))?,
+        None => output_path.rsplit_once('.').and_then(|(_, extension)| GraphFormat::from_name(extension)).unwrap_or(GraphFormat::Dot),
+    };
+    let registry = Registry::builtin();
+    let conditions = options.get("--where").map(|conditions| registry.conditions(conditions)).transpose()?.unwrap_or_default();
+    let columns = options.get("--columns").map(|names| registry.columns(names)).transpose()?.unwrap_or_default();
+    let graph = build(graph::read_graph_mosaics(input_path, &conditions)?);
+    graph::write_graph(&graph, &output_path, format, &columns)?;
+    let table = graph::format_components(&graph, &graph.components());
+    report(&output_path, format!("{}\nWrote a {} graph of {} mosaics and {} edges to {}", table, kind, graph.mosaics.len(), graph.edges.len(), output_path));
+    Ok(())
+}
+
+//Edits a mosaic (or a blank mosaic, given a size) in the terminal editor, printing the mosaic it's left as
+#[cfg(feature = "tui")]
+fn edit(start: &str, output_path: Option<&str>) -> Result<()> {
+    let mosaic = match start.parse::<usize>() {
+        Ok(size) if size > 0 => Mosaic::from_tiles(size, vec![0; size * size])?,
+        _ => start.parse()?,
+    };
+    tui::edit(mosaic, output_path).map(|mosaic| println!("{}", mosaic))
+}
+
+/* Explores the moves of a mosaic (see moves.rs) from the prompt: shows the mosaic and its numbered moves, and applies the move picked, or undoes the last with u,
+ until q, then prints every mosaic from the first to the last, each with the move leading to it. */
+#[cfg(feature = "interactive")]
+fn explore(code: &str) -> Result<()> {
+    let start: Mosaic = code.trim().parse()?;
+    let mut mosaic = start.clone();
+    let mut history: Vec<(moves::Move, Mosaic)> = Vec::new();
+    loop {
+        let moves = moves::moves(&mosaic);
+        eprintln!("\n{}\n{} (moves made: {}, possible: {})", render::to_unicode(&mosaic), mosaic, history.len(), moves.len());
+        for (number, step) in moves.iter().enumerate() {
+            eprintln!("{:>4}  {}", number + 1, step);
+        }
+        let choice: String = Input::new()
+        .with_prompt("Move to make (or u to undo, q to quit)?")
+        .interact_text()
+        .map_err(Error::other)?;
+        match choice.trim() {
+            "q" => break,
+            "u" => match history.pop() {
+                Some((step, _)) => {
+                    mosaic = step.inverse().apply(&mosaic);
+                    eprintln!("Undid {}", step);
+                }
+                None => eprintln!("No moves to undo"),
+            },
+            choice => match choice.parse::<usize>().ok().and_then(|number| moves.get(number.wrapping_sub(1))) {
+                Some(step) => {
+                    mosaic = step.apply(&mosaic);
+                    history.push((step.clone(), mosaic.clone()));
+                }
+                None => eprintln!("{} isn't a move number from 1 to {}, u or q", choice, moves.len()),
+            },
+        }
+    }
+    println!("{}", start);
+    for (step, mosaic) in &history {
+        println!("{}\t{}", mosaic, step);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "interactive")]
+fn generate_interactive() -> Result<()> {
     let size: usize = Input::new()
     .with_prompt("Size of generated mosaics?")
     .interact_text()
@@ -153,97 +950,374 @@ fn main() -> Result<()> {
     .interact_text()
     .unwrap();   
     
+    let output_path = output::expand_path(&output_path, Some(size), "txt")?;
     let now = Instant::now(); //Timing 
-    mosaic_gen(&output_path, size)?;
-    print!("Generation complete! ({:.6} s)", now.elapsed().as_secs_f64());
+    mosaic_gen(&GeneratorConfig::new(size, &output_path))?;
+    report(&output_path, format!("Generation complete! ({:.6} s)", now.elapsed().as_secs_f64()));
 
     Ok(())
 }
 
+/* Options of a subcommand mapped to their values, where `with_values` are the options taking a value and `flags` are those that don't (given the value "").
+Options can also come from a config file given with --config, with those on the command line taking precedence. */
+fn parse_options(options: &[&str], with_values: &[&str], flags: &[&str]) -> Result<HashMap<String, String>> {
+    let mut values = HashMap::new();
+    let mut config_path = None;
+    let mut options = options.iter();
+    while let Some(&option) = options.next() {
+        if flags.contains(&option) {
+            values.insert(option.to_string(), String::new());
+        } else if option == "--config" || with_values.contains(&option) {
+            let value = options.next().ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("option {} is missing a value", option)))?;
+            if option == "--config" {
+                config_path = Some(*value);
+            } else {
+                values.insert(option.to_string(), value.to_string());
+            }
+        } else {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("unrecognized option {}", option)));
+        }
+    }
+    if let Some(config_path) = config_path {
+        for (option, value) in config::read_config(config_path)? {
+            let value = match value {
+                Some(value) if with_values.contains(&option.as_str()) => value,
+                None if flags.contains(&option.as_str()) => String::new(),
+                _ if with_values.contains(&option.as_str()) || flags.contains(&option.as_str()) => {
+                    return Err(Error::new(ErrorKind::InvalidData, format!("{}: {} should be {}", config_path, &option[2..], if flags.contains(&option.as_str()) { "true or false" } else { "a string or number" })));
+                }
+                _ => return Err(Error::new(ErrorKind::InvalidData, format!("{}: unrecognized option {}", config_path, &option[2..]))),
+            };
+            values.entry(option).or_insert(value);
+        }
+    }
+    Ok(values)
+}
+
+fn invalid_value(option: &str, value: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("invalid value \"{}\" for {}", value, option))
+}
 
-/* Prints all size x size mosaics to a file at output_path
-This function essentially just counts upwards -- each mosaic is represented by a base-10 number with size^2 digits, produced by reading the mosaic left to right, top to bottom.
-e.g. 555020001 = 555
-                 020
-                 001
-During generation, we keep a list of valid tiles for each digit based on the tiles leftward and above that digit in the underlying mosaic.
-Whenever we have to "carry" a digit, we create new lists of valid tiles for every digit to the right of the carried digit
-This guarantees that we produce every suitably connected size x size mosaic, which are written to a file as they're iterated through 
-*/
-fn mosaic_gen(output_path: &str, size: usize ) -> Result<()> {
-    let vector_length = size*size - 1;
-    let mut mosaic: Vec<usize> = vec![0; vector_length + 1];
-    let mut curr_tile: usize = 0;
-    let mut rightward = true;
-    let mut digit_index: Vec<usize> = vec![0; vector_length + 1];
-    let mut valid_tiles_for: Vec<&[usize]> = Vec::with_capacity(vector_length + 1);
-    unsafe {
-        valid_tiles_for.set_len(vector_length + 1);
+//Number of bytes in a size like 512, 64K or 2G
+fn parse_size(option: &str, value: &str) -> Result<u64> {
+    let (digits, scale) = match value.char_indices().last() {
+        Some((index, suffix @ ('K' | 'M' | 'G' | 'T'))) => (&value[..index], 1u64 << (10 * (1 + "KMGT".find(suffix).unwrap()))),
+        _ => (value, 1),
+    };
+    digits.parse::<u64>().ok().and_then(|count| count.checked_mul(scale)).filter(|&bytes| bytes > 0).ok_or_else(|| invalid_value(option, value))
+}
+
+//Where a census's mosaics come from: generate:<size> for the mosaics of that size, or else a file
+fn census_source(input: &str) -> Result<census::Source<'_>> {
+    match input.strip_prefix("generate:") {
+        Some(size) => size.parse().ok().filter(|&size| size > 0).map(census::Source::Generated).ok_or_else(|| invalid_value("<input>", input)),
+        None => Ok(census::Source::File(input)),
     }
+}
 
-    let output_file: File = File::create(output_path)?;
-    let mut output_buffer = BufWriter::new(output_file);
+//Where the open blocks to extend come from: blocks:<size> or profiles:<size> to find the blocks (or their summary) of that size, or else a file
+fn extend_source(input: &str) -> Result<extend::Source<'_>> {
+    let size = |size: &str| size.parse().ok().filter(|&size| size <= extend::MAX_BLOCK_SIZE).ok_or_else(|| invalid_value("<input>", input));
+    match (input.strip_prefix("blocks:"), input.strip_prefix("profiles:")) {
+        (Some(blocks), _) => size(blocks).map(extend::Source::Blocks),
+        (None, Some(profiles)) => size(profiles).map(extend::Source::Profiles),
+        (None, None) => Ok(extend::Source::File(input)),
+    }
+}
 
-    loop {
-        //Determining the list of valid tiles for the current tile based on tiles to top/left, and whether tile is on right/bottom edge of mosaic
-        if rightward {
-            valid_tiles_for[curr_tile] = CONNECTION_TABLE [
-                if curr_tile%size == size - 1 {0} else {2} //right
-                +3*( if curr_tile/size == 0 {0} else { //up
-                    match mosaic[ curr_tile - size ] { 
-                    0|3|4|5 => 0,
-                    _ => 1
-                    }
-                })
-                +9*( if curr_tile%size == 0 {0} else { //left
-                    match mosaic[ curr_tile - 1 ] { 
-                    0|1|4|6 => 0,
-                    _ => 1
-                    }
-                })
-                +27*(if curr_tile/size == size - 1 {0} else {2}) //down
-            ];
-            
-            //Determining if there are no valid tiles based on the current configuration
-            if valid_tiles_for[curr_tile].len() == 0 {
-               rightward = false;
-                curr_tile -= 1;
-                continue;
-            }
+//The threads of each stage of a pipeline, and the batches queued between them, defaulting to Stages::default
+fn stages(options: &HashMap<String, String>) -> Result<Stages> {
+    let count = |option: &str, default: usize| match options.get(option) {
+        Some(value) => value.parse().ok().filter(|&count: &usize| count > 0).ok_or_else(|| invalid_value(option, value)),
+        None => Ok(default),
+    };
+    let defaults = Stages::default();
+    Ok(Stages {
+        filter_threads: count("--filter-threads", defaults.filter_threads)?,
+        compute_threads: count("--compute-threads", defaults.compute_threads)?,
+        queued_batches: count("--queued-batches", defaults.queued_batches)?,
+    })
+}
 
-            //Setting the current tile to the first valid tile
-            digit_index[curr_tile] = 1; //Note that digit index represents the index of the _next_ valid tile to be used for a given tile in the mosaic
-            mosaic[curr_tile] = valid_tiles_for[curr_tile][0];
-            
-            //
-            if curr_tile == vector_length {
-                rightward = false;
-                continue;
-            }
-            curr_tile += 1;
-            continue;
+fn chunking(options: &HashMap<String, String>) -> Result<Chunking> {
+    let records = match options.get("--chunk-records") {
+        Some(value) => Some(value.parse().ok().filter(|&records: &u64| records > 0).ok_or_else(|| invalid_value("--chunk-records", value))?),
+        None => None,
+    };
+    let bytes = options.get("--chunk-size").map(|value| parse_size("--chunk-size", value)).transpose()?;
+    Ok(Chunking { records, bytes, manifest: options.contains_key("--manifest") })
+}
+
+//Sizes given as a single size (5), a range (3..=6 or 3..7) or a comma separated list of these
+fn parse_sizes(value: &str) -> Result<Vec<usize>> {
+    Ok(parse_list("--sizes", value, 1)?.into_iter().map(|size| size as usize).collect())
+}
+
+//Numbers of at least `min` given as for parse_sizes, as the value of an option
+fn parse_list(option: &str, value: &str, min: u64) -> Result<Vec<u64>> {
+    let parse = |number: &str| number.trim().parse::<u64>().ok().filter(|&number| number >= min).ok_or_else(|| invalid_value(option, value));
+    let mut numbers = Vec::new();
+    for part in value.split(',') {
+        if let Some((start, end)) = part.split_once("..=") {
+            numbers.extend(parse(start)?..=parse(end)?);
+        } else if let Some((start, end)) = part.split_once("..") {
+            numbers.extend(parse(start)?..parse(end)?);
+        } else {
+            numbers.push(parse(part)?);
         }
+    }
+    if numbers.is_empty() {
+        return Err(invalid_value(option, value));
+    }
+    Ok(numbers)
+}
+
+fn parse_code(option: &str, code: &str) -> Result<Vec<usize>> {
+    let tiles = code.chars().map(|tile| mosaic::parse_tile(tile).map(|tile| tile as usize)).collect::<Option<Vec<usize>>>();
+    tiles.filter(|tiles| !tiles.is_empty()).ok_or_else(|| invalid_value(option, code))
+}
+
+//A place in the generation order given by at most one of `option` (a mosaic code), "<option>-after" (a mosaic code) and "<option>-rank" (a rank)
+fn position(options: &HashMap<String, String>, option: &str) -> Result<Option<Position>> {
+    let (after_option, rank_option) = (format!("{}-after", option), format!("{}-rank", option));
+    match (options.get(option), options.get(&after_option), options.get(&rank_option)) {
+        (Some(code), None, None) => Ok(Some(Position::Mosaic(parse_code(option, code)?))),
+        (None, Some(code), None) => Ok(Some(Position::After(parse_code(&after_option, code)?))),
+        (None, None, Some(rank)) => Ok(Some(Position::Rank(rank.parse().map_err(|_| invalid_value(&rank_option, rank))?))),
+        (None, None, None) => Ok(None),
+        _ => Err(Error::new(ErrorKind::InvalidInput, format!("generate takes only one of {}, {} and {}", option, after_option, rank_option))),
+    }
+}
 
-        if curr_tile == vector_length { //Writing complete mosaics
-            writeln!(output_buffer, "{}", mosaic.iter().map(|val| format!("{}", val)).collect::<Vec<String>>().join(""))?;
+/* Where generation starts and stops: --start/--start-after/--start-rank and --end/--end-after/--end-rank restrict generation to a range of the generation order,
+ as does fixing the first tiles with --prefix, all in the order given by --order (lex by default),
+ --max-mosaics and --max-seconds limit each size generated,
+ and --resume carries on from a resume token, given either as the token itself or as the path of the .resume file holding it (and in its order). */
+fn bounds(options: &HashMap<String, String>) -> Result<Bounds> {
+    let max_mosaics = match options.get("--max-mosaics") {
+        Some(value) => Some(value.parse().ok().filter(|&count: &u64| count > 0).ok_or_else(|| invalid_value("--max-mosaics", value))?),
+        None => None,
+    };
+    let max_seconds = match options.get("--max-seconds") {
+        Some(value) => Some(value.parse().ok().filter(|&seconds: &f64| seconds > 0.0).ok_or_else(|| invalid_value("--max-seconds", value))?),
+        None => None,
+    };
+    let resume = match options.get("--resume") {
+        Some(value) if Path::new(value).is_file() => Some(fs::read_to_string(value)?.parse()?),
+        Some(value) => Some(value.parse()?),
+        None => None,
+    };
+    let order = match options.get("--order") {
+        Some(order) => order.parse()?,
+        None => resume.as_ref().map_or(Order::Lex, |token: &ResumeToken| token.order),
+    };
+    Ok(Bounds {
+        resume,
+        start: position(options, "--start")?,
+        end: position(options, "--end")?,
+        prefix: options.get("--prefix").map(|prefix| parse_code("--prefix", prefix)).transpose()?,
+        max_mosaics,
+        max_seconds,
+        order,
+    })
+}
+
+//Tiles in a comma separated list like 7,8
+fn parse_tiles(option: &str, value: &str) -> Result<Vec<usize>> {
+    let parse = |tile: &str| match tile.trim().chars().collect::<Vec<char>>()[..] {
+        [tile] => mosaic::parse_tile(tile).map(|tile| tile as usize),
+        _ => None,
+    };
+    value.split(',').map(|tile| parse(tile).ok_or_else(|| invalid_value(option, value))).collect()
+}
+
+/* Which mosaics to generate (or take the census of): with --crossings and --components, only those with exactly that many crossing tiles and link components,
+ with --max-crossings, --max-cusps and --max-components, only those with at most that many,
+ and with --pattern, only those containing the pattern, in any orientation with --pattern-symmetric.
+--forbid-tiles leaves tiles out everywhere, and --forbid-at leaves them out at given positions, as <row>:<col>=<tiles> separated by semicolons (e.g. 0:0=9;2:3=7,8),
+ and --seed fixes the tiles covered by a smaller mosaic with its top left tile at a position, as <row>:<col>=<mosaic> (or just <mosaic>, at 0:0). */
+fn constraints(options: &HashMap<String, String>) -> Result<Constraints> {
+    let mut pattern = options.get("--pattern").map(|pattern| pattern.parse::<Pattern>()).transpose()?;
+    if options.contains_key("--pattern-symmetric") {
+        pattern = Some(pattern.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "--pattern-symmetric needs a --pattern"))?.symmetric());
+    }
+    let forbidden = options.get("--forbid-tiles").map(|tiles| parse_tiles("--forbid-tiles", tiles)).transpose()?.unwrap_or_default();
+    let mut forbidden_at = Vec::new();
+    if let Some(value) = options.get("--forbid-at") {
+        for position in value.split(';').filter(|position| !position.trim().is_empty()) {
+            let (place, tiles) = position.split_once('=').ok_or_else(|| invalid_value("--forbid-at", value))?;
+            let (row, col) = place.split_once(':').ok_or_else(|| invalid_value("--forbid-at", value))?;
+            let index = |index: &str| index.trim().parse::<usize>().map_err(|_| invalid_value("--forbid-at", value));
+            forbidden_at.push((index(row)?, index(col)?, parse_tiles("--forbid-at", tiles)?));
         }
+    }
+    let seed = match options.get("--seed").map(|value| (value, value.split_once('='))) {
+        Some((value, Some((place, code)))) => {
+            let (row, col) = place.split_once(':').ok_or_else(|| invalid_value("--seed", value))?;
+            let index = |index: &str| index.trim().parse::<usize>().map_err(|_| invalid_value("--seed", value));
+            Some((index(row)?, index(col)?, code.parse()?))
+        }
+        Some((code, None)) => Some((0, 0, code.parse()?)),
+        None => None,
+    };
+    let crossings = options.get("--crossings").map(|count| count.parse().map_err(|_| invalid_value("--crossings", count))).transpose()?;
+    let linking_number = options.get("--linking-number").map(|value| value.parse().map_err(|_| invalid_value("--linking-number", value))).transpose()?;
+    let components = options.get("--components").map(|count| count.parse().map_err(|_| invalid_value("--components", count))).transpose()?;
+    let count = |option: &str| options.get(option).map(|count| count.parse().map_err(|_| invalid_value(option, count))).transpose();
+    let conditions = options.get("--where").map(|conditions| Registry::builtin().conditions(conditions)).transpose()?.unwrap_or_default();
+    Ok(Constraints {
+        pattern,
+        crossings,
+        components,
+        max_crossings: count("--max-crossings")?,
+        max_cusps: count("--max-cusps")?,
+        max_components: count("--max-components")?,
+        forbidden,
+        forbidden_at,
+        seed,
+        reduced: options.contains_key("--reduced"),
+        space_efficient: options.contains_key("--space-efficient"),
+        connected: options.contains_key("--connected"),
+        alternating: options.contains_key("--alternating"),
+        prime: options.contains_key("--prime"),
+        linking_number,
+        conditions,
+    })
+}
 
-        //"Carrying" leftward when we've used every valid tile for the current tile
-        if digit_index[curr_tile] == valid_tiles_for[curr_tile].len() {
-            if curr_tile == 0 { //Ends the program
-                break;
-            }
-            curr_tile -= 1;
-            continue;
+/* Runs `generate`, whose size and output are given either as its first two arguments or as options.
+With --sizes, each size is generated to its own output (see output::sized_path) -- in turn, or all at once with --parallel --
+ and a summary of the counts and timings is printed at the end (and written to --summary, if given). */
+fn generate(arguments: &[&str]) -> Result<()> {
+    let positional = arguments.iter().take_while(|argument| !argument.starts_with("--")).count();
+    let mut options = parse_options(
+        &arguments[positional..],
+        &["--size", "--sizes", "--output", "--summary", "--prefix", "--start", "--start-after", "--end", "--end-after", "--start-rank", "--end-rank", "--max-mosaics", "--max-seconds", "--resume", "--order", "--crossings", "--components", "--max-crossings", "--max-cusps", "--max-components", "--linking-number", "--where", "--pattern", "--forbid-tiles", "--forbid-at", "--seed", "--tiles", "--threads", "--pin", "--metrics", "--log-interval", "--self-check", "--chunk-records", "--chunk-size"],
+        &["--pattern-symmetric", "--reduced", "--space-efficient", "--connected", "--alternating", "--prime", "--parallel", "--manifest", "--verbose", "--ranks", "--polite"],
+    )?;
+    match arguments[..positional] {
+        [size, output_path] => {
+            options.insert("--size".to_string(), size.to_string());
+            options.insert("--output".to_string(), output_path.to_string());
         }
+        [] => {}
+        _ => return Err(Error::new(ErrorKind::InvalidInput, "generate takes a size and an output path")),
+    }
+    let (sizes, output_path) = match (options.get("--size"), options.get("--sizes"), options.get("--output")) {
+        (Some(_), Some(_), _) => return Err(Error::new(ErrorKind::InvalidInput, "generate takes either --size or --sizes, not both")),
+        (Some(size), None, Some(output_path)) => match size.parse() {
+            Ok(size) if size > 0 => (vec![size], output_path.as_str()),
+            _ => return Err(invalid_value("--size", size)),
+        },
+        (None, Some(sizes), Some(output_path)) => (parse_sizes(sizes)?, output_path.as_str()),
+        _ => return Err(Error::new(ErrorKind::InvalidInput, "generate needs a size and an output path")),
+    };
+    let batch = options.contains_key("--sizes");
+    if batch && (output::is_stdout(output_path) || output_path.starts_with("unix:")) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("output {} can't be split into an output per size", output_path)));
+    }
+    //Outputs not already named by size with a {size} placeholder have the size added to their names
+    let output_paths = sizes
+        .iter()
+        .map(|&size| {
+            let path = output::expand_path(output_path, Some(size), "txt")?;
+            Ok(if batch && !output_path.contains("{size}") { output::sized_path(&path, size) } else { path })
+        })
+        .collect::<Result<Vec<String>>>()?;
+    let chunking = chunking(&options)?;
+    let bounds = bounds(&options)?;
+    let constraints = constraints(&options)?;
+    if batch && bounds.resume.is_some() {
+        return Err(Error::new(ErrorKind::InvalidInput, "--resume carries on a run of a single size, so can't be used with --sizes"));
+    }
+    let all_metrics: Vec<Arc<Metrics>> = sizes.iter().map(|&size| Arc::new(Metrics::new(size))).collect();
+    if let Some(address) = options.get("--metrics") {
+        metrics::serve(all_metrics.clone(), address)?;
+    }
+    //--log-interval sets how often --verbose logs progress, and implies it
+    let log_interval = match options.get("--log-interval") {
+        Some(value) => Some(value.parse().ok().filter(|&seconds: &f64| seconds > 0.0).ok_or_else(|| invalid_value("--log-interval", value))?),
+        None => options.contains_key("--verbose").then_some(10.0),
+    };
+    if let Some(interval) = log_interval {
+        metrics::log(all_metrics.clone(), interval);
+    }
+    let metrics = |index: usize| (options.contains_key("--metrics") || log_interval.is_some()).then(|| all_metrics[index].as_ref());
+    let self_check = match options.get("--self-check").map(String::as_str) {
+        Some("all") => Some(1.0),
+        Some(value) => Some(value.parse().ok().filter(|&probability: &f64| probability > 0.0 && probability <= 1.0).ok_or_else(|| invalid_value("--self-check", value))?),
+        None => None,
+    };
+    let emit = EmitOptions { ranks: options.contains_key("--ranks"), self_check };
+    let threads = options.get("--threads").map(|count| count.parse().ok().filter(|&count: &usize| count > 0).ok_or_else(|| invalid_value("--threads", count))).transpose()?.unwrap_or(1);
+    let placement = options.get("--pin").map(|placement| placement.parse()).transpose()?.unwrap_or_default();
+    let tile_set = match options.get("--tiles") {
+        Some(tiles) => parse_tiles("--tiles", tiles)?.iter().fold(0, |set: TileSet, &tile| set | 1 << tile),
+        None => ALL_TILES,
+    };
+    let configs: Vec<GeneratorConfig> = (0..sizes.len())
+        .map(|index| {
+            GeneratorConfig::new(sizes[index], &output_paths[index])
+                .with_tile_set(tile_set)
+                .with_constraints(constraints.clone())
+                .with_bounds(bounds.clone())
+                .with_threads(threads)
+                .with_placement(placement)
+                .with_polite(options.contains_key("--polite"))
+                .with_chunking(chunking)
+                .with_emit(emit)
+                .with_metrics(metrics(index))
+        })
+        .collect();
+    //Every size's settings are checked before any is generated
+    for config in &configs {
+        config.validate()?;
+    }
 
-        //Move to next tile in list of valid tiles
-        mosaic[curr_tile] = valid_tiles_for[curr_tile][digit_index[curr_tile]];
-        digit_index[curr_tile] += 1;
-        if curr_tile < vector_length {
-            curr_tile += 1;
-            rightward = true;
+    let now = Instant::now();
+    let run = |index: usize| -> Result<(Generated, f64)> {
+        let started = Instant::now();
+        let generated = mosaic_gen(&configs[index])?;
+        Ok((generated, started.elapsed().as_secs_f64()))
+    };
+    let results: Vec<(Generated, f64)> = if options.contains_key("--parallel") {
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..sizes.len()).map(|index| scope.spawn(move || run(index))).collect();
+            handles.into_iter().map(|handle| handle.join().expect("generation thread panicked")).collect::<Result<_>>()
+        })?
+    } else {
+        (0..sizes.len()).map(run).collect::<Result<_>>()?
+    };
+    let elapsed = now.elapsed().as_secs_f64();
+    if log_interval.is_some() {
+        for metrics in &all_metrics {
+            eprintln!("{}", metrics.log_line("done", (0.0, 0, 0)).0);
+        }
+    }
+    if !batch {
+        let mut message = match &results[0].0 {
+            Generated { count, resume: Some(token), .. } => format!("Stopped after {} mosaics ({:.6} s), resume with --resume {}", count, elapsed, token),
+            Generated { resume: None, .. } => format!("Generation complete! ({:.6} s)", elapsed),
+        };
+        if let Some(self_checked) = results[0].0.self_checked {
+            message += &format!(", {} mosaics self-checked", self_checked);
         }
+        report(&output_paths[0], message);
+        return Ok(());
+    }
+
+    //Sizes stopped by a limit have the token to resume them from, and the others "-"
+    let mut summary = String::from("size\tmosaics\tseconds\toutput\tresume\n");
+    for ((size, (generated, seconds)), path) in sizes.iter().zip(&results).zip(&output_paths) {
+        let resume = generated.resume.as_ref().map_or("-".to_string(), |token| token.to_string());
+        summary += &format!("{}\t{}\t{:.6}\t{}\t{}\n", size, generated.count, seconds, path, resume);
+    }
+    summary += &format!("total\t{}\t{:.6}\t\t\n", results.iter().map(|(generated, _)| generated.count).sum::<u64>(), elapsed);
+    print!("{}", summary);
+    if let Some(summary_path) = options.get("--summary") {
+        let mut summary_output = output::create(&output::expand_path(summary_path, None, "tsv")?)?;
+        summary_output.write_all(summary.as_bytes())?;
+        summary_output.finish()?;
     }
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,169 @@
+/*
+Manifests of chunked outputs (see ChunkedSink in output.rs), listing each chunk of the output with the ranks of the records it holds and its SHA-256 checksum,
+ so chunks can be processed in parallel and checked after being copied between machines.
+A manifest is a tab separated text file: comment lines start with # (the first few give the version of mosaic_gen and the parameters of the run), then there's a header line,
+ then a line per chunk in output order
+ chunk    first_rank    end_rank    records    bytes    sha256
+where chunk is the file name of the chunk (chunks sit alongside the manifest), and the chunk holds the records with ranks first_rank to end_rank - 1 of the full output.
+Checksums are of the chunk contents as written, before any compression by the output (so compressed uploads should be decompressed before verifying).
+*/
+
+use crate::output;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Result, Write};
+use std::path::Path;
+
+pub struct ChunkRecord {
+    pub path: String,
+    pub first_rank: u64,
+    pub records: u64,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+pub struct Manifest {
+    pub parameters: Vec<(String, String)>,
+    pub chunks: Vec<ChunkRecord>,
+}
+
+const HEADER: &str = "chunk\tfirst_rank\tend_rank\trecords\tbytes\tsha256";
+
+pub fn hex_digest(hasher: Sha256) -> String {
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl Manifest {
+    pub fn write(&self, path: &str) -> Result<()> {
+        let mut output_buffer = output::create(path)?;
+        writeln!(output_buffer, "#mosaic_gen chunk manifest")?;
+        writeln!(output_buffer, "#version: {}", env!("CARGO_PKG_VERSION"))?;
+        for (name, value) in &self.parameters {
+            writeln!(output_buffer, "#{}: {}", name, value)?;
+        }
+        writeln!(output_buffer, "{}", HEADER)?;
+        for chunk in &self.chunks {
+            writeln!(
+                output_buffer,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                chunk.path,
+                chunk.first_rank,
+                chunk.first_rank + chunk.records,
+                chunk.records,
+                chunk.bytes,
+                chunk.sha256
+            )?;
+        }
+        output_buffer.finish()
+    }
+
+    pub fn read(path: &str) -> Result<Manifest> {
+        let invalid = |line: &str| Error::new(ErrorKind::InvalidData, format!("invalid manifest line \"{}\"", line));
+        let mut manifest = Manifest { parameters: Vec::new(), chunks: Vec::new() };
+        let mut seen_header = false;
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some((name, value)) = comment.split_once(": ") {
+                    manifest.parameters.push((name.to_string(), value.to_string()));
+                }
+            } else if line == HEADER {
+                seen_header = true;
+            } else if !line.trim().is_empty() {
+                let fields: Vec<&str> = line.split('\t').collect();
+                let number = |index: usize| fields[index].parse::<u64>().map_err(|_| invalid(&line));
+                if !seen_header || fields.len() != 6 || number(2)? != number(1)? + number(3)? {
+                    return Err(invalid(&line));
+                }
+                manifest.chunks.push(ChunkRecord { path: fields[0].to_string(), first_rank: number(1)?, records: number(3)?, bytes: number(4)?, sha256: fields[5].to_string() });
+            }
+        }
+        if !seen_header {
+            return Err(Error::new(ErrorKind::InvalidData, format!("{} is missing the manifest header", path)));
+        }
+        Ok(manifest)
+    }
+}
+
+//Counts the records and bytes of a chunk, and computes its checksum
+fn scan_chunk(path: &Path) -> Result<(u64, u64, String)> {
+    let mut reader = BufReader::with_capacity(1 << 20, File::open(path)?);
+    let mut hasher = Sha256::new();
+    let (mut records, mut bytes) = (0, 0);
+    let mut buffer = vec![0; 1 << 20];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        records += buffer[..read].iter().filter(|&&byte| byte == b'\n').count() as u64;
+        bytes += read as u64;
+    }
+    Ok((records, bytes, hex_digest(hasher)))
+}
+
+/* Checks every chunk listed in a manifest against its record count, size and checksum, and that the chunks' ranks run on from each other,
+ printing the result for each chunk. Returns the number of records verified, or an error if any chunk failed. */
+pub fn verify_manifest(manifest_path: &str) -> Result<u64> {
+    let manifest = Manifest::read(manifest_path)?;
+    let directory = Path::new(manifest_path).parent().unwrap_or(Path::new(""));
+    let (mut failures, mut next_rank) = (0, 0);
+    for chunk in &manifest.chunks {
+        let problem = match scan_chunk(&directory.join(&chunk.path)) {
+            Err(error) => Some(error.to_string()),
+            Ok(_) if chunk.first_rank != next_rank => Some(format!("starts at rank {}, but the previous chunk ended at {}", chunk.first_rank, next_rank)),
+            Ok((records, _, _)) if records != chunk.records => Some(format!("has {} records, expected {}", records, chunk.records)),
+            Ok((_, bytes, _)) if bytes != chunk.bytes => Some(format!("has {} bytes, expected {}", bytes, chunk.bytes)),
+            Ok((_, _, sha256)) if sha256 != chunk.sha256 => Some(format!("has checksum {}, expected {}", sha256, chunk.sha256)),
+            Ok(_) => None,
+        };
+        match problem {
+            Some(problem) => {
+                println!("{}: FAILED ({})", chunk.path, problem);
+                failures += 1;
+            }
+            None => println!("{}: ok", chunk.path),
+        }
+        next_rank = chunk.first_rank + chunk.records;
+    }
+    if failures > 0 {
+        return Err(Error::new(ErrorKind::InvalidData, format!("{} of {} chunks failed verification", failures, manifest.chunks.len())));
+    }
+    Ok(next_rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn verification_catches_changed_chunks() {
+        let directory = env::temp_dir().join(format!("mosaic_gen-manifest-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let mut chunks = Vec::new();
+        for (index, contents) in ["2134\n2134\n", "2134\n"].iter().enumerate() {
+            let path = format!("mosaics.{:05}.txt", index);
+            fs::write(directory.join(&path), contents).unwrap();
+            let (records, bytes, sha256) = scan_chunk(&directory.join(&path)).unwrap();
+            chunks.push(ChunkRecord { path, first_rank: 2 * index as u64, records, bytes, sha256 });
+        }
+        //sha256sum of 2134\n
+        assert_eq!(chunks[1].sha256, "532599f2bfb4c9a08ea02938abb41f19d2def2edfe4d20aa2854206a4856d086");
+        let manifest_path = directory.join("mosaics.txt.manifest").to_str().unwrap().to_string();
+        Manifest { parameters: vec![("size".to_string(), "2".to_string())], chunks }.write(&manifest_path).unwrap();
+        let manifest = Manifest::read(&manifest_path).unwrap();
+        assert_eq!(manifest.parameters, [("version".to_string(), env!("CARGO_PKG_VERSION").to_string()), ("size".to_string(), "2".to_string())]);
+        assert_eq!(verify_manifest(&manifest_path).unwrap(), 3);
+
+        //The same size and records, but different tiles
+        fs::write(directory.join("mosaics.00001.txt"), "2143\n").unwrap();
+        assert!(verify_manifest(&manifest_path).is_err());
+        fs::write(&manifest_path, format!("{}\nmosaics.00000.txt\t0\t3\t2\t10\t-\n", HEADER)).unwrap();
+        assert!(Manifest::read(&manifest_path).is_err());
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
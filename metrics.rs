@@ -0,0 +1,156 @@
+/*
+Prometheus metrics for long-running generation jobs, served in the text exposition format from /metrics on the address given with --metrics.
+Each size being generated has its own set of metrics, labelled with the size.
+Counters are totals since the job started, so rates (mosaics or bytes per second, backtracks per second) are left to PromQL's rate().
+The progress gauge is the generator's estimate of the fraction of the search completed (see MosaicGenerator::progress).
+The same metrics can be logged instead, as a JSON object a line on stderr every so often (see log), for analyzing long runs after the fact:
+    {"event":"progress","size":6,"elapsed_seconds":60.0,"mosaics":1000000,"nodes":5300000,"backtracks":4200000,"bytes":37000000,
+     "mosaics_per_second":16500.0,"bytes_per_second":610500.0,"progress":0.0012,"prefix":"000000000021"}
+with the rates taken over the time since the size's last line, and the prefix the first two rows of the last mosaic generated. A size's last line,
+ once it's finished, has the event "done" and rates over the whole run.
+*/
+
+use std::io::{BufRead, BufReader, Result, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub struct Metrics {
+    size: usize,
+    started: OnceLock<Instant>,
+    generated: AtomicU64,
+    bytes_written: AtomicU64,
+    backtracks: AtomicU64,
+    nodes: AtomicU64,
+    progress: AtomicU64, //f64 bits
+    prefix: Mutex<String>,
+    finished: AtomicBool,
+}
+
+impl Metrics {
+    pub fn new(size: usize) -> Metrics {
+        Metrics {
+            size,
+            started: OnceLock::new(),
+            generated: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+            backtracks: AtomicU64::new(0),
+            nodes: AtomicU64::new(0),
+            progress: AtomicU64::new(0.0f64.to_bits()),
+            prefix: Mutex::new(String::new()),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    //Marks the start of generation, which may be some time after the metrics are created when several sizes are generated in turn
+    pub fn start(&self) {
+        self.started.get_or_init(Instant::now);
+    }
+
+    pub fn update(&self, generated: u64, bytes_written: u64, backtracks: u64, nodes: u64, progress: f64, prefix: &str) {
+        self.generated.store(generated, Ordering::Relaxed);
+        self.bytes_written.store(bytes_written, Ordering::Relaxed);
+        self.backtracks.store(backtracks, Ordering::Relaxed);
+        self.nodes.store(nodes, Ordering::Relaxed);
+        self.progress.store(progress.to_bits(), Ordering::Relaxed);
+        *self.prefix.lock().expect("metrics are never poisoned") = prefix.to_string();
+    }
+
+    //Marks the end of generation, after which the size isn't logged again
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.started.get().map_or(0.0, |started| started.elapsed().as_secs_f64())
+    }
+
+    /* The log line (see above) of an event, with rates since an earlier line's elapsed seconds, mosaics and bytes (0s for rates over the whole run).
+    Returns the line with this one's elapsed seconds, mosaics and bytes, for the rates of the next. */
+    pub fn log_line(&self, event: &str, (since, since_mosaics, since_bytes): (f64, u64, u64)) -> (String, (f64, u64, u64)) {
+        let (elapsed, mosaics, bytes) = (self.elapsed(), self.generated.load(Ordering::Relaxed), self.bytes_written.load(Ordering::Relaxed));
+        let rate = |count: u64, since_count: u64| if elapsed > since { count.saturating_sub(since_count) as f64 / (elapsed - since) } else { 0.0 };
+        let line = format!(
+            "{{\"event\":\"{}\",\"size\":{},\"elapsed_seconds\":{:.3},\"mosaics\":{},\"nodes\":{},\"backtracks\":{},\"bytes\":{},\"mosaics_per_second\":{:.1},\"bytes_per_second\":{:.1},\"progress\":{},\"prefix\":\"{}\"}}",
+            event,
+            self.size,
+            elapsed,
+            mosaics,
+            self.nodes.load(Ordering::Relaxed),
+            self.backtracks.load(Ordering::Relaxed),
+            bytes,
+            rate(mosaics, since_mosaics),
+            rate(bytes, since_bytes),
+            f64::from_bits(self.progress.load(Ordering::Relaxed)),
+            self.prefix.lock().expect("metrics are never poisoned")
+        );
+        (line, (elapsed, mosaics, bytes))
+    }
+
+    fn values(&self) -> [(&'static str, &'static str, &'static str, String); 6] {
+        [
+            ("mosaic_gen_mosaics_generated_total", "counter", "Suitably connected mosaics generated", self.generated.load(Ordering::Relaxed).to_string()),
+            ("mosaic_gen_bytes_written_total", "counter", "Bytes of mosaic codes written to the output", self.bytes_written.load(Ordering::Relaxed).to_string()),
+            ("mosaic_gen_backtracks_total", "counter", "Steps back to an earlier tile during the search", self.backtracks.load(Ordering::Relaxed).to_string()),
+            ("mosaic_gen_nodes_total", "counter", "Tiles placed during the search", self.nodes.load(Ordering::Relaxed).to_string()),
+            ("mosaic_gen_progress_ratio", "gauge", "Estimated fraction of the search completed", f64::from_bits(self.progress.load(Ordering::Relaxed)).to_string()),
+            ("mosaic_gen_elapsed_seconds", "gauge", "Time since generation started", self.elapsed().to_string()),
+        ]
+    }
+}
+
+fn exposition(all_metrics: &[Arc<Metrics>]) -> String {
+    let values: Vec<_> = all_metrics.iter().map(|metrics| (metrics.size, metrics.values())).collect();
+    let mut exposition = String::new();
+    for (index, &(name, kind, help, _)) in values[0].1.iter().enumerate() {
+        exposition += &format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind);
+        for (size, size_values) in &values {
+            exposition += &format!("{}{{size=\"{}\"}} {}\n", name, size, size_values[index].3);
+        }
+    }
+    exposition
+}
+
+fn respond(all_metrics: &[Arc<Metrics>], mut stream: TcpStream) -> Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(stream.try_clone()?).read_line(&mut request_line)?;
+    let (status, content_type, body) = match request_line.split_whitespace().nth(1) {
+        Some("/metrics") => ("200 OK", "text/plain; version=0.0.4", exposition(all_metrics)),
+        _ => ("404 Not Found", "text/plain", "metrics are served from /metrics\n".to_string()),
+    };
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, content_type, body.len(), body)?;
+    stream.flush()
+}
+
+//Starts serving the metrics (of one or more sizes) on a background thread, which runs until the process exits
+pub fn serve(all_metrics: Vec<Arc<Metrics>>, address: &str) -> Result<()> {
+    let listener = TcpListener::bind(address)?;
+    eprintln!("Serving metrics on http://{}/metrics", listener.local_addr()?);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(error) = respond(&all_metrics, stream) {
+                eprintln!("Metrics request failed: {}", error);
+            }
+        }
+    });
+    Ok(())
+}
+
+//Starts logging the progress of the sizes being generated to stderr every `interval` seconds on a background thread, which runs until the process exits
+pub fn log(all_metrics: Vec<Arc<Metrics>>, interval: f64) {
+    thread::spawn(move || {
+        let mut previous = vec![(0.0, 0, 0); all_metrics.len()];
+        loop {
+            thread::sleep(Duration::from_secs_f64(interval));
+            for (metrics, previous) in all_metrics.iter().zip(&mut previous) {
+                if metrics.started.get().is_some() && !metrics.finished.load(Ordering::Relaxed) {
+                    let (line, logged) = metrics.log_line("progress", *previous);
+                    eprintln!("{}", line);
+                    *previous = logged;
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,106 @@
+/*
+Searching a mosaic's Legendrian class for small representatives: the one with the fewest nonblank tiles, bounding the Legendrian analogue of the tile number
+ of a knot (the fewest nonblank tiles of any of its mosaics, as studied by Heap and Knowles among others), or the one with the fewest crossings,
+ bounding the least number of crossings of a front of the class.
+The search is breadth first over the moves of moves.rs, the Legendrian Reidemeister moves of reidemeister.rs and the reflection x -> -x, taking mosaics up to translation
+ on the board (each is kept moved to the corner, see symmetry::cornered, but moved anywhere on the board to find its moves), so the mosaics at depth d are those
+ that the fewest moves reaching them number d. Ties between mosaics as small by one measure are broken by the other.
+A search stops at a depth or a number of mosaics: if it stops because it has run out of mosaics to reach instead, it was exhaustive, and its best mosaic is
+ the smallest of every mosaic on the board the moves reach. That's still only an upper bound over the whole class, since the moves keep to the board and
+ make the Reidemeister moves only in the few shapes of reidemeister.rs -- a larger board (see Mosaic::embed) gives them more room.
+*/
+
+use crate::mosaic::Mosaic;
+use crate::moves::moves;
+use crate::reidemeister::reidemeister_moves;
+use crate::symmetry::cornered;
+use std::collections::HashSet;
+use std::io::{Error, ErrorKind, Result};
+
+const CROSSING: u8 = 9;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Objective {
+    Tiles,
+    Crossings,
+}
+
+impl Objective {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::Tiles => "tiles",
+            Objective::Crossings => "crossings",
+        }
+    }
+
+    //The measure minimized
+    pub fn measure(&self, mosaic: &Mosaic) -> usize {
+        self.key(mosaic).0
+    }
+
+    //What's minimized, as (this measure, the other) -- so the other breaks ties
+    fn key(&self, mosaic: &Mosaic) -> (usize, usize) {
+        let crossings = mosaic.tiles().iter().filter(|&&tile| tile == CROSSING).count();
+        match self {
+            Objective::Tiles => (mosaic.tile_count(), crossings),
+            Objective::Crossings => (crossings, mosaic.tile_count()),
+        }
+    }
+}
+
+pub struct Smallest {
+    pub best: Mosaic,
+    //The measure of the best mosaic that's minimized (see Objective), and the least number of moves reaching it
+    pub value: usize,
+    pub depth: usize,
+    pub explored: usize,
+    pub exhaustive: bool,
+}
+
+//The mosaics one move away from a mosaic, up to translation
+pub fn neighbors(mosaic: &Mosaic) -> Vec<Mosaic> {
+    let mut neighbors = Vec::new();
+    for translate in mosaic.translations() {
+        neighbors.extend(moves(&translate).into_iter().map(|step| cornered(&step.apply(&translate))));
+        neighbors.extend(reidemeister_moves(&translate).into_iter().map(|step| cornered(&step.apply(&translate))));
+    }
+    neighbors.push(cornered(&mosaic.flip_x()));
+    neighbors
+}
+
+//Searches out to a depth of moves from a suitably connected mosaic, stopping as soon as it's seen `max_mosaics` mosaics
+pub fn smallest(mosaic: &Mosaic, objective: Objective, max_depth: usize, max_mosaics: usize) -> Result<Smallest> {
+    if !mosaic.is_suitably_connected() {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} isn't a suitably connected mosaic", mosaic)));
+    }
+    let start = cornered(mosaic);
+    let mut best_key = objective.key(&start);
+    let mut seen = HashSet::from([start.clone()]);
+    let mut smallest = Smallest { best: start.clone(), value: best_key.0, depth: 0, explored: 1, exhaustive: false };
+    let mut frontier = vec![start];
+    'search: for depth in 1..=max_depth {
+        let mut next = Vec::new();
+        for mosaic in &frontier {
+            for neighbor in neighbors(mosaic) {
+                if seen.len() >= max_mosaics {
+                    break 'search;
+                }
+                if seen.insert(neighbor.clone()) {
+                    let key = objective.key(&neighbor);
+                    if key < best_key {
+                        best_key = key;
+                        (smallest.best, smallest.value, smallest.depth) = (neighbor.clone(), key.0, depth);
+                    }
+                    next.push(neighbor);
+                }
+            }
+        }
+        if next.is_empty() {
+            smallest.exhaustive = true;
+            break;
+        }
+        frontier = next;
+    }
+    smallest.explored = seen.len();
+    Ok(smallest)
+}
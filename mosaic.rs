@@ -0,0 +1,509 @@
+/*
+Basic representation of a single legendrian mosaic, shared by everything that works on mosaics after generation.
+A mosaic is stored as its tiles read left to right, top to bottom -- the same order as the base-10 mosaic codes written by mosaic_gen,
+ so a mosaic code can be converted into a Mosaic (and back) digit by digit.
+Each mosaic also has a stable, content-addressed ID (see Mosaic::id), for cross-referencing records between outputs, databases and papers.
+*/
+
+use crate::error::{Error, Result};
+use crate::pattern::Pattern;
+use crate::symmetry::{canonical, Symmetry, GROUP};
+use crate::tangle::{Rect, Tangle};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::ops::Range;
+use std::str::FromStr;
+
+#[cfg(not(feature = "virtual"))]
+pub const TILE_COUNT: u8 = 10;
+//With the `virtual` feature, tile 10 is a virtual crossing, written v in mosaic codes
+#[cfg(feature = "virtual")]
+pub const TILE_COUNT: u8 = 11;
+pub const VIRTUAL_CROSSING: u8 = 10;
+
+/*
+Connections within each tile -- PARTNER[tile][face] is the face joined to `face` by a strand of the tile, or NONE if no strand touches `face`.
+Faces are assigned as below:
+    1
+ 2 ▇▇ 0
+    3
+*/
+pub const NONE: u8 = 4;
+#[cfg(not(feature = "virtual"))]
+pub const PARTNER: [[u8; 4]; TILE_COUNT as usize] = CLASSICAL_PARTNER;
+//A virtual crossing joins the same faces as a crossing, but its strands just pass through each other
+#[cfg(feature = "virtual")]
+pub const PARTNER: [[u8; 4]; TILE_COUNT as usize] = {
+    let mut partner = [[NONE; 4]; TILE_COUNT as usize];
+    let mut tile = 0;
+    while tile < CLASSICAL_PARTNER.len() {
+        partner[tile] = CLASSICAL_PARTNER[tile];
+        tile += 1;
+    }
+    partner[VIRTUAL_CROSSING as usize] = CLASSICAL_PARTNER[9];
+    partner
+};
+const CLASSICAL_PARTNER: [[u8; 4]; 10] = [
+    [NONE, NONE, NONE, NONE], //0
+    [NONE, NONE, 3, 2],       //1
+    [3, NONE, NONE, 0],       //2
+    [1, 0, NONE, NONE],       //3
+    [NONE, 2, 1, NONE],       //4
+    [2, NONE, 0, NONE],       //5
+    [NONE, 3, NONE, 1],       //6
+    [1, 0, 3, 2],             //7
+    [3, 2, 1, 0],             //8
+    [2, 3, 0, 1],             //9
+];
+
+//The version of the tile numbering above, hashed into mosaic IDs so that IDs of mosaics under any other numbering can't be mistaken for these
+pub const TILE_SET_VERSION: u32 = 1;
+
+//The character for a tile in mosaic codes: its digit, or v for a virtual crossing
+pub fn tile_char(tile: u8) -> char {
+    if tile == VIRTUAL_CROSSING { 'v' } else { (b'0' + tile) as char }
+}
+
+//The tile written as a character of a mosaic code, if it is one
+pub fn parse_tile(c: char) -> Option<u8> {
+    match c {
+        'v' => Some(VIRTUAL_CROSSING),
+        _ => c.to_digit(10).map(|digit| digit as u8),
+    }
+    .filter(|&tile| tile < TILE_COUNT)
+}
+
+//A mosaic ID, written as 32 hex digits
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct MosaicId(pub u128);
+
+impl MosaicId {
+    //The ID of the mosaics with the given canonical form (see Mosaic::id)
+    pub fn of_canonical(canonical: &Mosaic) -> MosaicId {
+        let digest = Sha256::digest(format!("mosaic v{} {}x{} {}", TILE_SET_VERSION, canonical.size, canonical.size, canonical).as_bytes());
+        MosaicId(u128::from_be_bytes(digest[..16].try_into().expect("SHA-256 digests have 32 bytes")))
+    }
+}
+
+impl fmt::Display for MosaicId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Mosaic {
+    size: usize,
+    tiles: Vec<u8>,
+}
+
+impl Mosaic {
+    pub fn from_tiles(size: usize, tiles: Vec<u8>) -> Result<Mosaic> {
+        if tiles.len() != size * size {
+            return Err(Error::Invalid(format!("expected {} tiles for a {}x{} mosaic, found {}", size * size, size, size, tiles.len())));
+        }
+        if let Some(tile) = tiles.iter().find(|&&tile| tile >= TILE_COUNT) {
+            return Err(Error::Invalid(format!("invalid tile {}", tile)));
+        }
+        Ok(Mosaic { size, tiles })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn tiles(&self) -> &[u8] {
+        &self.tiles
+    }
+
+    //Overwrites the tiles with generated ones of the same size (which are always valid), reusing the mosaic rather than allocating another
+    pub fn set_generated_tiles(&mut self, tiles: &[usize]) {
+        for (tile, &generated) in self.tiles.iter_mut().zip(tiles) {
+            *tile = generated as u8;
+        }
+    }
+
+    /* The mosaic's ID: the first 128 bits of the SHA-256 digest of "mosaic v<tile set version> <size>x<size> <canonical form>" (see symmetry::canonical),
+     which only changes if the tile numbering does. Mosaics the same up to symmetry and translation (see symmetry.rs) share an ID,
+     so the translates of a front and its reflection x -> -x share an ID, and so does its Legendrian mirror. */
+    pub fn id(&self) -> MosaicId {
+        MosaicId::of_canonical(&canonical(self))
+    }
+
+    //The tiles in a rectangle of the mosaic, with how the strands leaving the rectangle are joined inside it (see tangle.rs)
+    pub fn submosaic(&self, rect: Rect) -> Result<Tangle> {
+        Tangle::cut(self, rect)
+    }
+
+    //Every rectangle of the mosaic where a smaller mosaic's tiles appear, in order
+    pub fn occurrences(&self, inner: &Mosaic) -> Vec<Rect> {
+        Pattern::from(inner).occurrences(self)
+    }
+
+    //Number of nonblank tiles, whose least value over the mosaics of a knot is its tile number
+    pub fn tile_count(&self) -> usize {
+        self.tiles.iter().filter(|&&tile| tile != 0).count()
+    }
+
+    /* How small the mosaic's front is, for picking the smallest representatives of a class or knot type, least first:
+    the size of the board it trims to, then its nonblank tiles and its crossings, with ties broken by the mosaic itself */
+    pub fn smallness(&self) -> (usize, usize, usize, &Mosaic) {
+        let size = self.bounds().map_or(1, |bounds| bounds.height.max(bounds.width));
+        (size, self.tile_count(), self.tiles.iter().filter(|&&tile| tile == 9).count(), self)
+    }
+
+    //The smallest rectangle of the mosaic holding all its (nonblank) tiles, or None for the empty mosaic
+    pub fn bounds(&self) -> Option<Rect> {
+        let occupied = (0..self.tiles.len()).filter(|&index| self.tiles[index] != 0);
+        let (rows, cols) = (occupied.clone().map(|index| index / self.size), occupied.map(|index| index % self.size));
+        let (top, left) = (rows.clone().min()?, cols.clone().min()?);
+        Some(Rect { row: top, col: left, height: rows.max()? + 1 - top, width: cols.max()? + 1 - left })
+    }
+
+    /* The mosaic on the smallest board holding its tiles: the blank rows and columns around its bounds removed, and its tiles moved to the top left corner
+    of a board as large as the longer side of its bounds (the 1x1 board for the empty mosaic). Translates of a front trim to the same mosaic, whatever the sizes of their boards,
+    so mosaics of different sizes can be compared (or deduplicated) by trimming them first. */
+    pub fn trim(&self) -> Mosaic {
+        let Some(bounds) = self.bounds() else {
+            return Mosaic { size: 1, tiles: vec![0] };
+        };
+        let size = bounds.height.max(bounds.width);
+        let mut tiles = vec![0; size * size];
+        for row in 0..bounds.height {
+            for col in 0..bounds.width {
+                tiles[row * size + col] = self.tiles[(bounds.row + row) * self.size + bounds.col + col];
+            }
+        }
+        Mosaic { size, tiles }
+    }
+
+    /* The mosaic with its tiles shifted down by `rows` and right by `cols` (up and left for negative shifts) on the same board, which the blank space
+    around its bounds has to leave room for. The translate is the same front moved in the plane, so it's in the same class (see symmetry::canonical). */
+    pub fn translate(&self, rows: isize, cols: isize) -> Result<Mosaic> {
+        let mut tiles = vec![0; self.tiles.len()];
+        if let Some(bounds) = self.bounds() {
+            let fits = |start: usize, length: usize, shift: isize| start.checked_add_signed(shift).is_some_and(|start| start + length <= self.size);
+            if !fits(bounds.row, bounds.height, rows) || !fits(bounds.col, bounds.width, cols) {
+                return Err(Error::Invalid(format!("{} can't be moved {} rows and {} columns without leaving the board", self, rows, cols)));
+            }
+            for (index, &tile) in self.tiles.iter().enumerate().filter(|&(_, &tile)| tile != 0) {
+                let (row, col) = ((index / self.size) as isize + rows, (index % self.size) as isize + cols);
+                tiles[row as usize * self.size + col as usize] = tile;
+            }
+        }
+        Ok(Mosaic { size: self.size, tiles })
+    }
+
+    //Every translate of the mosaic on its board (including itself), with the tiles in the top left corner first, then moving them right and down a column and row at a time
+    pub fn translations(&self) -> Vec<Mosaic> {
+        let Some(bounds) = self.bounds() else {
+            return vec![self.clone()];
+        };
+        let (rows, cols) = (self.size - bounds.height, self.size - bounds.width);
+        (0..=rows)
+            .flat_map(|row| (0..=cols).map(move |col| (row as isize - bounds.row as isize, col as isize - bounds.col as isize)))
+            .map(|(rows, cols)| self.translate(rows, cols).expect("the translates of the bounds fit on the board"))
+            .collect()
+    }
+
+    /* The mosaic placed on a blank new_size x new_size board with its top left tile at (row, col) -- the same front on a larger board.
+    Fails unless it fits, and the blank tiles around it meet its edges, so that the larger mosaic is suitably connected too. */
+    pub fn embed(&self, new_size: usize, (row, col): (usize, usize)) -> Result<Mosaic> {
+        if row + self.size > new_size || col + self.size > new_size {
+            return Err(Error::Invalid(format!("{} doesn't fit in a {}x{} mosaic at {}:{}", self, new_size, new_size, row, col)));
+        }
+        if let Some(loose_end) = self.loose_end() {
+            return Err(Error::Invalid(format!("{} can't be embedded in a larger mosaic, as {}", self, self.describe_loose_end(loose_end))));
+        }
+        let mut tiles = vec![0; new_size * new_size];
+        for (index, &tile) in self.tiles.iter().enumerate() {
+            tiles[(row + index / self.size) * new_size + col + index % self.size] = tile;
+        }
+        Ok(Mosaic { size: new_size, tiles })
+    }
+
+    //Index of the tile across `face` from the tile at `index`, or None at the edge of the mosaic
+    pub fn neighbor(&self, index: usize, face: u8) -> Option<usize> {
+        let (row, col) = (index / self.size, index % self.size);
+        match face {
+            0 if col + 1 < self.size => Some(index + 1),
+            1 if row > 0 => Some(index - self.size),
+            2 if col > 0 => Some(index - 1),
+            3 if row + 1 < self.size => Some(index + self.size),
+            _ => None,
+        }
+    }
+
+    /* Checks that every connection point of every tile meets a connection point of the adjacent tile,
+    and that no strand runs off the edge of the mosaic. */
+    pub fn is_suitably_connected(&self) -> bool {
+        self.loose_end().is_none()
+    }
+
+    //The first (index, face) of a tile with a connection point that isn't met by the adjacent tile (or runs off the edge of the mosaic), if there is one
+    pub fn loose_end(&self) -> Option<(usize, u8)> {
+        self.loose_ends().next()
+    }
+
+    //Every (index, face) of a tile with a connection point that isn't met, in order
+    pub fn loose_ends(&self) -> impl Iterator<Item = (usize, u8)> + '_ {
+        let connected = |index: usize, face: u8| PARTNER[self.tiles[index] as usize][face as usize] != NONE;
+        (0..self.tiles.len())
+            .flat_map(|index| (0..4).map(move |face| (index, face)))
+            .filter(move |&(index, face)| connected(index, face) && self.neighbor(index, face).is_none_or(|next| !connected(next, (face + 2) % 4)))
+    }
+
+    //Says where a loose end (see loose_end) is and why it's loose
+    pub fn describe_loose_end(&self, (index, face): (usize, u8)) -> String {
+        let (row, col) = (index / self.size, index % self.size);
+        let side = ["right face", "top", "left face", "bottom"][face as usize];
+        let unmatched = if self.neighbor(index, face).is_some() { "isn't met by the tile next to it" } else { "runs off the edge of the mosaic" };
+        format!("the strand leaving the {} of tile {} at ({}, {}) {}", side, tile_char(self.tiles[index]), row, col, unmatched)
+    }
+
+    /* The images of the mosaic under the Legendrian symmetries (see symmetry.rs). These are the only symmetries of the square remapping every tile to the tile
+    drawing the image of its front: a quarter turn or a reflection in the mosaic's midlines would take the cusps of tiles 7 and 8 to tiles that aren't cusps of a front. */
+    pub fn rotate180(&self) -> Mosaic {
+        Symmetry::Rotation.apply(self)
+    }
+
+    //The reflection x -> -x of the front, Legendrian isotopic to the mosaic
+    pub fn flip_x(&self) -> Mosaic {
+        Symmetry::FlipX.apply(self)
+    }
+
+    //The Legendrian mirror, reflecting the front in z -> -z
+    pub fn mirror(&self) -> Mosaic {
+        Symmetry::FlipZ.apply(self)
+    }
+
+    //The Legendrian symmetries fixing the mosaic exactly, tile for tile (see symmetry.rs) -- a subgroup of the four, always including the identity
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        GROUP.into_iter().filter(|symmetry| symmetry.apply(self) == *self).collect()
+    }
+
+    //Number of distinct mosaics the Legendrian symmetries take this one to, including itself
+    pub fn orbit_size(&self) -> usize {
+        GROUP.len() / self.symmetries().len()
+    }
+
+    /* Whether a blank row or column runs between tiles of the mosaic, so that the strands on either side of it can't meet --
+    the simplest way of seeing that a diagram is split (see Diagram::split for finding its pieces in general). */
+    pub fn has_corridor(&self) -> bool {
+        let size = self.size;
+        let corridor = |occupied: &dyn Fn(usize) -> bool| {
+            let lines: Vec<usize> = (0..size).filter(|&line| occupied(line)).collect();
+            lines.windows(2).any(|pair| pair[1] > pair[0] + 1)
+        };
+        corridor(&|row| (0..size).any(|col| self.tiles[row * size + col] != 0)) || corridor(&|col| (0..size).any(|row| self.tiles[row * size + col] != 0))
+    }
+
+    /* Whether the mosaic is space-efficient, as far as can be told from its parts: it fills its board (no row or column at an edge is blank,
+    so it can't be trimmed onto a smaller board), and no rectangle of tiles holds a lone strand or loop that could be pulled tight onto fewer tiles.
+    Space-efficiency in the mosaic number literature allows any sequence of planar isotopy moves, so this is a necessary condition rather than a complete test. */
+    pub fn is_space_efficient(&self) -> bool {
+        let size = self.size;
+        if self.bounds() != Some(Rect { row: 0, col: 0, height: size, width: size }) {
+            return false;
+        }
+        (0..size).all(|top| {
+            (0..size).all(|left| (top + 1..=size).all(|bottom| (left + 1..=size).all(|right| !self.has_slack(top..bottom, left..right))))
+        })
+    }
+
+    /* Whether the rows and columns given hold a single strand (or loop) and nothing else, using more tiles than it needs:
+    a strand crossing the rectangle needs only a staircase of tiles between the tiles it enters and leaves by, and a loop only four tiles. */
+    fn has_slack(&self, rows: Range<usize>, cols: Range<usize>) -> bool {
+        let inside = |index: &usize| rows.contains(&(index / self.size)) && cols.contains(&(index % self.size));
+        let block = || rows.clone().flat_map(|row| cols.clone().map(move |col| row * self.size + col));
+        if block().any(|index| self.tiles[index] >= 7) {
+            return false;
+        }
+        let occupied = block().filter(|&index| self.tiles[index] != 0).count();
+        if occupied <= 1 {
+            return false;
+        }
+        //Starts from where a strand enters the rectangle, or anywhere on a loop if none does
+        let connected = |&(index, face): &(usize, u8)| PARTNER[self.tiles[index] as usize][face as usize] != NONE;
+        let ends = block().flat_map(|index| (0..4).map(move |face| (index, face))).filter(connected);
+        let entry = ends.clone().find(|&(index, face)| !self.neighbor(index, face).is_some_and(|next| inside(&next)));
+        let (start, start_face) = entry.or_else(|| ends.clone().next()).expect("occupied tiles have strands");
+        let (mut index, mut face, mut visited) = (start, start_face, 1);
+        loop {
+            let exit = PARTNER[self.tiles[index] as usize][face as usize];
+            let Some(next) = self.neighbor(index, exit).filter(inside) else {
+                break;
+            };
+            face = (exit + 2) % 4;
+            if next == start && face == start_face {
+                break;
+            }
+            index = next;
+            visited += 1;
+        }
+        if visited < occupied {
+            return false;
+        }
+        match entry {
+            Some(_) => visited > (start / self.size).abs_diff(index / self.size) + (start % self.size).abs_diff(index % self.size) + 1,
+            None => visited > 4,
+        }
+    }
+}
+
+//Mosaics are parsed from (and printed as) their base-10 mosaic codes
+/* Reads a mosaic code, the tiles left to right then top to bottom (e.g. 2100340000210034), or the same tiles as a grid, a row to a line
+ (as {:#} writes mosaics), with any spaces between tiles ignored, e.g.
+    2100
+    3400
+    0021
+    0034 */
+impl FromStr for Mosaic {
+    type Err = Error;
+
+    fn from_str(code: &str) -> Result<Mosaic> {
+        let code = code.trim();
+        if code.contains('\n') {
+            return parse_grid(code);
+        }
+        let size = (code.len() as f64).sqrt().round() as usize;
+        if size * size != code.len() || size == 0 {
+            return Err(invalid_code(code, "it doesn't have a square number of digits"));
+        }
+        let tiles = code
+            .chars()
+            .map(parse_tile)
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| invalid_code(code, "it contains a character that isn't a tile"))?;
+        Mosaic::from_tiles(size, tiles)
+    }
+}
+
+fn invalid_code(code: &str, reason: &str) -> Error {
+    Error::Parse { what: "mosaic code", text: code.to_string(), reason: reason.to_string() }
+}
+
+//Reads a mosaic written as a grid (see Mosaic::from_str), which needs as many tiles in each row as there are rows
+fn parse_grid(grid: &str) -> Result<Mosaic> {
+    let rows: Vec<&str> = grid.lines().map(str::trim).filter(|row| !row.is_empty()).collect();
+    let mut tiles = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        let row = row
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(parse_tile)
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| invalid_code(grid, &format!("row {} contains a character that isn't a tile", index)))?;
+        if row.len() != rows.len() {
+            return Err(invalid_code(grid, &format!("row {} of its {} rows has {} tiles", index, rows.len(), row.len())));
+        }
+        tiles.extend(row);
+    }
+    Mosaic::from_tiles(rows.len(), tiles)
+}
+
+//Writes the mosaic code, or with {:#} the mosaic as a grid, a row to a line
+impl fmt::Display for Mosaic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, &tile) in self.tiles.iter().enumerate() {
+            if f.alternate() && index > 0 && index % self.size == 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", tile_char(tile))?;
+        }
+        Ok(())
+    }
+}
+
+//Lazily reads the mosaic codes in a file, or stdin for - (one per line, blank lines ignored)
+pub fn read_mosaics(path: &str) -> Result<impl Iterator<Item = Result<Mosaic>>> {
+    let reader: Box<dyn BufRead + Send> = if path == "-" { Box::new(BufReader::new(io::stdin())) } else { Box::new(BufReader::new(File::open(path)?)) };
+    Ok(reader.lines().filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        //Lines written by generate --ranks lead with the mosaic's rank
+        Ok(line) => Some(match line.split_once('\t') {
+            Some((rank, code)) if rank.bytes().all(|byte| byte.is_ascii_digit()) => code.parse(),
+            _ => line.parse(),
+        }),
+        Err(error) => Some(Err(Error::Io(error))),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagram::Diagram;
+
+    //The max-tb right-handed trefoil (tb 1, rot 0), the max-tb left-handed trefoil (tb -6, rot ±1) and the once-stabilized unknot (tb -2, rot ±1), from the 5x5 census
+    const KNOTS: [&str; 3] = ["0021025971629943943103554", "0021002971294663759403540", "0000000000000210024600354"];
+
+    //tb and the rotation number of the image, oriented the way the symmetry carries the traversed orientation of the knot
+    fn carried_invariants(mosaic: &Mosaic, symmetry: Symmetry) -> (i32, i32) {
+        let diagram = Diagram::trace(mosaic).unwrap();
+        let image = Diagram::trace(&symmetry.apply(mosaic)).unwrap();
+        let first = symmetry.apply_visit(mosaic.size(), diagram.components[0][0]);
+        let runs_with = image.components[0].contains(&first);
+        (image.tb(), if runs_with { image.rot() } else { -image.rot() })
+    }
+
+    #[test]
+    fn space_efficiency_needs_a_full_board_without_slack() {
+        let space_efficient = |code: &str| code.parse::<Mosaic>().unwrap().is_space_efficient();
+        //The only space-efficient 4x4 mosaic with four crossings
+        assert!(space_efficient("2134") && space_efficient("2121399429913434"));
+        /* The once-stabilized unknot can be trimmed, the unknot round the edge of a 3x3 board pulled onto a 2x2 one,
+         and the strand round the bottom right corner of the max-tb right-handed trefoil pulled onto two tiles */
+        assert!(!space_efficient(KNOTS[2]) && !space_efficient("251606354") && !space_efficient(KNOTS[0]));
+    }
+
+    #[test]
+    fn transforms_keep_tb_and_keep_or_negate_rot() {
+        for code in KNOTS {
+            let mosaic: Mosaic = code.parse().unwrap();
+            let diagram = Diagram::trace(&mosaic).unwrap();
+            let (tb, rot) = (diagram.tb(), diagram.rot());
+            assert_eq!(mosaic.flip_x(), Symmetry::FlipX.apply(&mosaic));
+            assert_eq!(mosaic.mirror(), Symmetry::FlipZ.apply(&mosaic));
+            assert_eq!(mosaic.rotate180(), Symmetry::Rotation.apply(&mosaic));
+            assert_eq!(carried_invariants(&mosaic, Symmetry::FlipX), (tb, rot), "x on {}", code);
+            assert_eq!(carried_invariants(&mosaic, Symmetry::FlipZ), (tb, -rot), "z on {}", code);
+            assert_eq!(carried_invariants(&mosaic, Symmetry::Rotation), (tb, -rot), "xz on {}", code);
+        }
+    }
+
+    #[test]
+    fn knots_have_the_census_invariants() {
+        let invariants: Vec<(i32, i32)> = KNOTS.iter().map(|code| Diagram::trace(&code.parse().unwrap()).map(|diagram| (diagram.tb(), diagram.rot().abs())).unwrap()).collect();
+        assert_eq!(invariants, [(1, 0), (-6, 1), (-2, 1)]);
+    }
+
+    #[test]
+    fn transforms_are_involutions() {
+        for code in KNOTS {
+            let mosaic: Mosaic = code.parse().unwrap();
+            assert_eq!(mosaic.flip_x().flip_x(), mosaic);
+            assert_eq!(mosaic.mirror().mirror(), mosaic);
+            assert_eq!(mosaic.rotate180().rotate180(), mosaic);
+            assert_eq!(mosaic.flip_x().mirror(), mosaic.rotate180());
+        }
+    }
+
+    #[test]
+    fn submosaics_and_files_fail_with_the_cause() {
+        let mosaic: Mosaic = KNOTS[0].parse().unwrap();
+        assert_eq!(mosaic.submosaic("0:0+2x2".parse().unwrap()).unwrap().rows(), "00/25");
+        assert!(matches!("0:0+2".parse::<Rect>(), Err(Error::Parse { what: "rectangle", .. })));
+        assert!(matches!(mosaic.submosaic("4:4+2x2".parse().unwrap()), Err(Error::Invalid(_))));
+
+        let path = std::env::temp_dir().join(format!("mosaic_gen-read-test-{}.txt", std::process::id()));
+        std::fs::write(&path, format!("{}\n\n7\t{}\n002\n", KNOTS[0], KNOTS[1])).unwrap();
+        let read: Vec<Result<Mosaic>> = read_mosaics(path.to_str().unwrap()).unwrap().collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.len(), 3);
+        assert_eq!(read[1].as_ref().unwrap(), &KNOTS[1].parse::<Mosaic>().unwrap());
+        assert!(matches!(read[2], Err(Error::Parse { what: "mosaic code", .. })));
+        assert!(matches!(read_mosaics(path.to_str().unwrap()), Err(Error::Io(_))));
+    }
+}
@@ -0,0 +1,199 @@
+/*
+Legendrian planar isotopy moves of mosaics: replacing a 2 x 2 block of tiles with another block that the front inside it is isotopic to, leaving the rest of the mosaic alone.
+Two blocks are interchangeable when
+    strands leave them through the same faces, and any crossings (or virtual crossings) are the same tiles in the same places,
+    the strands join the same ends -- the faces on the edge of the block, and the faces of its crossings -- passing the same cusps in the same order,
+     each from its upper branch to its lower or back the same way (so the front's cusps and their up and down directions are kept),
+    and any loops inside them have the same cusps.
+Each part of the front between its ends is then a crossingless arc with the same cusps in both blocks, so one block's front is isotopic to the other's,
+ through fronts fixed at the edges of the block and its crossings: a move is a Legendrian isotopy, keeping every invariant (and the mosaic suitably connected).
+Moves never change crossings, so they don't include the Legendrian Reidemeister moves, which change the number of crossings (see reidemeister.rs for those).
+The classes of interchangeable blocks are found once, from all the TILE_COUNT^4 blocks with their inner faces matched.
+*/
+
+use crate::mosaic::{tile_char, Mosaic, NONE, PARTNER, TILE_COUNT, VIRTUAL_CROSSING};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+const CROSSING: u8 = 9;
+
+//A 2 x 2 block of tiles, row by row
+pub type Block = [u8; 4];
+
+//The tile across each face of each tile of a block, or None if the face is on the edge of the block
+const INSIDE: [[Option<usize>; 4]; 4] = [[Some(1), None, None, Some(2)], [None, None, Some(0), Some(3)], [Some(3), Some(0), None, None], [None, Some(1), Some(2), None]];
+
+fn is_crossing(tile: u8) -> bool {
+    tile == CROSSING || tile == VIRTUAL_CROSSING
+}
+
+/* The cusp (if any) passed through a tile from face `from` to face `to`, as 0 or 1 for a left cusp traversed downward or upward, and 2 or 3 for a right cusp.
+The front's z axis runs along the mosaic's diagonal from bottom left to top right, so the right face of a left cusp and the top face of a right cusp are their upper branches. */
+fn cusp(from: u8, to: u8) -> Option<u8> {
+    match (from, to) {
+        (0, 3) => Some(0),
+        (3, 0) => Some(1),
+        (1, 2) => Some(2),
+        (2, 1) => Some(3),
+        _ => None,
+    }
+}
+
+//The cusps of a path traversed backwards
+fn reversed(cusps: &[u8]) -> Vec<u8> {
+    cusps.iter().rev().map(|cusp| cusp ^ 1).collect()
+}
+
+//What makes two blocks interchangeable (see above)
+#[derive(PartialEq, Eq, Hash)]
+struct Signature {
+    crossings: [Option<u8>; 4],
+    edge_faces: u16,                       //Faces on the edge of the block that strands leave by, as bits 4 * tile + face
+    paths: Vec<(usize, usize, Vec<u8>)>,   //Ends (4 * tile + face) joined by each path, and its cusps from the first end to the other
+    loops: Vec<Vec<u8>>,                   //Cusps of each loop, from the least of its starting points and directions
+}
+
+//The signature of a block, or None if its inner faces don't match
+fn signature(block: &Block) -> Option<Signature> {
+    let connected = |tile: usize, face: u8| PARTNER[block[tile] as usize][face as usize] != NONE;
+    for (tile, across) in INSIDE.iter().enumerate() {
+        for (face, &next) in (0..).zip(across) {
+            if let Some(next) = next {
+                if connected(tile, face) != connected(next, (face + 2) % 4) {
+                    return None;
+                }
+            }
+        }
+    }
+    let mut visited = [[false; 4]; 4];
+    //Follows a path entering `tile` by `face` until it reaches an edge of the block or a crossing (or comes back round a loop), returning the end it reaches and the cusps on the way
+    let trace = |mut tile: usize, mut face: u8, visited: &mut [[bool; 4]; 4]| {
+        let mut cusps = Vec::new();
+        loop {
+            if is_crossing(block[tile]) || visited[tile][face as usize] {
+                return (4 * tile + face as usize, cusps);
+            }
+            let exit = PARTNER[block[tile] as usize][face as usize];
+            visited[tile][face as usize] = true;
+            visited[tile][exit as usize] = true;
+            cusps.extend(cusp(face, exit));
+            match INSIDE[tile][exit as usize] {
+                Some(next) => (tile, face) = (next, (exit + 2) % 4),
+                None => return (4 * tile + exit as usize, cusps),
+            }
+        }
+    };
+    let mut paths = Vec::new();
+    let mut edge_faces = 0;
+    for tile in 0..4 {
+        for face in (0..4).filter(|&face| connected(tile, face)) {
+            let start = 4 * tile + face as usize;
+            //Paths start at the block's edge faces, or leave a crossing into the block
+            let path = match INSIDE[tile][face as usize] {
+                None => {
+                    edge_faces |= 1 << start;
+                    (!is_crossing(block[tile])).then(|| trace(tile, face, &mut visited))
+                }
+                Some(next) if is_crossing(block[tile]) => Some(trace(next, (face + 2) % 4, &mut visited)),
+                Some(_) => None,
+            };
+            if let Some((end, cusps)) = path.filter(|&(end, _)| start < end) {
+                paths.push((start, end, cusps));
+            }
+        }
+    }
+    paths.sort();
+    let mut loops = Vec::new();
+    for tile in (0..4).filter(|&tile| !is_crossing(block[tile])) {
+        for face in 0..4 {
+            if connected(tile, face) && !visited[tile][face as usize] {
+                //A strand not on any path is on a loop
+                let (_, cusps) = trace(tile, face, &mut visited);
+                let turns = [reversed(&cusps), cusps];
+                loops.push(turns.iter().flat_map(|turn| (0..turn.len().max(1)).map(|start| [&turn[start..], &turn[..start]].concat())).min().unwrap_or_default());
+            }
+        }
+    }
+    loops.sort();
+    let crossings = block.map(|tile| is_crossing(tile).then_some(tile));
+    Some(Signature { crossings, edge_faces, paths, loops })
+}
+
+//The classes of interchangeable blocks with more than one block, and the class of each block in them
+struct Classes {
+    class_of: HashMap<Block, usize>,
+    members: Vec<Vec<Block>>,
+}
+
+fn classes() -> &'static Classes {
+    static CLASSES: OnceLock<Classes> = OnceLock::new();
+    CLASSES.get_or_init(|| {
+        let mut by_signature: HashMap<Signature, Vec<Block>> = HashMap::new();
+        for index in 0..(TILE_COUNT as usize).pow(4) {
+            let block = [0, 1, 2, 3].map(|tile| (index / (TILE_COUNT as usize).pow(3 - tile as u32) % TILE_COUNT as usize) as u8);
+            if let Some(signature) = signature(&block) {
+                by_signature.entry(signature).or_default().push(block);
+            }
+        }
+        let mut members: Vec<Vec<Block>> = by_signature.into_values().filter(|blocks| blocks.len() > 1).collect();
+        members.sort();
+        let class_of = members.iter().enumerate().flat_map(|(class, blocks)| blocks.iter().map(move |&block| (block, class))).collect();
+        Classes { class_of, members }
+    })
+}
+
+//Replacing the 2 x 2 block with top left tile at (row, col) with another
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Move {
+    pub row: usize,
+    pub col: usize,
+    pub from: Block,
+    pub to: Block,
+}
+
+impl Move {
+    //The tiles of a block at (row, col)
+    fn block(mosaic: &Mosaic, row: usize, col: usize) -> Block {
+        let size = mosaic.size();
+        [0, 1, 2, 3].map(|tile| mosaic.tiles()[(row + tile / 2) * size + col + tile % 2])
+    }
+
+    pub fn apply(&self, mosaic: &Mosaic) -> Mosaic {
+        let size = mosaic.size();
+        let mut tiles = mosaic.tiles().to_vec();
+        for (tile, &replacement) in self.to.iter().enumerate() {
+            tiles[(self.row + tile / 2) * size + self.col + tile % 2] = replacement;
+        }
+        Mosaic::from_tiles(size, tiles).expect("moves replace tiles with tiles")
+    }
+
+    //The move undoing this one, for undoing moves in explore
+    pub fn inverse(&self) -> Move {
+        Move { row: self.row, col: self.col, from: self.to, to: self.from }
+    }
+}
+
+//Moves are written as the block's position and its tiles before and after, a row at a time, like (1, 2) 21/34 -> 25/34
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = |block: &Block| format!("{}{}/{}{}", tile_char(block[0]), tile_char(block[1]), tile_char(block[2]), tile_char(block[3]));
+        write!(f, "({}, {}) {} -> {}", self.row, self.col, rows(&self.from), rows(&self.to))
+    }
+}
+
+//Every move of a mosaic, by the position of its block (row by row) and then the block it's replaced with
+pub fn moves(mosaic: &Mosaic) -> Vec<Move> {
+    let classes = classes();
+    let size = mosaic.size();
+    let mut moves = Vec::new();
+    for row in 0..size.saturating_sub(1) {
+        for col in 0..size - 1 {
+            let from = Move::block(mosaic, row, col);
+            if let Some(&class) = classes.class_of.get(&from) {
+                moves.extend(classes.members[class].iter().filter(|&&to| to != from).map(|&to| Move { row, col, from, to }));
+            }
+        }
+    }
+    moves
+}
@@ -0,0 +1,138 @@
+/*
+Oriented legendrian mosaics, generated directly from oriented tiles rather than by orienting the components of each mosaic afterwards.
+Each strand of an oriented tile runs one way or the other, so there are 25 oriented tiles (the blank tile, two of each one-strand tile and four of each two-strand tile),
+ and oriented tiles are suitably connected when every strand leaving a tile enters its neighbor, as well as the strands meeting up as usual.
+An oriented mosaic is written as its mosaic code, a space, and a digit per tile giving the directions of its strands:
+ bit 0 for the strand through the tile's lowest numbered face and bit 1 for its other strand, set when the strand runs from its higher numbered face to its lower one
+ (faces numbered as in mosaic.rs).
+*/
+
+use crate::mosaic::{Mosaic, NONE, PARTNER};
+use crate::output;
+use std::io::{Result, Write};
+use std::sync::OnceLock;
+
+//Direction of a strand at a tile's face
+const UNUSED: u8 = 0;
+const ENTERS: u8 = 1;
+const LEAVES: u8 = 2;
+
+//A tile with directions for its strands, as (tile, directions, state of each face)
+type OrientedTile = (u8, u8, [u8; 4]);
+
+fn oriented_tiles() -> &'static [OrientedTile] {
+    static TILES: OnceLock<Vec<OrientedTile>> = OnceLock::new();
+    TILES.get_or_init(|| {
+        let mut tiles = Vec::new();
+        for (tile, partners) in PARTNER.iter().enumerate() {
+            let strands: Vec<(usize, usize)> = (0..4).filter(|&face| partners[face] != NONE && (partners[face] as usize) > face).map(|face| (face, partners[face] as usize)).collect();
+            for directions in 0..1u8 << strands.len() {
+                let mut faces = [UNUSED; 4];
+                for (strand, &(low, high)) in strands.iter().enumerate() {
+                    let reversed = directions & (1 << strand) != 0;
+                    faces[low] = if reversed { LEAVES } else { ENTERS };
+                    faces[high] = if reversed { ENTERS } else { LEAVES };
+                }
+                tiles.push((tile as u8, directions, faces));
+            }
+        }
+        tiles
+    })
+}
+
+/* Lists of the oriented tiles that could go at a position, indexed by the state of the face of the tile above meeting it, likewise for the tile to its left,
+ and whether it's on the right and bottom edges of the mosaic (where no strand can leave). */
+fn valid_tiles() -> &'static [Vec<usize>] {
+    static TABLE: OnceLock<Vec<Vec<usize>>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let opposite = |state: u8| match state {
+            ENTERS => LEAVES,
+            LEAVES => ENTERS,
+            _ => UNUSED,
+        };
+        let mut table = Vec::new();
+        for index in 0..36 {
+            let (above, left, right_edge, bottom_edge) = (index % 3, index / 3 % 3, index / 9 % 2 == 1, index / 18 == 1);
+            table.push(
+                oriented_tiles()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (_, _, faces))| {
+                        faces[1] == opposite(above as u8) && faces[2] == opposite(left as u8) && !(right_edge && faces[0] != UNUSED) && !(bottom_edge && faces[3] != UNUSED)
+                    })
+                    .map(|(oriented, _)| oriented)
+                    .collect(),
+            );
+        }
+        table
+    })
+}
+
+//Steps through the oriented size x size mosaics, in order of their oriented tiles read left to right, top to bottom
+struct OrientedGenerator {
+    size: usize,
+    tiles: Vec<usize>, //Index into oriented_tiles() of the tile at each position
+    valid: Vec<&'static [usize]>,
+    next: Vec<usize>, //Index into valid[position] of the next tile to try there
+    position: usize,
+    descending: bool, //Whether the search has just moved on to `position`, rather than back to it
+    finished: bool,
+}
+
+impl OrientedGenerator {
+    fn new(size: usize) -> OrientedGenerator {
+        OrientedGenerator { size, tiles: vec![0; size * size], valid: vec![&[]; size * size], next: vec![0; size * size], position: 0, descending: true, finished: false }
+    }
+
+    //Returns the next oriented mosaic, as its tiles and the directions of their strands
+    fn advance(&mut self) -> Option<(Vec<u8>, Vec<u8>)> {
+        let (size, last) = (self.size, self.size * self.size - 1);
+        while !self.finished {
+            let position = self.position;
+            if self.descending {
+                let (row, col) = (position / size, position % size);
+                let above = if row == 0 { UNUSED } else { oriented_tiles()[self.tiles[position - size]].2[3] };
+                let left = if col == 0 { UNUSED } else { oriented_tiles()[self.tiles[position - 1]].2[0] };
+                self.valid[position] = &valid_tiles()[above as usize + 3 * left as usize + 9 * (col == size - 1) as usize + 18 * (row == size - 1) as usize];
+                self.next[position] = 0;
+                self.descending = false;
+            }
+            if self.next[position] == self.valid[position].len() {
+                if position == 0 {
+                    self.finished = true;
+                } else {
+                    self.position -= 1;
+                }
+                continue;
+            }
+            self.tiles[position] = self.valid[position][self.next[position]];
+            self.next[position] += 1;
+            if position == last {
+                let tiles = oriented_tiles();
+                return Some((self.tiles.iter().map(|&tile| tiles[tile].0).collect(), self.tiles.iter().map(|&tile| tiles[tile].1).collect()));
+            }
+            self.position += 1;
+            self.descending = true;
+        }
+        None
+    }
+}
+
+/* Writes every oriented size x size mosaic to output_path, one per line as its mosaic code and directions (leaving out the empty mosaic).
+There's one oriented mosaic for each orientation of each mosaic's components, so this has 2^components lines for each nonempty mosaic that mosaic_gen generates.
+Returns the number of oriented mosaics written. */
+pub fn write_oriented(size: usize, output_path: &str) -> Result<u64> {
+    let mut output_buffer = output::create(output_path)?;
+    let mut generator = OrientedGenerator::new(size);
+    let mut count = 0;
+    while let Some((tiles, directions)) = generator.advance() {
+        if tiles.iter().all(|&tile| tile == 0) {
+            continue;
+        }
+        let mosaic = Mosaic::from_tiles(size, tiles)?;
+        writeln!(output_buffer, "{} {}", mosaic, directions.iter().map(|direction| direction.to_string()).collect::<String>())?;
+        count += 1;
+    }
+    output_buffer.finish()?;
+    Ok(count)
+}
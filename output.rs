@@ -0,0 +1,296 @@
+/*
+Destinations for generated mosaics, census records and exports, so output can go straight to another process instead of an intermediate file. An output path is one of
+ -                  standard output
+ unix:<path>        a connection to the Unix socket listening at <path>
+ s3://bucket/key    S3-compatible object storage, uploaded as it's written (requires the `s3` feature, see s3.rs)
+ anything else      a local file -- which may be a named pipe, so long as something is reading from it
+
+Output paths are templates (see expand_path), so the names of the many files of batch runs and chunked outputs can be generated, like mosaics_{size}x{size}_{date}.{ext}.
+
+Line-based outputs can also be split into numbered chunks (see ChunkedSink), which rotate to a new chunk once a chunk reaches a record or size limit,
+ and are listed with their checksums in a manifest (see manifest.rs).
+*/
+
+use crate::manifest::{hex_digest, ChunkRecord, Manifest};
+#[cfg(feature = "s3")]
+use crate::s3::S3Sink;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufWriter, Error, ErrorKind, Result, Write};
+use std::mem;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub trait Sink: Write {
+    //Completes the output once everything has been written. Local files only need flushing, but uploads aren't visible until they're finished
+    fn finish(&mut self) -> Result<()> {
+        self.flush()
+    }
+}
+
+impl<W: Write> Sink for BufWriter<W> {}
+
+pub fn is_stdout(path: &str) -> bool {
+    path == "-"
+}
+
+pub fn create(path: &str) -> Result<Box<dyn Sink>> {
+    if is_stdout(path) {
+        return Ok(Box::new(BufWriter::new(io::stdout())));
+    }
+    #[cfg(unix)]
+    if let Some(socket_path) = path.strip_prefix("unix:") {
+        return Ok(Box::new(BufWriter::new(UnixStream::connect(socket_path)?)));
+    }
+    #[cfg(feature = "s3")]
+    if let Some(location) = path.strip_prefix("s3://") {
+        return Ok(Box::new(S3Sink::create(location)?));
+    }
+    Ok(Box::new(BufWriter::new(File::create(path)?)))
+}
+
+//Current UTC time as (year, month, day, seconds into the day)
+pub fn utc_now() -> (i64, i64, i64, i64) {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).expect("system clock is after 1970").as_secs() as i64;
+    //Civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = seconds.div_euclid(86400) + 719468;
+    let (era, day_of_era) = (z.div_euclid(146097), z.rem_euclid(146097));
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day, seconds.rem_euclid(86400))
+}
+
+/* Fills in the placeholders of an output path template
+ {size}    the size of the mosaics written (only for outputs of a single size)
+ {date}    the current UTC date, as YYYY-MM-DD
+ {time}    the current UTC time, as HHMMSS
+ {ext}     the usual extension of the output, `extension` -- with .gz added for S3 outputs, which are compressed
+Paths without placeholders are left as they are. */
+pub fn expand_path(template: &str, size: Option<usize>, extension: &str) -> Result<String> {
+    let (year, month, day, time) = utc_now();
+    let mut path = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        path += &rest[..start];
+        let end = rest[start..].find('}').map(|end| start + end).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("unclosed {{ in output path {}", template)))?;
+        match &rest[start + 1..end] {
+            "size" => path += &size.ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("output path {} uses {{size}}, but the output isn't of a single size", template)))?.to_string(),
+            "date" => path += &format!("{:04}-{:02}-{:02}", year, month, day),
+            "time" => path += &format!("{:02}{:02}{:02}", time / 3600, time / 60 % 60, time % 60),
+            "ext" if template.starts_with("s3://") => path += &format!("{}.gz", extension),
+            "ext" => path += extension,
+            placeholder => return Err(Error::new(ErrorKind::InvalidInput, format!("unknown placeholder {{{}}} in output path {}", placeholder, template))),
+        }
+        rest = &rest[end + 1..];
+    }
+    path += rest;
+    Ok(path)
+}
+
+/* How an output is split into chunks -- by default outputs aren't chunked.
+Chunked outputs always get a manifest, and `manifest` asks for one for an unchunked output too (listing the output as its only chunk). */
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chunking {
+    pub records: Option<u64>,
+    pub bytes: Option<u64>,
+    pub manifest: bool,
+}
+
+impl Chunking {
+    fn is_chunked(&self) -> bool {
+        self.records.is_some() || self.bytes.is_some()
+    }
+
+    fn limit_reached(&self, records: u64, bytes: u64) -> bool {
+        self.records.is_some_and(|limit| records >= limit) || self.bytes.is_some_and(|limit| bytes >= limit)
+    }
+
+    //Parameters listed in the manifest
+    fn parameters(&self) -> Vec<(String, String)> {
+        let mut parameters = Vec::new();
+        if let Some(records) = self.records {
+            parameters.push(("chunk_records".to_string(), records.to_string()));
+        }
+        if let Some(bytes) = self.bytes {
+            parameters.push(("chunk_bytes".to_string(), bytes.to_string()));
+        }
+        parameters
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    &path[path.rfind('/').map_or(0, |slash| slash + 1)..]
+}
+
+//Inserts a tag into a path before the file's extensions (mosaics.txt.gz -> mosaics.<tag>.txt.gz)
+fn tag_path(path: &str, tag: &str) -> String {
+    let name_start = path.len() - file_name(path).len();
+    match path[name_start..].find('.') {
+        Some(dot) if dot > 0 => format!("{}.{}{}", &path[..name_start + dot], tag, &path[name_start + dot..]),
+        _ => format!("{}.{}", path, tag),
+    }
+}
+
+//Path of the output for one size of a batch of sizes (mosaics.txt -> mosaics.5x5.txt)
+pub fn sized_path(path: &str, size: usize) -> String {
+    tag_path(path, &format!("{}x{}", size, size))
+}
+
+//Path of the output for one mosaic of many, by its rank (mosaics.svg -> mosaics.12.svg)
+pub fn ranked_path(path: &str, rank: u64) -> String {
+    tag_path(path, &rank.to_string())
+}
+
+/* Output split into numbered chunks, each a separate output of its own, with a manifest listing the chunks written to <path>.manifest once the output is finished.
+Chunks only end at line ends, so a chunk may run past the size limit by up to a line. */
+pub struct ChunkedSink {
+    path: String,
+    chunking: Chunking,
+    parameters: Vec<(String, String)>,
+    current: Option<Box<dyn Sink>>,
+    chunk_records: u64,
+    chunk_bytes: u64,
+    chunk_hasher: Sha256,
+    chunks: Vec<ChunkRecord>,
+}
+
+impl ChunkedSink {
+    //Path of the chunk with the given index, numbered before the file's extensions (mosaics.txt.gz -> mosaics.00003.txt.gz)
+    fn chunk_path(&self, index: usize) -> String {
+        if !self.chunking.is_chunked() {
+            return self.path.clone();
+        }
+        tag_path(&self.path, &format!("{:05}", index))
+    }
+
+    //Finishes the current chunk, if there is one -- the next chunk is only created once there's something to write to it
+    fn rotate(&mut self) -> Result<()> {
+        if let Some(mut current) = self.current.take() {
+            current.finish()?;
+            let path = self.chunk_path(self.chunks.len());
+            self.chunks.push(ChunkRecord {
+                path: file_name(&path).to_string(),
+                first_rank: self.chunks.last().map_or(0, |chunk| chunk.first_rank + chunk.records),
+                records: self.chunk_records,
+                bytes: self.chunk_bytes,
+                sha256: hex_digest(mem::take(&mut self.chunk_hasher)),
+            });
+            self.chunk_records = 0;
+            self.chunk_bytes = 0;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ChunkedSink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.current.is_none() {
+            self.current = Some(create(&self.chunk_path(self.chunks.len()))?);
+        }
+        //Writes up to the end of the line that fills the chunk (if any), then rotates
+        let mut end = buf.len();
+        let mut full = false;
+        for (index, &byte) in buf.iter().enumerate() {
+            if byte == b'\n' {
+                self.chunk_records += 1;
+                if self.chunking.limit_reached(self.chunk_records, self.chunk_bytes + index as u64 + 1) {
+                    end = index + 1;
+                    full = true;
+                    break;
+                }
+            }
+        }
+        self.current.as_mut().expect("a chunk was just created").write_all(&buf[..end])?;
+        self.chunk_hasher.update(&buf[..end]);
+        self.chunk_bytes += end as u64;
+        if full {
+            self.rotate()?;
+        }
+        Ok(end)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.current.as_mut().map_or(Ok(()), |current| current.flush())
+    }
+}
+
+impl Sink for ChunkedSink {
+    fn finish(&mut self) -> Result<()> {
+        //An empty output still gets an (empty) chunk, so there's always something to process
+        if self.chunks.is_empty() && self.current.is_none() {
+            self.current = Some(create(&self.chunk_path(0))?);
+        }
+        self.rotate()?;
+        let manifest = Manifest { parameters: mem::take(&mut self.parameters), chunks: mem::take(&mut self.chunks) };
+        manifest.write(&format!("{}.manifest", self.path))
+    }
+}
+
+/* Creates an output that's split into chunks according to `chunking`, or a single output if it isn't chunked.
+`parameters` describe the run producing the output, and are listed in the manifest (if there is one) along with the chunking. */
+pub fn create_chunked(path: &str, chunking: &Chunking, parameters: &[(&str, String)]) -> Result<Box<dyn Sink>> {
+    if !chunking.is_chunked() && !chunking.manifest {
+        return create(path);
+    }
+    if is_stdout(path) || path.starts_with("unix:") {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("output {} can't be chunked or given a manifest", path)));
+    }
+    let mut all_parameters: Vec<(String, String)> = parameters.iter().map(|(name, value)| (name.to_string(), value.clone())).collect();
+    all_parameters.extend(chunking.parameters());
+    Ok(Box::new(ChunkedSink {
+        path: path.to_string(),
+        chunking: *chunking,
+        parameters: all_parameters,
+        current: None,
+        chunk_records: 0,
+        chunk_bytes: 0,
+        chunk_hasher: Sha256::new(),
+        chunks: Vec::new(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn chunks_rotate_at_line_ends() {
+        let directory = env::temp_dir().join(format!("mosaic_gen-chunks-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("mosaics.txt").to_str().unwrap().to_string();
+        let chunk = |index: usize| fs::read_to_string(directory.join(format!("mosaics.{:05}.txt", index))).unwrap();
+
+        //Three records to a chunk, written in pieces that don't line up with the records
+        let mut output = create_chunked(&path, &Chunking { records: Some(3), ..Chunking::default() }, &[("size", "2".to_string())]).unwrap();
+        output.write_all(b"2134\n2").unwrap();
+        output.write_all(b"134\n2134\n2134\n2134\n21").unwrap();
+        output.write_all(b"34\n").unwrap();
+        output.finish().unwrap();
+        assert_eq!((chunk(0), chunk(1)), ("2134\n".repeat(3), "2134\n".repeat(3)));
+        let manifest = Manifest::read(&format!("{}.manifest", path)).unwrap();
+        let ranks: Vec<(&str, u64, u64)> = manifest.chunks.iter().map(|chunk| (chunk.path.as_str(), chunk.first_rank, chunk.records)).collect();
+        assert_eq!(ranks, [("mosaics.00000.txt", 0, 3), ("mosaics.00001.txt", 3, 3)]);
+        assert!(manifest.parameters.contains(&("size".to_string(), "2".to_string())) && manifest.parameters.contains(&("chunk_records".to_string(), "3".to_string())));
+
+        //A chunk ends with the line that takes it to the size limit
+        let mut output = create_chunked(&path, &Chunking { bytes: Some(8), ..Chunking::default() }, &[]).unwrap();
+        output.write_all(b"2134\n2134\n2134\n").unwrap();
+        output.finish().unwrap();
+        assert_eq!((chunk(0), chunk(1)), ("2134\n2134\n".to_string(), "2134\n".to_string()));
+        assert_eq!(Manifest::read(&format!("{}.manifest", path)).unwrap().chunks.len(), 2);
+        assert!(create_chunked("-", &Chunking { records: Some(3), ..Chunking::default() }, &[]).is_err());
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
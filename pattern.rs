@@ -0,0 +1,164 @@
+/*
+Sub-mosaic patterns: rectangular blocks of tiles that a mosaic contains if the block appears somewhere in it, e.g. to find every mosaic extending a local configuration.
+A pattern is written as its rows of tiles, top to bottom and separated by /, with . for a position that can be any tile, e.g.
+    99/99   a 2 x 2 block of crossings
+    5.5     two horizontal strands with any tile between them
+Patterns can also be matched up to the symmetries of the square (rotations and reflections), which act on the tiles as well as their positions.
+The generator prunes with patterns (see MosaicGenerator::with_pattern), abandoning a partial mosaic once every placement of the pattern contradicts its tiles so far.
+*/
+
+use crate::mosaic::{parse_tile, Mosaic, NONE, PARTNER, VIRTUAL_CROSSING};
+use crate::tangle::Rect;
+use std::io::{Error, ErrorKind, Result};
+use std::str::FromStr;
+
+//One orientation of a pattern
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Block {
+    height: usize,
+    width: usize,
+    tiles: Vec<Option<usize>>, //Row by row, None for any tile
+}
+
+impl Block {
+    //Builds a block of the given dimensions from the (row, column) of the original block at each position, moving the faces of each tile with `face_map`
+    fn transformed(&self, height: usize, width: usize, source: impl Fn(usize, usize) -> (usize, usize), face_map: [u8; 4]) -> Block {
+        let mut tiles = Vec::with_capacity(height * width);
+        for row in 0..height {
+            for col in 0..width {
+                let (source_row, source_col) = source(row, col);
+                tiles.push(self.tiles[source_row * self.width + source_col].map(|tile| transform_tile(tile, face_map)));
+            }
+        }
+        Block { height, width, tiles }
+    }
+
+    //Rotated a quarter turn anticlockwise
+    fn rotated(&self) -> Block {
+        self.transformed(self.width, self.height, |row, col| (col, self.width - 1 - row), [1, 2, 3, 0])
+    }
+
+    //Reflected left to right
+    fn reflected(&self) -> Block {
+        self.transformed(self.height, self.width, |row, col| (row, self.width - 1 - col), [2, 1, 0, 3])
+    }
+
+    //Whether the block matches the tiles of a size x size mosaic with its top left corner at `corner`, considering only the first `filled` tiles
+    fn matches_at(&self, tiles: &[usize], size: usize, corner: usize, filled: usize) -> bool {
+        (0..self.height).all(|row| {
+            (0..self.width).all(|col| {
+                let index = corner + row * size + col;
+                index >= filled || self.tiles[row * self.width + col].is_none_or(|tile| tile == tiles[index])
+            })
+        })
+    }
+}
+
+//The tile whose strands are those of `tile` with each face moved to face_map[face]
+pub fn transform_tile(tile: usize, face_map: [u8; 4]) -> usize {
+    //A virtual crossing joins the same faces as a crossing, but stays virtual
+    if tile == VIRTUAL_CROSSING as usize {
+        return tile;
+    }
+    let mut partners = [NONE; 4];
+    for face in 0..4 {
+        let partner = PARTNER[tile][face];
+        if partner != NONE {
+            partners[face_map[face] as usize] = face_map[partner as usize];
+        }
+    }
+    PARTNER.iter().position(|&tile_partners| tile_partners == partners).expect("the tiles are closed under symmetries of the square")
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+    blocks: Vec<Block>, //Every orientation the pattern is matched in
+}
+
+impl Pattern {
+    //Matches the pattern in all of its rotations and reflections, as well as as given
+    pub fn symmetric(mut self) -> Pattern {
+        let mut blocks = Vec::new();
+        let mut block = self.blocks[0].clone();
+        for _ in 0..4 {
+            for oriented in [block.clone(), block.reflected()] {
+                if !blocks.contains(&oriented) {
+                    blocks.push(oriented);
+                }
+            }
+            block = block.rotated();
+        }
+        self.blocks = blocks;
+        self
+    }
+
+    /* Whether a size x size mosaic whose first `filled` tiles are those given could contain the pattern, i.e. whether some placement of the pattern
+    doesn't contradict those tiles. With every tile filled, this is whether the mosaic contains the pattern. */
+    pub fn could_contain(&self, tiles: &[usize], size: usize, filled: usize) -> bool {
+        self.blocks.iter().any(|block| {
+            if block.height > size || block.width > size {
+                return false;
+            }
+            //Placements starting among the unfilled tiles can't be contradicted yet
+            if filled <= (size - block.height) * size + size - block.width {
+                return true;
+            }
+            (0..=size - block.height).any(|row| (0..=size - block.width).any(|col| block.matches_at(tiles, size, row * size + col, filled)))
+        })
+    }
+
+    pub fn is_contained_in(&self, mosaic: &Mosaic) -> bool {
+        let tiles: Vec<usize> = mosaic.tiles().iter().map(|&tile| tile as usize).collect();
+        self.could_contain(&tiles, mosaic.size(), tiles.len())
+    }
+
+    //Every rectangle of a mosaic holding the pattern (in any of its orientations), in order
+    pub fn occurrences(&self, mosaic: &Mosaic) -> Vec<Rect> {
+        let (tiles, size): (Vec<usize>, usize) = (mosaic.tiles().iter().map(|&tile| tile as usize).collect(), mosaic.size());
+        let mut occurrences: Vec<Rect> = self
+            .blocks
+            .iter()
+            .filter(|block| block.height <= size && block.width <= size)
+            .flat_map(|block| {
+                let placements = (0..=size - block.height).flat_map(move |row| (0..=size - block.width).map(move |col| (row, col)));
+                placements.filter(|&(row, col)| block.matches_at(&tiles, size, row * size + col, tiles.len())).map(|(row, col)| Rect { row, col, height: block.height, width: block.width })
+            })
+            .collect();
+        occurrences.sort();
+        occurrences.dedup();
+        occurrences
+    }
+}
+
+//A mosaic's tiles as a pattern, matching just those tiles
+impl From<&Mosaic> for Pattern {
+    fn from(mosaic: &Mosaic) -> Pattern {
+        let tiles = mosaic.tiles().iter().map(|&tile| Some(tile as usize)).collect();
+        Pattern { blocks: vec![Block { height: mosaic.size(), width: mosaic.size(), tiles }] }
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = Error;
+
+    fn from_str(pattern: &str) -> Result<Pattern> {
+        let invalid = || Error::new(ErrorKind::InvalidInput, format!("invalid pattern \"{}\" (rows of tiles or . separated by /)", pattern));
+        let rows: Vec<&str> = pattern.trim().split('/').collect();
+        let width = rows[0].len();
+        if width == 0 || rows.iter().any(|row| row.len() != width) {
+            return Err(invalid());
+        }
+        let tiles = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|tile| match tile {
+                '.' => Some(None),
+                _ => parse_tile(tile).map(|tile| Some(tile as usize)),
+            })
+            .collect::<Option<Vec<Option<usize>>>>()
+            .ok_or_else(invalid)?;
+        Ok(Pattern { blocks: vec![Block { height: rows.len(), width, tiles }] })
+    }
+}
@@ -0,0 +1,364 @@
+/*
+Stream processing of mosaics for shell pipelines: mosaic codes are read from stdin, one per line, put through a sequence of steps, and whatever comes out the other end is written to stdout.
+Lines can also be census records (see census.rs), which are read by their mosaic code. The steps, in the order they're given, are
+    canonicalize            the least image of the mosaic under the Legendrian symmetries and translation (see symmetry::canonical)
+    corner                  the mosaic moved as far up and left as it goes (see symmetry::cornered)
+    trim                    the mosaic on the smallest board holding its tiles (see Mosaic::trim), so trim canonicalize gives translates of a front
+                             on boards of any size the same canonical form, for deduplicating mosaics across sizes
+    embed <size>            the mosaic in the top left corner of a larger blank board (see Mosaic::embed), e.g. to compare mosaics of different sizes by
+                             their IDs on a common board -- failing for mosaics too large or not suitably connected, so put it after valid for mosaics that may not be
+    mirror                  the Legendrian mirror of the mosaic, reflecting the front in z -> -z (transposing the mosaic)
+    flip                    the reflection x -> -x of the front, which is Legendrian isotopic to the mosaic
+    rotate                  the half turn of the front and the mosaic, the Legendrian mirror up to isotopy
+    valid                   keeps only the suitably connected mosaics
+    --where <conditions>    keeps only the mosaics meeting conditions on their invariants, like tb>=1;name=3_1 (see invariant.rs)
+    simplify                writes the mosaic's PD code simplified by Reidemeister I moves and its number of loops without crossings (see export::pd_code),
+                             tab separated, instead of the mosaic -- so it's the last step, and leaves out mosaics without PD codes (virtual or not suitably connected)
+    id                      writes the mosaic followed by its ID (see Mosaic::id), tab separated, so it's also the last step
+    by-type                 groups the mosaics by knot type instead of writing them, and once they're all in, writes a line for each type with its number
+                             of mosaics and its smallest mosaic (trimmed, see Mosaic::smallness), tab separated -- so it's the last step too, leaving out
+                             mosaics that aren't suitably connected. Knots are typed by name (see knots.rs), in order of crossing number, and unnamed knots,
+                             virtual knots and links after them by their Jones polynomials, or for links, which have one for each orientation,
+                             by their Kauffman brackets up to a unit and a power of A
+Mosaics go through the steps in parallel (see pipeline.rs), but come out in the order they went in.
+Canonical forms come from a class store (see store.rs), so canonicalizing a mosaic already seen, or an image of one, is a lookup; with --store <path> before the steps,
+ the store is kept in a file, for canonical forms (and their class numbers) shared across runs.
+With --dedup <expected lines> before the steps, lines are only written the first time they come out, as far as a Bloom filter can tell (see dedup.rs),
+ at --false-positive-rate <rate> (0.001 by default) with the expected number of distinct lines, and exactly with --exact.
+With --memory-limit <size>, the class store, by-type and the exact pass each keep to about that much memory, spilling to temporary files past it
+ (see store.rs, TypeCounts and sort.rs) -- the Bloom filter is sized by --dedup however much memory that takes.
+*/
+
+use crate::dedup::{Dedup, DedupOptions};
+use crate::diagram::Diagram;
+use crate::error::{Error, Result};
+use crate::export::{format_pd, pd_code};
+use crate::invariant::{Condition, Registry};
+use crate::invariants::{jones, kauffman_bracket, Polynomial};
+use crate::knots::{knot_name, table_order};
+use crate::mosaic::{Mosaic, VIRTUAL_CROSSING};
+use crate::output;
+use crate::pipeline::{self, Stages};
+use crate::store::ClassStore;
+use crate::sort::sort_lines;
+use crate::symmetry::cornered;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//Estimated memory used by each knot type counted, beyond its name and smallest mosaic's tiles: the strings, the mosaic, the count and its share of the hash table
+const TYPE_OVERHEAD: u64 = 10 * mem::size_of::<usize>() as u64;
+
+//Numbers the runs of the process spilling knot types, for their files' names
+static SPILL_NUMBER: AtomicUsize = AtomicUsize::new(0);
+
+pub enum Step {
+    Canonicalize,
+    Corner,
+    Trim,
+    Embed(usize),
+    Mirror,
+    Flip,
+    Rotate,
+    Valid,
+    Where(Vec<Condition>),
+    Simplify,
+    Id,
+    ByType,
+}
+
+impl Step {
+    //The steps named by the arguments of the pipe command, in order
+    pub fn parse_all(arguments: &[&str], registry: &Registry) -> Result<Vec<Step>> {
+        let mut steps = Vec::new();
+        let mut arguments = arguments.iter();
+        while let Some(&argument) = arguments.next() {
+            match steps.last() {
+                Some(Step::Simplify) => return Err(Error::Invalid("simplify writes PD codes rather than mosaics, so it has to be the last step".to_string())),
+                Some(Step::Id) => return Err(Error::Invalid("id writes mosaics with their IDs, so it has to be the last step".to_string())),
+                Some(Step::ByType) => return Err(Error::Invalid("by-type writes knot types rather than mosaics, so it has to be the last step".to_string())),
+                _ => {}
+            }
+            steps.push(match argument {
+                "canonicalize" => Step::Canonicalize,
+                "corner" => Step::Corner,
+                "trim" => Step::Trim,
+                "embed" => {
+                    let size = arguments.next().ok_or_else(|| Error::Invalid("embed needs a size".to_string()))?;
+                    Step::Embed(size.parse().map_err(|_| Error::Parse { what: "size for embed", text: size.to_string(), reason: "it should be a number of tiles".to_string() })?)
+                }
+                "mirror" => Step::Mirror,
+                "flip" => Step::Flip,
+                "rotate" => Step::Rotate,
+                "valid" => Step::Valid,
+                "--where" => Step::Where(registry.conditions(arguments.next().ok_or_else(|| Error::Invalid("--where needs conditions".to_string()))?)?),
+                "simplify" => Step::Simplify,
+                "id" => Step::Id,
+                "by-type" => Step::ByType,
+                _ => return Err(Error::Parse {
+                        what: "step",
+                        text: argument.to_string(),
+                        reason: "the steps are canonicalize, corner, trim, embed <size>, mirror, flip, rotate, valid, --where <conditions>, simplify, id and by-type".to_string(),
+                    }),
+            });
+        }
+        Ok(steps)
+    }
+}
+
+//What comes out of the steps for a mosaic: a line to write, or a mosaic to count towards its knot type (see by-type)
+enum Output {
+    Line(String),
+    Typed(String, Mosaic),
+}
+
+//The knot type a suitably connected mosaic is grouped by (see by-type), or None if it isn't one
+fn knot_type(mosaic: &Mosaic) -> Option<String> {
+    let diagram = Diagram::trace(mosaic)?;
+    let kind = if mosaic.tiles().contains(&VIRTUAL_CROSSING) { "virtual " } else { "" };
+    if diagram.component_count() > 1 {
+        //Reversing components multiplies the bracket by (-A^3)^k, so up to a unit and a power of A it doesn't depend on the orientation
+        let bracket = kauffman_bracket(&diagram);
+        let first = bracket.coefficients.iter().position(|&coefficient| coefficient != 0).unwrap_or(0);
+        let sign = bracket.coefficients.get(first).map_or(1, |coefficient| coefficient.signum());
+        let normalized = Polynomial { low: 0, coefficients: bracket.coefficients[first..].iter().map(|&coefficient| sign * coefficient).collect() };
+        return Some(format!("{}link of {} components with bracket {}", kind, diagram.component_count(), normalized.format("A", 1)));
+    }
+    let jones = jones(&diagram)?;
+    match knot_name(&jones).filter(|_| kind.is_empty()) {
+        Some(name) => Some(name),
+        None => Some(format!("{}knot with jones {}", kind, jones.format("t", 2))),
+    }
+}
+
+//What comes out of a mosaic put through the steps, or None if a step leaves it out
+fn process(mut mosaic: Mosaic, steps: &[Step], store: &ClassStore) -> io::Result<Option<Output>> {
+    for step in steps {
+        match step {
+            Step::Canonicalize => mosaic = store.canonical(&mosaic)?,
+            Step::Corner => mosaic = cornered(&mosaic),
+            Step::Trim => mosaic = mosaic.trim(),
+            Step::Embed(size) => mosaic = mosaic.embed(*size, (0, 0))?,
+            Step::Mirror => mosaic = mosaic.mirror(),
+            Step::Flip => mosaic = mosaic.flip_x(),
+            Step::Rotate => mosaic = mosaic.rotate180(),
+            Step::Valid if !mosaic.is_suitably_connected() => return Ok(None),
+            Step::Where(conditions) if !conditions.iter().all(|condition| condition.admits(&mosaic)) => return Ok(None),
+            Step::Simplify => {
+                let Some(diagram) = Diagram::trace(&mosaic).filter(|_| !mosaic.tiles().contains(&VIRTUAL_CROSSING)) else {
+                    return Ok(None);
+                };
+                let (code, loops) = pd_code(&diagram, true);
+                return Ok(Some(Output::Line(format!("{}\t{}", format_pd(&code), loops))));
+            }
+            Step::Id => return Ok(Some(Output::Line(format!("{}\t{}", mosaic, store.id(&mosaic)?)))),
+            Step::ByType => return Ok(knot_type(&mosaic).map(|knot_type| Output::Typed(knot_type, mosaic.trim()))),
+            Step::Valid | Step::Where(_) => {}
+        }
+    }
+    Ok(Some(Output::Line(mosaic.to_string())))
+}
+
+/* The number of mosaics of each knot type and the smallest, for by-type. Over the memory limit, the types counted so far are spilled to a temporary file
+ as lines of the type, count and smallest mosaic, tab separated, and once the mosaics are all in, the file is sorted (see sort.rs) so each type's lines can be added up in turn. */
+struct TypeCounts {
+    types: HashMap<String, (u64, Mosaic)>,
+    used: u64,
+    memory_limit: Option<u64>,
+    //The temporary file spilled to, and the same sorted, removed once the types have been written (or a run fails)
+    path: PathBuf,
+    sorted_path: PathBuf,
+    spilled: Option<BufWriter<File>>,
+}
+
+impl TypeCounts {
+    fn new(memory_limit: Option<u64>) -> TypeCounts {
+        let path = env::temp_dir().join(format!("mosaic_gen-types-{}-{}.txt", process::id(), SPILL_NUMBER.fetch_add(1, Ordering::Relaxed)));
+        TypeCounts { types: HashMap::new(), used: 0, memory_limit, sorted_path: path.with_extension("sorted.txt"), path, spilled: None }
+    }
+
+    fn add(&mut self, knot_type: String, mosaic: Mosaic) -> io::Result<()> {
+        match self.types.get_mut(&knot_type) {
+            Some((count, smallest)) => {
+                *count += 1;
+                if mosaic.smallness() < smallest.smallness() {
+                    *smallest = mosaic;
+                }
+            }
+            None => {
+                self.used += (knot_type.len() + mosaic.tiles().len()) as u64 + TYPE_OVERHEAD;
+                self.types.insert(knot_type, (1, mosaic));
+            }
+        }
+        if self.memory_limit.is_some_and(|limit| self.used >= limit) {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        if self.spilled.is_none() {
+            self.spilled = Some(BufWriter::new(File::create(&self.path)?));
+        }
+        let spilled = self.spilled.as_mut().expect("the spill file was just created");
+        for (knot_type, (count, smallest)) in self.types.drain() {
+            writeln!(spilled, "{}\t{}\t{}", knot_type, count, smallest)?;
+        }
+        self.used = 0;
+        Ok(())
+    }
+
+    //Each type's count and smallest mosaic, named knots first, in order of crossing number, then the rest by their invariants
+    fn finish(mut self, mut write: impl FnMut(String) -> io::Result<()>) -> io::Result<()> {
+        if self.spilled.is_none() {
+            let mut types: Vec<(String, (u64, Mosaic))> = self.types.drain().collect();
+            types.sort_by_key(|(knot_type, _)| table_order(knot_type));
+            for (knot_type, (count, smallest)) in types {
+                write(format!("{}\t{}\t{}", knot_type, count, smallest))?;
+            }
+            return Ok(());
+        }
+        self.spill()?;
+        self.spilled.take().expect("the types were spilled").flush()?;
+        sort_lines(&self.path.to_string_lossy(), &self.sorted_path.to_string_lossy(), self.memory_limit, false)?;
+        //The named knots are few, so are put in order in memory; the rest are in order in the sorted file already
+        let mut named = Vec::new();
+        for_each_type(&self.sorted_path, |knot_type, count, smallest| {
+            if table_order(&knot_type).0 != usize::MAX {
+                named.push((table_order(&knot_type), count, smallest));
+            }
+            Ok(())
+        })?;
+        named.sort_unstable_by(|first, second| first.0.cmp(&second.0));
+        for ((_, _, _, knot_type), count, smallest) in named {
+            write(format!("{}\t{}\t{}", knot_type, count, smallest))?;
+        }
+        for_each_type(&self.sorted_path, |knot_type, count, smallest| match table_order(&knot_type).0 {
+            usize::MAX => write(format!("{}\t{}\t{}", knot_type, count, smallest)),
+            _ => Ok(()),
+        })
+    }
+}
+
+impl Drop for TypeCounts {
+    fn drop(&mut self) {
+        self.spilled = None;
+        if self.path.exists() {
+            let _ = fs::remove_file(&self.path);
+            let _ = fs::remove_file(&self.sorted_path);
+        }
+    }
+}
+
+//Adds up the lines of each type in a sorted file of spilled types, calling `each` with each type's count and smallest mosaic in turn
+fn for_each_type(path: &Path, mut each: impl FnMut(String, u64, Mosaic) -> io::Result<()>) -> io::Result<()> {
+    let mut current: Option<(String, u64, Mosaic)> = None;
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("invalid spilled knot type {}", line));
+        let mut fields = line.rsplitn(3, '\t');
+        let (smallest, count, knot_type) = (fields.next().ok_or_else(invalid)?, fields.next().ok_or_else(invalid)?, fields.next().ok_or_else(invalid)?);
+        let (count, smallest): (u64, Mosaic) = (count.parse().map_err(|_| invalid())?, smallest.parse()?);
+        match &mut current {
+            Some((current_type, current_count, current_smallest)) if current_type == knot_type => {
+                *current_count += count;
+                if smallest.smallness() < current_smallest.smallness() {
+                    *current_smallest = smallest;
+                }
+            }
+            _ => {
+                if let Some((knot_type, count, smallest)) = current.take() {
+                    each(knot_type, count, smallest)?;
+                }
+                current = Some((knot_type.to_string(), count, smallest));
+            }
+        }
+    }
+    match current {
+        Some((knot_type, count, smallest)) => each(knot_type, count, smallest),
+        None => Ok(()),
+    }
+}
+
+//The numbers of lines read and written by a run, and those dropped as duplicates and restored by the exact pass (see dedup.rs)
+pub struct Counts {
+    pub read: u64,
+    pub written: u64,
+    pub dropped: u64,
+    pub restored: u64,
+}
+
+//Puts every mosaic on stdin through the steps, writing the results to stdout, deduplicated if asked
+pub fn run(steps: &[Step], stages: &Stages, store: &ClassStore, dedup: Option<&DedupOptions>, memory_limit: Option<u64>) -> Result<Counts> {
+    let mut read = 0;
+    let lines = BufReader::new(io::stdin()).lines().filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty())).map(|line| {
+        read += 1;
+        Ok(line?.split(" | ").next().unwrap_or_default().parse::<Mosaic>()?)
+    });
+    let mut output = output::create("-")?;
+    let mut dedup = dedup.map(Dedup::new).transpose()?;
+    let mut written = 0;
+    let mut types = TypeCounts::new(memory_limit);
+    let mut write = |line: String| -> io::Result<()> {
+        if dedup.as_mut().map_or(Ok(true), |dedup| dedup.admit(&line))? {
+            written += 1;
+            writeln!(output, "{}", line)?;
+        }
+        Ok(())
+    };
+    pipeline::run(lines, stages, |_| true, |mosaic| process(mosaic, steps, store), |result| match result {
+        Output::Line(line) => write(line),
+        Output::Typed(knot_type, mosaic) => types.add(knot_type, mosaic),
+    })?;
+    types.finish(&mut write)?;
+    let (dropped, restored) = dedup.map_or(Ok((0, 0)), |dedup| dedup.finish(&mut output))?;
+    output.finish()?;
+    store.flush()?;
+    Ok(Counts { read, written: written + restored, dropped, restored })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::generate_with;
+    use std::ops::ControlFlow;
+
+    //The lines by-type writes for the 4x4 mosaics, within a memory limit
+    fn by_type(memory_limit: Option<u64>) -> Vec<String> {
+        let mut types = TypeCounts::new(memory_limit);
+        let _ = generate_with::<()>(4, |mosaic| {
+            if let Some(knot_type) = knot_type(mosaic) {
+                types.add(knot_type, mosaic.trim()).unwrap();
+            }
+            ControlFlow::Continue(())
+        });
+        let mut lines = Vec::new();
+        types.finish(|line| {
+            lines.push(line);
+            Ok(())
+        })
+        .unwrap();
+        lines
+    }
+
+    #[test]
+    fn steps_fail_with_the_cause() {
+        let registry = Registry::builtin();
+        assert!(matches!(Step::parse_all(&["canonicalize", "twist"], &registry), Err(Error::Parse { what: "step", .. })));
+        assert!(matches!(Step::parse_all(&["embed", "six"], &registry), Err(Error::Parse { what: "size for embed", .. })));
+        assert!(matches!(Step::parse_all(&["id", "valid"], &registry), Err(Error::Invalid(_))));
+        assert_eq!(Step::parse_all(&["valid", "embed", "6", "id"], &registry).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn spilled_types_add_up_the_same() {
+        let lines = by_type(None);
+        assert!(lines[0].starts_with("0_1\t"));
+        assert_eq!(by_type(Some(256)), lines);
+    }
+}
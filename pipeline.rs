@@ -0,0 +1,107 @@
+/*
+Staged runs, connected by bounded channels: a source thread producing mosaics (read from a file, or generated as they're needed), a pool of threads filtering them,
+ a pool of threads computing the expensive part of each item (like a census record's invariants), and the calling thread writing the results to a sink as they come out.
+Each stage only waits on the one before it, so generation doesn't wait for invariants to be computed, and the slow stages get as many threads as they're given (see Stages),
+ while a stage running ahead of the rest only gets a few batches ahead before waiting for them.
+Items go through the stages in batches of BATCH_SIZE, numbered as the source produces them, so the sink gets the results in the source's order, whichever thread computed them.
+An error from any stage ends the run with that error, once the sink reaches it.
+*/
+
+use std::collections::BTreeMap;
+use std::io::Result;
+use std::num::NonZeroUsize;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Scope};
+
+//Items sent between stages at a time, so the channels aren't contended per item
+const BATCH_SIZE: usize = 256;
+
+//A numbered batch of items, or the error the batch ran into
+type Batch<T> = (u64, Result<Vec<T>>);
+
+/* The parallelism of each stage of a run: the number of threads filtering items and computing results,
+ and the number of batches that can wait between one stage and the next. */
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stages {
+    pub filter_threads: usize,
+    pub compute_threads: usize,
+    pub queued_batches: usize,
+}
+
+impl Default for Stages {
+    //A filtering thread, and a computing thread for each core
+    fn default() -> Stages {
+        Stages { filter_threads: 1, compute_threads: thread::available_parallelism().map_or(1, NonZeroUsize::get), queued_batches: 16 }
+    }
+}
+
+//Sends the source's items in numbered batches, stopping at the first error or once the next stage has stopped listening
+fn produce<T>(mut source: impl Iterator<Item = Result<T>>, sender: SyncSender<Batch<T>>) {
+    for number in 0.. {
+        let batch: Vec<Result<T>> = source.by_ref().take(BATCH_SIZE).collect();
+        if batch.is_empty() {
+            break;
+        }
+        let batch: Result<Vec<T>> = batch.into_iter().collect();
+        let failed = batch.is_err();
+        if sender.send((number, batch)).is_err() || failed {
+            break;
+        }
+    }
+}
+
+//Starts a stage of `threads` threads taking batches from `input` and sending on the results of `work` for each item, leaving out items it gives None for
+fn pool<'scope, T: Send + 'scope, U: Send + 'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    threads: usize,
+    queued_batches: usize,
+    input: Receiver<Batch<T>>,
+    work: impl Fn(T) -> Result<Option<U>> + Send + Sync + 'scope,
+) -> Receiver<Batch<U>> {
+    let (sender, output) = sync_channel(queued_batches);
+    let (input, work) = (Arc::new(Mutex::new(input)), Arc::new(work));
+    for _ in 0..threads.max(1) {
+        let (input, sender, work) = (Arc::clone(&input), sender.clone(), Arc::clone(&work));
+        scope.spawn(move || {
+            //Only one idle thread waits on the channel at a time, holding the lock until a batch arrives
+            while let Ok((number, batch)) = input.lock().expect("pipeline stages don't panic holding their input").recv() {
+                let batch = batch.and_then(|batch| batch.into_iter().filter_map(|item| work(item).transpose()).collect());
+                if sender.send((number, batch)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    output
+}
+
+/* Runs the source, filter and compute stages (see above), giving each result to the sink in the order of the source's items.
+Returns the sink's first error, or the first error of a stage, if there was one. */
+pub fn run<T: Send, U: Send>(
+    source: impl Iterator<Item = Result<T>> + Send,
+    stages: &Stages,
+    filter: impl Fn(&T) -> bool + Send + Sync,
+    compute: impl Fn(T) -> Result<Option<U>> + Send + Sync,
+    mut sink: impl FnMut(U) -> Result<()>,
+) -> Result<()> {
+    thread::scope(|scope| {
+        let (sender, produced) = sync_channel(stages.queued_batches);
+        scope.spawn(move || produce(source, sender));
+        let filtered = pool(scope, stages.filter_threads, stages.queued_batches, produced, move |item| Ok(filter(&item).then_some(item)));
+        let computed = pool(scope, stages.compute_threads, stages.queued_batches, filtered, compute);
+        //Batches finished out of order wait here until the ones before them are written
+        let (mut next, mut pending) = (0, BTreeMap::new());
+        for (number, batch) in computed {
+            pending.insert(number, batch);
+            while let Some(batch) = pending.remove(&next) {
+                for result in batch? {
+                    sink(result)?;
+                }
+                next += 1;
+            }
+        }
+        Ok(())
+    })
+}
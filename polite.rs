@@ -0,0 +1,99 @@
+/*
+A polite mode for enumerations left running in the background for days on a workstation someone's using, so the machine stays usable:
+ the generating threads drop to the lowest priority (nice 19), and on Linux to the idle I/O class too, so they only get the CPU and disk
+ once nothing else wants them, and the output is written in bursts that are spread out by ThrottledSink.
+Priorities are per thread on Linux, and a thread can't raise its own priority again, so lower_priority is for the thread running a run,
+ before it starts any others (which inherit it).
+*/
+
+use crate::output::Sink;
+use std::io::{Error, Result, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+//The bytes a throttled output collects before writing them out in one burst
+const BURST: usize = 1 << 16;
+//The share of the time a throttled output spends writing, at most
+const WRITE_SHARE: f64 = 0.1;
+//The shortest pause worth sleeping for: shorter pauses are saved up until they add up to one
+const MIN_PAUSE: Duration = Duration::from_millis(5);
+
+//Drops the calling thread (and the threads it starts from now on) to the lowest CPU priority, and on Linux the idle I/O class
+#[cfg(unix)]
+pub fn lower_priority() -> Result<()> {
+    //Safety: setpriority only takes plain values. It returns -1 on failure (a priority can always be lowered, so only for an unusual sandbox)
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, 19) } != 0 {
+        return Err(Error::last_os_error());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        //ioprio_set(IOPRIO_WHO_PROCESS, this thread, IOPRIO_CLASS_IDLE), which libc has no wrapper for
+        const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+        const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+        const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+        //Safety: the syscall only takes plain values
+        if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT) } != 0 {
+            return Err(Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn lower_priority() -> Result<()> {
+    Err(Error::new(std::io::ErrorKind::Unsupported, "polite mode lowers priorities on Unix only"))
+}
+
+/* An output written in bursts of BURST bytes, pausing after each for long enough that writing takes at most WRITE_SHARE of the time.
+The pauses adapt to how busy the disk is: a burst written straight into the page cache only owes a moment's pause, while one held up
+ behind other programs' I/O (or the kernel catching up on writing back earlier ones) owes a pause nine times as long, leaving the disk to them. */
+pub struct ThrottledSink {
+    inner: Box<dyn Sink>,
+    burst: Vec<u8>,
+    owed: Duration,
+}
+
+impl ThrottledSink {
+    pub fn new(inner: Box<dyn Sink>) -> ThrottledSink {
+        ThrottledSink { inner, burst: Vec::with_capacity(BURST), owed: Duration::ZERO }
+    }
+
+    //Writes out the burst so far, then pauses for as long as writing it took (and any pauses saved up) owes
+    fn write_burst(&mut self) -> Result<()> {
+        if self.burst.is_empty() {
+            return Ok(());
+        }
+        let started = Instant::now();
+        self.inner.write_all(&self.burst)?;
+        self.inner.flush()?;
+        self.burst.clear();
+        self.owed += started.elapsed().mul_f64((1.0 - WRITE_SHARE) / WRITE_SHARE);
+        if self.owed >= MIN_PAUSE {
+            thread::sleep(self.owed);
+            self.owed = Duration::ZERO;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ThrottledSink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.burst.extend_from_slice(buf);
+        if self.burst.len() >= BURST {
+            self.write_burst()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.write_burst()?;
+        self.inner.flush()
+    }
+}
+
+impl Sink for ThrottledSink {
+    fn finish(&mut self) -> Result<()> {
+        self.write_burst()?;
+        self.inner.finish()
+    }
+}
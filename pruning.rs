@@ -0,0 +1,171 @@
+/*
+Pruning rules for the generator's depth-first search, abandoning a partial mosaic as soon as no mosaic extending it could meet a constraint,
+ instead of generating every mosaic and filtering out those that don't.
+A Pruner is told about each tile as it's set, in order, and says whether the tiles so far have ruled out every completion.
+Pruners must be admissible: a partial mosaic is only pruned if no mosaic it extends meets the constraint, so pruning never loses a mosaic that filtering would keep,
+ and at a complete mosaic the pruner decides the constraint exactly (so constraints can also be checked by running their pruners over a complete mosaic, see Constraints::admits).
+The built-in pruners bound the numbers of crossings, cusps and link components, and contain a pattern (see pattern.rs).
+*/
+
+use crate::generator::CROSSING;
+use crate::mosaic::{NONE, PARTNER};
+use crate::pattern::Pattern;
+
+pub trait Pruner: Send {
+    /* Whether the tiles of a size x size mosaic up to and including `tile`, which has just been set, can't be completed to a mosaic meeting the constraint.
+    A pruner is told about every tile (in order) before the generator moves past it, and about each tile again whenever it changes,
+     but not necessarily about a tile that an earlier pruner has already pruned, so any state kept for a tile should depend only on the tiles up to it. */
+    fn prunes(&mut self, tiles: &[usize], size: usize, tile: usize) -> bool;
+}
+
+//A running count of the tiles matching `weight` up to each tile, weighted (e.g. 2 for a tile with two cusps)
+struct RunningCount {
+    counts: Vec<usize>,
+    weight: fn(usize) -> usize,
+}
+
+impl RunningCount {
+    fn new(length: usize, weight: fn(usize) -> usize) -> RunningCount {
+        RunningCount { counts: vec![0; length], weight }
+    }
+
+    //The count up to and including `tile`, which has just been set
+    fn update(&mut self, tiles: &[usize], tile: usize) -> usize {
+        self.counts[tile] = if tile == 0 { 0 } else { self.counts[tile - 1] } + (self.weight)(tiles[tile]);
+        self.counts[tile]
+    }
+}
+
+//Mosaics with between `min` and `max` crossing tiles, pruning partial mosaics with too many crossings or too few tiles left to reach `min`
+pub struct Crossings {
+    min: usize,
+    max: usize,
+    count: RunningCount,
+    interior_after: Vec<usize>, //Number of tiles after each tile that aren't on the edge of the mosaic, and so could be crossings
+}
+
+impl Crossings {
+    pub fn new(size: usize, min: usize, max: usize) -> Crossings {
+        let length = size * size;
+        let interior = |tile: usize| (1..size - 1).contains(&(tile / size)) && (1..size - 1).contains(&(tile % size));
+        Crossings {
+            min,
+            max,
+            count: RunningCount::new(length, |tile| (tile == CROSSING) as usize),
+            interior_after: (0..length).map(|tile| (tile + 1..length).filter(|&later| interior(later)).count()).collect(),
+        }
+    }
+}
+
+impl Pruner for Crossings {
+    fn prunes(&mut self, tiles: &[usize], _: usize, tile: usize) -> bool {
+        let crossings = self.count.update(tiles, tile);
+        crossings > self.max || crossings + self.interior_after[tile] < self.min
+    }
+}
+
+//Mosaics with at most `max` cusps, counting the left and right cusps of tile 8 separately
+pub struct Cusps {
+    max: usize,
+    count: RunningCount,
+}
+
+impl Cusps {
+    pub fn new(size: usize, max: usize) -> Cusps {
+        Cusps {
+            max,
+            count: RunningCount::new(size * size, |tile| match tile {
+                2 | 4 => 1,
+                8 => 2,
+                _ => 0,
+            }),
+        }
+    }
+}
+
+impl Pruner for Cusps {
+    fn prunes(&mut self, tiles: &[usize], _: usize, tile: usize) -> bool {
+        self.count.update(tiles, tile) > self.max
+    }
+}
+
+//No strand at an open end
+const NO_END: u8 = u8::MAX;
+
+/* Mosaics of links with between `min` and `max` components, pruning partial mosaics that have already closed up more than `max` components
+ or can no longer reach `min` (each strand left open closes into at most one more, and each new component needs at least four turns, two to a tile at most). */
+pub struct Components {
+    min: usize,
+    max: usize,
+    ends: Vec<u8>, //Open strand ends after each tile (see track_components), size + 1 to a tile
+    closed: Vec<usize>, //Number of components closed up by each tile and the tiles before it
+}
+
+impl Components {
+    pub fn new(size: usize, min: usize, max: usize) -> Components {
+        Components { min, max, ends: vec![NO_END; size * size * (size + 1)], closed: vec![0; size * size] }
+    }
+
+    /* Follows the strands of a tile that's just been set on from the open ends left by the tiles before it.
+    After each tile, the open ends are the bottom faces of the last tile set in each column (ends 0 to size - 1) and the right face of the tile itself (end size),
+     and ends[end] is the other open end its strand runs to through the tiles so far, or NO_END if no strand leaves there.
+    A component closes up when a tile joins its top and left faces, and those ends were already joined to each other. */
+    fn track_components(&mut self, tiles: &[usize], size: usize, tile: usize) {
+        let col = tile % size;
+        let (earlier, later) = self.ends.split_at_mut(tile * (size + 1));
+        let previous = |end: usize| if tile == 0 { NO_END } else { earlier[(tile - 1) * (size + 1) + end] };
+        let current = &mut later[..size + 1];
+        let partners = &PARTNER[tiles[tile]];
+        //Strand ends are numbered as open ends, with the tile's top and left faces at the ends they replace (col and size), and its bottom and right faces after them
+        let (up, left, down, right) = (col, size, size + 1, size + 2);
+        let face_end = [right, up, left, down];
+        let through_tile = |end: usize| {
+            let face = face_end.iter().position(|&face_end| face_end == end).expect("only the tile's faces are followed through it");
+            match partners[face] {
+                NONE => NO_END,
+                partner => face_end[partner as usize] as u8,
+            }
+        };
+        //Follows a strand from one of the ends it'll still have once the tile's set, alternating between strands of earlier tiles and this one
+        let follow = |start: usize| {
+            let (mut end, mut through_earlier) = (start, start <= size);
+            for _ in 0..4 {
+                let next = if through_earlier { previous(end) } else { through_tile(end) };
+                if next == NO_END || (next as usize != up && next as usize != left) {
+                    return next;
+                }
+                end = next as usize;
+                through_earlier = !through_earlier;
+            }
+            NO_END
+        };
+        let renumbered = |end: u8| match end as usize {
+            end if end == down => col as u8,
+            end if end == right => size as u8,
+            _ => end,
+        };
+        let new_end = |end: usize| if through_tile(end) == NO_END { NO_END } else { renumbered(follow(end)) };
+        for (end, current_end) in current.iter_mut().enumerate().take(size) {
+            *current_end = if end == col { new_end(down) } else if previous(end) == NO_END { NO_END } else { renumbered(follow(end)) };
+        }
+        current[size] = new_end(right);
+        let closes = partners[1] == 2 && previous(up) == left as u8;
+        self.closed[tile] = if tile == 0 { 0 } else { self.closed[tile - 1] } + closes as usize;
+    }
+}
+
+impl Pruner for Components {
+    fn prunes(&mut self, tiles: &[usize], size: usize, tile: usize) -> bool {
+        self.track_components(tiles, size, tile);
+        let closed = self.closed[tile];
+        let open_ends = self.ends[tile * (size + 1)..(tile + 1) * (size + 1)].iter().filter(|&&end| end != NO_END).count();
+        closed + (open_ends > 0) as usize > self.max || closed + open_ends / 2 + (tiles.len() - 1 - tile) / 2 < self.min
+    }
+}
+
+//Mosaics containing the pattern, pruning partial mosaics once every placement of it contradicts their tiles
+impl Pruner for Pattern {
+    fn prunes(&mut self, tiles: &[usize], size: usize, tile: usize) -> bool {
+        !self.could_contain(tiles, size, tile + 1)
+    }
+}
@@ -0,0 +1,200 @@
+/*
+Python bindings (built with maturin from pyproject.toml, which enables the `python` feature), so the python side of the project can work with mosaics directly instead of through text files:
+
+    import legendrian_mosaics as lm
+    for mosaic in lm.Generator(4):
+        if mosaic.components() == 1:
+            print(mosaic, mosaic.tb(), mosaic.rot(), mosaic.knot_name())
+
+Mosaic methods that need the traced diagram raise ValueError for mosaics that aren't suitably connected.
+*/
+
+//The glue pyo3's macros generate converts each method's PyErr into itself
+#![allow(clippy::useless_conversion)]
+
+use crate::census::{self, CensusRecord};
+use crate::diagram::Diagram;
+use crate::error::Error;
+use crate::export::{self, regina_link};
+use crate::generator::{Constraints, MosaicGenerator};
+use crate::invariants::jones;
+use crate::knots::knot_name;
+use crate::mosaic::Mosaic;
+use crate::output::Chunking;
+use crate::pipeline::Stages;
+use crate::render::to_svg;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+#[pyclass(name = "Mosaic")]
+#[derive(Clone)]
+struct PyMosaic {
+    inner: Mosaic,
+}
+
+impl PyMosaic {
+    fn diagram(&self) -> PyResult<Diagram> {
+        Diagram::trace(&self.inner).ok_or_else(|| PyValueError::new_err(format!("{} is not suitably connected", self.inner)))
+    }
+}
+
+#[pymethods]
+impl PyMosaic {
+    #[new]
+    fn new(code: &str) -> PyResult<PyMosaic> {
+        let inner = code.parse().map_err(|error: Error| PyValueError::new_err(error.to_string()))?;
+        Ok(PyMosaic { inner })
+    }
+
+    #[getter]
+    fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    #[getter]
+    fn tiles(&self) -> Vec<u8> {
+        self.inner.tiles().to_vec()
+    }
+
+    fn is_suitably_connected(&self) -> bool {
+        self.inner.is_suitably_connected()
+    }
+
+    fn components(&self) -> PyResult<usize> {
+        Ok(self.diagram()?.component_count())
+    }
+
+    fn crossings(&self) -> PyResult<usize> {
+        Ok(self.diagram()?.crossings.len())
+    }
+
+    fn writhe(&self) -> PyResult<i32> {
+        Ok(self.diagram()?.writhe())
+    }
+
+    fn tb(&self) -> PyResult<i32> {
+        Ok(self.diagram()?.tb())
+    }
+
+    //Rotation number of the traversed orientation
+    fn rot(&self) -> PyResult<i32> {
+        Ok(self.diagram()?.rot())
+    }
+
+    fn gauss_codes(&self) -> PyResult<Vec<Vec<i32>>> {
+        Ok(self.diagram()?.gauss_codes())
+    }
+
+    fn crossing_signs(&self) -> PyResult<Vec<i32>> {
+        Ok(self.diagram()?.crossing_signs())
+    }
+
+    //Jones polynomial as a string in t, or None for the empty mosaic
+    fn jones(&self) -> PyResult<Option<String>> {
+        Ok(jones(&self.diagram()?).map(|polynomial| polynomial.format("t", 2)))
+    }
+
+    fn knot_name(&self) -> PyResult<Option<String>> {
+        let diagram = self.diagram()?;
+        Ok(if diagram.component_count() == 1 { jones(&diagram).and_then(|polynomial| knot_name(&polynomial)) } else { None })
+    }
+
+    //Codes of the mosaics of the pieces of a split diagram (see Diagram::split), or just the trimmed mosaic if it isn't split
+    fn split(&self) -> PyResult<Vec<String>> {
+        Ok(self.diagram()?.split().iter().map(|piece| piece.to_string()).collect())
+    }
+
+    //Labels of the Legendrian symmetries fixing the mosaic exactly (see symmetry.rs), starting with the identity, 1
+    fn symmetries(&self) -> Vec<&'static str> {
+        self.inner.symmetries().iter().map(|symmetry| symmetry.label()).collect()
+    }
+
+    fn orbit_size(&self) -> usize {
+        self.inner.orbit_size()
+    }
+
+    //Python expression building the link with Regina
+    fn regina(&self) -> PyResult<String> {
+        Ok(regina_link(&self.diagram()?))
+    }
+
+    //Census record line (see census.rs), or None for empty or unsuitably connected mosaics
+    fn census_record(&self) -> Option<String> {
+        CensusRecord::new(&self.inner).map(|record| record.to_string())
+    }
+
+    //Stable ID of the mosaic, as 32 hex digits (see Mosaic::id)
+    fn id(&self) -> String {
+        self.inner.id().to_string()
+    }
+
+    //Standalone SVG drawing of the mosaic, as drawn by to_image.py
+    fn svg(&self) -> String {
+        to_svg(&self.inner)
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Mosaic(\"{}\")", self.inner)
+    }
+
+    fn __eq__(&self, other: &PyMosaic) -> bool {
+        self.inner == other.inner
+    }
+
+    fn __hash__(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.inner.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+//Iterator over every suitably connected size x size mosaic, in the same order as mosaic_gen
+#[pyclass(name = "Generator")]
+struct PyGenerator {
+    inner: MosaicGenerator,
+}
+
+#[pymethods]
+impl PyGenerator {
+    #[new]
+    fn new(size: usize) -> PyResult<PyGenerator> {
+        if size == 0 {
+            return Err(PyValueError::new_err("mosaics must have size at least 1"));
+        }
+        Ok(PyGenerator { inner: MosaicGenerator::new(size) })
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<PyMosaic> {
+        slf.inner.next().map(|inner| PyMosaic { inner })
+    }
+}
+
+//Writes census records for every mosaic in a file, returning the number written
+#[pyfunction]
+fn write_census(input_path: &str, output_path: &str) -> PyResult<usize> {
+    Ok(census::write_census(&census::Source::File(input_path), output_path, &Chunking::default(), &Constraints::default(), &[], None, &Stages::default()).map_err(std::io::Error::from)?.0)
+}
+
+//Writes a Regina script for every mosaic in a file, returning the number of links exported
+#[pyfunction]
+fn regina_export(input_path: &str, output_path: &str) -> PyResult<usize> {
+    Ok(export::regina_export(input_path, output_path)?)
+}
+
+#[pymodule]
+fn legendrian_mosaics(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyMosaic>()?;
+    module.add_class::<PyGenerator>()?;
+    module.add_function(wrap_pyfunction!(write_census, module)?)?;
+    module.add_function(wrap_pyfunction!(regina_export, module)?)?;
+    Ok(())
+}
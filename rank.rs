@@ -0,0 +1,116 @@
+/*
+Global ranks of mosaics in the generation order, without generating the mosaics before them, so the mosaics written by shards of a size
+ (runs with --prefix, --start or --end, or resumed runs) can be numbered as a single run would number them.
+The mosaics before some tiles are counted from prefix counts: for each tile, the mosaics sharing the tiles before it but with an earlier tile there.
+Those are the completions of the board so far, which depend only on its frontier -- which faces along the bottom of the tiles read so far
+ (the last in each column) and the right of the last tile strands leave by -- so they're counted once for each step and frontier, and kept.
+Reading by columns (see Order) is reading the transposed board by rows, with each tile's faces transposed: reflecting in the diagonal swaps
+ right with bottom and top with left.
+*/
+
+use crate::generator::Order;
+use crate::mosaic::{NONE, PARTNER, TILE_COUNT};
+use std::collections::HashMap;
+
+//Faces in the reading of the board: right, top, left and bottom of the board as it's read (see above)
+const RIGHT: usize = 0;
+const TOP: usize = 1;
+const LEFT: usize = 2;
+const BOTTOM: usize = 3;
+
+//The largest size ranks are counted for, with a bit of the frontier for each column and one for the right
+pub const MAX_SIZE: usize = 63;
+
+//The completions of a size x size board read in an order, from each step and frontier (bit `col` for the bottom of each column, bit size for the right)
+struct Counter {
+    size: usize,
+    column_major: bool,
+    completions: HashMap<(usize, u64), u128>,
+}
+
+impl Counter {
+    fn new(size: usize, order: Order) -> Counter {
+        Counter { size, column_major: order.column_major(), completions: HashMap::new() }
+    }
+
+    fn connects(&self, tile: usize, face: usize) -> bool {
+        //The faces of the transposed tile, reading by columns
+        let face = if self.column_major { [BOTTOM, LEFT, TOP, RIGHT][face] } else { face };
+        PARTNER[tile][face] != NONE
+    }
+
+    //The frontier after a tile at a step, or None if it doesn't fit there
+    fn place(&self, step: usize, frontier: u64, tile: usize) -> Option<u64> {
+        let (row, col, size) = (step / self.size, step % self.size, self.size);
+        let above = frontier & (1 << col) != 0;
+        let left = frontier & (1 << size) != 0;
+        let fits = self.connects(tile, TOP) == above
+            && self.connects(tile, LEFT) == left
+            && (col + 1 < size || !self.connects(tile, RIGHT))
+            && (row + 1 < size || !self.connects(tile, BOTTOM));
+        let set = |frontier: u64, bit: usize, on: bool| if on { frontier | 1 << bit } else { frontier & !(1 << bit) };
+        fits.then(|| set(set(frontier, col, self.connects(tile, BOTTOM)), size, self.connects(tile, RIGHT)))
+    }
+
+    //The number of ways to fill the board from a step on, from a frontier
+    fn count(&mut self, step: usize, frontier: u64) -> u128 {
+        if step == self.size * self.size {
+            return 1;
+        }
+        if let Some(&count) = self.completions.get(&(step, frontier)) {
+            return count;
+        }
+        let nexts: Vec<u64> = (0..TILE_COUNT as usize).filter_map(|tile| self.place(step, frontier, tile)).collect();
+        let count = nexts.into_iter().map(|next| self.count(step + 1, next)).sum();
+        self.completions.insert((step, frontier), count);
+        count
+    }
+
+    //The number of mosaics whose tiles, read in order, come before `read` lexicographically, and whether `read` is a mosaic
+    fn before(&mut self, read: &[usize]) -> (u128, bool) {
+        let (mut before, mut frontier) = (0, 0);
+        for (step, &tile) in read.iter().enumerate() {
+            for earlier in 0..tile.min(TILE_COUNT as usize) {
+                if let Some(next) = self.place(step, frontier, earlier) {
+                    before += self.count(step + 1, next);
+                }
+            }
+            match self.place(step, frontier, tile) {
+                Some(next) => frontier = next,
+                None => return (before, false),
+            }
+        }
+        (before, true)
+    }
+
+    //Adds every way of continuing a prefix to `length` tiles that some mosaic starts with, in order
+    fn extend(&mut self, prefix: &mut Vec<usize>, frontier: u64, length: usize, reverse: bool, prefixes: &mut Vec<Vec<usize>>) {
+        if prefix.len() == length {
+            prefixes.push(prefix.clone());
+            return;
+        }
+        let step = prefix.len();
+        for index in 0..TILE_COUNT as usize {
+            let tile = if reverse { TILE_COUNT as usize - 1 - index } else { index };
+            if let Some(next) = self.place(step, frontier, tile).filter(|&next| self.count(step + 1, next) > 0) {
+                prefix.push(tile);
+                self.extend(prefix, next, length, reverse, prefixes);
+                prefix.pop();
+            }
+        }
+    }
+}
+
+//The number of size x size mosaics before the tiles (which needn't be a mosaic) in an order, which for a mosaic is its rank
+pub fn rank(tiles: &[usize], size: usize, order: Order) -> u128 {
+    let mut counter = Counter::new(size, order);
+    let (before, mosaic) = counter.before(&order.read(tiles, size));
+    if order.reverse() { counter.count(0, 0) - before - mosaic as u128 } else { before }
+}
+
+//The first `length` tiles of the size x size mosaics, in the order's reading of the board, each once and in the order
+pub fn prefixes(size: usize, order: Order, length: usize) -> Vec<Vec<usize>> {
+    let mut prefixes = Vec::new();
+    Counter::new(size, order).extend(&mut Vec::new(), 0, length.min(size * size), order.reverse(), &mut prefixes);
+    prefixes
+}
@@ -0,0 +1,110 @@
+/*
+Legendrian Reidemeister moves of mosaics, which change the number of crossings, as replacements of small blocks of tiles (see moves.rs for the moves that don't).
+Each is a Legendrian isotopy of the front inside the block, fixed at its edges, so the strands leave the block through the same faces before and after:
+    I   a kink: a strand crossing itself around a loop holding two cusps, of opposite directions, which untwists to a smooth turn --
+         one crossing (a positive one) and two cusps fewer, so tb and rot are kept
+            21              00
+            94     <->      10
+    II  a thin tongue of the front ending in a cusp, crossing a strand, which pulls back past the strand -- two crossings fewer
+            66              34
+            99     <->      55
+            34              00
+Blocks are written a row at a time, with the front read as for symmetry.rs. These and their images under the Legendrian symmetries (each again a move of the front)
+ are the moves, in both directions -- the same way stabilization.rs makes its stabilizations from a block replacement. They're far from all the ways of making the Legendrian Reidemeister moves on a board, but together with the planar isotopy moves
+ they reach the others whenever there's room to move the strands into these shapes first.
+*/
+
+use crate::mosaic::{tile_char, Mosaic};
+use crate::symmetry::GROUP;
+use std::fmt;
+use std::sync::OnceLock;
+
+//A block replacement by its name, the height and width of its block, and the block's tiles before and after
+pub type BaseMove = (&'static str, usize, usize, &'static [u8], &'static [u8]);
+
+//The moves above, removing crossings
+const BASE_MOVES: [BaseMove; 2] = [("I", 2, 2, &[2, 1, 9, 4], &[0, 0, 1, 0]), ("II", 3, 2, &[6, 6, 9, 9, 3, 4], &[3, 4, 5, 5, 0, 0])];
+
+//A replacement of a height x width block of tiles by another, as one of the moves above (by its name, I or II)
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Rule {
+    pub kind: &'static str,
+    pub height: usize,
+    pub width: usize,
+    pub from: Vec<u8>,
+    pub to: Vec<u8>,
+}
+
+//The rules of some base replacements and their images under the symmetries, in the directions given (forward, and backward too if `both_ways`)
+pub fn symmetric_rules(base: &[BaseMove], both_ways: bool) -> Vec<Rule> {
+    let mut rules: Vec<Rule> = Vec::new();
+    for &(kind, height, width, before, after) in base {
+        for symmetry in GROUP {
+            let (image_height, image_width, from) = symmetry.apply_block(height, width, before);
+            let (_, _, to) = symmetry.apply_block(height, width, after);
+            let directions = if both_ways { vec![(from.clone(), to.clone()), (to, from)] } else { vec![(from, to)] };
+            for (from, to) in directions {
+                let rule = Rule { kind, height: image_height, width: image_width, from, to };
+                if !rules.contains(&rule) {
+                    rules.push(rule);
+                }
+            }
+        }
+    }
+    rules
+}
+
+//Every Reidemeister rule, from the base moves and their images under the symmetries, in both directions
+pub fn rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| symmetric_rules(&BASE_MOVES, true))
+}
+
+//A rule applied to the block with top left tile at (row, col)
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Replacement {
+    pub row: usize,
+    pub col: usize,
+    pub rule: &'static Rule,
+}
+
+impl Replacement {
+    pub fn apply(&self, mosaic: &Mosaic) -> Mosaic {
+        let size = mosaic.size();
+        let mut tiles = mosaic.tiles().to_vec();
+        for (index, &tile) in self.rule.to.iter().enumerate() {
+            tiles[(self.row + index / self.rule.width) * size + self.col + index % self.rule.width] = tile;
+        }
+        Mosaic::from_tiles(size, tiles).expect("moves replace tiles with tiles")
+    }
+}
+
+//Moves are written as the block's position, the type of move and the block's tiles before and after, a row at a time, like (1, 2) II 66/99/34 -> 34/55/00
+impl fmt::Display for Replacement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = |tiles: &[u8]| tiles.chunks(self.rule.width).map(|row| row.iter().map(|&tile| tile_char(tile)).collect::<String>()).collect::<Vec<String>>().join("/");
+        write!(f, "({}, {}) {} {} -> {}", self.row, self.col, self.rule.kind, rows(&self.rule.from), rows(&self.rule.to))
+    }
+}
+
+//Every Legendrian Reidemeister move of a mosaic, by rule and then by the position of its block (row by row)
+pub fn reidemeister_moves(mosaic: &Mosaic) -> Vec<Replacement> {
+    replacements(mosaic, rules())
+}
+
+//Every replacement of a mosaic's blocks by some rules, by rule and then by the position of its block
+pub fn replacements(mosaic: &Mosaic, rules: &'static [Rule]) -> Vec<Replacement> {
+    let size = mosaic.size();
+    let mut moves = Vec::new();
+    for rule in rules {
+        for row in 0..(size + 1).saturating_sub(rule.height) {
+            for col in 0..(size + 1).saturating_sub(rule.width) {
+                let matches = (0..rule.from.len()).all(|index| mosaic.tiles()[(row + index / rule.width) * size + col + index % rule.width] == rule.from[index]);
+                if matches {
+                    moves.push(Replacement { row, col, rule });
+                }
+            }
+        }
+    }
+    moves
+}
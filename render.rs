@@ -0,0 +1,536 @@
+/*
+Rendering of mosaics, as SVG, PNG, TikZ, or ASCII or Unicode art.
+The images are drawn to match the tile images used by to_image.py:
+ each tile is a 128 x 128 square, the mosaic has a 4 unit grey border, and the whole mosaic is rotated 45° counterclockwise so the front projection reads left to right.
+Smooth turns are quarter circles about the corner between their faces, and cusps meet just off the center of the tile, toward the corner between their faces.
+Every format is drawn from the same strokes (see tile_strokes): SVG and TikZ write them as paths, and PNG images are rasterized from points along them and compressed with flate2.
+ASCII art is unrotated, three characters square to a tile, with a strand leaving each connected face and a character in the middle for the tile:
+    .  ┐ and └ (tiles 1 and 3)     <  >  the left and right cusps (2 and 4)    -  |  straight strands (5 and 6)
+    \  /  └ + ┐ and ┌ + ┘ (7 and 8)   +  a crossing, with the strand from top to bottom over (9)    o  a virtual crossing
+Unicode art (for terminals, see tui.rs) is drawn the same way with box-drawing characters.
+*/
+
+use crate::mosaic::{read_mosaics, Mosaic, NONE, PARTNER, VIRTUAL_CROSSING};
+use crate::output;
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+use std::collections::BTreeSet;
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, SQRT_2};
+use std::fmt::Write as _;
+use std::io::{Error, ErrorKind, Result, Write};
+
+const TILE_SIZE: f64 = 128.0;
+const BORDER_SIZE: f64 = 4.0;
+const BORDER_COLOR: &str = "#c4c4c4";
+const BORDER_RGBA: [u8; 4] = [196, 196, 196, 255];
+const STRAND_WIDTH: f64 = 8.0;
+
+type Point = (f64, f64);
+
+//A piece of a strand, in the coordinates of the unrotated mosaic
+enum Stroke {
+    Line(Point, Point),
+    //A quarter circle about a corner of the tile, drawn clockwise on screen or not
+    Arc { start: Point, end: Point, corner: Point, clockwise: bool },
+    //A cubic Bézier curve
+    Curve([Point; 4]),
+}
+
+impl Stroke {
+    //SVG path data for the stroke
+    fn svg_path(&self) -> String {
+        match self {
+            Stroke::Line(start, end) => format!("M {} {} L {} {}", start.0, start.1, end.0, end.1),
+            Stroke::Arc { start, end, clockwise, .. } => format!("M {} {} A {} {} 0 0 {} {} {}", start.0, start.1, TILE_SIZE / 2.0, TILE_SIZE / 2.0, *clockwise as u8, end.0, end.1),
+            Stroke::Curve([start, first, second, end]) => format!("M {} {} C {} {} {} {} {} {}", start.0, start.1, first.0, first.1, second.0, second.1, end.0, end.1),
+        }
+    }
+
+    //The stroke as Bézier curves (a quarter circle is close enough to one), for formats without arcs
+    fn curves(&self) -> [Point; 4] {
+        match *self {
+            Stroke::Line(start, end) => [start, start, end, end],
+            Stroke::Arc { start, end, corner, .. } => {
+                //Control points go from each end toward the other end's direction from the corner, by the usual 0.552 of the radius
+                let kappa = 4.0 * (SQRT_2 - 1.0) / 3.0;
+                let toward = |from: Point, other: Point| (from.0 + kappa * (other.0 - corner.0), from.1 + kappa * (other.1 - corner.1));
+                [start, toward(start, end), toward(end, start), end]
+            }
+            Stroke::Curve(points) => points,
+        }
+    }
+
+    //Points along the stroke, `steps` apart in its parameter
+    fn points(&self, steps: usize) -> Vec<Point> {
+        match *self {
+            Stroke::Arc { start, end, corner, .. } => {
+                let angle = |point: Point| (point.1 - corner.1).atan2(point.0 - corner.0);
+                let (from, mut to) = (angle(start), angle(end));
+                //The quarter turn between the ends, whichever way round it is
+                if (to - from).abs() > FRAC_PI_2 + 1e-9 {
+                    to += if to < from { 4.0 * FRAC_PI_2 } else { -4.0 * FRAC_PI_2 };
+                }
+                (0..=steps)
+                    .map(|step| from + (to - from) * step as f64 / steps as f64)
+                    .map(|angle| (corner.0 + TILE_SIZE / 2.0 * angle.cos(), corner.1 + TILE_SIZE / 2.0 * angle.sin()))
+                    .collect()
+            }
+            _ => {
+                let [p0, p1, p2, p3] = self.curves();
+                (0..=steps)
+                    .map(|step| {
+                        let t = step as f64 / steps as f64;
+                        let weights = [(1.0 - t).powi(3), 3.0 * (1.0 - t).powi(2) * t, 3.0 * (1.0 - t) * t * t, t.powi(3)];
+                        let (x, y) = [p0, p1, p2, p3].iter().zip(weights).fold((0.0, 0.0), |(x, y), (point, weight)| (x + weight * point.0, y + weight * point.1));
+                        (x, y)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+//Midpoint of a face of the tile with top left corner (x, y)
+fn face_point(x: f64, y: f64, face: u8) -> Point {
+    let half = TILE_SIZE / 2.0;
+    match face {
+        0 => (x + TILE_SIZE, y + half),
+        1 => (x + half, y),
+        2 => (x, y + half),
+        _ => (x + half, y + TILE_SIZE),
+    }
+}
+
+//Unit vector pointing into the tile from a face
+fn inward(face: u8) -> Point {
+    match face {
+        0 => (-1.0, 0.0),
+        1 => (0.0, 1.0),
+        2 => (1.0, 0.0),
+        _ => (0.0, -1.0),
+    }
+}
+
+//The strokes of the strand of a tile joining faces a and b
+fn strand_strokes(tile: u8, x: f64, y: f64, a: u8, b: u8) -> Vec<Stroke> {
+    let (start, end) = (face_point(x, y, a), face_point(x, y, b));
+    if (a + 2) % 4 == b {
+        return vec![Stroke::Line(start, end)];
+    }
+    //Corner between the two faces
+    let corner = (x + if a == 0 || b == 0 { TILE_SIZE } else { 0.0 }, y + if a == 3 || b == 3 { TILE_SIZE } else { 0.0 });
+    let center = (x + TILE_SIZE / 2.0, y + TILE_SIZE / 2.0);
+    if matches!(tile, 2 | 4 | 8) {
+        //Cusp: both halves leave the cusp point heading toward the corner, and meet their faces square on
+        let toward = ((corner.0 - center.0).signum(), (corner.1 - center.1).signum());
+        let cusp = (center.0 + 6.0 * toward.0, center.1 + 6.0 * toward.1);
+        let control = (cusp.0 + 14.0 * toward.0, cusp.1 + 14.0 * toward.1);
+        return [(start, a), (end, b)]
+            .iter()
+            .map(|&(point, face)| Stroke::Curve([cusp, control, (point.0 + 28.0 * inward(face).0, point.1 + 28.0 * inward(face).1), point]))
+            .collect();
+    }
+    //Smooth turn: quarter circle about the corner, drawn clockwise when start -> end turns clockwise about the corner on screen
+    let clockwise = (start.0 - corner.0) * (end.1 - corner.1) - (start.1 - corner.1) * (end.0 - corner.0) > 0.0;
+    vec![Stroke::Arc { start, end, corner, clockwise }]
+}
+
+//The strokes of a tile with top left corner (x, y), a group for each strand (or piece of a strand broken by a crossing)
+fn tile_strokes(tile: u8, x: f64, y: f64) -> Vec<Vec<Stroke>> {
+    if tile == 9 {
+        //Over strand runs between faces 1 and 3, and the under strand is broken around it
+        let (gap, middle) = (2.0 * STRAND_WIDTH, y + TILE_SIZE / 2.0);
+        return vec![
+            strand_strokes(tile, x, y, 1, 3),
+            vec![Stroke::Line((x, middle), (x + TILE_SIZE / 2.0 - gap, middle))],
+            vec![Stroke::Line((x + TILE_SIZE / 2.0 + gap, middle), (x + TILE_SIZE, middle))],
+        ];
+    }
+    (0..4)
+        .filter_map(|face| {
+            let partner = PARTNER[tile as usize][face as usize];
+            (partner != NONE && face < partner).then(|| strand_strokes(tile, x, y, face, partner))
+        })
+        .collect()
+}
+
+//The top left corner of the tile at an index of a mosaic
+fn tile_corner(mosaic: &Mosaic, index: usize) -> Point {
+    let size = mosaic.size();
+    (BORDER_SIZE + (index % size) as f64 * TILE_SIZE, BORDER_SIZE + (index / size) as f64 * TILE_SIZE)
+}
+
+//Width of the unrotated mosaic, border included
+fn mosaic_width(mosaic: &Mosaic) -> f64 {
+    mosaic.size() as f64 * TILE_SIZE + 2.0 * BORDER_SIZE
+}
+
+//Renders a mosaic as a standalone SVG document
+pub fn to_svg(mosaic: &Mosaic) -> String {
+    let width = mosaic_width(mosaic);
+    let rotated_width = width * SQRT_2;
+    let offset = (rotated_width - width) / 2.0;
+    let mut svg = String::new();
+
+    writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0:.0}\" height=\"{0:.0}\" viewBox=\"{1:.3} {1:.3} {0:.3} {0:.3}\">", rotated_width, -offset).unwrap();
+    writeln!(svg, "<g transform=\"rotate(-45 {0} {0})\">", width / 2.0).unwrap();
+    writeln!(svg, "<rect x=\"0\" y=\"0\" width=\"{0}\" height=\"{0}\" fill=\"{1}\"/>", width, BORDER_COLOR).unwrap();
+    for (i, &tile) in mosaic.tiles().iter().enumerate() {
+        let (x, y) = tile_corner(mosaic, i);
+        writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\" stroke=\"{}\" stroke-width=\"1\"/>", x, y, TILE_SIZE, TILE_SIZE, BORDER_COLOR).unwrap();
+        for strokes in tile_strokes(tile, x, y) {
+            let path: Vec<String> = strokes.iter().map(Stroke::svg_path).collect();
+            writeln!(svg, "<path d=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"{}\"/>", path.join(" "), STRAND_WIDTH).unwrap();
+        }
+        //Virtual crossings are circled, as usual
+        if tile == VIRTUAL_CROSSING {
+            writeln!(svg, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"{}\"/>", x + TILE_SIZE / 2.0, y + TILE_SIZE / 2.0, TILE_SIZE / 6.0, STRAND_WIDTH / 2.0).unwrap();
+        }
+    }
+    writeln!(svg, "</g>").unwrap();
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
+
+//Renders a mosaic as a TikZ picture, a unit to a tile, to be included in a LaTeX document loading the tikz package
+pub fn to_tikz(mosaic: &Mosaic) -> String {
+    let size = mosaic.size() as f64;
+    //TikZ's y axis points up, so rows go down from 0
+    let point = |(x, y): Point| format!("({:.3},{:.3})", (x - BORDER_SIZE) / TILE_SIZE, -(y - BORDER_SIZE) / TILE_SIZE);
+    let mut tikz = String::new();
+    writeln!(tikz, "% {}", mosaic).unwrap();
+    writeln!(tikz, "\\begin{{tikzpicture}}[rotate=45, line cap=round]").unwrap();
+    writeln!(tikz, "\\draw[gray!40, line width=0.5pt] (0,0) grid ({},{});", size, -size).unwrap();
+    writeln!(tikz, "\\draw[gray!60, line width=1pt] (0,0) rectangle ({},{});", size, -size).unwrap();
+    for (i, &tile) in mosaic.tiles().iter().enumerate() {
+        let (x, y) = tile_corner(mosaic, i);
+        for strokes in tile_strokes(tile, x, y) {
+            for stroke in strokes {
+                let [start, first, second, end] = stroke.curves();
+                match stroke {
+                    Stroke::Line(..) => writeln!(tikz, "\\draw[line width=1.5pt] {} -- {};", point(start), point(end)).unwrap(),
+                    _ => writeln!(tikz, "\\draw[line width=1.5pt] {} .. controls {} and {} .. {};", point(start), point(first), point(second), point(end)).unwrap(),
+                }
+            }
+        }
+        if tile == VIRTUAL_CROSSING {
+            writeln!(tikz, "\\draw[line width=0.75pt] {} circle ({:.3});", point((x + TILE_SIZE / 2.0, y + TILE_SIZE / 2.0)), 1.0 / 6.0).unwrap();
+        }
+    }
+    writeln!(tikz, "\\end{{tikzpicture}}").unwrap();
+    tikz
+}
+
+//The characters in the middle of each tile in ASCII art, and in Unicode art
+const ASCII_MIDDLES: [char; 11] = [' ', '.', '<', '.', '>', '-', '|', '\\', '/', '+', 'o'];
+const UNICODE_MIDDLES: [char; 11] = [' ', '╮', '┌', '╰', '┘', '─', '│', '╲', '╱', '╂', '○'];
+
+//The three lines of a tile's text art, with `horizontal` and `vertical` strands leaving its connected faces and `middles` in the middle
+fn tile_art(tile: u8, horizontal: char, vertical: char, middles: &[char; 11]) -> [String; 3] {
+    let strand = |face: usize, strand: char| if PARTNER[tile as usize][face] != NONE { strand } else { ' ' };
+    [format!(" {} ", strand(1, vertical)), format!("{}{}{}", strand(2, horizontal), middles[tile as usize], strand(0, horizontal)), format!(" {} ", strand(3, vertical))]
+}
+
+//The three lines of a tile's Unicode art (see to_unicode)
+pub fn unicode_tile(tile: u8) -> [String; 3] {
+    tile_art(tile, '─', '│', &UNICODE_MIDDLES)
+}
+
+//Renders a mosaic as text, three characters square to a tile, drawing each tile with `tile_lines`
+fn text_art(mosaic: &Mosaic, tile_lines: impl Fn(u8) -> [String; 3]) -> String {
+    let mut art = String::new();
+    for row in mosaic.tiles().chunks(mosaic.size()) {
+        let tiles: Vec<[String; 3]> = row.iter().map(|&tile| tile_lines(tile)).collect();
+        for line in 0..3 {
+            writeln!(art, "{}", tiles.iter().map(|tile| tile[line].as_str()).collect::<String>().trim_end()).unwrap();
+        }
+    }
+    art
+}
+
+//Renders a mosaic as ASCII art (see above), one line of text per row of characters
+pub fn to_ascii(mosaic: &Mosaic) -> String {
+    text_art(mosaic, |tile| tile_art(tile, '-', '|', &ASCII_MIDDLES))
+}
+
+//Renders a mosaic as Unicode art, like ASCII art but drawn with box-drawing characters, with the cusps square and the smooth turns rounded
+pub fn to_unicode(mosaic: &Mosaic) -> String {
+    text_art(mosaic, unicode_tile)
+}
+
+//Renders a mosaic as an RGBA image, rotated as the SVG is, returning its width (and height) and its pixels row by row
+fn rasterize(mosaic: &Mosaic) -> (usize, Vec<[u8; 4]>) {
+    let width = mosaic_width(mosaic);
+    let pixels_wide = (width * SQRT_2).ceil() as usize;
+    let (offset, center) = ((pixels_wide as f64 - width) / 2.0, width / 2.0);
+    let mut pixels = vec![[0; 4]; pixels_wide * pixels_wide];
+    //Rotating 45° counterclockwise about the center of the mosaic, and back
+    let rotated = |(x, y): Point| (center + offset + FRAC_1_SQRT_2 * ((x - center) + (y - center)), center + offset + FRAC_1_SQRT_2 * ((y - center) - (x - center)));
+    let unrotated = |(x, y): Point| {
+        let (x, y) = (x - center - offset, y - center - offset);
+        (center + FRAC_1_SQRT_2 * (x - y), center + FRAC_1_SQRT_2 * (x + y))
+    };
+    let grid_line = |coordinate: f64| {
+        let within = (coordinate - BORDER_SIZE).rem_euclid(TILE_SIZE);
+        !(0.5..=TILE_SIZE - 0.5).contains(&within)
+    };
+    for (index, pixel) in pixels.iter_mut().enumerate() {
+        let (x, y) = unrotated(((index % pixels_wide) as f64 + 0.5, (index / pixels_wide) as f64 + 0.5));
+        if !(0.0..width).contains(&x) || !(0.0..width).contains(&y) {
+            continue;
+        }
+        let in_border = x < BORDER_SIZE || y < BORDER_SIZE || x >= width - BORDER_SIZE || y >= width - BORDER_SIZE;
+        *pixel = if in_border || grid_line(x) || grid_line(y) { BORDER_RGBA } else { [255; 4] };
+    }
+    //Strands are drawn as discs stamped along them
+    let mut stamp = |point: Point, radius: f64| {
+        let (x, y) = rotated(point);
+        let range = |coordinate: f64| (coordinate - radius).floor().max(0.0) as usize..((coordinate + radius).ceil() as usize).min(pixels_wide);
+        for row in range(y) {
+            for col in range(x) {
+                if (col as f64 + 0.5 - x).powi(2) + (row as f64 + 0.5 - y).powi(2) <= radius * radius {
+                    pixels[row * pixels_wide + col] = [0, 0, 0, 255];
+                }
+            }
+        }
+    };
+    for (i, &tile) in mosaic.tiles().iter().enumerate() {
+        let (x, y) = tile_corner(mosaic, i);
+        for stroke in tile_strokes(tile, x, y).into_iter().flatten() {
+            for point in stroke.points(64) {
+                stamp(point, STRAND_WIDTH / 2.0);
+            }
+        }
+        if tile == VIRTUAL_CROSSING {
+            let (radius, steps) = (TILE_SIZE / 6.0, 96);
+            for step in 0..steps {
+                let angle = 4.0 * FRAC_PI_2 * step as f64 / steps as f64;
+                stamp((x + TILE_SIZE / 2.0 + radius * angle.cos(), y + TILE_SIZE / 2.0 + radius * angle.sin()), STRAND_WIDTH / 4.0);
+            }
+        }
+    }
+    (pixels_wide, pixels)
+}
+
+//Renders a mosaic as a PNG image, rotated as the SVG is, with a transparent background outside the mosaic
+pub fn to_png(mosaic: &Mosaic) -> Vec<u8> {
+    let (width, pixels) = rasterize(mosaic);
+    //Each row starts with its filter type, 0 for none
+    let mut data = ZlibEncoder::new(Vec::with_capacity(pixels.len()), Compression::default());
+    for row in pixels.chunks(width) {
+        data.write_all(&[0]).and_then(|_| data.write_all(row.as_flattened())).expect("writing to a Vec can't fail");
+    }
+    let data = data.finish().expect("writing to a Vec can't fail");
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut chunk = |kind: &[u8; 4], contents: &[u8]| {
+        png.extend((contents.len() as u32).to_be_bytes());
+        let mut crc = Crc::new();
+        crc.update(kind);
+        crc.update(contents);
+        png.extend(kind);
+        png.extend(contents);
+        png.extend(crc.sum().to_be_bytes());
+    };
+    let mut header = Vec::new();
+    header.extend((width as u32).to_be_bytes());
+    header.extend((width as u32).to_be_bytes());
+    header.extend([8, 6, 0, 0, 0]); //8 bits per channel of RGBA, deflate, no filtering, no interlacing
+    chunk(b"IHDR", &header);
+    chunk(b"IDAT", &data);
+    chunk(b"IEND", &[]);
+    png
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Svg,
+    Png,
+    Tikz,
+    Ascii,
+    Unicode,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Svg => "svg",
+            Format::Png => "png",
+            Format::Tikz => "tex",
+            Format::Ascii | Format::Unicode => "txt",
+        }
+    }
+
+    //Whether mosaics in this format are written one after another to a single output, rather than to an image file each
+    fn is_text(&self) -> bool {
+        matches!(self, Format::Tikz | Format::Ascii | Format::Unicode)
+    }
+
+    pub fn from_name(name: &str) -> Option<Format> {
+        [Format::Svg, Format::Png, Format::Tikz, Format::Ascii, Format::Unicode].into_iter().find(|format| format!("{:?}", format).eq_ignore_ascii_case(name) || format.extension() == name)
+    }
+}
+
+//Writes a mosaic in the given format, with text art under the mosaic's code
+pub fn write_mosaic(output: &mut dyn Write, mosaic: &Mosaic, format: Format) -> Result<()> {
+    match format {
+        Format::Svg => output.write_all(to_svg(mosaic).as_bytes()),
+        Format::Png => output.write_all(&to_png(mosaic)),
+        Format::Tikz => output.write_all(to_tikz(mosaic).as_bytes()),
+        Format::Ascii => write!(output, "{}\n{}", mosaic, to_ascii(mosaic)),
+        Format::Unicode => write!(output, "{}\n{}", mosaic, to_unicode(mosaic)),
+    }
+}
+
+/* Renders the mosaics in a file (or stdin, for -) in the given format, or only those at the given ranks (counting from 0) if `ranks` is given.
+TikZ pictures and ASCII or Unicode art are written one after another to the output, separated by blank lines,
+ while each SVG or PNG image is written to its own file, named by the output with the mosaic's rank before its extensions (see output::ranked_path),
+ or to the output itself when only one mosaic is rendered by rank.
+Returns the number of mosaics rendered. */
+pub fn render_file(input_path: &str, output_path: &str, format: Format, ranks: Option<&BTreeSet<u64>>) -> Result<usize> {
+    let single = ranks.is_some_and(|ranks| ranks.len() == 1);
+    if !format.is_text() && !single && (output::is_stdout(output_path) || output_path.starts_with("unix:")) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} images are written to a file each, so can't all go to {}", format.extension(), output_path)));
+    }
+    let mut text_output = if format.is_text() { Some(output::create(output_path)?) } else { None };
+    let mut rendered = 0;
+    for (rank, mosaic) in read_mosaics(input_path)?.enumerate() {
+        let rank = rank as u64;
+        if ranks.is_some_and(|ranks| ranks.last().is_some_and(|&last| rank > last)) {
+            break;
+        }
+        if ranks.is_some_and(|ranks| !ranks.contains(&rank)) {
+            continue;
+        }
+        let mosaic = mosaic?;
+        match &mut text_output {
+            Some(output) => {
+                if rendered > 0 {
+                    writeln!(output)?;
+                }
+                write_mosaic(output, &mosaic, format)?;
+            }
+            None => {
+                let path = if single { output_path.to_string() } else { output::ranked_path(output_path, rank) };
+                let mut image = output::create(&path)?;
+                write_mosaic(&mut image, &mosaic, format)?;
+                image.finish()?;
+            }
+        }
+        rendered += 1;
+    }
+    if let Some(mut output) = text_output {
+        output.finish()?;
+    }
+    if let Some(missing) = ranks.and_then(|ranks| ranks.iter().nth(rendered)) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("the input has no mosaic at rank {}", missing)));
+    }
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    //The standard 2x2 unknot, the max-tb right-handed trefoil and a once-stabilized unknot
+    const UNKNOT: &str = "2134";
+    const TREFOIL: &str = "0021025971629943943103554";
+    const STABILIZED: &str = "0000000000000210024600354";
+
+    //A file of mosaics in the temporary directory, removed when dropped
+    struct TempFile(String);
+
+    impl TempFile {
+        fn new(name: &str, contents: &str) -> TempFile {
+            let path = env::temp_dir().join(format!("mosaic_gen-render-test-{}-{}", process::id(), name)).to_str().unwrap().to_string();
+            fs::write(&path, contents).unwrap();
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn ascii_art() {
+        assert_eq!(to_ascii(&UNKNOT.parse().unwrap()), "\n <--.\n |  |\n |  |\n .-->\n\n");
+        let trefoil = [
+            "",
+            "       <--.",
+            "       |  |",
+            "       |  |",
+            " <-----+--\\--.",
+            " |     |  |  |",
+            " |     |  |  |",
+            " |  <--+--+-->",
+            " |  |  |  |",
+            " |  |  |  |",
+            " .--+-->  .--.",
+            "    |        |",
+            "    |        |",
+            "    .-------->",
+            "",
+            "",
+        ];
+        assert_eq!(to_ascii(&TREFOIL.parse().unwrap()), trefoil.join("\n"));
+    }
+
+    #[test]
+    fn svg_of_the_unknot() {
+        let svg = [
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"373\" height=\"373\" viewBox=\"-54.676 -54.676 373.352 373.352\">",
+            "<g transform=\"rotate(-45 132 132)\">",
+            "<rect x=\"0\" y=\"0\" width=\"264\" height=\"264\" fill=\"#c4c4c4\"/>",
+            "<rect x=\"4\" y=\"4\" width=\"128\" height=\"128\" fill=\"white\" stroke=\"#c4c4c4\" stroke-width=\"1\"/>",
+            "<path d=\"M 74 74 C 88 88 104 68 132 68 M 74 74 C 88 88 68 104 68 132\" fill=\"none\" stroke=\"black\" stroke-width=\"8\"/>",
+            "<rect x=\"132\" y=\"4\" width=\"128\" height=\"128\" fill=\"white\" stroke=\"#c4c4c4\" stroke-width=\"1\"/>",
+            "<path d=\"M 132 68 A 64 64 0 0 1 196 132\" fill=\"none\" stroke=\"black\" stroke-width=\"8\"/>",
+            "<rect x=\"4\" y=\"132\" width=\"128\" height=\"128\" fill=\"white\" stroke=\"#c4c4c4\" stroke-width=\"1\"/>",
+            "<path d=\"M 132 196 A 64 64 0 0 1 68 132\" fill=\"none\" stroke=\"black\" stroke-width=\"8\"/>",
+            "<rect x=\"132\" y=\"132\" width=\"128\" height=\"128\" fill=\"white\" stroke=\"#c4c4c4\" stroke-width=\"1\"/>",
+            "<path d=\"M 190 190 C 176 176 196 160 196 132 M 190 190 C 176 176 160 196 132 196\" fill=\"none\" stroke=\"black\" stroke-width=\"8\"/>",
+            "</g>",
+            "</svg>",
+            "",
+        ];
+        assert_eq!(to_svg(&UNKNOT.parse().unwrap()), svg.join("\n"));
+    }
+
+    #[test]
+    fn pngs_decode_to_the_rasterized_pixels() {
+        for code in [UNKNOT, TREFOIL] {
+            let mosaic: Mosaic = code.parse().unwrap();
+            let png = to_png(&mosaic);
+            let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+            let mut decoded = vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut decoded).unwrap();
+            let (width, pixels) = rasterize(&mosaic);
+            assert_eq!((info.width as usize, info.height as usize), (width, width));
+            assert_eq!((info.color_type, info.bit_depth), (png::ColorType::Rgba, png::BitDepth::Eight));
+            assert_eq!(&decoded[..info.buffer_size()], pixels.as_flattened());
+            //Transparent in the corners, outside the rotated mosaic, and grey in the middle of its top border
+            assert_eq!(pixels[0], [0; 4]);
+            assert_eq!(pixels[width / 2 * width + 1], BORDER_RGBA);
+        }
+    }
+
+    #[test]
+    fn ranks_pick_the_mosaics_rendered() {
+        let input = TempFile::new("input.txt", &format!("{}\n{}\n\n{}\n", UNKNOT, TREFOIL, STABILIZED));
+        let mosaics: Vec<Mosaic> = [UNKNOT, TREFOIL, STABILIZED].iter().map(|code| code.parse().unwrap()).collect();
+        let text = TempFile::new("ranks.txt", "");
+        assert_eq!(render_file(&input.0, &text.0, Format::Ascii, Some(&BTreeSet::from([0, 2]))).unwrap(), 2);
+        assert_eq!(fs::read_to_string(&text.0).unwrap(), format!("{}\n{}\n{}\n{}", mosaics[0], to_ascii(&mosaics[0]), mosaics[2], to_ascii(&mosaics[2])));
+        //A single rank's image goes to the output itself
+        let image = TempFile::new("rank.png", "");
+        assert_eq!(render_file(&input.0, &image.0, Format::Png, Some(&BTreeSet::from([1]))).unwrap(), 1);
+        assert_eq!(fs::read(&image.0).unwrap(), to_png(&mosaics[1]));
+        let error = render_file(&input.0, &text.0, Format::Ascii, Some(&BTreeSet::from([1, 3]))).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,210 @@
+/*
+Normal rulings of the front a mosaic encodes, and its ruling polynomial.
+Read as a front, the x axis runs along the mosaic's diagonal from its top left corner to its bottom right, so a tile at (row, col) sits at x = row + col,
+ with its faces 1 and 2 on its left (above and below) and its faces 0 and 3 on its right. Moving across a tile only changes the strands through it:
+ tile 2 is a left cusp, tile 4 a right cusp, tile 8 a right cusp followed by a left cusp, tile 9 a crossing, and every other strand just passes through.
+The tiles on each antidiagonal share an x coordinate, so they're taken one at a time from the bottom of the front (the mosaic's bottom left) to its top,
+ as if the front were perturbed slightly to put its cusps and crossings at different x coordinates.
+
+A ruling pairs up the strands over every x, so that the strands meeting at each cusp are paired, and the pairs only change at the crossings chosen as switches,
+ where the two crossing strands swap partners. Paired strands only meet at their cusps, so they never cross each other, and the ruling is normal if the pairs meeting at each switch are
+ nested or disjoint rather than interlaced. Sweeping across the front, the rulings are counted by the pairing they leave, so they never have to be listed.
+The (ungraded) ruling polynomial R(z) is the sum over normal rulings of z^(switches - right cusps + 1), which is a Legendrian isotopy invariant (Chekanov and Pushkar):
+ it's nonzero exactly when the Chekanov-Eliashberg algebra has an augmentation, which makes tb maximal in the knot type, e.g. z^2 + 2 for the trefoil with tb = 1.
+*/
+
+use crate::diagram::Diagram;
+use crate::invariants::Polynomial;
+use crate::mosaic::{Mosaic, NONE, PARTNER, VIRTUAL_CROSSING};
+use std::collections::HashMap;
+
+//Pairings of the strands over some x, in order from the bottom of the front, with the number of rulings reaching each pairing by their number of switches so far
+type Rulings = HashMap<Vec<u8>, Vec<u64>>;
+
+fn add_rulings(rulings: &mut Rulings, pairing: Vec<u8>, counts: &[u64], extra_switches: usize) {
+    let entry = rulings.entry(pairing).or_default();
+    if entry.len() < counts.len() + extra_switches {
+        entry.resize(counts.len() + extra_switches, 0);
+    }
+    for (switches, &count) in counts.iter().enumerate() {
+        entry[switches + extra_switches] += count;
+    }
+}
+
+//Two new strands paired with each other at position k, from a left cusp
+fn left_cusp(rulings: Rulings, k: usize) -> Rulings {
+    let shift = |strand: u8| if (strand as usize) < k { strand } else { strand + 2 };
+    let mut result = Rulings::new();
+    for (pairing, counts) in rulings {
+        let mut paired: Vec<u8> = pairing.iter().map(|&partner| shift(partner)).collect();
+        paired.splice(k..k, [k as u8 + 1, k as u8]);
+        add_rulings(&mut result, paired, &counts, 0);
+    }
+    result
+}
+
+//Removes the strands at positions k and k + 1, which meet at a right cusp, keeping the rulings that pair them
+fn right_cusp(rulings: Rulings, k: usize) -> Rulings {
+    let shift = |strand: u8| if (strand as usize) < k { strand } else { strand - 2 };
+    let mut result = Rulings::new();
+    for (pairing, counts) in rulings {
+        if pairing[k] as usize != k + 1 {
+            continue;
+        }
+        let paired: Vec<u8> = pairing.iter().enumerate().filter(|&(strand, _)| strand != k && strand != k + 1).map(|(_, &partner)| shift(partner)).collect();
+        add_rulings(&mut result, paired, &counts, 0);
+    }
+    result
+}
+
+//The strands at positions k and k + 1 cross, either keeping their partners or, at a normal switch, swapping them
+fn crossing(rulings: Rulings, k: usize) -> Rulings {
+    let swap = |strand: u8| match strand as usize {
+        strand if strand == k => k as u8 + 1,
+        strand if strand == k + 1 => k as u8,
+        _ => strand,
+    };
+    let mut result = Rulings::new();
+    for (pairing, counts) in rulings {
+        let (below, above) = (pairing[k] as usize, pairing[k + 1] as usize);
+        //Paired strands only meet at their cusps
+        if below == k + 1 {
+            continue;
+        }
+        //The pairs are disjoint, or one is nested inside the other
+        let normal = if below < k { above > k + 1 || above < below } else { k + 1 < above && above < below };
+        if normal {
+            add_rulings(&mut result, pairing.clone(), &counts, 1);
+        }
+        let mut crossed = vec![0; pairing.len()];
+        for (strand, &partner) in pairing.iter().enumerate() {
+            crossed[swap(strand as u8) as usize] = swap(partner);
+        }
+        add_rulings(&mut result, crossed, &counts, 0);
+    }
+    result
+}
+
+//Where the strands over x change, reading the front from left to right, at the position of the lowest strand involved (counted from the bottom of the front)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FrontEvent {
+    LeftCusp(usize),
+    RightCusp(usize),
+    Crossing(usize),
+}
+
+//The events of a mosaic's front in order (see above), each with the position of its tile, or None for mosaics with virtual crossings
+pub fn front_events(mosaic: &Mosaic) -> Option<Vec<(FrontEvent, (usize, usize))>> {
+    let (size, tiles) = (mosaic.size(), mosaic.tiles());
+    if tiles.contains(&VIRTUAL_CROSSING) {
+        return None;
+    }
+    let mut events = Vec::new();
+    for x in 0..2 * size - 1 {
+        //Strands below the tile, i.e. leaving the tiles already passed on this antidiagonal
+        let mut below = 0;
+        for row in (0..size).rev().filter(|&row| row <= x && x - row < size) {
+            let tile = tiles[row * size + x - row];
+            let position = (row, x - row);
+            match tile {
+                2 => events.push((FrontEvent::LeftCusp(below), position)),
+                4 => events.push((FrontEvent::RightCusp(below), position)),
+                8 => events.extend([(FrontEvent::RightCusp(below), position), (FrontEvent::LeftCusp(below), position)]),
+                9 => events.push((FrontEvent::Crossing(below), position)),
+                _ => (),
+            }
+            below += [0, 3].iter().filter(|&&face| PARTNER[tile as usize][face] != NONE).count();
+        }
+    }
+    Some(events)
+}
+
+//The ruling polynomial of a diagram's front (see above), which is 0 if it has no normal rulings, or None for mosaics with virtual crossings
+pub fn ruling_polynomial(diagram: &Diagram) -> Option<Polynomial> {
+    let events = front_events(&diagram.mosaic)?;
+    let mut rulings = Rulings::from([(Vec::new(), vec![1])]);
+    for &(event, _) in &events {
+        rulings = match event {
+            FrontEvent::LeftCusp(k) => left_cusp(rulings, k),
+            FrontEvent::RightCusp(k) => right_cusp(rulings, k),
+            FrontEvent::Crossing(k) => crossing(rulings, k),
+        };
+    }
+    let right_cusps = events.iter().filter(|(event, _)| matches!(event, FrontEvent::RightCusp(_))).count() as i32;
+    let counts = rulings.remove(&Vec::new()).unwrap_or_default();
+    Some(counts.iter().enumerate().fold(Polynomial::monomial(0, 0), |polynomial, (switches, &count)| {
+        polynomial.add(&Polynomial::monomial(switches as i32 - right_cusps + 1, count as i64))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::generate_with;
+    use crate::invariants::jones;
+    use crate::knots::knot_name;
+    use std::ops::ControlFlow;
+
+    //The knots of every 4x4 mosaic and of every `stride`th 5x5 one that have no virtual crossings, with their names
+    fn knots(stride: usize) -> Vec<(Diagram, Option<String>)> {
+        let mut knots = Vec::new();
+        for (size, stride) in [(4, 1), (5, stride)] {
+            let mut index = 0;
+            let _ = generate_with::<()>(size, |mosaic| {
+                index += 1;
+                if index % stride != 0 || mosaic.tiles().contains(&VIRTUAL_CROSSING) {
+                    return ControlFlow::Continue(());
+                }
+                if let Some(diagram) = Diagram::trace(mosaic).filter(|diagram| diagram.component_count() == 1) {
+                    let name = jones(&diagram).and_then(|jones| knot_name(&jones));
+                    knots.push((diagram, name));
+                }
+                ControlFlow::Continue(())
+            });
+        }
+        knots
+    }
+
+    #[test]
+    fn rulings_of_the_unknot_and_trefoils() {
+        //The standard unknot, the max-tb right-handed trefoil, a stabilization of it, and the max-tb left-handed trefoil
+        for (code, ruling) in [
+            ("0000000000000000002100034", Polynomial::monomial(0, 1)),
+            ("0021025971629943943103554", Polynomial { low: 0, coefficients: vec![2, 0, 1] }),
+            ("0021002891299463791603434", Polynomial::monomial(0, 0)),
+            ("0021002971294663759403540", Polynomial::monomial(1, 1)),
+        ] {
+            let diagram = Diagram::trace(&code.parse().unwrap()).unwrap();
+            assert_eq!(ruling_polynomial(&diagram), Some(ruling), "{}", code);
+        }
+    }
+
+    #[test]
+    fn only_max_tb_knots_have_rulings() {
+        let mut ruled = 0;
+        for (diagram, name) in knots(97) {
+            let ruling = ruling_polynomial(&diagram).unwrap();
+            let max_tb = match name.as_deref() {
+                Some("0_1") => -1,
+                Some("3_1") => 1,
+                Some("m(3_1)") => -6,
+                _ => continue,
+            };
+            //A stabilized front has no normal rulings, and the unknot with tb = -1 has just the one
+            assert_eq!(!ruling.is_zero(), diagram.tb() == max_tb, "{}", diagram.mosaic);
+            assert!(name.as_deref() != Some("0_1") || ruling.is_zero() || ruling == Polynomial::monomial(0, 1), "{}", diagram.mosaic);
+            ruled += !ruling.is_zero() as usize;
+        }
+        assert!(ruled > 10);
+    }
+
+    #[test]
+    fn rulings_are_kept_by_the_symmetries() {
+        for (diagram, _) in knots(997) {
+            let ruling = ruling_polynomial(&diagram);
+            for image in [diagram.mosaic.flip_x(), diagram.mosaic.mirror(), diagram.mosaic.rotate180()] {
+                assert_eq!(ruling_polynomial(&Diagram::trace(&image).unwrap()), ruling, "{} and {}", diagram.mosaic, image);
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+/*
+Output to S3-compatible object storage (enabled by the `s3` feature), for outputs too large for local scratch disks.
+An s3://bucket/key output is gzip compressed as it's written, and each PART_SIZE chunk of compressed output is sent as one part of a multipart upload,
+ so only about one part is ever held in memory. The object appears once the output is finished; if writing fails the upload is aborted.
+
+Requests are signed with AWS signature version 4, using credentials from the usual environment variables:
+ AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, AWS_SESSION_TOKEN (optional), AWS_REGION (default us-east-1),
+ and AWS_ENDPOINT_URL (optional) for S3-compatible stores other than AWS, which are addressed path-style.
+Failed requests are retried with exponential backoff, except for client errors that retrying can't fix.
+*/
+
+use crate::output::{self, Sink};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::mem;
+use std::thread;
+use std::time::Duration;
+
+//S3 requires every part but the last to be at least 5 MiB, and allows 10000 parts -- so objects can be up to about 160 GB compressed
+const PART_SIZE: usize = 16 << 20;
+const MAX_ATTEMPTS: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hmac(key: &[u8], message: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+//Percent-encodes everything but unreserved characters (and '/', for paths), as signature version 4 expects
+fn uri_encode(value: &str, keep_slashes: bool) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            b'/' if keep_slashes => "/".to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+//Current UTC time as (YYYYMMDD, YYYYMMDDTHHMMSSZ)
+fn timestamp() -> (String, String) {
+    let (year, month, day, time) = output::utc_now();
+    let date = format!("{:04}{:02}{:02}", year, month, day);
+    let date_time = format!("{}T{:02}{:02}{:02}Z", date, time / 3600, time / 60 % 60, time % 60);
+    (date, date_time)
+}
+
+struct Client {
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+    region: String,
+    scheme: String,
+    host: String,
+    path: String, //URI encoded path of the object
+}
+
+impl Client {
+    fn from_env(bucket: &str, key: &str) -> Result<Client> {
+        let variable = |name: &str| env::var(name).map_err(|_| Error::new(ErrorKind::InvalidInput, format!("{} must be set for S3 output", name)));
+        let region = env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        let (scheme, host, path) = match env::var("AWS_ENDPOINT_URL") {
+            Ok(endpoint) => {
+                let (scheme, host) = endpoint.split_once("://").unwrap_or(("https", &endpoint));
+                (scheme.to_string(), host.trim_end_matches('/').to_string(), format!("/{}/{}", uri_encode(bucket, false), uri_encode(key, true)))
+            }
+            Err(_) => ("https".to_string(), format!("{}.s3.{}.amazonaws.com", bucket, region), format!("/{}", uri_encode(key, true))),
+        };
+        Ok(Client {
+            access_key: variable("AWS_ACCESS_KEY_ID")?,
+            secret_key: variable("AWS_SECRET_ACCESS_KEY")?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+            region,
+            scheme,
+            host,
+            path,
+        })
+    }
+
+    //Builds a request signed for sending `body`
+    fn signed_request(&self, method: &str, query: &[(&str, &str)], body: &[u8]) -> ureq::Request {
+        let (date, date_time) = timestamp();
+        let payload_hash = hex(&Sha256::digest(body));
+        let mut query: Vec<(String, String)> = query.iter().map(|(name, value)| (uri_encode(name, false), uri_encode(value, false))).collect();
+        query.sort();
+        let query = query.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<String>>().join("&");
+
+        let mut headers = vec![("host", self.host.clone()), ("x-amz-content-sha256", payload_hash.clone()), ("x-amz-date", date_time.clone())];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<&str>>().join(";");
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+        let canonical_request = format!("{}\n{}\n{}\n{}\n{}\n{}", method, self.path, query, canonical_headers, signed_headers, payload_hash);
+
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", date_time, scope, hex(&Sha256::digest(canonical_request.as_bytes())));
+        let signing_key = [date.as_str(), &self.region, "s3", "aws4_request"].iter().fold(format!("AWS4{}", self.secret_key).into_bytes(), |key, part| hmac(&key, part));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key,
+            scope,
+            signed_headers,
+            hex(&hmac(&signing_key, &string_to_sign))
+        );
+
+        let url = format!("{}://{}{}{}{}", self.scheme, self.host, self.path, if query.is_empty() { "" } else { "?" }, query);
+        let mut request = ureq::request(method, &url).set("Authorization", &authorization);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.set(name, value);
+        }
+        request
+    }
+
+    //Sends a signed request, retrying transport errors, throttling and server errors
+    fn send(&self, method: &str, query: &[(&str, &str)], body: &[u8]) -> Result<ureq::Response> {
+        let mut attempt = 1;
+        loop {
+            let error = match self.signed_request(method, query, body).send_bytes(body) {
+                Ok(response) => return Ok(response),
+                Err(ureq::Error::Status(status, response)) if status < 500 && status != 429 => {
+                    let message = response.into_string().unwrap_or_default();
+                    return Err(Error::other(format!("S3 {} {} failed with status {}: {}", method, self.path, status, message)));
+                }
+                Err(error) => error,
+            };
+            if attempt == MAX_ATTEMPTS {
+                return Err(Error::other(format!("S3 {} {} failed after {} attempts: {}", method, self.path, attempt, error)));
+            }
+            thread::sleep(RETRY_DELAY * 2u32.pow(attempt - 1));
+            attempt += 1;
+        }
+    }
+}
+
+//Text of the first <tag> element of an XML response
+fn xml_element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let length = xml[start..].find(&format!("</{}>", tag))?;
+    Some(&xml[start..start + length])
+}
+
+pub struct S3Sink {
+    client: Client,
+    upload_id: String,
+    encoder: Option<GzEncoder<Vec<u8>>>,
+    etags: Vec<String>,
+}
+
+impl S3Sink {
+    //Starts a multipart upload to `location` (bucket/key)
+    pub fn create(location: &str) -> Result<S3Sink> {
+        let (bucket, key) = location
+            .split_once('/')
+            .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("S3 output s3://{} should be of the form s3://bucket/key", location)))?;
+        let client = Client::from_env(bucket, key)?;
+        let response = client.send("POST", &[("uploads", "")], &[])?.into_string()?;
+        let upload_id = xml_element(&response, "UploadId")
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("no upload ID in response to starting upload: {}", response)))?
+            .to_string();
+        Ok(S3Sink { client, upload_id, encoder: Some(GzEncoder::new(Vec::with_capacity(PART_SIZE), Compression::default())), etags: Vec::new() })
+    }
+
+    fn upload_part(&mut self, part: &[u8]) -> Result<()> {
+        let part_number = (self.etags.len() + 1).to_string();
+        let response = self.client.send("PUT", &[("partNumber", &part_number), ("uploadId", &self.upload_id)], part)?;
+        let etag = response.header("ETag").ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("no ETag in response to uploading part {}", part_number)))?;
+        self.etags.push(etag.to_string());
+        Ok(())
+    }
+
+    fn encoder(&mut self) -> Result<&mut GzEncoder<Vec<u8>>> {
+        self.encoder.as_mut().ok_or_else(|| Error::other("S3 output has already been finished"))
+    }
+}
+
+impl Write for S3Sink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let encoder = self.encoder()?;
+        encoder.write_all(buf)?;
+        if encoder.get_ref().len() >= PART_SIZE {
+            let part = mem::replace(encoder.get_mut(), Vec::with_capacity(PART_SIZE));
+            self.upload_part(&part)?;
+        }
+        Ok(buf.len())
+    }
+
+    //Parts can only be sent once they're large enough, so there's nothing to do until the output is finished
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Sink for S3Sink {
+    fn finish(&mut self) -> Result<()> {
+        let encoder = self.encoder.take().ok_or_else(|| Error::other("S3 output has already been finished"))?;
+        let part = encoder.finish()?;
+        self.upload_part(&part)?;
+        let parts: String = self
+            .etags
+            .iter()
+            .enumerate()
+            .map(|(index, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", index + 1, etag))
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts);
+        let response = self.client.send("POST", &[("uploadId", &self.upload_id)], body.as_bytes())?.into_string()?;
+        //Completion can fail after S3 has already responded with 200
+        if response.contains("<Error>") {
+            return Err(Error::other(format!("S3 upload failed to complete: {}", response)));
+        }
+        self.upload_id.clear();
+        Ok(())
+    }
+}
+
+//Aborts the upload if it was never completed, so the parts already sent don't linger (and keep being billed)
+impl Drop for S3Sink {
+    fn drop(&mut self) {
+        if !self.upload_id.is_empty() {
+            let upload_id = mem::take(&mut self.upload_id);
+            if let Err(error) = self.client.send("DELETE", &[("uploadId", &upload_id)], &[]) {
+                eprintln!("Failed to abort S3 upload: {}", error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+
+    //A request received by the fake S3 server: method, path and query, headers (lowercase names), and body
+    type Request = (String, String, Vec<(String, String)>, Vec<u8>);
+
+    //Reads a request from a connection to the fake server
+    fn read_request(reader: &mut impl BufRead) -> Request {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let mut words = line.split_whitespace().map(str::to_string);
+        let (method, target) = (words.next().unwrap(), words.next().unwrap());
+        let mut headers = Vec::new();
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            match header.trim().split_once(':') {
+                Some((name, value)) => headers.push((name.trim().to_lowercase(), value.trim().to_string())),
+                None => break,
+            }
+        }
+        let length = headers.iter().find(|(name, _)| name == "content-length").map_or(0, |(_, length)| length.parse().unwrap());
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        (method, target, headers, body)
+    }
+
+    #[test]
+    fn encoding_and_responses() {
+        assert_eq!(uri_encode("runs/5x5 lex.txt", true), "runs/5x5%20lex.txt");
+        assert_eq!(uri_encode("a/b+c~", false), "a%2Fb%2Bc~");
+        let response = "<InitiateMultipartUploadResult><Bucket>b</Bucket><UploadId>abc-123</UploadId></InitiateMultipartUploadResult>";
+        assert_eq!(xml_element(response, "UploadId"), Some("abc-123"));
+        assert_eq!(xml_element(response, "Key"), None);
+        assert_eq!(hex(&[0, 15, 255]), "000fff");
+    }
+
+    //Runs a multipart upload against a local server, which fails the first attempt to send the part
+    #[test]
+    fn uploads_compressed_parts_with_retry() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        env::set_var("AWS_ENDPOINT_URL", format!("http://{}", listener.local_addr().unwrap()));
+        env::set_var("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE");
+        env::set_var("AWS_SECRET_ACCESS_KEY", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY");
+        let server = thread::spawn(move || {
+            let responses = [
+                ("200 OK", "", "<InitiateMultipartUploadResult><UploadId>abc-123</UploadId></InitiateMultipartUploadResult>"),
+                ("500 Internal Server Error", "", ""),
+                ("200 OK", "ETag: \"part-1\"\r\n", ""),
+                ("200 OK", "", "<CompleteMultipartUploadResult></CompleteMultipartUploadResult>"),
+            ];
+            let mut requests = Vec::new();
+            for (status, headers, body) in responses {
+                let mut stream = listener.accept().unwrap().0;
+                requests.push(read_request(&mut BufReader::new(&mut stream)));
+                write!(stream, "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}", status, headers, body.len(), body).unwrap();
+            }
+            requests
+        });
+
+        let mut sink = S3Sink::create("mosaics/runs/5x5.txt.gz").unwrap();
+        sink.write_all(b"2134\n").unwrap();
+        sink.finish().unwrap();
+        drop(sink);
+        let requests = server.join().unwrap();
+        let targets: Vec<(&str, &str)> = requests.iter().map(|(method, target, _, _)| (method.as_str(), target.as_str())).collect();
+        assert_eq!(
+            targets,
+            [
+                ("POST", "/mosaics/runs/5x5.txt.gz?uploads="),
+                ("PUT", "/mosaics/runs/5x5.txt.gz?partNumber=1&uploadId=abc-123"),
+                ("PUT", "/mosaics/runs/5x5.txt.gz?partNumber=1&uploadId=abc-123"),
+                ("POST", "/mosaics/runs/5x5.txt.gz?uploadId=abc-123"),
+            ]
+        );
+        for (_, _, headers, body) in &requests {
+            let header = |name: &str| headers.iter().find(|(header, _)| header == name).map(|(_, value)| value.as_str());
+            assert!(header("authorization").is_some_and(|authorization| authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/")));
+            assert_eq!(header("x-amz-content-sha256"), Some(hex(&Sha256::digest(body)).as_str()));
+        }
+        let mut uploaded = String::new();
+        GzDecoder::new(&requests[2].3[..]).read_to_string(&mut uploaded).unwrap();
+        assert_eq!(uploaded, "2134\n");
+        assert_eq!(String::from_utf8_lossy(&requests[3].3), "<CompleteMultipartUpload><Part><PartNumber>1</PartNumber><ETag>\"part-1\"</ETag></Part></CompleteMultipartUpload>");
+    }
+}
@@ -0,0 +1,242 @@
+/*
+Markov chain sampling of the diagrams of a Legendrian class: a random walk from a seed mosaic through moves that keep the front's Legendrian isotopy class,
+ for experimenting on "typical" diagrams of a class on a given board rather than the few found by hand. Each step picks a kind of move with probability
+ proportional to its weight, then proposes one of that kind:
+    block       a move replacing a 2 x 2 block of tiles (see moves.rs), picked uniformly from the mosaic's moves
+    translate   moving the mosaic's tiles a row or column up, down, left or right on the board (see Mosaic::translate), staying put if there's no room
+    flip        the reflection x -> -x of the front (see symmetry.rs), which is Legendrian isotopic to it
+    reidemeister a Legendrian Reidemeister move (see reidemeister.rs), picked uniformly from the mosaic's, adding or removing crossings -- not made by default
+Block and Reidemeister moves are accepted by the Metropolis-Hastings rule, with probability min(1, m(x) / m(y)) for m(x) the number of moves of that kind
+ of the mosaic x and m(y) that of the proposal, since mosaics with more moves would otherwise be visited more often. Every kind of step is then reversible for the uniform distribution, so the chain samples uniformly
+ from the mosaics its moves reach from the seed on its board (all with the seed's crossings unless Reidemeister moves are made) once it's mixed,
+ which is what the burn-in is for, with consecutive samples taken `thin` steps apart to be less correlated.
+The seed can be put on a larger board first (see Mosaic::embed) to give its front more room to wander.
+Samples are reproducible: the walk is driven by a splitmix64 generator from a given seed number.
+*/
+
+use crate::moves::{moves, Move};
+use crate::mosaic::Mosaic;
+use crate::output;
+use crate::reidemeister::{reidemeister_moves, Replacement};
+use std::fmt;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::str::FromStr;
+
+//The splitmix64 generator (Steele, Lea and Flood), which is tiny, fast and good enough for random walks
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    //A uniform number in 0..count (which mustn't be 0), with negligible bias for the counts here
+    pub fn below(&mut self, count: usize) -> usize {
+        ((self.next_u64() as u128 * count as u128) >> 64) as usize
+    }
+
+    //A uniform number in [0, 1)
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+//The relative weights of the kinds of move, written like block=1,translate=0.1,flip=0.01,reidemeister=0 (kinds left out keep their defaults)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MoveWeights {
+    pub block: f64,
+    pub translate: f64,
+    pub flip: f64,
+    pub reidemeister: f64,
+}
+
+impl Default for MoveWeights {
+    fn default() -> MoveWeights {
+        MoveWeights { block: 1.0, translate: 0.1, flip: 0.01, reidemeister: 0.0 }
+    }
+}
+
+impl fmt::Display for MoveWeights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "block={},translate={},flip={},reidemeister={}", self.block, self.translate, self.flip, self.reidemeister)
+    }
+}
+
+impl MoveWeights {
+    fn total(&self) -> f64 {
+        self.block + self.translate + self.flip + self.reidemeister
+    }
+}
+
+impl FromStr for MoveWeights {
+    type Err = Error;
+
+    fn from_str(weights: &str) -> Result<MoveWeights> {
+        let invalid = |message: String| Error::new(ErrorKind::InvalidInput, format!("invalid move weights \"{}\": {}", weights, message));
+        let mut parsed = MoveWeights::default();
+        for weight in weights.split(',').map(str::trim).filter(|weight| !weight.is_empty()) {
+            let (kind, value) = weight.split_once('=').ok_or_else(|| invalid(format!("{} isn't like block=1", weight)))?;
+            let value = value.trim().parse::<f64>().ok().filter(|value| value.is_finite() && *value >= 0.0).ok_or_else(|| invalid(format!("{} isn't a weight", value)))?;
+            match kind.trim() {
+                "block" => parsed.block = value,
+                "translate" => parsed.translate = value,
+                "flip" => parsed.flip = value,
+                "reidemeister" => parsed.reidemeister = value,
+                kind => return Err(invalid(format!("unknown kind of move {} (the kinds are block, translate, flip and reidemeister)", kind))),
+            }
+        }
+        if parsed.total() <= 0.0 {
+            return Err(invalid("every weight is 0".to_string()));
+        }
+        Ok(parsed)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SamplerOptions {
+    pub weights: MoveWeights,
+    //Steps taken before the first sample, and between samples
+    pub burn_in: u64,
+    pub thin: u64,
+    pub seed: u64,
+}
+
+impl Default for SamplerOptions {
+    fn default() -> SamplerOptions {
+        SamplerOptions { weights: MoveWeights::default(), burn_in: 10000, thin: 100, seed: 0 }
+    }
+}
+
+/* A mosaic a step could move to, with its moves and the ratio m(x) / m(y) of the chances of proposing the step and its inverse
+ (1 for translations and flips, which are always proposed with the same chance) */
+pub struct Proposal {
+    pub mosaic: Mosaic,
+    moves: Vec<Move>,
+    reidemeister_moves: Vec<Replacement>,
+    pub ratio: f64,
+}
+
+//A random walk through the moves of a mosaic, yielding its samples as an iterator
+pub struct Sampler {
+    mosaic: Mosaic,
+    moves: Vec<Move>,
+    reidemeister_moves: Vec<Replacement>,
+    rng: Rng,
+    options: SamplerOptions,
+    burnt_in: bool,
+    pub steps: u64,
+    pub accepted: u64,
+}
+
+impl Sampler {
+    //A walk from a suitably connected mosaic
+    pub fn new(seed: Mosaic, options: SamplerOptions) -> Result<Sampler> {
+        if !seed.is_suitably_connected() {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("{} isn't a suitably connected mosaic", seed)));
+        }
+        let (moves, reidemeister_moves) = (moves(&seed), Sampler::reidemeister_moves(&seed, &options));
+        Ok(Sampler { mosaic: seed, moves, reidemeister_moves, rng: Rng::new(options.seed), options, burnt_in: false, steps: 0, accepted: 0 })
+    }
+
+    //A mosaic's Reidemeister moves, only found if they're ever made
+    fn reidemeister_moves(mosaic: &Mosaic, options: &SamplerOptions) -> Vec<Replacement> {
+        if options.weights.reidemeister > 0.0 { reidemeister_moves(mosaic) } else { Vec::new() }
+    }
+
+    pub fn mosaic(&self) -> &Mosaic {
+        &self.mosaic
+    }
+
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    //A random proposal from the mosaic, or None if the kind of move picked has no room (see above)
+    pub fn propose(&mut self) -> Option<Proposal> {
+        let weights = self.options.weights;
+        let choice = self.rng.unit() * weights.total();
+        let mosaic = if choice < weights.block {
+            if self.moves.is_empty() {
+                return None;
+            }
+            self.moves[self.rng.below(self.moves.len())].apply(&self.mosaic)
+        } else if choice < weights.block + weights.translate {
+            let (rows, cols) = [(1, 0), (-1, 0), (0, 1), (0, -1)][self.rng.below(4)];
+            self.mosaic.translate(rows, cols).ok()?
+        } else if choice < weights.block + weights.translate + weights.flip {
+            self.mosaic.flip_x()
+        } else {
+            if self.reidemeister_moves.is_empty() {
+                return None;
+            }
+            self.reidemeister_moves[self.rng.below(self.reidemeister_moves.len())].apply(&self.mosaic)
+        };
+        let (moves, reidemeister_moves) = (moves(&mosaic), Sampler::reidemeister_moves(&mosaic, &self.options));
+        let ratio = if choice < weights.block {
+            self.moves.len() as f64 / moves.len() as f64
+        } else if choice < weights.block + weights.translate + weights.flip {
+            1.0
+        } else {
+            self.reidemeister_moves.len() as f64 / reidemeister_moves.len() as f64
+        };
+        Some(Proposal { mosaic, moves, reidemeister_moves, ratio })
+    }
+
+    //Moves to a proposal
+    pub fn accept(&mut self, proposal: Proposal) {
+        self.mosaic = proposal.mosaic;
+        self.moves = proposal.moves;
+        self.reidemeister_moves = proposal.reidemeister_moves;
+        self.accepted += 1;
+    }
+
+    //Takes a step of the walk, returning whether it moved (a rejected proposal, or a move with no room to make it, stays put)
+    pub fn step(&mut self) -> bool {
+        self.steps += 1;
+        match self.propose() {
+            Some(proposal) if self.rng.unit() < proposal.ratio => {
+                self.accept(proposal);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    //Fraction of the steps so far that moved
+    pub fn acceptance_rate(&self) -> f64 {
+        self.accepted as f64 / self.steps.max(1) as f64
+    }
+}
+
+impl Iterator for Sampler {
+    type Item = Mosaic;
+
+    //The next sample, after the burn-in for the first and `thin` steps (at least one) for the rest
+    fn next(&mut self) -> Option<Mosaic> {
+        let steps = if self.burnt_in { self.options.thin.max(1) } else { self.options.burn_in };
+        self.burnt_in = true;
+        for _ in 0..steps {
+            self.step();
+        }
+        Some(self.mosaic.clone())
+    }
+}
+
+//Writes `count` samples of the walk from a seed mosaic to an output, one mosaic code a line, returning the sampler for its statistics
+pub fn sample(seed: Mosaic, output_path: &str, count: u64, options: SamplerOptions) -> Result<Sampler> {
+    let mut sampler = Sampler::new(seed, options)?;
+    let mut output = output::create(output_path)?;
+    for mosaic in sampler.by_ref().take(count as usize) {
+        writeln!(output, "{}", mosaic)?;
+    }
+    output.finish()?;
+    Ok(sampler)
+}
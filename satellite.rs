@@ -0,0 +1,122 @@
+/*
+Legendrian satellites of mosaics, such as the Legendrian Whitehead double.
+A satellite is built from the n-copy of a front -- n copies of the knot, each pushed slightly up the z axis of the front from the last -- with a pattern tangle inserted into its n parallel strands.
+On a mosaic, the n-copy replaces every tile with an n x n block of tiles carrying n parallel strands: straight tiles and crossings just become n straight strands or n^2 crossings,
+ and smooth turns become n nested turns, but the copies of a cusp cross each other, so a cusp becomes a staircase of n cusps with a crossing between each pair of copies, e.g.
+    0 0 2           4 9 9
+    0 2 9   and     9 4 0
+    2 9 9           4 0 0
+ for the left and right cusps with n = 3. This is the Legendrian push-off, so the copies link each other tb times, where tb is the knot's Thurston-Bennequin number.
+
+A pattern is an m x m block of tiles, written as a mosaic code, whose strands enter by the first n rows of its left edge and leave by the last n columns of its bottom edge, meeting nothing else at its edges.
+It's inserted at the last tile of the mosaic's first occupied row, which is always a smooth turn (tile 1), whose n-copy turns n strands from the left edge of the block down its bottom edge:
+ the columns to its left and the rows below it are stretched by m - n tiles to make room, continuing the strands crossing them, and the stretched block is replaced by the pattern.
+The Whitehead double uses the clasp of a right cusp hooked through a left cusp (2 9 / 9 4, in the middle of the pattern) on the 2-copy, giving the Legendrian Whitehead double (twisted by the tb framing of the push-off).
+*/
+
+use crate::mosaic::{Mosaic, NONE, PARTNER, VIRTUAL_CROSSING};
+use std::cmp::Ordering;
+use std::io::{Error, ErrorKind, Result};
+
+//The Whitehead clasp, joining the two strands entering on the left with a right cusp and the two leaving by the bottom with a left cusp, hooked together
+pub const WHITEHEAD_CLASP: &str = "5510129139460316";
+
+//The n x n block of an n-copy standing in for a tile (see above)
+fn copy_block(tile: u8, n: usize) -> Vec<Vec<u8>> {
+    let block = |tile_at: &dyn Fn(usize, usize) -> u8| (0..n).map(|row| (0..n).map(|col| tile_at(row, col)).collect()).collect();
+    //Tiles before, on and after the block's antidiagonal
+    let staircase = |[before, on, after]: [u8; 3]| {
+        block(&|row, col| match (row + col).cmp(&(n - 1)) {
+            Ordering::Less => before,
+            Ordering::Equal => on,
+            Ordering::Greater => after,
+        })
+    };
+    match tile {
+        //Cusps become staircases of cusps, with the copies crossing on the side their branches leave by
+        2 => staircase([0, 2, 9]),
+        4 => staircase([9, 4, 0]),
+        8 => staircase([9, 8, 9]),
+        //Smooth turns become nested turns, straight along the strands leading into them
+        1 => staircase([5, 1, 6]),
+        3 => staircase([6, 3, 5]),
+        //Straight strands, crossings and smooth double turns keep their tiles, the double turns carrying staircases from the left edge to the bottom and from the top edge to the right
+        _ => block(&|_, _| tile),
+    }
+}
+
+//The number of strands of a pattern, checking that they meet its edges as a pattern's should (see above)
+fn pattern_strands(pattern: &Mosaic) -> Result<usize> {
+    let size = pattern.size();
+    let tiles = pattern.tiles();
+    let connected = |index: usize, face: u8| PARTNER[tiles[index] as usize][face as usize] != NONE;
+    let left: Vec<bool> = (0..size).map(|row| connected(row * size, 2)).collect();
+    let bottom: Vec<bool> = (0..size).map(|col| connected((size - 1) * size + col, 3)).collect();
+    let strands = left.iter().filter(|&&end| end).count();
+    let meets_edges = left.iter().enumerate().all(|(row, &end)| end == (row < strands))
+        && bottom.iter().enumerate().all(|(col, &end)| end == (col >= size - strands))
+        && (0..size).all(|line| !connected(line, 1) && !connected(line * size + size - 1, 0));
+    let matched = (0..tiles.len()).all(|index| {
+        (0..4).all(|face| match pattern.neighbor(index, face) {
+            Some(next) => connected(index, face) == connected(next, (face + 2) % 4),
+            None => true,
+        })
+    });
+    if strands == 0 || !meets_edges || !matched {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} isn't a pattern, whose strands enter by the top of its left edge and leave by the right of its bottom edge", pattern),
+        ));
+    }
+    Ok(strands)
+}
+
+/* The satellite of a mosaic with the given pattern (see above), on a mosaic n times the size of the original plus m - n, where n is the number of strands of the m x m pattern.
+For links, the pattern goes on the component through the last tile of the first occupied row. */
+pub fn satellite(mosaic: &Mosaic, pattern: &Mosaic) -> Result<Mosaic> {
+    let n = pattern_strands(pattern)?;
+    let (size, tiles) = (mosaic.size(), mosaic.tiles());
+    if !mosaic.is_suitably_connected() {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} isn't a suitably connected mosaic", mosaic)));
+    }
+    if tiles.contains(&VIRTUAL_CROSSING) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} has virtual crossings, which fronts don't", mosaic)));
+    }
+    //The last tile of the first occupied row has no strand above it or to its right, so it can only be a smooth turn
+    let turn = tiles
+        .iter()
+        .position(|&tile| tile != 0)
+        .map(|first| (first / size, (0..size).rev().find(|&col| tiles[first / size * size + col] != 0).expect("the row is occupied")))
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("{} is empty", mosaic)))?;
+    debug_assert_eq!(tiles[turn.0 * size + turn.1], 1);
+
+    //The n-copy, with the columns to the left of the turn's block and the rows below it stretched to fit the pattern
+    let stretch = pattern.size() - n;
+    let new_size = n * size + stretch;
+    let (stretched_col, stretched_row) = (n * turn.1, n * (turn.0 + 1));
+    let mut rows: Vec<Vec<u8>> = vec![Vec::with_capacity(new_size); n * size];
+    for (row, tile_row) in tiles.chunks(size).enumerate() {
+        for (col, &tile) in tile_row.iter().enumerate() {
+            if col == turn.1 {
+                for copy_row in 0..n {
+                    let strand_left = col > 0 && PARTNER[tile_row[col - 1] as usize][0] != NONE;
+                    rows[n * row + copy_row].extend(vec![if strand_left { 5 } else { 0 }; stretch]);
+                }
+            }
+            for (copy_row, block_row) in copy_block(tile, n).into_iter().enumerate() {
+                rows[n * row + copy_row].extend(block_row);
+            }
+        }
+    }
+    let strands_below: Vec<u8> = rows[stretched_row - 1].iter().map(|&tile| if PARTNER[tile as usize][3] != NONE { 6 } else { 0 }).collect();
+    rows.splice(stretched_row..stretched_row, vec![strands_below; stretch]);
+
+    //Inserts the pattern over the stretched block
+    let (top, left) = (n * turn.0, stretched_col);
+    for (pattern_row, pattern_tiles) in pattern.tiles().chunks(pattern.size()).enumerate() {
+        rows[top + pattern_row][left..left + pattern.size()].copy_from_slice(pattern_tiles);
+    }
+    let mosaic = Mosaic::from_tiles(new_size, rows.concat())?;
+    debug_assert!(mosaic.is_suitably_connected());
+    Ok(mosaic)
+}
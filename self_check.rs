@@ -0,0 +1,81 @@
+/*
+A second opinion on the mosaics the generator writes, for catching subtle generator bugs after optimizations of its connection table and search.
+It checks suitable connectedness again from scratch, sharing nothing with the generator or Mosaic::is_suitably_connected (which both work from PARTNER):
+ each tile's sides are read off its own drawing below, and every edge of the board -- between two tiles, or between a tile and the boundary -- is checked
+ to have a strand crossing it from both sides or from neither.
+A run checks each mosaic with a given probability (1 to check them all), picked by a splitmix64 generator seeded by the size so that runs are repeatable.
+*/
+
+use crate::mosaic::TILE_COUNT;
+use crate::sample::Rng;
+use std::io::{Error, ErrorKind, Result};
+
+//The sides of each tile a strand reaches, as (left, right, top, bottom), from the tiles' drawings
+const SIDES: [(bool, bool, bool, bool); 11] = [
+    (false, false, false, false), //0 blank
+    (true, false, false, true),   //1 ╮
+    (false, true, false, true),   //2 ╭, the left cusp <
+    (false, true, true, false),   //3 ╰
+    (true, false, true, false),   //4 ╯, the right cusp >
+    (true, true, false, false),   //5 ─
+    (false, false, true, true),   //6 │
+    (true, true, true, true),     //7 ╮ and ╰
+    (true, true, true, true),     //8 ╭ and ╯
+    (true, true, true, true),     //9 crossing
+    (true, true, true, true),     //10 virtual crossing
+];
+
+pub struct SelfCheck {
+    probability: f64,
+    rng: Rng,
+    pub checked: u64,
+}
+
+impl SelfCheck {
+    pub fn new(size: usize, probability: f64) -> SelfCheck {
+        SelfCheck { probability, rng: Rng::new(size as u64), checked: 0 }
+    }
+
+    //Checks a mosaic written by the generator as `line` (if it's picked), with an error saying where it falls apart if it isn't suitably connected
+    pub fn check(&mut self, tiles: &[usize], size: usize, line: &str) -> Result<()> {
+        if self.probability < 1.0 && self.rng.unit() >= self.probability {
+            return Ok(());
+        }
+        self.checked += 1;
+        match loose_edge(tiles, size) {
+            None => Ok(()),
+            Some(edge) => Err(Error::new(ErrorKind::InvalidData, format!("self-check failed: the generator wrote {}, but {}", line, edge))),
+        }
+    }
+}
+
+//Where a size x size mosaic isn't suitably connected, if it isn't
+fn loose_edge(tiles: &[usize], size: usize) -> Option<String> {
+    if tiles.len() != size * size {
+        return Some(format!("it has {} tiles rather than {}", tiles.len(), size * size));
+    }
+    if let Some(index) = tiles.iter().position(|&tile| tile >= TILE_COUNT as usize) {
+        return Some(format!("tile {} isn't a tile", index));
+    }
+    let sides = |row: usize, col: usize| SIDES[tiles[row * size + col]];
+    for row in 0..size {
+        //The edges left of each tile in the row, and right of its last tile, with the boundary taken as reaching no side
+        for col in 0..=size {
+            let left = col > 0 && sides(row, col - 1).1;
+            let right = col < size && sides(row, col).0;
+            if left != right {
+                return Some(format!("the edge left of column {} in row {} is crossed from only one side", col, row));
+            }
+        }
+    }
+    for col in 0..size {
+        for row in 0..=size {
+            let above = row > 0 && sides(row - 1, col).3;
+            let below = row < size && sides(row, col).2;
+            if above != below {
+                return Some(format!("the edge above row {} in column {} is crossed from only one side", row, col));
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,218 @@
+/*
+Serde support (enabled by the `serde` feature), so mosaics, resume tokens, census records and the settings of runs round-trip through JSON, TOML, bincode and the like.
+Mosaics and resume tokens have two forms: human-readable formats get their compact forms, the mosaic code (e.g. "0000000000210034") or the token a stopped run
+ prints (e.g. "4:1000:2121379424603540"), and binary formats get their structured forms, their parts as a struct. Human-readable formats read either form back,
+ and both are checked as they're read, so a mosaic that's read back has the right number of tiles and a resume token names a mosaic of its size.
+Orders and placements are written by name, and conditions as they're written on the command line (like tb>=1), read back with the built-in invariants.
+A GeneratorConfig only borrows its metrics, so it's written as its GeneratorSettings, everything but the metrics, and a config read back publishes nowhere.
+Everything else (census records, Bounds, Constraints, patterns, Chunking, EmitOptions and Stages) derives its structured form where it's defined.
+*/
+
+use crate::affinity::Placement;
+use crate::generator::{GeneratorConfig, GeneratorSettings, MosaicGenerator, Order, ResumeToken};
+use crate::invariant::{Condition, Registry};
+use crate::mosaic::Mosaic;
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+//The structured form of a mosaic
+#[derive(Serialize, Deserialize)]
+struct MosaicParts {
+    size: usize,
+    tiles: Vec<u8>,
+}
+
+//The structured form of a resume token
+#[derive(Serialize, Deserialize)]
+struct ResumeTokenParts {
+    size: usize,
+    generated: u64,
+    last: Vec<usize>,
+    order: Order,
+}
+
+//Either form of a value, as a human-readable format can hold it
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Form<T> {
+    Compact(String),
+    Structured(T),
+}
+
+//Reads either form in human-readable formats, and the structured form in the rest
+fn deserialize_form<'de, D: Deserializer<'de>, T: Deserialize<'de>>(deserializer: D) -> Result<Form<T>, D::Error> {
+    if deserializer.is_human_readable() {
+        Form::deserialize(deserializer)
+    } else {
+        T::deserialize(deserializer).map(Form::Structured)
+    }
+}
+
+impl Serialize for Mosaic {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            MosaicParts { size: self.size(), tiles: self.tiles().to_vec() }.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Mosaic {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Mosaic, D::Error> {
+        match deserialize_form(deserializer)? {
+            Form::Compact(code) => code.parse().map_err(de::Error::custom),
+            Form::Structured(MosaicParts { size, tiles }) => Mosaic::from_tiles(size, tiles).map_err(de::Error::custom),
+        }
+    }
+}
+
+impl Serialize for ResumeToken {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            ResumeTokenParts { size: self.size, generated: self.generated, last: self.last.clone(), order: self.order }.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ResumeToken {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ResumeToken, D::Error> {
+        match deserialize_form(deserializer)? {
+            Form::Compact(token) => token.parse().map_err(de::Error::custom),
+            Form::Structured(ResumeTokenParts { size, generated, last, order }) => {
+                if MosaicGenerator::new(size).resume_after(&last).is_none() {
+                    return Err(de::Error::custom(format!("the last mosaic of a resume token for {}x{} mosaics isn't a {}x{} mosaic", size, size, size, size)));
+                }
+                Ok(ResumeToken { size, generated, last, order })
+            }
+        }
+    }
+}
+
+impl Serialize for Order {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Order {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Order, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Placement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Placement {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Placement, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Condition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Condition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Condition, D::Error> {
+        let condition = String::deserialize(deserializer)?;
+        let mut conditions = Registry::builtin().conditions(&condition).map_err(de::Error::custom)?;
+        match (conditions.pop(), conditions.is_empty()) {
+            (Some(condition), true) => Ok(condition),
+            _ => Err(de::Error::custom(format!("{} isn't a single condition", condition))),
+        }
+    }
+}
+
+impl Serialize for GeneratorConfig<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GeneratorSettings::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GeneratorConfig<'_> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        GeneratorSettings::deserialize(deserializer).map(GeneratorConfig::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Bounds, Constraints, EmitOptions, Position};
+    use crate::output::Chunking;
+
+    fn token() -> ResumeToken {
+        "4:1000:2121379424603540".parse().unwrap()
+    }
+
+    fn config() -> GeneratorConfig<'static> {
+        let constraints = Constraints {
+            pattern: Some("21/.3".parse().unwrap()),
+            max_crossings: Some(2),
+            forbidden: vec![10],
+            seed: Some((1, 1, "9".parse().unwrap())),
+            conditions: Registry::builtin().conditions("tb>=1;name=3_1").unwrap(),
+            ..Constraints::default()
+        };
+        let bounds = Bounds { resume: Some(token()), end: Some(Position::Rank(5000)), order: Order::Lex, max_seconds: Some(1.5), ..Bounds::default() };
+        GeneratorConfig::new(4, "mosaics.txt")
+            .with_constraints(constraints)
+            .with_bounds(bounds)
+            .with_threads(4)
+            .with_placement(Placement::Spread)
+            .with_chunking(Chunking { records: Some(100), ..Chunking::default() })
+            .with_emit(EmitOptions { ranks: true, self_check: Some(0.5) })
+    }
+
+    #[test]
+    fn json_uses_the_compact_forms() {
+        let mosaic: Mosaic = "0000000000210034".parse().unwrap();
+        assert_eq!(serde_json::to_string(&mosaic).unwrap(), "\"0000000000210034\"");
+        assert_eq!(serde_json::from_str::<Mosaic>("\"0000000000210034\"").unwrap(), mosaic);
+        assert_eq!(serde_json::to_string(&token()).unwrap(), "\"4:1000:2121379424603540\"");
+        assert_eq!(serde_json::from_str::<ResumeToken>(&serde_json::to_string(&token()).unwrap()).unwrap(), token());
+    }
+
+    #[test]
+    fn json_reads_the_structured_forms() {
+        let mosaic = serde_json::from_str::<Mosaic>(r#"{"size":2,"tiles":[2,1,3,4]}"#).unwrap();
+        assert_eq!(mosaic, "2134".parse().unwrap());
+        let token = serde_json::from_str::<ResumeToken>(r#"{"size":4,"generated":1000,"last":[2,1,2,1,3,7,9,4,2,4,6,0,3,5,4,0],"order":"lex"}"#).unwrap();
+        assert_eq!(token, self::token());
+        assert!(serde_json::from_str::<Mosaic>(r#"{"size":2,"tiles":[2,1,3]}"#).is_err());
+        assert!(serde_json::from_str::<ResumeToken>(r#"{"size":4,"generated":1,"last":[1,2],"order":"lex"}"#).is_err());
+    }
+
+    #[test]
+    fn bincode_uses_the_structured_forms() {
+        let mosaic: Mosaic = "0000000000210034".parse().unwrap();
+        let bytes = bincode::serialize(&mosaic).unwrap();
+        assert_eq!(bytes, bincode::serialize(&MosaicParts { size: 4, tiles: mosaic.tiles().to_vec() }).unwrap());
+        assert_eq!(bincode::deserialize::<Mosaic>(&bytes).unwrap(), mosaic);
+        let bytes = bincode::serialize(&token()).unwrap();
+        assert_eq!(bincode::deserialize::<ResumeToken>(&bytes).unwrap(), token());
+        assert!(bincode::deserialize::<Mosaic>(&bincode::serialize(&MosaicParts { size: 3, tiles: vec![0; 4] }).unwrap()).is_err());
+    }
+
+    #[test]
+    fn generator_configs_round_trip() {
+        let json = serde_json::to_string(&config()).unwrap();
+        assert!(json.contains("\"tb>=1\"") && json.contains("\"spread\"") && json.contains("\"order\":\"lex\"") && json.contains("\"4:1000:2121379424603540\""));
+        let read: GeneratorConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&read).unwrap(), json);
+        let bytes = bincode::serialize(&config()).unwrap();
+        let read: GeneratorConfig = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(bincode::serialize(&read).unwrap(), bytes);
+        assert_eq!(serde_json::to_string(&read).unwrap(), json);
+    }
+}
@@ -0,0 +1,292 @@
+/*
+HTTP server for querying a file of generated mosaics without wrangling the file itself. The mosaics are loaded once at startup,
+ and census records (see census.rs) are computed for each request. The rank of a mosaic is its position (from 0) among the mosaics of its size in the file,
+ which for files written by mosaic_gen is its position in the generation order.
+
+ GET  /mosaics/{n}/{rank}          census record of the n x n mosaic with the given rank
+ GET  /random/{n}                  census record of a uniformly random n x n mosaic
+ POST /identify                    census record of the mosaic code in the request body (which needn't be in the file)
+ GET  /render.svg?mosaic={code}    SVG image of a mosaic (see render.rs), also available as a POST of the code
+
+Census records are returned as JSON objects, with "rank" and "name" null when unknown. Errors are returned as {"error": message}.
+*/
+
+use crate::atlas::unknot_label;
+use crate::census::CensusRecord;
+use crate::error::Error;
+use crate::mosaic::{read_mosaics, Mosaic};
+use crate::render::to_svg;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Read, Result, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+//Largest request body accepted, far longer than any mosaic code we could generate
+const MAX_BODY_LENGTH: usize = 1 << 16;
+
+struct Database {
+    mosaics: BTreeMap<usize, Vec<Mosaic>>,
+    ranks: HashMap<Mosaic, usize>,
+}
+
+impl Database {
+    fn load(input_path: &str) -> Result<Database> {
+        let mut database = Database { mosaics: BTreeMap::new(), ranks: HashMap::new() };
+        for mosaic in read_mosaics(input_path)? {
+            let mosaic = mosaic?;
+            let of_size = database.mosaics.entry(mosaic.size()).or_default();
+            database.ranks.entry(mosaic.clone()).or_insert(of_size.len());
+            of_size.push(mosaic);
+        }
+        Ok(database)
+    }
+
+    fn of_size(&self, size: &str) -> std::result::Result<&[Mosaic], Response> {
+        let size: usize = size.parse().map_err(|_| Response::error(400, &format!("invalid size \"{}\"", size)))?;
+        match self.mosaics.get(&size) {
+            Some(mosaics) => Ok(mosaics),
+            None => Err(Response::error(404, &format!("no {}x{} mosaics loaded", size, size))),
+        }
+    }
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(body: String) -> Response {
+        Response { status: 200, content_type: "application/json", body }
+    }
+
+    fn error(status: u16, message: &str) -> Response {
+        Response { status, content_type: "application/json", body: format!("{{\"error\": {}}}", json_string(message)) }
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn record_json(record: &CensusRecord, rank: Option<usize>) -> String {
+    format!(
+        "{{\"mosaic\": \"{}\", \"size\": {}, \"rank\": {}, \"components\": {}, \"crossings\": {}, \"seifert_circles\": {}, \"genus_bound\": {}, \"bridge_number\": {}, \"alternating\": {}, \"sign\": {}, \"symmetries\": {}, \"composite\": {}, \"determinant\": {}, \"signature\": {}, \"lambda_plus\": {}, \"lambda_minus\": {}, \"ruling_polynomial\": {}, \"augmentation_number\": {}, \"tb\": {}, \"rot\": {}, \"name\": {}, \"atlas\": {}, \"unknot_class\": {}, \"jones\": {}, \"id\": \"{}\"}}",
+        record.mosaic,
+        record.mosaic.size(),
+        rank.map_or("null".to_string(), |rank| rank.to_string()),
+        record.components,
+        record.crossings,
+        record.seifert_circles,
+        record.genus_bound.map_or("null".to_string(), |genus| genus.to_string()),
+        record.bridge_number,
+        record.alternating,
+        record.sign,
+        json_string(&record.symmetry_labels()),
+        record.composite,
+        record.determinant.map_or("null".to_string(), |determinant| determinant.to_string()),
+        record.signature.map_or("null".to_string(), |signature| signature.to_string()),
+        record.grid.map_or("null".to_string(), |(plus, _)| plus.to_string()),
+        record.grid.map_or("null".to_string(), |(_, minus)| minus.to_string()),
+        record.ruling.as_ref().map_or("null".to_string(), |ruling| json_string(&ruling.format("z", 1))),
+        record.augmentations.as_deref().map_or("null".to_string(), json_string),
+        record.tb,
+        record.rot,
+        record.name.as_deref().map_or("null".to_string(), json_string),
+        json_string(record.atlas.label()),
+        record.unknot.map_or("null".to_string(), |class| json_string(&unknot_label(class))),
+        json_string(&record.jones.format("t", 2)),
+        record.mosaic.id()
+    )
+}
+
+fn parse_mosaic(code: &str) -> std::result::Result<Mosaic, Response> {
+    code.parse().map_err(|error: Error| Response::error(400, &error.to_string()))
+}
+
+fn record_response(database: &Database, mosaic: &Mosaic) -> Response {
+    match CensusRecord::new(mosaic) {
+        Some(record) => Response::json(record_json(&record, database.ranks.get(mosaic).copied())),
+        None => Response::error(422, &format!("mosaic {} is empty or isn't suitably connected", mosaic)),
+    }
+}
+
+fn route(database: &Database, method: &str, target: &str, body: &str) -> std::result::Result<Response, Response> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    match (method, segments.as_slice()) {
+        ("GET", ["mosaics", size, rank]) => {
+            let mosaics = database.of_size(size)?;
+            let mosaic = rank
+                .parse::<usize>()
+                .ok()
+                .and_then(|rank| mosaics.get(rank))
+                .ok_or_else(|| Response::error(404, &format!("no mosaic of rank {} (there are {})", rank, mosaics.len())))?;
+            Ok(record_response(database, mosaic))
+        }
+        ("GET", ["random", size]) => {
+            let mosaics = database.of_size(size)?;
+            let random = RandomState::new().build_hasher().finish();
+            Ok(record_response(database, &mosaics[(random % mosaics.len() as u64) as usize]))
+        }
+        ("POST", ["identify"]) => Ok(record_response(database, &parse_mosaic(body)?)),
+        ("GET" | "POST", ["render.svg"]) => {
+            let code = match method {
+                "GET" => query
+                    .split('&')
+                    .find_map(|parameter| parameter.strip_prefix("mosaic="))
+                    .ok_or_else(|| Response::error(400, "missing mosaic parameter"))?,
+                _ => body,
+            };
+            Ok(Response { status: 200, content_type: "image/svg+xml", body: to_svg(&parse_mosaic(code)?) })
+        }
+        (_, ["mosaics", _, _] | ["random", _] | ["identify"]) => Err(Response::error(405, &format!("method {} not allowed", method))),
+        _ => Err(Response::error(404, &format!("no endpoint {}", path))),
+    }
+}
+
+fn handle(database: &Database, stream: TcpStream) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let response = match request_line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        _ if content_length > MAX_BODY_LENGTH => Response::error(413, "request body too long"),
+        [method, target, _] => {
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            let body = String::from_utf8_lossy(&body);
+            route(database, method, target, body.trim()).unwrap_or_else(|error| error)
+        }
+        _ => Response::error(400, "malformed request line"),
+    };
+
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Unprocessable Entity",
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+//Serves queries on the mosaics in the input file until the process is killed, handling each connection on its own thread
+pub fn serve(address: &str, input_path: &str) -> Result<()> {
+    let database = Arc::new(Database::load(input_path)?);
+    let listener = TcpListener::bind(address)?;
+    for (size, mosaics) in &database.mosaics {
+        println!("Loaded {} {}x{} mosaics", mosaics.len(), size, size);
+    }
+    println!("Serving on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("Connection failed: {}", error);
+                continue;
+            }
+        };
+        let database = Arc::clone(&database);
+        thread::spawn(move || {
+            if let Err(error) = handle(&database, stream) {
+                eprintln!("Request failed: {}", error);
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    //The standard 2x2 unknot and the max-tb right-handed trefoil
+    const UNKNOT: &str = "2134";
+    const TREFOIL: &str = "0021025971629943943103554";
+
+    //A database of the two mosaics above, loaded from a file
+    fn database() -> Database {
+        let path = env::temp_dir().join(format!("mosaic_gen-server-{}", process::id())).to_str().unwrap().to_string();
+        fs::write(&path, format!("{}\n{}\n", UNKNOT, TREFOIL)).unwrap();
+        let database = Database::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        database
+    }
+
+    #[test]
+    fn routes_answer_queries() {
+        let database = database();
+        let respond = |method: &str, target: &str, body: &str| route(&database, method, target, body).unwrap_or_else(|error| error);
+        let record = respond("GET", "/mosaics/5/0", "");
+        assert_eq!(record.status, 200);
+        assert!(record.body.starts_with(&format!("{{\"mosaic\": \"{}\", \"size\": 5, \"rank\": 0,", TREFOIL)));
+        assert!(record.body.contains("\"name\": \"3_1\""));
+        assert!(respond("GET", "/random/2", "").body.contains(&format!("\"mosaic\": \"{}\"", UNKNOT)));
+        assert_eq!(respond("GET", &format!("/render.svg?mosaic={}", UNKNOT), "").content_type, "image/svg+xml");
+        let statuses: Vec<u16> = ["/mosaics/5/1", "/mosaics/3/0", "/mosaics/x/0", "/render.svg", "/nowhere"].iter().map(|target| respond("GET", target, "").status).collect();
+        assert_eq!(statuses, [404, 404, 400, 400, 404]);
+        let identified = respond("POST", "/identify", "0000000000000210024600354");
+        assert!(identified.body.contains("\"rank\": null") && identified.body.contains("\"tb\": -2"));
+        assert_eq!(respond("POST", "/identify", "2100").status, 422);
+        assert_eq!(respond("DELETE", "/identify", "").status, 405);
+    }
+
+    #[test]
+    fn requests_get_http_responses() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            write!(stream, "POST /identify HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", UNKNOT.len(), UNKNOT).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        handle(&database(), listener.accept().unwrap().0).unwrap();
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n"));
+        assert!(response.ends_with("\"}") && response.contains("\"rank\": 0,"));
+        assert_eq!(json_string("a \"b\"\n"), "\"a \\\"b\\\"\\u000a\"");
+    }
+}
@@ -0,0 +1,72 @@
+/*
+Generation into a consumer in the same process, for embedding the generator in a service that computes invariants of the mosaics as they come.
+The consumer is a MosaicConsumer, given each mosaic in generation order (unlike the output::Sink a run writes its lines to). A bounded channel's SyncSender is a consumer that blocks while the channel is full,
+ so generation pauses whenever the consumer falls behind, never holding more than the channel's capacity of mosaics in memory,
+ and stops once the receiver is dropped. An unbounded Sender is a consumer too, for consumers that keep up (or don't mind the memory).
+*/
+
+use crate::error::{Error, Result};
+use crate::generator::MosaicGenerator;
+use crate::mosaic::Mosaic;
+use std::io;
+use std::sync::mpsc::{Sender, SyncSender};
+
+pub trait MosaicConsumer {
+    //Takes the next mosaic, blocking for as long as the consumer needs; an error stops generation with that error
+    fn accept(&mut self, mosaic: Mosaic) -> Result<()>;
+}
+
+fn receiver_dropped() -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::BrokenPipe, "the receiver of the mosaics was dropped"))
+}
+
+impl MosaicConsumer for SyncSender<Mosaic> {
+    fn accept(&mut self, mosaic: Mosaic) -> Result<()> {
+        self.send(mosaic).map_err(|_| receiver_dropped())
+    }
+}
+
+impl MosaicConsumer for Sender<Mosaic> {
+    fn accept(&mut self, mosaic: Mosaic) -> Result<()> {
+        self.send(mosaic).map_err(|_| receiver_dropped())
+    }
+}
+
+/* Gives every mosaic of a generator to a consumer, returning how many it took.
+Stops at the consumer's first error (a BrokenPipe error for a channel whose receiver was dropped), which is returned. */
+pub fn generate_into(generator: MosaicGenerator, consumer: &mut impl MosaicConsumer) -> Result<u64> {
+    let mut generated = 0;
+    for mosaic in generator {
+        consumer.accept(mosaic)?;
+        generated += 1;
+    }
+    Ok(generated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::{channel, sync_channel};
+    use std::thread;
+
+    #[test]
+    fn generates_every_mosaic_into_a_channel() {
+        let (mut sender, receiver) = channel();
+        let generated = generate_into(MosaicGenerator::new(4), &mut sender).unwrap();
+        drop(sender);
+        let mosaics: Vec<Mosaic> = receiver.iter().collect();
+        assert_eq!(generated, mosaics.len() as u64);
+        assert_eq!(mosaics, MosaicGenerator::new(4).collect::<Vec<Mosaic>>());
+    }
+
+    #[test]
+    fn generation_stops_once_the_receiver_is_dropped() {
+        let (mut sender, receiver) = sync_channel(2);
+        let generating = thread::spawn(move || generate_into(MosaicGenerator::new(4), &mut sender));
+        let first: Vec<Mosaic> = receiver.iter().take(10).collect();
+        drop(receiver);
+        let error = generating.join().unwrap().unwrap_err();
+        assert!(matches!(&error, Error::Io(error) if error.kind() == io::ErrorKind::BrokenPipe), "{}", error);
+        assert_eq!(first, MosaicGenerator::new(4).take(10).collect::<Vec<Mosaic>>());
+    }
+}
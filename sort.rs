@@ -0,0 +1,144 @@
+/*
+Sorting (and deduplicating) files of mosaic codes or other line-based records, within a memory budget.
+Lines are sorted in memory until they'd take more than the memory limit, then the sorted run is spilled to a temporary file (in the system temporary directory,
+ or TMPDIR), and the runs are merged once the input is exhausted -- so files much larger than memory can be sorted on shared machines.
+Mosaic codes of one size sort into generation order, so this also puts back together outputs generated out of order.
+*/
+
+use crate::error::Result;
+use crate::output;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::process;
+
+//Estimated memory used by each line held in memory, beyond its text: the String itself and its share of the Vec
+const LINE_OVERHEAD: u64 = 3 * mem::size_of::<usize>() as u64;
+
+//Temporary run files, removed once sorting finishes (or fails)
+struct Runs {
+    paths: Vec<PathBuf>,
+}
+
+impl Runs {
+    fn spill(&mut self, lines: &mut Vec<String>, unique: bool) -> io::Result<()> {
+        let path = env::temp_dir().join(format!("mosaic_gen-sort-{}-{}.txt", process::id(), self.paths.len()));
+        self.paths.push(path.clone());
+        let mut run = BufWriter::new(File::create(&path)?);
+        write_sorted(&mut run, lines, unique)?;
+        run.flush()?;
+        lines.clear();
+        Ok(())
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+//Sorts lines and writes them out, returning the number written
+fn write_sorted(output: &mut dyn Write, lines: &mut [String], unique: bool) -> io::Result<u64> {
+    lines.sort_unstable();
+    let mut written = 0;
+    let mut previous: Option<&String> = None;
+    for line in lines.iter() {
+        if unique && previous == Some(line) {
+            continue;
+        }
+        writeln!(output, "{}", line)?;
+        previous = Some(line);
+        written += 1;
+    }
+    Ok(written)
+}
+
+//Merges sorted runs into the output, returning the number of lines written
+fn merge(output: &mut dyn Write, runs: &Runs, unique: bool) -> io::Result<u64> {
+    let mut readers = runs.paths.iter().map(|path| Ok(BufReader::new(File::open(path)?).lines())).collect::<io::Result<Vec<_>>>()?;
+    let mut heap = BinaryHeap::new();
+    for (index, reader) in readers.iter_mut().enumerate() {
+        if let Some(line) = reader.next() {
+            heap.push(Reverse((line?, index)));
+        }
+    }
+    let mut written = 0;
+    let mut previous: Option<String> = None;
+    while let Some(Reverse((line, index))) = heap.pop() {
+        if let Some(next) = readers[index].next() {
+            heap.push(Reverse((next?, index)));
+        }
+        if unique && previous.as_ref() == Some(&line) {
+            continue;
+        }
+        writeln!(output, "{}", line)?;
+        previous = Some(line);
+        written += 1;
+    }
+    Ok(written)
+}
+
+/* Writes the lines of `input_path` to `output_path` in sorted order, leaving out repeated lines if `unique`,
+ and holding at most about `memory_limit` bytes of lines in memory (if given). Returns the number of lines written. */
+pub fn sort_lines(input_path: &str, output_path: &str, memory_limit: Option<u64>, unique: bool) -> Result<u64> {
+    let mut runs = Runs { paths: Vec::new() };
+    let mut lines = Vec::new();
+    let mut used = 0;
+    for line in BufReader::new(File::open(input_path)?).lines() {
+        let line = line?;
+        used += line.len() as u64 + LINE_OVERHEAD;
+        lines.push(line);
+        if memory_limit.is_some_and(|limit| used >= limit) {
+            runs.spill(&mut lines, unique)?;
+            used = 0;
+        }
+    }
+
+    let mut output_buffer = output::create(output_path)?;
+    let written = if runs.paths.is_empty() {
+        write_sorted(&mut output_buffer, &mut lines, unique)?
+    } else {
+        if !lines.is_empty() {
+            runs.spill(&mut lines, unique)?;
+        }
+        merge(&mut output_buffer, &runs, unique)?
+    };
+    output_buffer.finish()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn spilled_runs_merge_in_order() {
+        let directory = env::temp_dir();
+        let (input_path, output_path) = (directory.join(format!("mosaic_gen-sort-test-{}.txt", process::id())), directory.join(format!("mosaic_gen-sorted-test-{}.txt", process::id())));
+        let lines: Vec<String> = (0..500).map(|line| format!("{:03}", line * 7 % 300)).collect();
+        fs::write(&input_path, lines.join("\n") + "\n").unwrap();
+        let (input, output) = (input_path.to_str().unwrap(), output_path.to_str().unwrap());
+        let mut sorted = lines.clone();
+        sorted.sort();
+        for (memory_limit, unique) in [(None, false), (Some(256), false), (Some(256), true)] {
+            let written = sort_lines(input, output, memory_limit, unique).unwrap();
+            let mut expected = sorted.clone();
+            if unique {
+                expected.dedup();
+            }
+            assert_eq!(written, expected.len() as u64);
+            assert_eq!(fs::read_to_string(&output_path).unwrap().lines().collect::<Vec<_>>(), expected);
+        }
+        let _ = fs::remove_file(&input_path);
+        let _ = fs::remove_file(&output_path);
+        assert!(matches!(sort_lines(input, output, None, false), Err(Error::Io(_))));
+    }
+}
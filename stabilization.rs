@@ -0,0 +1,128 @@
+/*
+Stabilizations of mosaics, and the fewest of them connecting two Legendrian representatives of a knot type.
+A stabilization adds a zigzag to a strand of the front: two cusps of opposite sides, passed in the same direction, with no crossing between them.
+That lowers tb by 1 and changes rot by 1 -- up or down as the strand is oriented, so whether it's positive or negative depends on the orientation of the knot.
+On a mosaic it's a block replacement like the Reidemeister moves (see reidemeister.rs): a straight strand along the bottom of a block, picking up a zigzag
+    00              21
+    55     ->       43
+with its images under the symmetries, and the destabilizations are these the other way round.
+Any two Legendrian representatives of a knot type are connected by Legendrian isotopies, stabilizations and destabilizations, and the fewest (de)stabilizations
+ needed measure how far apart they sit in the knot type's mountain range, the (tb, rot) of its classes. Each changes tb by 1, so there are at least |tb - tb'|
+ of them, and at least ||rot| - |rot'|| (taking the knots up to orientation), with as many as tb - tb' differs from by an even number.
+The search is the breadth first search of minimize.rs, a level at a time: every mosaic the moves there, translations and the reflection x -> -x reach
+ from the mosaics of a level, before the mosaics their (de)stabilizations reach, which make up the next level. So mosaics are reached by the fewest
+ (de)stabilizations, and then the fewest moves.
+What it finds is an upper bound, for the same reasons as in minimize.rs -- the mosaics stay on the board, and the moves only take their few shapes.
+*/
+
+use crate::diagram::Diagram;
+use crate::invariants::jones;
+use crate::minimize::neighbors;
+use crate::mosaic::Mosaic;
+use crate::reidemeister::{replacements, symmetric_rules, BaseMove, Rule};
+use crate::symmetry::cornered;
+use std::collections::{HashMap, VecDeque};
+use std::io::{Error, ErrorKind, Result};
+use std::sync::OnceLock;
+
+const BASE_STABILIZATION: BaseMove = ("S", 2, 2, &[0, 0, 5, 5], &[2, 1, 4, 3]);
+
+//The stabilization rules, and the destabilization rules undoing them
+fn rules() -> &'static (Vec<Rule>, Vec<Rule>) {
+    static RULES: OnceLock<(Vec<Rule>, Vec<Rule>)> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let stabilizations = symmetric_rules(&[BASE_STABILIZATION], false);
+        let destabilizations = stabilizations.iter().map(|rule| Rule { kind: "D", from: rule.to.clone(), to: rule.from.clone(), ..rule.clone() }).collect();
+        (stabilizations, destabilizations)
+    })
+}
+
+//The mosaics one stabilization or destabilization away from a mosaic, up to translation, each with whether it's a stabilization
+pub fn stabilizations(mosaic: &Mosaic) -> Vec<(Mosaic, bool)> {
+    let (stabilizations, destabilizations) = rules();
+    let mut neighbors = Vec::new();
+    for translate in mosaic.translations() {
+        neighbors.extend(replacements(&translate, stabilizations).into_iter().map(|step| (cornered(&step.apply(&translate)), true)));
+        neighbors.extend(replacements(&translate, destabilizations).into_iter().map(|step| (cornered(&step.apply(&translate)), false)));
+    }
+    neighbors
+}
+
+//A step of a path between mosaics: the mosaic reached, and whether it was reached by a stabilization, a destabilization or neither (None)
+pub type Step = (Mosaic, Option<bool>);
+
+pub struct Connection {
+    //The mosaics from one to the other, starting with the first
+    pub path: Vec<Step>,
+    pub stabilizations: usize,
+    pub destabilizations: usize,
+    pub explored: usize,
+}
+
+//(tb, |rot|) of a knot mosaic (with rot taken up to orientation)
+pub fn classical(mosaic: &Mosaic) -> Option<(i32, i32)> {
+    Diagram::trace(mosaic).filter(|diagram| diagram.component_count() == 1).map(|diagram| (diagram.tb(), diagram.rot().abs()))
+}
+
+//The fewest (de)stabilizations the mosaics could be connected by, from tb and rot: |tb - tb'|, or ||rot| - |rot'|| if that's more and differs from it by an even number
+pub fn lower_bound((tb, rot): (i32, i32), (other_tb, other_rot): (i32, i32)) -> usize {
+    let (tb, rot) = ((tb - other_tb).unsigned_abs(), (rot - other_rot).unsigned_abs());
+    (if rot > tb { rot + (rot - tb) % 2 } else { tb }) as usize
+}
+
+/* Searches for the fewest stabilizations and destabilizations connecting two knot mosaics of the same knot type (as far as their Jones polynomials tell),
+ going through at most `max_stabilizations` of them and stopping as soon as it's seen `max_mosaics` mosaics, and returning None if it didn't connect them */
+pub fn connect(from: &Mosaic, to: &Mosaic, max_stabilizations: usize, max_mosaics: usize) -> Result<Option<Connection>> {
+    let invalid = |message: String| Error::new(ErrorKind::InvalidInput, message);
+    let knot = |mosaic: &Mosaic| {
+        Diagram::trace(mosaic).filter(|diagram| diagram.component_count() == 1).ok_or_else(|| invalid(format!("{} isn't a suitably connected knot mosaic", mosaic)))
+    };
+    let (from_diagram, to_diagram) = (knot(from)?, knot(to)?);
+    if jones(&from_diagram) != jones(&to_diagram) {
+        return Err(invalid(format!("{} and {} aren't the same knot type (their Jones polynomials differ)", from, to)));
+    }
+    if from.size() != to.size() {
+        return Err(invalid(format!("{} and {} aren't on the same size of board", from, to)));
+    }
+    let (start, target) = (cornered(from), cornered(to));
+    //Each mosaic reached, with the mosaic before it and the step from that one
+    let mut reached: HashMap<Mosaic, Option<Step>> = HashMap::from([(start.clone(), None)]);
+    let mut level = vec![start];
+    for _ in 0..=max_stabilizations {
+        let mut queue = VecDeque::from(level);
+        let mut next = Vec::new();
+        while let Some(mosaic) = queue.pop_front() {
+            if mosaic == target {
+                let mut path = vec![(mosaic, None)];
+                while let Some((before, step)) = reached[&path.last().expect("the path isn't empty").0].clone() {
+                    path.last_mut().expect("the path isn't empty").1 = step;
+                    path.push((before, None));
+                }
+                path.reverse();
+                let count = |stabilization: bool| path.iter().filter(|(_, step)| *step == Some(stabilization)).count();
+                return Ok(Some(Connection { stabilizations: count(true), destabilizations: count(false), path, explored: reached.len() }));
+            }
+            for neighbor in neighbors(&mosaic) {
+                if !reached.contains_key(&neighbor) {
+                    if reached.len() >= max_mosaics {
+                        return Ok(None);
+                    }
+                    reached.insert(neighbor.clone(), Some((mosaic.clone(), None)));
+                    queue.push_back(neighbor);
+                }
+            }
+            next.extend(stabilizations(&mosaic).into_iter().map(|(neighbor, stabilization)| (neighbor, mosaic.clone(), stabilization)));
+        }
+        level = Vec::new();
+        for (neighbor, before, stabilization) in next {
+            if !reached.contains_key(&neighbor) {
+                if reached.len() >= max_mosaics {
+                    return Ok(None);
+                }
+                reached.insert(neighbor.clone(), Some((before, Some(stabilization))));
+                level.push(neighbor);
+            }
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,346 @@
+/*
+A hash-consed store of canonical forms: each class of mosaics up to symmetry and translation (see symmetry::canonical) is interned once, numbered in the order classes are first seen,
+ along with its canonical form and ID (see Mosaic::id), and every mosaic looked up is remembered with its class number --
+ so once a mosaic has been seen, finding its class again takes a hash lookup rather than a canonicalization (translating four images of the mosaic to the corner).
+The images found on the way to a mosaic's canonical form are remembered too, so the first sighting of any of them is just as quick.
+A store can be kept in a file, holding the canonical mosaic code of each class in order, one per line after a comment line, with new classes appended as they're found,
+ so class numbers stay the same across runs sharing the file. Like the invariant cache (see cache.rs), a store can be shared by the threads of a pipeline.
+With a memory limit, once the classes and mosaics remembered would take more than the limit, the classes found so far are spilled to a temporary file
+ (in the system temporary directory, or TMPDIR) sorted by canonical form, with every RUN_STRIDE-th form kept in memory to find the rest by, and the mosaics
+ remembered are forgotten; a run as large as the one spilled before it is merged into it, so there are only a few runs to look in. A class missing from memory is then looked for in each spilled run with a seek, and the canonical forms and IDs of spilled classes
+ are found again from the mosaics (see symmetry::canonical), so a store takes about the limit plus a RUN_STRIDE-th of the spilled classes' size.
+*/
+
+use crate::mosaic::{Mosaic, MosaicId};
+use crate::symmetry::{canonical, cornered, GROUP};
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Result, Seek, SeekFrom, Write};
+use std::iter;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+
+const HEADER: &str = "#mosaic_gen class store";
+//Every RUN_STRIDE-th canonical form of a spilled run is kept in memory, so finding a class reads at most RUN_STRIDE lines of the run
+const RUN_STRIDE: usize = 64;
+//Estimated memory used by each mosaic remembered, beyond its tiles: the mosaic, its class number and its share of the hash table
+const ENTRY_OVERHEAD: u64 = 6 * mem::size_of::<usize>() as u64;
+//Estimated memory used by each class's representative, beyond its tiles: the mosaic and its ID
+const REPRESENTATIVE_OVERHEAD: u64 = (4 * mem::size_of::<usize>() + mem::size_of::<MosaicId>()) as u64;
+
+//Numbers the spilled runs of every store in the process, for their file names
+static RUN_NUMBER: AtomicUsize = AtomicUsize::new(0);
+
+//Classes spilled to a temporary file as lines of canonical mosaic codes and their class numbers, tab separated, in order of code
+struct Run {
+    path: PathBuf,
+    file: BufReader<File>,
+    //Every RUN_STRIDE-th code, with where its line starts
+    index: Vec<(String, u64)>,
+    classes: usize,
+}
+
+impl Run {
+    //Writes out classes by their canonical forms and numbers, in order of code
+    fn create(classes: impl Iterator<Item = Result<(String, usize)>>) -> Result<Run> {
+        let path = env::temp_dir().join(format!("mosaic_gen-store-{}-{}.txt", process::id(), RUN_NUMBER.fetch_add(1, Ordering::Relaxed)));
+        let mut output = BufWriter::new(File::create(&path)?);
+        let (mut index, mut offset, mut count) = (Vec::new(), 0, 0);
+        let written = || -> Result<()> {
+            for class in classes {
+                let (code, number) = class?;
+                let line = format!("{}\t{}\n", code, number);
+                output.write_all(line.as_bytes())?;
+                if count % RUN_STRIDE == 0 {
+                    index.push((code, offset));
+                }
+                offset += line.len() as u64;
+                count += 1;
+            }
+            output.flush()
+        };
+        match written().and_then(|()| File::open(&path)) {
+            //A block of RUN_STRIDE lines is read at once to find a class in it
+            Ok(file) => Ok(Run { path, file: BufReader::with_capacity(4096, file), index, classes: count }),
+            Err(error) => {
+                let _ = fs::remove_file(&path);
+                Err(error)
+            }
+        }
+    }
+
+    fn spill(mut classes: Vec<(String, usize)>) -> Result<Run> {
+        classes.sort_unstable();
+        Run::create(classes.into_iter().map(Ok))
+    }
+
+    //Merges two runs into one, as their classes are distinct
+    fn merge(first: &Run, second: &Run) -> Result<Run> {
+        let read = |run: &Run| -> Result<_> {
+            Ok(BufReader::new(File::open(&run.path)?).lines().map(|line| {
+                let line = line?;
+                let (code, number) = line.split_once('\t').ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("invalid spilled class {}", line)))?;
+                Ok((code.to_string(), number.parse().map_err(|_| Error::new(ErrorKind::InvalidData, format!("invalid spilled class {}", line)))?))
+            }).peekable())
+        };
+        let (mut first, mut second) = (read(first)?, read(second)?);
+        Run::create(iter::from_fn(|| match (first.peek(), second.peek()) {
+            (Some(Ok(one)), Some(Ok(other))) if other < one => second.next(),
+            (Some(_), _) => first.next(),
+            (None, _) => second.next(),
+        }))
+    }
+
+    //The number of the class with a canonical form, if it's in the run
+    fn find(&mut self, code: &str) -> Result<Option<usize>> {
+        let block = self.index.partition_point(|(first, _)| first.as_str() <= code);
+        if block == 0 {
+            return Ok(None);
+        }
+        self.file.seek(SeekFrom::Start(self.index[block - 1].1))?;
+        let mut line = String::new();
+        for _ in 0..RUN_STRIDE {
+            line.clear();
+            if self.file.read_line(&mut line)? == 0 {
+                break;
+            }
+            let (first, number) = line.trim_end().split_once('\t').unwrap_or_default();
+            if first == code {
+                return Ok(number.parse().ok());
+            }
+            if first > code {
+                break;
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[derive(Default)]
+struct Classes {
+    //The canonical form and ID of each class since the last spill, by number less the number of classes spilled
+    representatives: Vec<(Mosaic, MosaicId)>,
+    //Every mosaic seen since the last spill, with its class number
+    numbers: HashMap<Mosaic, usize>,
+    spilled: Vec<Run>,
+    spilled_classes: usize,
+    //The estimated memory taken by the representatives and the mosaics remembered
+    used: u64,
+    memory_limit: Option<u64>,
+}
+
+impl Classes {
+    fn class_count(&self) -> usize {
+        self.spilled_classes + self.representatives.len()
+    }
+
+    //Adds a class (unless it's already there) by its canonical form, returning its number and whether it's new
+    fn insert(&mut self, canonical: Mosaic) -> Result<(usize, bool)> {
+        if let Some(&number) = self.numbers.get(&canonical) {
+            return Ok((number, false));
+        }
+        if !self.spilled.is_empty() {
+            let code = canonical.to_string();
+            for run in &mut self.spilled {
+                if let Some(number) = run.find(&code)? {
+                    self.remember(canonical, number);
+                    return Ok((number, false));
+                }
+            }
+        }
+        Ok((self.push(canonical), true))
+    }
+
+    //Adds a class known to be new by its canonical form, returning its number
+    fn push(&mut self, canonical: Mosaic) -> usize {
+        let number = self.class_count();
+        self.used += canonical.tiles().len() as u64 + REPRESENTATIVE_OVERHEAD;
+        self.representatives.push((canonical.clone(), MosaicId::of_canonical(&canonical)));
+        self.remember(canonical, number);
+        number
+    }
+
+    fn remember(&mut self, mosaic: Mosaic, number: usize) {
+        let used = mosaic.tiles().len() as u64 + ENTRY_OVERHEAD;
+        if self.numbers.insert(mosaic, number).is_none() {
+            self.used += used;
+        }
+    }
+
+    //The canonical form and ID of a class, if it hasn't been spilled
+    fn representative(&self, number: usize) -> Option<&(Mosaic, MosaicId)> {
+        number.checked_sub(self.spilled_classes).and_then(|index| self.representatives.get(index))
+    }
+
+    //Spills the classes in memory to a run if they're over the memory limit, forgetting the mosaics remembered
+    fn spill_if_full(&mut self) -> Result<()> {
+        if self.memory_limit.is_none_or(|limit| self.used < limit) {
+            return Ok(());
+        }
+        let first = self.spilled_classes;
+        let representatives = mem::take(&mut self.representatives);
+        self.spilled_classes += representatives.len();
+        self.numbers.clear();
+        self.used = 0;
+        if !representatives.is_empty() {
+            self.spilled.push(Run::spill(representatives.into_iter().enumerate().map(|(index, (canonical, _))| (canonical.to_string(), first + index)).collect())?);
+        }
+        //Runs as large as the one before them are merged into it, so there are only about as many runs as doublings of the classes spilled
+        while let [.., previous, last] = &self.spilled[..] {
+            if last.classes < previous.classes {
+                break;
+            }
+            let merged = Run::merge(previous, last)?;
+            self.spilled.truncate(self.spilled.len() - 2);
+            self.spilled.push(merged);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct ClassStore {
+    classes: RwLock<Classes>,
+    output: Option<Mutex<BufWriter<File>>>,
+}
+
+impl ClassStore {
+    //An empty store kept in memory, within about `memory_limit` bytes (if given) before spilling classes to disk
+    pub fn in_memory(memory_limit: Option<u64>) -> ClassStore {
+        ClassStore { classes: RwLock::new(Classes { memory_limit, ..Classes::default() }), output: None }
+    }
+
+    //Opens a store file, loading the classes it already has (or creating it), within about `memory_limit` bytes (if given) before spilling classes to disk
+    pub fn open(path: &str, memory_limit: Option<u64>) -> Result<ClassStore> {
+        let mut classes = Classes { memory_limit, ..Classes::default() };
+        let exists = Path::new(path).exists();
+        if exists {
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+                if !line.starts_with('#') && !line.trim().is_empty() {
+                    classes.push(line.trim().parse()?);
+                    classes.spill_if_full()?;
+                }
+            }
+        }
+        let mut output = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+        if !exists {
+            writeln!(output, "{}", HEADER)?;
+        }
+        Ok(ClassStore { classes: RwLock::new(classes), output: Some(Mutex::new(output)) })
+    }
+
+    //The number of a mosaic's class, interning the class if it's the first of its mosaics seen
+    pub fn intern(&self, mosaic: &Mosaic) -> Result<usize> {
+        if let Some(&number) = self.classes.read().expect("the store is never poisoned").numbers.get(mosaic) {
+            return Ok(number);
+        }
+        //The canonical form is the least of these images, as in symmetry::canonical
+        let images: Vec<Mosaic> = GROUP.iter().map(|symmetry| cornered(&symmetry.apply(mosaic))).collect();
+        let canonical = images.iter().min().expect("the group isn't empty").clone();
+        let mut classes = self.classes.write().expect("the store is never poisoned");
+        let (number, new) = classes.insert(canonical.clone())?;
+        if new {
+            if let Some(output) = &self.output {
+                writeln!(output.lock().expect("the store is never poisoned"), "{}", canonical)?;
+            }
+        }
+        for image in images.into_iter().chain([mosaic.clone()]) {
+            classes.remember(image, number);
+        }
+        classes.spill_if_full()?;
+        Ok(number)
+    }
+
+    //The canonical form and ID of a mosaic's class, from the store unless the class has been spilled
+    fn representative(&self, mosaic: &Mosaic) -> Result<(Mosaic, MosaicId)> {
+        let number = self.intern(mosaic)?;
+        match self.classes.read().expect("the store is never poisoned").representative(number) {
+            Some(representative) => Ok(representative.clone()),
+            None => {
+                let canonical = canonical(mosaic);
+                let id = MosaicId::of_canonical(&canonical);
+                Ok((canonical, id))
+            }
+        }
+    }
+
+    //The canonical form of a mosaic, from its class
+    pub fn canonical(&self, mosaic: &Mosaic) -> Result<Mosaic> {
+        Ok(self.representative(mosaic)?.0)
+    }
+
+    //The ID of a mosaic's class, which is the mosaic's ID (see Mosaic::id)
+    pub fn id(&self, mosaic: &Mosaic) -> Result<MosaicId> {
+        Ok(self.representative(mosaic)?.1)
+    }
+
+    //The number of classes interned
+    pub fn class_count(&self) -> usize {
+        self.classes.read().expect("the store is never poisoned").class_count()
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        match &self.output {
+            Some(output) => output.lock().expect("the store is never poisoned").flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::generate_with;
+    use std::ops::ControlFlow;
+
+    fn mosaics(size: usize) -> Vec<Mosaic> {
+        let mut mosaics = Vec::new();
+        let _ = generate_with::<()>(size, |mosaic| {
+            mosaics.push(mosaic.clone());
+            ControlFlow::Continue(())
+        });
+        mosaics
+    }
+
+    #[test]
+    fn spilled_store_numbers_classes_the_same() {
+        let mosaics = mosaics(4);
+        let (in_memory, spilling) = (ClassStore::in_memory(None), ClassStore::in_memory(Some(4096)));
+        for mosaic in &mosaics {
+            assert_eq!(in_memory.intern(mosaic).unwrap(), spilling.intern(mosaic).unwrap());
+        }
+        for mosaic in &mosaics {
+            assert_eq!(in_memory.intern(mosaic).unwrap(), spilling.intern(mosaic).unwrap());
+            assert_eq!(spilling.canonical(mosaic).unwrap(), canonical(mosaic));
+            assert_eq!(in_memory.id(mosaic).unwrap(), spilling.id(mosaic).unwrap());
+        }
+        assert_eq!(in_memory.class_count(), spilling.class_count());
+        let classes = spilling.classes.read().unwrap();
+        assert!(classes.spilled_classes > 0);
+        //Merging keeps the runs to about one per doubling
+        assert!(classes.spilled.len() <= usize::BITS as usize - classes.spilled_classes.leading_zeros() as usize);
+    }
+
+    #[test]
+    fn spilled_runs_are_removed() {
+        let store = ClassStore::in_memory(Some(1024));
+        for mosaic in mosaics(3) {
+            store.intern(&mosaic).unwrap();
+        }
+        let paths: Vec<PathBuf> = store.classes.read().unwrap().spilled.iter().map(|run| run.path.clone()).collect();
+        assert!(!paths.is_empty() && paths.iter().all(|path| path.exists()));
+        drop(store);
+        assert!(paths.iter().all(|path| !path.exists()));
+    }
+}
@@ -0,0 +1,150 @@
+/*
+Legendrian symmetries of mosaics.
+Read as a front (the mosaic rotated 45°), the x axis of the front runs along the mosaic's diagonal from its top left corner to its bottom right, and the z axis along the other diagonal.
+Only the symmetries of the square fixing the diagonals keep cusps as cusps, and each of them is a contactomorphism on the front:
+    x   the reflection x -> -x of the front (reflecting the mosaic in its other diagonal), which is contact isotopic to the identity
+    z   the reflection z -> -z of the front (transposing the mosaic), the Legendrian mirror
+    xz  the half turn of the front and the mosaic, the Legendrian mirror up to isotopy
+Crossings stay crossings: the over strand of a front crossing is determined by the slopes of its strands, so the image of a crossing is again tile 9 (see pattern::transform_tile).
+x preserves rotation numbers, while z and xz negate them.
+Along with the identity, these make up a group of four symmetries (acting on the mosaic as the Klein four-group), so a mosaic's orbit has 4 / |stabilizer| mosaics.
+*/
+
+use crate::diagram::{Diagram, Visit};
+use crate::mosaic::Mosaic;
+use crate::pattern::transform_tile;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Symmetry {
+    Identity,
+    FlipX,
+    FlipZ,
+    Rotation,
+}
+
+pub const GROUP: [Symmetry; 4] = [Symmetry::Identity, Symmetry::FlipX, Symmetry::FlipZ, Symmetry::Rotation];
+
+impl Symmetry {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Symmetry::Identity => "1",
+            Symmetry::FlipX => "x",
+            Symmetry::FlipZ => "z",
+            Symmetry::Rotation => "xz",
+        }
+    }
+
+    //How the symmetry changes rotation numbers (tb is unchanged by all of them): x keeps them, while z and xz negate them
+    pub fn rot_sign(&self) -> i32 {
+        match self {
+            Symmetry::Identity | Symmetry::FlipX => 1,
+            Symmetry::FlipZ | Symmetry::Rotation => -1,
+        }
+    }
+
+    //(row, column) of the image of a position in a size x size mosaic
+    fn position(&self, size: usize, row: usize, col: usize) -> (usize, usize) {
+        self.block_position(size, size, row, col)
+    }
+
+    //(row, column) of the image of a position in a height x width block, whose image is width x height for the reflections
+    fn block_position(&self, height: usize, width: usize, row: usize, col: usize) -> (usize, usize) {
+        match self {
+            Symmetry::Identity => (row, col),
+            Symmetry::FlipX => (width - 1 - col, height - 1 - row),
+            Symmetry::FlipZ => (col, row),
+            Symmetry::Rotation => (height - 1 - row, width - 1 - col),
+        }
+    }
+
+    //The image of a pass through a tile of a size x size mosaic: the same stretch of strand in the image, run in the same direction
+    pub fn apply_visit(&self, size: usize, visit: Visit) -> Visit {
+        let (row, col) = self.position(size, visit.tile / size, visit.tile % size);
+        let faces = self.faces();
+        Visit { tile: row * size + col, entry: faces[visit.entry as usize], exit: faces[visit.exit as usize] }
+    }
+
+    //The image of each face of a tile
+    fn faces(&self) -> [u8; 4] {
+        match self {
+            Symmetry::Identity => [0, 1, 2, 3],
+            Symmetry::FlipX => [1, 0, 3, 2],
+            Symmetry::FlipZ => [3, 2, 1, 0],
+            Symmetry::Rotation => [2, 3, 0, 1],
+        }
+    }
+
+    pub fn apply(&self, mosaic: &Mosaic) -> Mosaic {
+        let size = mosaic.size();
+        let mut tiles = vec![0; size * size];
+        for (index, &tile) in mosaic.tiles().iter().enumerate() {
+            let (row, col) = self.position(size, index / size, index % size);
+            tiles[row * size + col] = transform_tile(tile as usize, self.faces()) as u8;
+        }
+        Mosaic::from_tiles(size, tiles).expect("symmetries map tiles to tiles")
+    }
+
+    //The image of a height x width block of tiles (row by row), with its height and width
+    pub fn apply_block(&self, height: usize, width: usize, block: &[u8]) -> (usize, usize, Vec<u8>) {
+        let (image_height, image_width) = if matches!(self, Symmetry::FlipX | Symmetry::FlipZ) { (width, height) } else { (height, width) };
+        let mut tiles = vec![0; block.len()];
+        for (index, &tile) in block.iter().enumerate() {
+            let (row, col) = self.block_position(height, width, index / width, index % width);
+            tiles[row * image_width + col] = transform_tile(tile as usize, self.faces()) as u8;
+        }
+        (image_height, image_width, tiles)
+    }
+}
+
+//(rows, columns) the mosaic's tiles can be moved up and left before meeting the edge of the board
+fn slack(mosaic: &Mosaic) -> (usize, usize) {
+    mosaic.bounds().map_or((0, 0), |bounds| (bounds.row, bounds.col))
+}
+
+//The mosaic moved as far up and left as it goes, which represents its class up to translation on the board
+pub fn cornered(mosaic: &Mosaic) -> Mosaic {
+    let (rows, cols) = slack(mosaic);
+    mosaic.translate(-(rows as isize), -(cols as isize)).expect("the tiles can move as far as their slack")
+}
+
+//The least of the classes of a mosaic's images under the symmetries, which represents its class up to symmetry and translation
+pub fn canonical(mosaic: &Mosaic) -> Mosaic {
+    GROUP.iter().map(|symmetry| cornered(&symmetry.apply(mosaic))).min().expect("the group isn't empty")
+}
+
+//A symmetry fixing a mosaic's class, and for knots whether it reverses the traversed orientation (taking the Legendrian knot L to -L rather than to itself)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassSymmetry {
+    pub symmetry: Symmetry,
+    pub reversing: Option<bool>,
+}
+
+/* The symmetries other than the identity fixing the class of a diagram's mosaic up to translation on the board, so that the image of its front is the same front moved around.
+The empty mosaic is fixed by every symmetry. */
+pub fn class_symmetries(diagram: &Diagram) -> Vec<ClassSymmetry> {
+    let mosaic = &diagram.mosaic;
+    let size = mosaic.size();
+    let class = cornered(mosaic);
+    let (rows, cols) = slack(mosaic);
+    GROUP[1..]
+        .iter()
+        .filter_map(|&symmetry| {
+            let image = symmetry.apply(mosaic);
+            if cornered(&image) != class {
+                return None;
+            }
+            //Follows the image of the first visit back to the same strand of the original mosaic, to see which way the image runs along it
+            let reversing = match diagram.components.as_slice() {
+                [component] => {
+                    let (image_rows, image_cols) = slack(&image);
+                    let first = symmetry.apply_visit(size, component[0]);
+                    let tile = (first.tile / size + rows - image_rows) * size + first.tile % size + cols - image_cols;
+                    Some(!component.iter().any(|visit| visit.tile == tile && visit.entry == first.entry && visit.exit == first.exit))
+                }
+                _ => None,
+            };
+            Some(ClassSymmetry { symmetry, reversing })
+        })
+        .collect()
+}
@@ -0,0 +1,154 @@
+/*
+Blocks of tiles cut out of mosaics, as tangles: the tiles of a rectangle of a mosaic, and how the strands entering the rectangle are joined up inside it.
+A tangle's ends are the faces on the edge of the block that strands leave by, named by the side and the row or column they're in --
+ t0 for the top of the first column, then r, b and l for the right, bottom and left sides -- and listed clockwise from the top left corner.
+Each strand through the block joins two ends (passing through any crossings on the way), and any other strands are loops inside it,
+ so a tangle is written as its tiles (as rows separated by /, like a pattern, see pattern.rs), the pairs of ends joined, and the number of loops, e.g.
+    25/81 r0-l1 b1-b0 0 loops   a strand from the right of the top row to the left of the bottom row (through the cusp of tile 8), and one between the two bottom faces
+    21/34 1 loop                a loop, with no strands leaving the block
+*/
+
+use crate::error::{Error, Result};
+use crate::mosaic::{tile_char, Mosaic, NONE, PARTNER};
+use std::fmt;
+use std::str::FromStr;
+
+//The rows row..row + height and columns col..col + width of a mosaic, written like 1:2+3x4 (at row 1, column 2, 3 rows high and 4 columns wide)
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Rect {
+    pub row: usize,
+    pub col: usize,
+    pub height: usize,
+    pub width: usize,
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}+{}x{}", self.row, self.col, self.height, self.width)
+    }
+}
+
+impl FromStr for Rect {
+    type Err = Error;
+
+    fn from_str(rect: &str) -> Result<Rect> {
+        let invalid = || Error::Parse { what: "rectangle", text: rect.to_string(), reason: "rectangles are like 1:2+3x4, at row 1, column 2, 3 rows high and 4 columns wide".to_string() };
+        let (place, dimensions) = rect.trim().split_once('+').ok_or_else(invalid)?;
+        let (row, col) = place.split_once(':').ok_or_else(invalid)?;
+        let (height, width) = dimensions.split_once('x').ok_or_else(invalid)?;
+        let number = |number: &str| number.trim().parse::<usize>().map_err(|_| invalid());
+        let rect = Rect { row: number(row)?, col: number(col)?, height: number(height)?, width: number(width)? };
+        if rect.height == 0 || rect.width == 0 {
+            return Err(invalid());
+        }
+        Ok(rect)
+    }
+}
+
+//A face on the edge of a tangle's block, as the side it's on (by the face number of mosaic.rs) and the row or column along that side
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct End {
+    pub side: u8,
+    pub position: usize,
+}
+
+impl fmt::Display for End {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", ['r', 't', 'l', 'b'][self.side as usize], self.position)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Tangle {
+    pub height: usize,
+    pub width: usize,
+    pub tiles: Vec<u8>,          //Row by row
+    pub ends: Vec<End>,          //Clockwise from the top left corner
+    pub arcs: Vec<(End, End)>,   //The ends joined by each strand, in the order of their first ends
+    pub loops: usize,
+}
+
+impl Tangle {
+    //The tangle of the tiles of a mosaic in a rectangle, which must lie inside it
+    pub fn cut(mosaic: &Mosaic, rect: Rect) -> Result<Tangle> {
+        let size = mosaic.size();
+        if rect.row + rect.height > size || rect.col + rect.width > size {
+            return Err(Error::Invalid(format!("rectangle {} doesn't fit in a {}x{} mosaic", rect, size, size)));
+        }
+        let (height, width) = (rect.height, rect.width);
+        let tiles: Vec<u8> = (0..height).flat_map(|row| (0..width).map(move |col| (row, col))).map(|(row, col)| mosaic.tiles()[(rect.row + row) * size + rect.col + col]).collect();
+        let connected = |index: usize, face: u8| PARTNER[tiles[index] as usize][face as usize] != NONE;
+        //The tile across a face inside the block, if any
+        let across = |index: usize, face: u8| {
+            let (row, col) = (index / width, index % width);
+            match face {
+                0 if col + 1 < width => Some(index + 1),
+                1 if row > 0 => Some(index - width),
+                2 if col > 0 => Some(index - 1),
+                3 if row + 1 < height => Some(index + width),
+                _ => None,
+            }
+        };
+        let end_at = |index: usize, face: u8| End { side: face, position: if face == 0 || face == 2 { index / width } else { index % width } };
+        //The faces on the block's edge, clockwise from the top left corner
+        let edge = (0..width)
+            .map(|col| (col, 1))
+            .chain((0..height).map(|row| (row * width + width - 1, 0)))
+            .chain((0..width).rev().map(|col| ((height - 1) * width + col, 3)))
+            .chain((0..height).rev().map(|row| (row * width, 2)));
+        let edge_faces: Vec<(usize, u8)> = edge.filter(|&(index, face)| connected(index, face)).collect();
+
+        let mut visited = vec![[false; 4]; tiles.len()];
+        /* Follows a strand entering the tile at `index` by `face` (returning None if it's been followed already) until it leaves the block,
+         returning the tile and face it leaves by, or comes back round to where it started, returning Some(None) */
+        let mut trace = |mut index: usize, mut face: u8| {
+            if visited[index][face as usize] {
+                return None;
+            }
+            loop {
+                let exit = PARTNER[tiles[index] as usize][face as usize];
+                visited[index][face as usize] = true;
+                visited[index][exit as usize] = true;
+                match across(index, exit) {
+                    Some(next) => (index, face) = (next, (exit + 2) % 4),
+                    None => return Some(Some((index, exit))),
+                }
+                if visited[index][face as usize] {
+                    return Some(None);
+                }
+            }
+        };
+        let mut arcs = Vec::new();
+        for &(index, face) in &edge_faces {
+            if let Some(Some((last, exit))) = trace(index, face) {
+                arcs.push((end_at(index, face), end_at(last, exit)));
+            }
+        }
+        //Every strand not on an arc is on a loop
+        let mut loops = 0;
+        for index in 0..tiles.len() {
+            for face in (0..4).filter(|&face| connected(index, face)) {
+                if trace(index, face) == Some(None) {
+                    loops += 1;
+                }
+            }
+        }
+        let ends = edge_faces.iter().map(|&(index, face)| end_at(index, face)).collect();
+        Ok(Tangle { height, width, tiles, ends, arcs, loops })
+    }
+
+    //The tangle's tiles as rows separated by /, which is also a pattern matching them (see pattern.rs)
+    pub fn rows(&self) -> String {
+        self.tiles.chunks(self.width).map(|row| row.iter().map(|&tile| tile_char(tile)).collect::<String>()).collect::<Vec<String>>().join("/")
+    }
+}
+
+impl fmt::Display for Tangle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.rows())?;
+        for (first, second) in &self.arcs {
+            write!(f, " {}-{}", first, second)?;
+        }
+        write!(f, " {} loop{}", self.loops, if self.loops == 1 { "" } else { "s" })
+    }
+}
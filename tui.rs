@@ -0,0 +1,415 @@
+/*
+Terminal UIs (enabled by the `tui` feature), drawing mosaics as Unicode art (see render::to_unicode).
+
+The census browser lists the records of a census file by rank, with the selected mosaic drawn beside the values of its fields. The keys are
+    up/down, page up/down, home/end    move through the records (or k and j)
+    g <rank>                           jumps to the record at a rank of the file, counting from 0 (or the next one left by the filter)
+    / <text>, n                        finds the next record whose line contains the text, and finds it again
+    f <conditions>                     shows only the records whose mosaics meet conditions on their invariants, like tb>=1;name=3_1 (see invariant.rs)
+    esc                                shows every record again
+    q                                  quits
+The whole census is read into memory, and filtering computes the invariants it needs for every record, so filtering a large census takes a while.
+
+The mosaic editor places tiles on a board, highlighting the tiles with loose ends (see Mosaic::loose_ends) as they're placed,
+ and shows the invariants and knot name of the mosaic once it's suitably connected. The keys are
+    arrow keys (or h, j, k, l)         move around the board
+    0-9 (and v, for virtual)           place a tile
+    space, backspace                   change the tile to the next tile, or blank it
+    s                                  appends the mosaic's code to the output file
+    e <path>                           renders the mosaic to a file, in the format of its extension (see render.rs)
+    q                                  quits, printing the mosaic's code
+*/
+
+use crate::census::CensusRecord;
+use crate::invariant::Registry;
+use crate::mosaic::{parse_tile, Mosaic, TILE_COUNT};
+use crate::output;
+use crate::render::{self, to_unicode, unicode_tile, Format};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{Result, Write};
+
+//The names of the fields of a census record (see census.rs), in order
+const FIELDS: [&str; 22] = [
+    "mosaic code", "components", "crossings", "seifert circles", "genus bound", "bridge number", "alternating", "sign", "symmetries", "composite", "determinant",
+    "signature", "grid", "ruling polynomial", "augmentation number", "tb", "rot", "knot name", "atlas", "unknot class", "jones polynomial", "id",
+];
+
+//What typing is being collected for, at the bottom of the screen
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Jump,
+    Search,
+    Filter,
+}
+
+impl Prompt {
+    fn label(&self) -> &'static str {
+        match self {
+            Prompt::Jump => "go to rank: ",
+            Prompt::Search => "search: ",
+            Prompt::Filter => "filter: ",
+        }
+    }
+}
+
+//A terminal UI, redrawn after each key press until it's done
+trait Screen {
+    fn draw(&mut self, frame: &mut Frame);
+
+    //Handles a key press, returning false once the UI should close
+    fn handle(&mut self, code: KeyCode) -> Result<bool>;
+}
+
+struct Browser {
+    records: Vec<String>,
+    visible: Vec<usize>, //Ranks of the records left by the filter, in order
+    selected: usize,     //Position of the selected record in visible
+    top: usize,          //Position of the first record listed
+    filter: Option<String>,
+    search: Option<String>,
+    prompt: Option<(Prompt, String)>,
+    message: String,
+    registry: Registry,
+}
+
+//The fields of a census record
+fn fields(record: &str) -> Vec<&str> {
+    record.split(" | ").map(str::trim).collect()
+}
+
+impl Browser {
+    fn new(records: Vec<String>) -> Browser {
+        let visible = (0..records.len()).collect();
+        Browser { records, visible, selected: 0, top: 0, filter: None, search: None, prompt: None, message: String::new(), registry: Registry::builtin() }
+    }
+
+    fn rank(&self) -> Option<usize> {
+        self.visible.get(self.selected).copied()
+    }
+
+    //Moves the selection by `step` records, staying within the records shown
+    fn step(&mut self, step: isize) {
+        self.selected = self.selected.saturating_add_signed(step).min(self.visible.len().saturating_sub(1));
+    }
+
+    fn jump(&mut self, rank: &str) {
+        match rank.trim().parse::<usize>() {
+            Ok(rank) if rank < self.records.len() => {
+                self.selected = self.visible.partition_point(|&visible| visible < rank).min(self.visible.len().saturating_sub(1));
+                if self.rank() != Some(rank) {
+                    self.message = format!("record {} isn't shown by the filter", rank);
+                }
+            }
+            _ => self.message = format!("no record at rank {} (the census has {})", rank, self.records.len()),
+        }
+    }
+
+    //Selects the next record (after the selected one, cyclically) whose line contains the text searched for
+    fn find_next(&mut self) {
+        let Some(text) = &self.search else {
+            return;
+        };
+        let count = self.visible.len();
+        match (1..=count).map(|offset| (self.selected + offset) % count).find(|&position| self.records[self.visible[position]].contains(text.as_str())) {
+            Some(position) => self.selected = position,
+            None => self.message = format!("no record contains {}", text),
+        }
+    }
+
+    fn apply_filter(&mut self, conditions: &str) {
+        let conditions = match self.registry.conditions(conditions) {
+            Ok(conditions) => conditions,
+            Err(error) => {
+                self.message = error.to_string();
+                return;
+            }
+        };
+        let rank = self.rank().unwrap_or(0);
+        self.visible = (0..self.records.len())
+            .filter(|&rank| fields(&self.records[rank])[0].parse::<Mosaic>().is_ok_and(|mosaic| conditions.iter().all(|condition| condition.admits(&mosaic))))
+            .collect();
+        self.selected = self.visible.partition_point(|&visible| visible < rank).min(self.visible.len().saturating_sub(1));
+        self.filter = Some(conditions.iter().map(|condition| format!("{:?}", condition)).collect::<Vec<String>>().join(";"));
+        self.message = format!("{} of {} records meet the filter", self.visible.len(), self.records.len());
+    }
+
+    fn clear_filter(&mut self) {
+        let rank = self.rank().unwrap_or(0);
+        self.visible = (0..self.records.len()).collect();
+        self.selected = rank;
+        self.filter = None;
+    }
+
+    //Lists the records around the selected one, scrolling only when the selection leaves the list
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect) {
+        let height = area.height.saturating_sub(2) as usize;
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if height > 0 && self.selected >= self.top + height {
+            self.top = self.selected + 1 - height;
+        }
+        let end = (self.top + height).min(self.visible.len());
+        let items: Vec<ListItem> = self.visible[self.top..end]
+            .iter()
+            .map(|&rank| {
+                let fields = fields(&self.records[rank]);
+                ListItem::new(format!("{:>8}  {}  {}", rank, fields[0], fields.get(17).filter(|&&name| name != "-").unwrap_or(&"")))
+            })
+            .collect();
+        let title = match &self.filter {
+            Some(filter) => format!(" {} of {} records, where {} ", self.visible.len(), self.records.len(), filter),
+            None => format!(" {} records ", self.records.len()),
+        };
+        let list = List::new(items).block(Block::bordered().title(title)).highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected((!self.visible.is_empty()).then(|| self.selected - self.top));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    //Draws the selected mosaic above the values of its record's fields
+    fn draw_record(&self, frame: &mut Frame, area: Rect) {
+        let mut lines: Vec<Line> = Vec::new();
+        if let Some(rank) = self.rank() {
+            let fields = fields(&self.records[rank]);
+            match fields[0].parse::<Mosaic>() {
+                Ok(mosaic) => lines.extend(to_unicode(&mosaic).lines().map(|line| Line::from(line.to_string()))),
+                Err(error) => lines.push(Line::from(error.to_string())),
+            }
+            lines.push(Line::from(""));
+            for (index, value) in fields.iter().enumerate() {
+                let name = FIELDS.get(index).map_or(format!("column {}", index + 1), |name| name.to_string());
+                lines.push(Line::from(format!("{:<20} {}", name, value)));
+            }
+        }
+        let title = self.rank().map_or(" no records ".to_string(), |rank| format!(" record {} ", rank));
+        frame.render_widget(Paragraph::new(Text::from(lines)).block(Block::bordered().title(title)), area);
+    }
+}
+
+impl Screen for Browser {
+    fn handle(&mut self, code: KeyCode) -> Result<bool> {
+        if let Some((prompt, mut input)) = self.prompt.take() {
+            match code {
+                KeyCode::Enter => match prompt {
+                    Prompt::Jump => self.jump(&input),
+                    Prompt::Search => {
+                        self.search = Some(input).filter(|input| !input.is_empty());
+                        self.find_next();
+                    }
+                    Prompt::Filter => self.apply_filter(&input),
+                },
+                KeyCode::Esc => {}
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.prompt = Some((prompt, input));
+                }
+                KeyCode::Char(character) => {
+                    input.push(character);
+                    self.prompt = Some((prompt, input));
+                }
+                _ => self.prompt = Some((prompt, input)),
+            }
+            return Ok(true);
+        }
+        self.message.clear();
+        match code {
+            KeyCode::Char('q') => return Ok(false),
+            KeyCode::Up | KeyCode::Char('k') => self.step(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.step(1),
+            KeyCode::PageUp => self.step(-20),
+            KeyCode::PageDown => self.step(20),
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = self.visible.len().saturating_sub(1),
+            KeyCode::Char('g') => self.prompt = Some((Prompt::Jump, String::new())),
+            KeyCode::Char('/') => self.prompt = Some((Prompt::Search, String::new())),
+            KeyCode::Char('n') => self.find_next(),
+            KeyCode::Char('f') => self.prompt = Some((Prompt::Filter, self.filter.clone().unwrap_or_default())),
+            KeyCode::Esc => self.clear_filter(),
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list, record] = Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(main);
+        self.draw_list(frame, list);
+        self.draw_record(frame, record);
+        let status_line = match &self.prompt {
+            Some((prompt, input)) => format!("{}{}", prompt.label(), input),
+            None if !self.message.is_empty() => self.message.clone(),
+            None => "↑↓ move   g go to rank   / search   n next match   f filter   esc clear filter   q quit".to_string(),
+        };
+        frame.render_widget(Paragraph::new(status_line), status);
+    }
+}
+
+struct Editor {
+    size: usize,
+    tiles: Vec<u8>,
+    cursor: usize,
+    output_path: Option<String>,
+    export: Option<String>, //The path being typed for an export
+    message: String,
+}
+
+impl Editor {
+    fn mosaic(&self) -> Mosaic {
+        Mosaic::from_tiles(self.size, self.tiles.clone()).expect("the editor only places tiles")
+    }
+
+    fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let (row, col) = (self.cursor / self.size, self.cursor % self.size);
+        let clamp = |position: usize, step: isize| position.saturating_add_signed(step).min(self.size - 1);
+        self.cursor = clamp(row, rows) * self.size + clamp(col, cols);
+    }
+
+    //Appends the mosaic's code to the output file, returning what was done to say so
+    fn save(&self) -> Result<String> {
+        let Some(output_path) = &self.output_path else {
+            return Ok("no output file was given to save to".to_string());
+        };
+        writeln!(OpenOptions::new().create(true).append(true).open(output_path)?, "{}", self.mosaic())?;
+        Ok(format!("appended {} to {}", self.mosaic(), output_path))
+    }
+
+    fn export(&self, path: &str) -> Result<String> {
+        let format = path.rsplit_once('.').and_then(|(_, extension)| Format::from_name(extension)).unwrap_or(Format::Svg);
+        let mut output = output::create(path)?;
+        render::write_mosaic(&mut output, &self.mosaic(), format)?;
+        output.finish()?;
+        Ok(format!("rendered the mosaic to {}", path))
+    }
+
+    //Draws the board, with the cursor's tile reversed and the tiles with loose ends in red
+    fn draw_board(&self, frame: &mut Frame, area: Rect, mosaic: &Mosaic) {
+        let loose: HashSet<usize> = mosaic.loose_ends().map(|(index, _)| index).collect();
+        let mut lines: Vec<Line> = Vec::new();
+        for row in 0..self.size {
+            let tiles: Vec<(usize, [String; 3])> = (0..self.size).map(|col| row * self.size + col).map(|index| (index, unicode_tile(self.tiles[index]))).collect();
+            for line in 0..3 {
+                let spans = tiles.iter().map(|(index, art)| {
+                    let mut style = Style::new();
+                    if loose.contains(index) {
+                        style = style.fg(Color::Red);
+                    }
+                    if *index == self.cursor {
+                        style = style.add_modifier(Modifier::REVERSED);
+                    }
+                    Span::styled(art[line].clone(), style)
+                });
+                lines.push(Line::from(spans.collect::<Vec<Span>>()));
+            }
+        }
+        frame.render_widget(Paragraph::new(Text::from(lines)).block(Block::bordered().title(format!(" {}x{} mosaic ", self.size, self.size))), area);
+    }
+
+    //Says whether the mosaic is suitably connected, and if it is gives its invariants
+    fn draw_status(&self, frame: &mut Frame, area: Rect, mosaic: &Mosaic) {
+        let mut lines = vec![Line::from(mosaic.to_string()), Line::from("")];
+        let loose_ends: Vec<(usize, u8)> = mosaic.loose_ends().collect();
+        if let Some(&first) = loose_ends.first() {
+            lines.push(Line::from(format!("not suitably connected: {} loose ends", loose_ends.len())));
+            lines.push(Line::from(mosaic.describe_loose_end(first)));
+        } else if let Some(record) = CensusRecord::new(mosaic) {
+            lines.push(Line::from("suitably connected"));
+            lines.push(Line::from(format!("{:<14} {}", "components", record.components)));
+            lines.push(Line::from(format!("{:<14} {}", "crossings", record.crossings)));
+            lines.push(Line::from(format!("{:<14} {}", "tb", record.tb)));
+            lines.push(Line::from(format!("{:<14} {}", "rot", record.rot)));
+            lines.push(Line::from(format!("{:<14} {}", "jones", record.jones.format("t", 2))));
+            lines.push(Line::from(format!("{:<14} {}", "knot name", record.name.as_deref().unwrap_or("-"))));
+        } else {
+            lines.push(Line::from("suitably connected, but empty"));
+        }
+        frame.render_widget(Paragraph::new(Text::from(lines)).block(Block::bordered().title(" mosaic ")), area);
+    }
+}
+
+impl Screen for Editor {
+    fn handle(&mut self, code: KeyCode) -> Result<bool> {
+        if let Some(mut path) = self.export.take() {
+            match code {
+                KeyCode::Enter if !path.is_empty() => self.message = self.export(&path).unwrap_or_else(|error| error.to_string()),
+                KeyCode::Enter | KeyCode::Esc => {}
+                KeyCode::Backspace => {
+                    path.pop();
+                    self.export = Some(path);
+                }
+                KeyCode::Char(character) => {
+                    path.push(character);
+                    self.export = Some(path);
+                }
+                _ => self.export = Some(path),
+            }
+            return Ok(true);
+        }
+        self.message.clear();
+        match code {
+            KeyCode::Char('q') => return Ok(false),
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1, 0),
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(0, 1),
+            KeyCode::Char(' ') => self.tiles[self.cursor] = (self.tiles[self.cursor] + 1) % TILE_COUNT,
+            KeyCode::Backspace | KeyCode::Delete => self.tiles[self.cursor] = 0,
+            KeyCode::Char('s') => self.message = self.save().unwrap_or_else(|error| error.to_string()),
+            KeyCode::Char('e') => self.export = Some(String::new()),
+            KeyCode::Char(character) => match parse_tile(character) {
+                Some(tile) => self.tiles[self.cursor] = tile,
+                None => self.message = format!("{} isn't a tile", character),
+            },
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let mosaic = self.mosaic();
+        let [main, status] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let board_width = 3 * self.size as u16 + 2;
+        let [board, details] = Layout::horizontal([Constraint::Length(board_width), Constraint::Min(0)]).areas(main);
+        self.draw_board(frame, board, &mosaic);
+        self.draw_status(frame, details, &mosaic);
+        let status_line = match &self.export {
+            Some(path) => format!("render to: {}", path),
+            None if !self.message.is_empty() => self.message.clone(),
+            None => "arrows move   0-9 place a tile   space next tile   backspace blank   s save   e render   q quit".to_string(),
+        };
+        frame.render_widget(Paragraph::new(status_line), status);
+    }
+}
+
+//Runs a UI in the terminal, restoring the terminal afterwards
+fn run(screen: &mut impl Screen) -> Result<()> {
+    let mut terminal: DefaultTerminal = ratatui::init();
+    let result = (|| loop {
+        terminal.draw(|frame| screen.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !screen.handle(key.code)? {
+                return Ok(());
+            }
+        }
+    })();
+    ratatui::restore();
+    result
+}
+
+//Browses the records of a census file until q is pressed
+pub fn browse(census_path: &str) -> Result<()> {
+    let records: Vec<String> = fs::read_to_string(census_path)?.lines().filter(|line| !line.trim().is_empty()).map(String::from).collect();
+    run(&mut Browser::new(records))
+}
+
+//Edits a mosaic until q is pressed, saving to the output file (if one's given) when asked, and returns the mosaic as it was left
+pub fn edit(mosaic: Mosaic, output_path: Option<&str>) -> Result<Mosaic> {
+    let mut editor = Editor { size: mosaic.size(), tiles: mosaic.tiles().to_vec(), cursor: 0, output_path: output_path.map(String::from), export: None, message: String::new() };
+    run(&mut editor)?;
+    Ok(editor.mosaic())
+}
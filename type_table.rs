@@ -0,0 +1,132 @@
+/*
+Tables of how many suitably connected mosaics realize each knot type at each size, read from files of generated mosaics (of one size or several).
+Each row is a knot type named by knots.rs, in order of crossing number, followed by rows for the knots that couldn't be named, the virtual knots and the links,
+ and a total -- so the table covers every suitably connected mosaic, with a column for each size of mosaic read. Blank mosaics (like the one the generator
+ writes first) have no components, so aren't counted.
+The table has a row for each knot type knots.rs names and three more, so it takes as little memory for a huge file as for a small one,
+ and unlike by-type (see pipe.rs) it needs no memory limit.
+Tables are written as tab separated values, as a Markdown table, or as a LaTeX tabular for a report.
+*/
+
+use crate::diagram::Diagram;
+use crate::invariants::jones;
+use crate::knots::{knot_name, table_order};
+use crate::mosaic::{read_mosaics, Mosaic, VIRTUAL_CROSSING};
+use crate::pipeline::{self, Stages};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Error, Result, Write};
+
+//A row of the table, in order
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Row {
+    Knot((usize, usize, bool, String)),
+    OtherKnots,
+    VirtualKnots,
+    Links,
+}
+
+impl Row {
+    //The row a mosaic is counted in, or None if it isn't suitably connected (or is blank)
+    fn of(mosaic: &Mosaic) -> Option<Row> {
+        let diagram = Diagram::trace(mosaic)?;
+        match diagram.component_count() {
+            0 => None,
+            1 if mosaic.tiles().contains(&VIRTUAL_CROSSING) => Some(Row::VirtualKnots),
+            1 => Some(jones(&diagram).and_then(|jones| knot_name(&jones)).map_or(Row::OtherKnots, |name| Row::Knot(table_order(&name)))),
+            _ => Some(Row::Links),
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            Row::Knot((_, _, _, name)) => name,
+            Row::OtherKnots => "unnamed knots",
+            Row::VirtualKnots => "virtual knots",
+            Row::Links => "links",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TableFormat {
+    Tsv,
+    Markdown,
+    Latex,
+}
+
+impl TableFormat {
+    pub fn from_name(name: &str) -> Option<TableFormat> {
+        match name {
+            "tsv" | "txt" => Some(TableFormat::Tsv),
+            "markdown" | "md" => Some(TableFormat::Markdown),
+            "latex" | "tex" => Some(TableFormat::Latex),
+            _ => None,
+        }
+    }
+}
+
+//The number of mosaics of each size in each row
+#[derive(Default)]
+pub struct TypeTable {
+    pub sizes: BTreeSet<usize>,
+    pub counts: BTreeMap<Row, BTreeMap<usize, u64>>,
+}
+
+impl TypeTable {
+    //Counts the mosaics in some files of mosaics (or stdin, for -), computing their knot types in parallel (see pipeline.rs)
+    pub fn count(input_paths: &[&str], stages: &Stages) -> Result<TypeTable> {
+        let mut sources = Vec::new();
+        for input_path in input_paths {
+            sources.push(read_mosaics(input_path)?.map(|mosaic| mosaic.map_err(Error::from)));
+        }
+        let mut table = TypeTable::default();
+        pipeline::run(sources.into_iter().flatten(), stages, |_| true, |mosaic| Ok(Row::of(&mosaic).map(|row| (row, mosaic.size()))), |(row, size)| {
+            table.sizes.insert(size);
+            *table.counts.entry(row).or_default().entry(size).or_insert(0) += 1;
+            Ok(())
+        })?;
+        Ok(table)
+    }
+
+    //The table's rows as their labels and counts by size (0 where a size has none), with the total last
+    fn rows(&self) -> Vec<(String, Vec<u64>)> {
+        let mut rows: Vec<(String, Vec<u64>)> =
+            self.counts.iter().map(|(row, counts)| (row.label().to_string(), self.sizes.iter().map(|size| counts.get(size).copied().unwrap_or(0)).collect())).collect();
+        let totals = self.sizes.iter().map(|size| self.counts.values().filter_map(|counts| counts.get(size)).sum()).collect();
+        rows.push(("total".to_string(), totals));
+        rows
+    }
+
+    pub fn write(&self, output: &mut dyn Write, format: TableFormat) -> Result<()> {
+        let sizes: Vec<String> = self.sizes.iter().map(|size| format!("{}x{}", size, size)).collect();
+        let rows = self.rows();
+        match format {
+            TableFormat::Tsv => {
+                writeln!(output, "type\t{}", sizes.join("\t"))?;
+                for (label, counts) in rows {
+                    writeln!(output, "{}\t{}", label, counts.iter().map(u64::to_string).collect::<Vec<String>>().join("\t"))?;
+                }
+            }
+            TableFormat::Markdown => {
+                writeln!(output, "| type | {} |", sizes.join(" | "))?;
+                writeln!(output, "|---|{}", "---:|".repeat(sizes.len()))?;
+                for (label, counts) in rows {
+                    writeln!(output, "| {} | {} |", label, counts.iter().map(u64::to_string).collect::<Vec<String>>().join(" | "))?;
+                }
+            }
+            TableFormat::Latex => {
+                writeln!(output, "\\begin{{tabular}}{{l{}}}\n\\hline\ntype & {} \\\\\n\\hline", "r".repeat(sizes.len()), sizes.join(" & "))?;
+                for (index, (label, counts)) in rows.iter().enumerate() {
+                    if index + 1 == rows.len() {
+                        writeln!(output, "\\hline")?;
+                    }
+                    //Knot names are typeset as math, like $m(3_1)$
+                    let label = if label.contains('_') { format!("${}$", label) } else { label.clone() };
+                    writeln!(output, "{} & {} \\\\", label, counts.iter().map(u64::to_string).collect::<Vec<String>>().join(" & "))?;
+                }
+                writeln!(output, "\\hline\n\\end{{tabular}}")?;
+            }
+        }
+        Ok(())
+    }
+}
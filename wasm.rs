@@ -0,0 +1,108 @@
+/*
+JavaScript bindings (built with wasm-bindgen when the `wasm` feature is enabled), so a web page can explore mosaics without a server:
+
+    import init, { Mosaic, generate } from "./legendrian_mosaics.js";
+    await init();
+    for (const code of generate(4, 100)) {
+        const mosaic = new Mosaic(code);
+        document.body.innerHTML += mosaic.svg();
+    }
+
+Generation happens on the page's thread, so it's limited to mosaics of size MAX_GENERATED_SIZE and below.
+*/
+
+use crate::diagram::Diagram;
+use crate::error::Error;
+use crate::generator::MosaicGenerator;
+use crate::invariants::jones;
+use crate::knots::knot_name;
+use crate::mosaic::Mosaic;
+use crate::render::to_svg;
+use wasm_bindgen::prelude::*;
+
+const MAX_GENERATED_SIZE: usize = 5;
+
+//Codes of the first `limit` suitably connected size x size mosaics
+#[wasm_bindgen]
+pub fn generate(size: usize, limit: usize) -> Result<Vec<String>, JsError> {
+    if size == 0 || size > MAX_GENERATED_SIZE {
+        return Err(JsError::new(&format!("can only generate mosaics of size 1 to {}", MAX_GENERATED_SIZE)));
+    }
+    Ok(MosaicGenerator::new(size).take(limit).map(|mosaic| mosaic.to_string()).collect())
+}
+
+#[wasm_bindgen(js_name = Mosaic)]
+pub struct JsMosaic {
+    inner: Mosaic,
+    diagram: Option<Diagram>,
+}
+
+#[wasm_bindgen(js_class = Mosaic)]
+impl JsMosaic {
+    #[wasm_bindgen(constructor)]
+    pub fn new(code: &str) -> Result<JsMosaic, JsError> {
+        let inner: Mosaic = code.parse().map_err(|error: Error| JsError::new(&error.to_string()))?;
+        let diagram = Diagram::trace(&inner);
+        Ok(JsMosaic { inner, diagram })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tiles(&self) -> Vec<u8> {
+        self.inner.tiles().to_vec()
+    }
+
+    #[wasm_bindgen(js_name = isSuitablyConnected)]
+    pub fn is_suitably_connected(&self) -> bool {
+        self.diagram.is_some()
+    }
+
+    pub fn svg(&self) -> String {
+        to_svg(&self.inner)
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_code(&self) -> String {
+        self.inner.to_string()
+    }
+
+    //The stable ID of the mosaic, as 32 hex digits (see Mosaic::id)
+    pub fn id(&self) -> String {
+        self.inner.id().to_string()
+    }
+
+    //Invariants are undefined for mosaics that aren't suitably connected
+    pub fn components(&self) -> Option<usize> {
+        self.diagram.as_ref().map(Diagram::component_count)
+    }
+
+    pub fn crossings(&self) -> Option<usize> {
+        self.diagram.as_ref().map(|diagram| diagram.crossings.len())
+    }
+
+    pub fn writhe(&self) -> Option<i32> {
+        self.diagram.as_ref().map(Diagram::writhe)
+    }
+
+    pub fn tb(&self) -> Option<i32> {
+        self.diagram.as_ref().map(Diagram::tb)
+    }
+
+    pub fn rot(&self) -> Option<i32> {
+        self.diagram.as_ref().map(Diagram::rot)
+    }
+
+    pub fn jones(&self) -> Option<String> {
+        self.diagram.as_ref().and_then(jones).map(|polynomial| polynomial.format("t", 2))
+    }
+
+    #[wasm_bindgen(js_name = knotName)]
+    pub fn knot_name(&self) -> Option<String> {
+        let diagram = self.diagram.as_ref().filter(|diagram| diagram.component_count() == 1)?;
+        jones(diagram).and_then(|polynomial| knot_name(&polynomial))
+    }
+}